@@ -1,4 +1,4 @@
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", feature = "linux-backend"))]
 fn main() {
     const HEADER_FILE: &str = "src/native/linux/bluetooth_linux.h";
     println!("cargo:rerun-if-changed={HEADER_FILE}");
@@ -17,5 +17,5 @@ fn main() {
     println!("cargo:rustc-link-lib=bluetooth");
 }
 
-#[cfg(not(target_os = "linux"))]
+#[cfg(not(all(target_os = "linux", feature = "linux-backend")))]
 fn main() {}
@@ -26,7 +26,7 @@ fn main() -> WiimoteResult<()> {
             loop {
                 let input_report = d.lock().unwrap().read_timeout(50);
                 if let Ok(report) = input_report {
-                    dbg!(report);
+                    println!("{report}");
                 }
                 std::thread::sleep(Duration::from_millis(50));
             }
@@ -0,0 +1,75 @@
+//! The backends Wii remotes are found and connected with.
+
+use crate::native::NATIVE_BACKEND;
+
+/// A backend a [`WiimoteDevice`](crate::prelude::WiimoteDevice) is connected with,
+/// see [`WiimoteDevice::backend`](crate::prelude::WiimoteDevice::backend).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The HID devices of paired Wii remotes on Windows.
+    WindowsHid,
+    /// The L2CAP channels of Wii remotes connected through BlueZ on Linux.
+    Bluez,
+    /// A custom transport wrapped with [`WiimoteDevice::with_transport`](crate::prelude::WiimoteDevice::with_transport),
+    /// Wii remotes are never found by scanning with it.
+    Custom,
+}
+
+impl Backend {
+    /// Returns the backends compiled in for the target platform, in the order
+    /// [`BackendPreference::Auto`] scans with them.
+    #[must_use]
+    pub const fn available() -> &'static [Self] {
+        match &NATIVE_BACKEND {
+            Some(backend) => std::slice::from_ref(backend),
+            None => &[],
+        }
+    }
+
+    /// Returns true if the backend is compiled in and can find Wii remotes.
+    #[must_use]
+    pub fn is_available(self) -> bool {
+        Self::available().contains(&self)
+    }
+}
+
+/// The backends a [`WiimoteManager`](crate::prelude::WiimoteManager) scans with,
+/// see [`WiimoteManagerBuilder::backend`](crate::prelude::WiimoteManagerBuilder::backend).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BackendPreference {
+    /// Scans with all available backends in order, a Wii remote found by several
+    /// backends is connected with the first one.
+    #[default]
+    Auto,
+    /// Only scans with the backend, no Wii remotes are found if it is not available.
+    Only(Backend),
+}
+
+impl BackendPreference {
+    /// Returns the available backends to scan with, in order.
+    #[cfg(feature = "manager-thread")]
+    pub(crate) fn backends(self) -> Vec<Backend> {
+        match self {
+            Self::Auto => Backend::available().to_vec(),
+            Self::Only(backend) if backend.is_available() => vec![backend],
+            Self::Only(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "manager-thread"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends() {
+        assert_eq!(BackendPreference::Auto.backends(), Backend::available());
+        assert!(!Backend::Custom.is_available());
+        assert!(BackendPreference::Only(Backend::Custom)
+            .backends()
+            .is_empty());
+        for &backend in Backend::available() {
+            assert_eq!(BackendPreference::Only(backend).backends(), [backend]);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam_channel::{RecvTimeoutError, Sender};
+
+use crate::device::SharedDevice;
+use crate::output::OutputReport;
+
+/// The raw battery level of a Wii remote with fresh batteries.
+const FULL_BATTERY_LEVEL: u8 = 0xC8;
+
+/// The battery state of the Wii remote from the last status report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    /// The raw battery level, about 0xC8 with fresh batteries.
+    pub level: u8,
+    /// The battery low flag of the status report, set by the Wii remote itself.
+    pub low: bool,
+}
+
+impl BatteryStatus {
+    /// Returns the approximate remaining charge from 0 to 100 percent.
+    /// The battery level is not linear, so this is only a rough estimate.
+    #[must_use]
+    pub fn percentage(&self) -> u8 {
+        let percentage = u16::from(self.level) * 100 / u16::from(FULL_BATTERY_LEVEL);
+        u8::try_from(percentage).unwrap_or(u8::MAX).min(100)
+    }
+}
+
+/// Requests status reports at an interval to keep the battery state up to date.
+/// The status reports are processed while reading input reports from the `WiimoteDevice`.
+pub(crate) struct StatusPoller {
+    sender: Sender<Option<Duration>>,
+}
+
+impl StatusPoller {
+    pub(crate) fn new(device: Arc<SharedDevice>) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        std::thread::spawn(move || {
+            // The thread ends when the poller is dropped together with the `WiimoteDevice`.
+            let mut interval = None;
+            loop {
+                let received = match interval {
+                    Some(interval) => receiver.recv_timeout(interval),
+                    None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(new_interval) => interval = new_interval,
+                    Err(RecvTimeoutError::Timeout) => {
+                        _ = device.write(&OutputReport::StatusRequest);
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+        Self { sender }
+    }
+
+    /// Sets the interval between status requests, `None` stops the requests.
+    pub(crate) fn set_interval(&self, interval: Option<Duration>) {
+        _ = self.sender.send(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_percentage() {
+        let status = |level| BatteryStatus { level, low: false };
+
+        assert_eq!(status(0).percentage(), 0);
+        assert_eq!(status(0x64).percentage(), 50);
+        assert_eq!(status(FULL_BATTERY_LEVEL).percentage(), 100);
+        assert_eq!(status(0xFF).percentage(), 100);
+    }
+}
@@ -0,0 +1,213 @@
+//! Treats the buttons of the Wii remote and its extensions as one kind of input,
+//! so input bindings can be configured without handling each source separately.
+
+use std::collections::HashMap;
+
+use crate::extensions::{
+    ClassicControllerButton, ClassicControllerButtons, ExtensionInput, NunchuckButton,
+};
+use crate::input::{Button, ButtonData, WiimoteData};
+use crate::prelude::*;
+
+/// A button of the Wii remote or one of its extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WiimoteInput {
+    Wiimote(Button),
+    Nunchuck(NunchuckButton),
+    ClassicController(ClassicControllerButton),
+    /// The front button of the balance board, reported as the A button of the core buttons.
+    BalanceBoard,
+}
+
+impl WiimoteInput {
+    /// Returns the pressed inputs of the core buttons and the decoded extension input.
+    /// The core buttons of a balance board are reported as [`Self::BalanceBoard`].
+    #[must_use]
+    pub fn pressed(
+        model: WiimoteModel,
+        buttons: ButtonData,
+        input: Option<&ExtensionInput>,
+    ) -> Vec<Self> {
+        let balance_board = model == WiimoteModel::BalanceBoard
+            || matches!(input, Some(ExtensionInput::BalanceBoard(_)));
+        let mut pressed = Vec::new();
+        if balance_board {
+            if BalanceBoard::is_front_button_pressed(buttons) {
+                pressed.push(Self::BalanceBoard);
+            }
+        } else {
+            pressed.extend(buttons.pressed().map(Self::Wiimote));
+        }
+
+        match input {
+            Some(ExtensionInput::Nunchuck(data)) => pressed.extend(
+                NunchuckButton::ALL
+                    .into_iter()
+                    .filter(|button| data.buttons.contains(button.flag()))
+                    .map(Self::Nunchuck),
+            ),
+            Some(ExtensionInput::ClassicController(data)) => {
+                pressed.extend(classic_controller_inputs(data.buttons));
+            }
+            Some(ExtensionInput::ClassicControllerPro(data)) => {
+                pressed.extend(classic_controller_inputs(data.buttons));
+            }
+            Some(ExtensionInput::BalanceBoard(_) | ExtensionInput::Custom(_)) | None => {}
+        }
+        pressed
+    }
+}
+
+fn classic_controller_inputs(
+    buttons: ClassicControllerButtons,
+) -> impl Iterator<Item = WiimoteInput> {
+    ClassicControllerButton::ALL
+        .into_iter()
+        .filter(move |button| buttons.contains(button.flag()))
+        .map(WiimoteInput::ClassicController)
+}
+
+/// Maps the inputs of a Wii remote to actions of the application.
+/// Each Wii remote can use its own table, e.g. to let players configure their bindings.
+///
+/// ```
+/// use wiimote_rs::bindings::{InputBindings, WiimoteInput};
+/// use wiimote_rs::input::Button;
+/// use wiimote_rs::prelude::*;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// enum Action {
+///     Jump,
+///     Fire,
+/// }
+///
+/// let mut bindings = InputBindings::new();
+/// bindings.bind(WiimoteInput::Wiimote(Button::A), Action::Jump);
+/// bindings.bind(WiimoteInput::Nunchuck(NunchuckButton::Z), Action::Fire);
+/// assert_eq!(bindings.action(WiimoteInput::Wiimote(Button::A)), Some(&Action::Jump));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputBindings<A> {
+    actions: HashMap<WiimoteInput, A>,
+}
+
+impl<A> Default for InputBindings<A> {
+    fn default() -> Self {
+        Self {
+            actions: HashMap::new(),
+        }
+    }
+}
+
+impl<A> InputBindings<A> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds the input to the action, returns the action previously bound to the input.
+    pub fn bind(&mut self, input: WiimoteInput, action: A) -> Option<A> {
+        self.actions.insert(input, action)
+    }
+
+    /// Removes the binding of the input, returns the action that was bound to it.
+    pub fn unbind(&mut self, input: WiimoteInput) -> Option<A> {
+        self.actions.remove(&input)
+    }
+
+    /// Returns the action bound to the input.
+    #[must_use]
+    pub fn action(&self, input: WiimoteInput) -> Option<&A> {
+        self.actions.get(&input)
+    }
+
+    /// Returns the bound inputs and their actions.
+    pub fn iter(&self) -> impl Iterator<Item = (WiimoteInput, &A)> {
+        self.actions.iter().map(|(input, action)| (*input, action))
+    }
+
+    /// Returns the actions of the pressed inputs in a data report of `device`,
+    /// decoding the extension bytes with the connected extension.
+    #[must_use]
+    pub fn map_report(&self, device: &WiimoteDevice, data: &WiimoteData) -> Vec<&A> {
+        let extension = device.extension();
+        let input = extension
+            .as_ref()
+            .and_then(|extension| extension.decode(data));
+        let buttons = if data.report_id() == 0x3D {
+            ButtonData::empty()
+        } else {
+            data.buttons()
+        };
+        self.map(device.model(), buttons, input.as_ref())
+    }
+
+    /// Returns the actions of the pressed inputs, see [`WiimoteInput::pressed`].
+    #[must_use]
+    pub fn map(
+        &self,
+        model: WiimoteModel,
+        buttons: ButtonData,
+        input: Option<&ExtensionInput>,
+    ) -> Vec<&A> {
+        WiimoteInput::pressed(model, buttons, input)
+            .into_iter()
+            .filter_map(|input| self.action(input))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pressed_inputs() {
+        // C pressed, Z released
+        let nunchuck = ExtensionInput::Nunchuck(NunchuckData::from([0x80, 0x80, 0, 0, 0, 0b01]));
+        assert_eq!(
+            WiimoteInput::pressed(WiimoteModel::Original, ButtonData::B, Some(&nunchuck)),
+            [
+                WiimoteInput::Wiimote(Button::B),
+                WiimoteInput::Nunchuck(NunchuckButton::C)
+            ]
+        );
+
+        assert_eq!(
+            WiimoteInput::pressed(WiimoteModel::BalanceBoard, ButtonData::A, None),
+            [WiimoteInput::BalanceBoard]
+        );
+    }
+
+    #[test]
+    fn test_bindings() {
+        let mut bindings = InputBindings::new();
+        bindings.bind(WiimoteInput::Wiimote(Button::A), "jump");
+        bindings.bind(
+            WiimoteInput::ClassicController(ClassicControllerButton::B),
+            "jump",
+        );
+        bindings.bind(WiimoteInput::Wiimote(Button::Home), "pause");
+
+        // b pressed on the Classic Controller
+        let classic = ExtensionInput::ClassicController(ClassicControllerData::from([
+            0x20,
+            0x20,
+            0x10,
+            0x00,
+            0xFF,
+            0b1011_1111,
+        ]));
+        assert_eq!(
+            bindings.map(WiimoteModel::Original, ButtonData::empty(), Some(&classic)),
+            [&"jump"]
+        );
+        assert_eq!(
+            bindings.unbind(WiimoteInput::Wiimote(Button::Home)),
+            Some("pause")
+        );
+        assert!(bindings
+            .map(WiimoteModel::Original, ButtonData::HOME, None)
+            .is_empty());
+    }
+}
@@ -0,0 +1,380 @@
+//! Capturing the reports exchanged with a Wii remote in the btsnoop format of Bluetooth HCI logs.
+//!
+//! A [`BtsnoopCapture`] writes every input and output report as an ACL packet on the HID interrupt
+//! channel, so captures can be opened in Wireshark and compared with HCI logs of other hosts,
+//! e.g. the Bluetooth HCI snoop log of an Android device or a capture of `btmon`.
+//!
+//! The capture starts with an L2CAP connection of the HID interrupt channel, which lets Wireshark
+//! decode the following packets as HIDP. The connection handle and channel IDs are fixed values,
+//! they do not match those of the actual connection.
+//!
+//! Format Documentation: <https://www.fte.com/webhelpii/hsu/Content/Technical_Information/BT_Snoop_File_Format.htm>
+
+use std::io::{ErrorKind, Write};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::prelude::*;
+
+const MAGIC: &[u8; 8] = b"btsnoop\0";
+const VERSION: u32 = 1;
+/// HCI UART (H4), each packet starts with its HCI packet type.
+const DATALINK_H4: u32 = 1002;
+/// Microseconds from midnight, January 1st, 0 AD to the Unix epoch.
+const EPOCH_OFFSET_MICROS: u64 = 0x00DC_DDB3_0F2F_8000;
+const FLAG_RECEIVED: u32 = 0b01;
+
+const H4_ACL_DATA: u8 = 0x02;
+/// Connection handle 1 with the packet boundary flag of a first, automatically flushable packet.
+const ACL_HANDLE_FLAGS: u16 = 0x2001;
+const SIGNALING_CID: u16 = 0x0001;
+const INTERRUPT_CID: u16 = 0x0041;
+const HID_INTERRUPT_PSM: u16 = 0x0013;
+const CONNECTION_REQUEST: u8 = 0x02;
+const CONNECTION_RESPONSE: u8 = 0x03;
+
+const HIDP_INPUT_PREFIX: u8 = 0xA1;
+const HIDP_OUTPUT_PREFIX: u8 = 0xA2;
+
+/// Writes the file header and the connection of the HID interrupt channel.
+fn write_header(writer: &mut impl Write) -> std::io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_be_bytes())?;
+    writer.write_all(&DATALINK_H4.to_be_bytes())?;
+
+    let mut request = [CONNECTION_REQUEST, 1, 4, 0, 0, 0, 0, 0];
+    request[4..6].copy_from_slice(&HID_INTERRUPT_PSM.to_le_bytes());
+    request[6..8].copy_from_slice(&INTERRUPT_CID.to_le_bytes());
+    write_packet(writer, false, SIGNALING_CID, &request)?;
+
+    // Destination and source channel followed by the result and status 0 (successful).
+    let mut response = [CONNECTION_RESPONSE, 1, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    response[4..6].copy_from_slice(&INTERRUPT_CID.to_le_bytes());
+    response[6..8].copy_from_slice(&INTERRUPT_CID.to_le_bytes());
+    write_packet(writer, true, SIGNALING_CID, &response)?;
+    writer.flush()
+}
+
+/// Writes a record with the payload in an L2CAP frame on the channel.
+fn write_packet(
+    writer: &mut impl Write,
+    received: bool,
+    channel_id: u16,
+    payload: &[u8],
+) -> std::io::Result<()> {
+    #[allow(clippy::cast_possible_truncation)] // Reports are at most 23 bytes
+    let l2cap_length = payload.len() as u16;
+    let acl_length = l2cap_length + 4;
+    let packet_length = u32::from(acl_length) + 5;
+    let flags = if received { FLAG_RECEIVED } else { 0 };
+    #[allow(clippy::cast_possible_truncation)] // Overflows in more than 500000 years
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_micros() as u64)
+        + EPOCH_OFFSET_MICROS;
+
+    writer.write_all(&packet_length.to_be_bytes())?;
+    writer.write_all(&packet_length.to_be_bytes())?;
+    writer.write_all(&flags.to_be_bytes())?;
+    // Cumulative drops
+    writer.write_all(&0u32.to_be_bytes())?;
+    writer.write_all(&timestamp.to_be_bytes())?;
+
+    writer.write_all(&[H4_ACL_DATA])?;
+    writer.write_all(&ACL_HANDLE_FLAGS.to_le_bytes())?;
+    writer.write_all(&acl_length.to_le_bytes())?;
+    writer.write_all(&l2cap_length.to_le_bytes())?;
+    writer.write_all(&channel_id.to_le_bytes())?;
+    writer.write_all(payload)
+}
+
+/// Writes a report to the capture, stops the capture if writing it failed.
+fn capture<W: Write>(sink: &Mutex<Option<W>>, received: bool, report: &[u8]) {
+    let mut sink = sink.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(writer) = sink.as_mut() else {
+        return;
+    };
+    let mut payload = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE + 1];
+    payload[0] = if received {
+        HIDP_INPUT_PREFIX
+    } else {
+        HIDP_OUTPUT_PREFIX
+    };
+    let report = &report[..report.len().min(WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE)];
+    payload[1..=report.len()].copy_from_slice(report);
+
+    let result = write_packet(writer, received, INTERRUPT_CID, &payload[..=report.len()])
+        .and_then(|()| writer.flush());
+    if let Err(err) = result {
+        log::warn!("Stopped capturing reports of Wii remote: {err}");
+        *sink = None;
+    }
+}
+
+/// A transport that writes the reports exchanged with the wrapped transport to a btsnoop capture.
+///
+/// Failing to write the capture stops it without affecting the communication.
+pub struct BtsnoopCapture<T: NativeWiimote, W: Write> {
+    transport: T,
+    /// Shared with the writers returned by [`NativeWiimote::try_clone_writer`].
+    sink: Arc<Mutex<Option<W>>>,
+}
+
+/// The writer of a [`BtsnoopCapture`], captures the output reports written by the wrapped writer.
+struct CaptureWriter<W: Write> {
+    writer: Box<dyn NativeWiimoteWriter>,
+    sink: Arc<Mutex<Option<W>>>,
+}
+
+impl<W: Write + Send> NativeWiimoteWriter for CaptureWriter<W> {
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        let result = self.writer.write(buffer);
+        if result.is_ok() {
+            capture(&self.sink, false, buffer);
+        }
+        result
+    }
+
+    fn cancel_pending_io(&mut self) {
+        self.writer.cancel_pending_io();
+    }
+}
+
+impl<T: NativeWiimote, W: Write> BtsnoopCapture<T, W> {
+    /// Wraps `transport` and writes the capture header to `writer`.
+    /// Use [`WiimoteDevice::with_transport`] to communicate through the capture.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the header could not be written.
+    pub fn new(transport: T, mut writer: W) -> std::io::Result<Self> {
+        write_header(&mut writer)?;
+        Ok(Self::with_header_written(transport, writer))
+    }
+
+    fn with_header_written(transport: T, writer: W) -> Self {
+        Self {
+            transport,
+            sink: Arc::new(Mutex::new(Some(writer))),
+        }
+    }
+
+    /// Returns the wrapped transport and the writer, `None` if writing the capture failed.
+    /// Writers cloned from the capture stop capturing.
+    pub fn into_inner(self) -> (T, Option<W>) {
+        let writer = self
+            .sink
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        (self.transport, writer)
+    }
+
+    fn capture_read(&self, result: &WiimoteResult<usize>, buffer: &[u8]) {
+        if let Ok(size @ 1..) = result {
+            capture(&self.sink, true, &buffer[..*size]);
+        }
+    }
+}
+
+impl<T: NativeWiimote, W: Write + Send + 'static> NativeWiimote for BtsnoopCapture<T, W> {
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        let result = self.transport.read(buffer);
+        self.capture_read(&result, buffer);
+        result
+    }
+
+    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize> {
+        let result = self.transport.read_timeout(buffer, timeout_millis);
+        self.capture_read(&result, buffer);
+        result
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll_read(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        buffer: &mut [u8],
+    ) -> std::task::Poll<WiimoteResult<usize>> {
+        let result = self.transport.poll_read(cx, buffer);
+        if let std::task::Poll::Ready(result) = &result {
+            self.capture_read(result, buffer);
+        }
+        result
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        let result = self.transport.write(buffer);
+        if result.is_ok() {
+            capture(&self.sink, false, buffer);
+        }
+        result
+    }
+
+    fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+        let writer = self.transport.try_clone_writer()?;
+        Some(Box::new(CaptureWriter {
+            writer,
+            sink: Arc::clone(&self.sink),
+        }))
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::fd::RawFd> {
+        self.transport.readiness_fd()
+    }
+
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    fn hid_info(&self) -> Option<crate::hid::HidInfo> {
+        self.transport.hid_info()
+    }
+
+    fn last_received(&self) -> Option<std::time::Instant> {
+        self.transport.last_received()
+    }
+
+    fn identifier(&self) -> &str {
+        self.transport.identifier()
+    }
+
+    fn model(&self) -> WiimoteModel {
+        self.transport.model()
+    }
+}
+
+impl WiimoteDevice {
+    /// Captures the reports exchanged with the Wii remote from now on to `writer` in the btsnoop format,
+    /// see [`BtsnoopCapture`]. The capture ends when the Wii remote disconnects.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected
+    /// or the capture header could not be written.
+    pub fn start_btsnoop_capture(
+        &self,
+        mut writer: impl Write + Send + 'static,
+    ) -> std::io::Result<()> {
+        if !self.is_connected() {
+            return Err(ErrorKind::NotConnected.into());
+        }
+        write_header(&mut writer)?;
+        self.wrap_transport(|transport| {
+            Box::new(BtsnoopCapture::with_header_written(transport, writer))
+        })
+        .map_err(|_| ErrorKind::NotConnected.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::SimulatedWiimote;
+
+    const HEADER_LENGTH: usize = 16;
+    const RECORD_HEADER_LENGTH: usize = 24;
+
+    /// Splits the records of a capture into their flags and packet data.
+    fn records(mut capture: &[u8]) -> Vec<(u32, Vec<u8>)> {
+        let mut records = Vec::new();
+        while capture.len() >= RECORD_HEADER_LENGTH {
+            let length = u32::from_be_bytes(capture[4..8].try_into().unwrap()) as usize;
+            let flags = u32::from_be_bytes(capture[8..12].try_into().unwrap());
+            let end = RECORD_HEADER_LENGTH + length;
+            records.push((flags, capture[RECORD_HEADER_LENGTH..end].to_vec()));
+            capture = &capture[end..];
+        }
+        assert!(capture.is_empty());
+        records
+    }
+
+    #[test]
+    fn test_capture() {
+        let mut capture = BtsnoopCapture::new(SimulatedWiimote::default(), Vec::new()).unwrap();
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        capture.write(&[0x15, 0x00]).unwrap();
+        let size = capture.read_timeout(&mut buffer, 100).unwrap();
+        assert_eq!(buffer[0], 0x20);
+
+        let (_, writer) = capture.into_inner();
+        let writer = writer.unwrap();
+        assert_eq!(&writer[..8], MAGIC);
+        assert_eq!(writer[8..HEADER_LENGTH], [0, 0, 0, 1, 0, 0, 0x03, 0xEA]);
+
+        let records = records(&writer[HEADER_LENGTH..]);
+        assert_eq!(records.len(), 4);
+        // The connection request and response of the interrupt channel on the signaling channel.
+        assert_eq!(records[0].1[7..11], [0x01, 0x00, CONNECTION_REQUEST, 0x01]);
+        assert_eq!(records[1].0, FLAG_RECEIVED);
+
+        let (flags, output) = &records[2];
+        assert_eq!(*flags, 0);
+        assert_eq!(output[..5], [H4_ACL_DATA, 0x01, 0x20, 7, 0]);
+        assert_eq!(
+            output[5..],
+            [3, 0, 0x41, 0x00, HIDP_OUTPUT_PREFIX, 0x15, 0x00]
+        );
+
+        let (flags, input) = &records[3];
+        assert_eq!(*flags, FLAG_RECEIVED);
+        assert_eq!(input[9], HIDP_INPUT_PREFIX);
+        assert_eq!(input[10..], buffer[..size]);
+    }
+
+    /// A transport with a writer that accepts every report.
+    struct SplitTransport(SimulatedWiimote);
+
+    struct AcceptingWriter;
+
+    impl NativeWiimoteWriter for AcceptingWriter {
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            Ok(buffer.len())
+        }
+    }
+
+    impl NativeWiimote for SplitTransport {
+        fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+            self.0.read(buffer)
+        }
+
+        fn read_timeout(
+            &mut self,
+            buffer: &mut [u8],
+            timeout_millis: usize,
+        ) -> WiimoteResult<usize> {
+            self.0.read_timeout(buffer, timeout_millis)
+        }
+
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            self.0.write(buffer)
+        }
+
+        fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+            Some(Box::new(AcceptingWriter))
+        }
+
+        fn identifier(&self) -> &str {
+            self.0.identifier()
+        }
+
+        fn model(&self) -> WiimoteModel {
+            self.0.model()
+        }
+    }
+
+    #[test]
+    fn test_capture_cloned_writer() {
+        let transport = SplitTransport(SimulatedWiimote::default());
+        let capture = BtsnoopCapture::new(transport, Vec::new()).unwrap();
+        let mut writer = capture.try_clone_writer().unwrap();
+        writer.write(&[0x11, 0x10]).unwrap();
+
+        let (_, capture_writer) = capture.into_inner();
+        let records = records(&capture_writer.unwrap()[HEADER_LENGTH..]);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].0, 0);
+        assert_eq!(records[2].1[9..], [HIDP_OUTPUT_PREFIX, 0x11, 0x10]);
+
+        // The writer stops capturing once the capture was taken apart.
+        writer.write(&[0x11, 0x00]).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 #[allow(clippy::cast_sign_loss, clippy::cast_possible_wrap)] // Numbers will not be that large
 pub fn normalize<TValue, TResult>(
     value: TValue,
@@ -22,3 +24,196 @@ where
     (Into::<TResult>::into(value) - Into::<TResult>::into(zero))
         / (Into::<TResult>::into(max) - Into::<TResult>::into(zero))
 }
+
+/// Returns the stick position in the range -1.0 to 1.0,
+/// scaled separately on both sides of the center to the calibrated minimum and maximum.
+pub fn normalize_stick(value: u8, min: u8, center: u8, max: u8) -> f64 {
+    let limit = if value < center { min } else { max };
+    let range = f64::from(limit) - f64::from(center);
+    if range == 0.0 {
+        return 0.0;
+    }
+    let normalized = (f64::from(value) - f64::from(center)) / range.abs();
+    normalized.clamp(-1.0, 1.0)
+}
+
+/// Returns the checksum of extension calibration data, stored in the last two of its 16 bytes.
+pub fn calibration_checksum_valid(data: &[u8; 16]) -> bool {
+    let mut checksum = 0x55u8;
+    for byte in &data[..14] {
+        checksum = checksum.wrapping_add(*byte);
+    }
+    checksum == data[14] && checksum.wrapping_add(0xAA) == data[15]
+}
+
+/// Suppresses small deflections of sticks and triggers around their rest position.
+/// Deflections outside the deadzone are rescaled to still reach 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deadzone {
+    None,
+    /// Centers the stick while its distance from the center is at most the radius.
+    Radial(f64),
+    /// Centers each axis of the stick separately while its deflection is at most the size.
+    Axial(f64),
+}
+
+impl Deadzone {
+    /// Applies the deadzone to a stick position in the range -1.0 to 1.0.
+    #[must_use]
+    pub fn apply_stick(self, (x, y): (f64, f64)) -> (f64, f64) {
+        match self {
+            Self::None => (x, y),
+            Self::Radial(radius) => {
+                let magnitude = x.hypot(y);
+                if magnitude <= radius {
+                    return (0.0, 0.0);
+                }
+                let scaled = rescale(magnitude, radius);
+                (x / magnitude * scaled, y / magnitude * scaled)
+            }
+            Self::Axial(size) => (
+                rescale(x.abs(), size).copysign(x),
+                rescale(y.abs(), size).copysign(y),
+            ),
+        }
+    }
+
+    /// Applies the deadzone to a trigger value in the range 0.0 to 1.0.
+    #[must_use]
+    pub fn apply_trigger(self, value: f64) -> f64 {
+        match self {
+            Self::None => value,
+            Self::Radial(size) | Self::Axial(size) => rescale(value, size),
+        }
+    }
+}
+
+/// Rescales a magnitude above `deadzone` to the range 0.0 to 1.0.
+fn rescale(magnitude: f64, deadzone: f64) -> f64 {
+    if magnitude <= deadzone {
+        0.0
+    } else {
+        ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0)
+    }
+}
+
+/// Maps the deflection of sticks and triggers after the deadzone, e.g. for finer control near the center.
+#[derive(Clone, Default)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    /// Cubes the deflection, small movements result in very small values.
+    Cubed,
+    /// Raises the deflection to the power, exponents above 1.0 are finer near the center.
+    Power(f64),
+    /// Maps a deflection in the range 0.0 to 1.0 to the range 0.0 to 1.0.
+    Custom(Arc<dyn Fn(f64) -> f64 + Send + Sync>),
+}
+
+impl std::fmt::Debug for ResponseCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Linear => f.write_str("Linear"),
+            Self::Cubed => f.write_str("Cubed"),
+            Self::Power(exponent) => f.debug_tuple("Power").field(exponent).finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+impl ResponseCurve {
+    /// Maps a deflection in the range 0.0 to 1.0, the sign of negative values is kept.
+    #[must_use]
+    pub fn apply(&self, value: f64) -> f64 {
+        let magnitude = value.abs().min(1.0);
+        let mapped = match self {
+            Self::Linear => magnitude,
+            Self::Cubed => magnitude.powi(3),
+            Self::Power(exponent) => magnitude.powf(*exponent),
+            Self::Custom(curve) => curve(magnitude).clamp(0.0, 1.0),
+        };
+        mapped.copysign(value)
+    }
+}
+
+/// The deadzone and response curve applied to the calibrated sticks and triggers of extensions,
+/// see [`ConnectedExtension::axes`](crate::prelude::ConnectedExtension::axes).
+#[derive(Debug, Clone)]
+pub struct StickProcessing {
+    pub stick_deadzone: Deadzone,
+    pub trigger_deadzone: Deadzone,
+    pub curve: ResponseCurve,
+}
+
+impl Default for StickProcessing {
+    fn default() -> Self {
+        Self {
+            stick_deadzone: Deadzone::Radial(0.1),
+            trigger_deadzone: Deadzone::Axial(0.05),
+            curve: ResponseCurve::Linear,
+        }
+    }
+}
+
+impl StickProcessing {
+    /// Applies the deadzone and the response curve to a stick position in the range -1.0 to 1.0.
+    /// The curve is applied to the distance from the center with a radial deadzone, to each axis otherwise.
+    #[must_use]
+    pub fn apply_stick(&self, stick: (f64, f64)) -> (f64, f64) {
+        let (x, y) = self.stick_deadzone.apply_stick(stick);
+        if matches!(self.stick_deadzone, Deadzone::Radial(_)) {
+            let magnitude = x.hypot(y);
+            if magnitude == 0.0 {
+                return (0.0, 0.0);
+            }
+            let scaled = self.curve.apply(magnitude.min(1.0));
+            (x / magnitude * scaled, y / magnitude * scaled)
+        } else {
+            (self.curve.apply(x), self.curve.apply(y))
+        }
+    }
+
+    /// Applies the deadzone and the response curve to a trigger value in the range 0.0 to 1.0.
+    #[must_use]
+    pub fn apply_trigger(&self, value: f64) -> f64 {
+        self.curve
+            .apply(self.trigger_deadzone.apply_trigger(value.clamp(0.0, 1.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadzones() {
+        assert_eq!(Deadzone::Radial(0.2).apply_stick((0.1, 0.1)), (0.0, 0.0));
+        let (x, y) = Deadzone::Radial(0.2).apply_stick((0.0, 0.6));
+        assert_eq!(x, 0.0);
+        assert!((y - 0.5).abs() < 1e-9);
+
+        let (x, y) = Deadzone::Axial(0.2).apply_stick((0.1, -0.6));
+        assert_eq!(x, 0.0);
+        assert!((y + 0.5).abs() < 1e-9);
+        assert_eq!(Deadzone::Axial(0.5).apply_trigger(0.75), 0.5);
+    }
+
+    #[test]
+    fn test_response_curves() {
+        assert_eq!(ResponseCurve::Cubed.apply(-0.5), -0.125);
+        assert_eq!(ResponseCurve::Power(2.0).apply(0.5), 0.25);
+        let custom = ResponseCurve::Custom(Arc::new(|value| value * 2.0));
+        assert_eq!(custom.apply(0.25), 0.5);
+        assert_eq!(custom.apply(0.75), 1.0);
+
+        let processing = StickProcessing {
+            stick_deadzone: Deadzone::Radial(0.5),
+            trigger_deadzone: Deadzone::None,
+            curve: ResponseCurve::Cubed,
+        };
+        let (x, y) = processing.apply_stick((0.0, -1.0));
+        assert_eq!(x, 0.0);
+        assert!((y + 1.0).abs() < 1e-9);
+        assert_eq!(processing.apply_trigger(0.5), 0.125);
+    }
+}
@@ -1,13 +1,71 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+#[cfg(unix)]
+use std::os::fd::RawFd;
+#[cfg(unix)]
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+#[cfg(all(target_os = "windows", feature = "windows-backend"))]
+use std::sync::PoisonError;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock, TryLockError};
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
+use crate::battery::StatusPoller;
 use crate::calibration::normalize;
-use crate::extensions::{MotionPlus, WiimoteExtension};
-use crate::input::InputReport;
-use crate::native::{NativeWiimote, NativeWiimoteDevice};
-use crate::output::{Addressing, OutputReport};
+use crate::events::EventPublisher;
+use crate::extensions::{ConnectedExtension, MotionPlus, TEMPERATURE_CHANGE_THRESHOLD};
+#[cfg(all(target_os = "windows", feature = "windows-backend"))]
+use crate::hid::HidInfo;
+use crate::idle::IdleWatchdog;
+use crate::input::{AcknowledgeData, InputReport, StatusData, StatusFlags, WiimoteData};
+use crate::latency::ReportRateTracker;
+use crate::liveness::LivenessWatchdog;
+use crate::native::{parse_bluetooth_address, NativeWiimote, NativeWiimoteDevice};
+#[cfg(feature = "manager-thread")]
+use crate::native::{wiimote_reopen, NATIVE_BACKEND};
+use crate::output::{Addressing, DataReporingMode, OutputReport, PlayerLedFlags};
+use crate::poll::PeekedReport;
 use crate::prelude::*;
-use crate::simple_io;
+use crate::progress;
+#[cfg(feature = "tokio")]
+use crate::report_stream::ReportStream;
+use crate::rumble::RumbleScheduler;
+use crate::simple_io::{self, MemoryRequest, MemoryResponse};
+use crate::subscription::ReportSubscribers;
+use crate::trace::enter_span;
+
+/// The longest time a read waits for the transport while the liveness watchdog is enabled.
+const LIVENESS_READ_SLICE_MILLIS: usize = 100;
+
+/// Opens the transport of the same Wii remote again, see [`WiimoteDevice::set_reconnect_policy`].
+type ReopenTransport = Arc<dyn Fn() -> Option<Box<dyn NativeWiimote>> + Send + Sync>;
+
+/// The IR camera and speaker configurations enabled by the application,
+/// applied again if the Wii remote resets them.
+#[derive(Debug, Default, Clone, Copy)]
+struct PeripheralConfig {
+    ir_camera: Option<(IrMode, IrSensitivity)>,
+    speaker: Option<SpeakerConfig>,
+}
+
+/// The EEPROM block the accelerometer calibration was read from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationBlock {
+    /// The calibration block at 0x0016.
+    #[default]
+    Primary,
+    /// The backup calibration block at 0x0020, used if the checksum of the primary block is invalid.
+    Secondary,
+}
+
+impl CalibrationBlock {
+    const fn address(self) -> u32 {
+        match self {
+            Self::Primary => 0x0016,
+            Self::Secondary => 0x0020,
+        }
+    }
+}
 
 /// The calibration data for the accelerometer of the Wii remote.
 /// Can be used to convert raw accelerometer data to acceleration values.
@@ -19,9 +77,45 @@ pub struct AccelerometerCalibration {
     x_gravity: u16,
     y_gravity: u16,
     z_gravity: u16,
+    block: CalibrationBlock,
 }
 
 impl AccelerometerCalibration {
+    /// Decodes the 10 bytes of a calibration block, the last byte is the checksum.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the checksum does not match.
+    fn from_eeprom(data: &[u8], block: CalibrationBlock) -> WiimoteResult<Self> {
+        // https://www.wiibrew.org/wiki/Wiimote#EEPROM_Memory
+        // The first four bytes store the calibrated zero offsets for the accelerometer
+        // (high 8 bits of X,Y,Z in the first three bytes, low 2 bits packed in the fourth byte as --XXYYZZ).
+        // The next four bytes store the force of gravity on those axes.
+        let mut checksum = 0x55u8;
+        for byte in &data[..9] {
+            checksum = checksum.wrapping_add(*byte);
+        }
+        if checksum != data[9] {
+            return Err(WiimoteDeviceError::InvalidChecksum.into());
+        }
+
+        Ok(Self {
+            x_zero_offset: ((data[0] as u16) << 2) | ((data[3] as u16) >> 4 & 0b11),
+            y_zero_offset: ((data[1] as u16) << 2) | ((data[3] as u16) >> 2 & 0b11),
+            z_zero_offset: ((data[2] as u16) << 2) | ((data[3] as u16) & 0b11),
+            x_gravity: ((data[4] as u16) << 2) | ((data[7] as u16) >> 4 & 0b11),
+            y_gravity: ((data[5] as u16) << 2) | ((data[7] as u16) >> 2 & 0b11),
+            z_gravity: ((data[6] as u16) << 2) | ((data[7] as u16) & 0b11),
+            block,
+        })
+    }
+
+    /// Returns the EEPROM block the calibration was read from.
+    #[must_use]
+    pub const fn block(&self) -> CalibrationBlock {
+        self.block
+    }
+
     /// Returns the acceleration values from the raw data using the current calibration.
     #[must_use]
     pub fn get_acceleration(&self, data: &AccelerometerData) -> (f64, f64, f64) {
@@ -33,6 +127,7 @@ impl AccelerometerCalibration {
 }
 
 /// The raw accelerometer data from the Wii remote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AccelerometerData {
     x: u16,
     y: u16,
@@ -40,6 +135,12 @@ pub struct AccelerometerData {
 }
 
 impl AccelerometerData {
+    /// Returns the raw 10-bit values of the X, Y and Z axis.
+    #[must_use]
+    pub const fn raw(&self) -> (u16, u16, u16) {
+        (self.x, self.y, self.z)
+    }
+
     /// The first two bytes are button data, the next three bytes are acceleration data.
     #[must_use]
     pub const fn from_normal_reporting(data: &[u8]) -> Self {
@@ -65,34 +166,500 @@ impl AccelerometerData {
     }
 }
 
+/// The hardware revision of a Wii remote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WiimoteModel {
+    /// The original Wii remote (RVL-CNT-01).
+    Original,
+    /// The Wii remote Plus with a built-in Motion Plus (RVL-CNT-01-TR).
+    Plus,
+    /// The Wii Balance Board (RVL-WBC-01), which reports as a Wii remote with a permanently
+    /// connected balance board extension.
+    BalanceBoard,
+}
+
+impl WiimoteModel {
+    /// Returns the hardware features of the model.
+    #[must_use]
+    pub const fn capabilities(self) -> WiimoteCapabilities {
+        let is_wiimote = !matches!(self, Self::BalanceBoard);
+        WiimoteCapabilities {
+            speaker: is_wiimote,
+            ir_camera: is_wiimote,
+            accelerometer: is_wiimote,
+            rumble: is_wiimote,
+        }
+    }
+
+    /// Refines the model reported by the transport with the detected hardware,
+    /// the product ID of some Wii remote Plus revisions and the balance board match the original Wii remote.
+    const fn detected(
+        self,
+        motion_plus: Option<MotionPlusType>,
+        extension: Option<WiimoteExtension>,
+    ) -> Self {
+        match (motion_plus, extension) {
+            (_, Some(WiimoteExtension::BalanceBoard)) => Self::BalanceBoard,
+            (Some(MotionPlusType::Builtin), _) => Self::Plus,
+            _ => self,
+        }
+    }
+}
+
+/// The hardware features of a Wii remote model, see [`WiimoteModel::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct WiimoteCapabilities {
+    pub speaker: bool,
+    pub ir_camera: bool,
+    pub accelerometer: bool,
+    pub rumble: bool,
+}
+
+/// The native device and the rumble state sent with every output report.
+/// Shared with background threads writing output reports.
+pub(crate) struct SharedDevice {
+    device: Mutex<Option<Box<dyn NativeWiimote>>>,
+    /// Writer of the transport if supported, so writes do not wait for a blocking read holding `device`.
+    writer: Mutex<Option<Box<dyn NativeWiimoteWriter>>>,
+    /// Readiness file descriptor of the transport or -1, readable without waiting for a read holding `device`.
+    #[cfg(unix)]
+    readiness_fd: AtomicI32,
+    /// The HID device of the transport opened last.
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    hid_info: Mutex<Option<HidInfo>>,
+    /// The transport was closed because reading or writing failed, not by [`Self::disconnect`].
+    lost: AtomicBool,
+    /// A background thread is opening the lost Wii remote again.
+    reconnecting: AtomicBool,
+    /// The background thread opened the Wii remote again,
+    /// its state is restored by [`WiimoteDevice::process_pending_changes`].
+    restore_pending: AtomicBool,
+    rumble_enabled: AtomicBool,
+    /// Raw output reports get the rumble bit of the current rumble state and update it.
+    raw_rumble_tracking: AtomicBool,
+    /// Speaker data reports get the rumble bit of the current rumble state.
+    speaker_rumble: AtomicBool,
+    leds: AtomicU8,
+    reporting_mode: Mutex<Option<DataReporingMode>>,
+    ir_camera_enabled: AtomicBool,
+    speaker_enabled: AtomicBool,
+}
+
+/// Returns whether a failed write means the Wii remote is gone. A timed out write, e.g. because
+/// the send buffer of a non-blocking socket is full, leaves the Wii remote connected.
+fn is_fatal_write_error(error: &WiimoteError) -> bool {
+    !matches!(error, WiimoteError::WriteTimedOut)
+}
+
+impl SharedDevice {
+    fn new(device: Box<dyn NativeWiimote>) -> Self {
+        Self {
+            writer: Mutex::new(device.try_clone_writer()),
+            #[cfg(unix)]
+            readiness_fd: AtomicI32::new(device.readiness_fd().unwrap_or(-1)),
+            #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+            hid_info: Mutex::new(device.hid_info()),
+            device: Mutex::new(Some(device)),
+            lost: AtomicBool::new(false),
+            reconnecting: AtomicBool::new(false),
+            restore_pending: AtomicBool::new(false),
+            rumble_enabled: AtomicBool::new(false),
+            raw_rumble_tracking: AtomicBool::new(true),
+            speaker_rumble: AtomicBool::new(true),
+            leds: AtomicU8::new(0),
+            reporting_mode: Mutex::new(None),
+            ir_camera_enabled: AtomicBool::new(false),
+            speaker_enabled: AtomicBool::new(false),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Box<dyn NativeWiimote>>> {
+        match self.device.lock() {
+            Ok(device) => device,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    fn lock_writer(&self) -> MutexGuard<'_, Option<Box<dyn NativeWiimoteWriter>>> {
+        match self.writer.lock() {
+            Ok(writer) => writer,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Replaces the transport, e.g. after the Wii remote reconnected.
+    fn connect(&self, device: Box<dyn NativeWiimote>) {
+        self.connect_locked(&mut self.lock(), device);
+    }
+
+    /// Connects the reopened transport unless the Wii remote was connected or closed deliberately
+    /// in the meantime, returns whether it was connected.
+    fn connect_if_lost(&self, device: Box<dyn NativeWiimote>) -> bool {
+        let mut device_guard = self.lock();
+        if device_guard.is_some() || !self.is_lost() {
+            return false;
+        }
+        self.connect_locked(&mut device_guard, device);
+        true
+    }
+
+    fn connect_locked(
+        &self,
+        device_guard: &mut MutexGuard<'_, Option<Box<dyn NativeWiimote>>>,
+        device: Box<dyn NativeWiimote>,
+    ) {
+        *self.lock_writer() = device.try_clone_writer();
+        #[cfg(unix)]
+        self.set_readiness_fd(device.readiness_fd());
+        #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+        self.set_hid_info(device.hid_info());
+        **device_guard = Some(device);
+        self.lost.store(false, Ordering::Relaxed);
+        // The reopened Wii remote starts with the IR camera and speaker disabled.
+        self.ir_camera_enabled.store(false, Ordering::Relaxed);
+        self.speaker_enabled.store(false, Ordering::Relaxed);
+    }
+
+    /// Closes the transport after it failed while its lock is held.
+    fn close(&self, device_guard: &mut MutexGuard<'_, Option<Box<dyn NativeWiimote>>>) {
+        #[cfg(unix)]
+        self.set_readiness_fd(None);
+        if device_guard.take().is_some() {
+            self.lost.store(true, Ordering::Relaxed);
+        }
+        _ = self.lock_writer().take();
+    }
+
+    /// Returns true if the transport was closed because reading or writing failed.
+    pub(crate) fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    #[cfg(unix)]
+    fn set_readiness_fd(&self, fd: Option<RawFd>) {
+        self.readiness_fd.store(fd.unwrap_or(-1), Ordering::Relaxed);
+    }
+
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    fn set_hid_info(&self, hid_info: Option<HidInfo>) {
+        // A transport that is not a HID device keeps the information of the last HID device.
+        if hid_info.is_some() {
+            *self.hid_info.lock().unwrap_or_else(PoisonError::into_inner) = hid_info;
+        }
+    }
+
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    fn hid_info(&self) -> Option<HidInfo> {
+        self.hid_info
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<RawFd> {
+        let fd = self.readiness_fd.load(Ordering::Relaxed);
+        (fd >= 0).then_some(fd)
+    }
+
+    /// Writes the report through the writer of the transport if available,
+    /// otherwise waits until the transport is not used for reading.
+    fn write_bytes(&self, buffer: &[u8]) -> WiimoteResult<usize> {
+        let mut writer_guard = self.lock_writer();
+        if let Some(writer) = writer_guard.as_mut() {
+            let result = writer.write(buffer);
+            if result.as_ref().is_err_and(is_fatal_write_error) {
+                _ = writer_guard.take();
+                drop(writer_guard);
+                // A blocked read fails by itself once the Wii remote disconnected.
+                if let Ok(mut device_guard) = self.device.try_lock() {
+                    #[cfg(unix)]
+                    self.set_readiness_fd(None);
+                    if device_guard.take().is_some() {
+                        self.lost.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+            return result;
+        }
+        drop(writer_guard);
+
+        let mut device_guard = self.lock();
+        let device = device_guard.as_mut().ok_or(WiimoteError::Disconnected)?;
+        let result = device.write(buffer);
+        if result.as_ref().is_err_and(is_fatal_write_error) {
+            self.close(&mut device_guard);
+        }
+        result
+    }
+
+    pub(crate) fn write(&self, output_report: &OutputReport) -> WiimoteResult<()> {
+        output_report.validate()?;
+        let rumble = match output_report {
+            OutputReport::Rumble(new_rumble) => {
+                // Rumble is sent in every output report, so the new value needs to be stored.
+                self.rumble_enabled.store(*new_rumble, Ordering::Relaxed);
+                *new_rumble
+            }
+            OutputReport::SpeakerData(..) if !self.speaker_rumble.load(Ordering::Relaxed) => {
+                // The cleared rumble bit turns the motor off, like a rumble report.
+                self.rumble_enabled.store(false, Ordering::Relaxed);
+                false
+            }
+            _ => self.rumble_enabled.load(Ordering::Relaxed),
+        };
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        let size = output_report.fill_buffer(rumble, &mut buffer);
+        if let Err(err) = self.write_bytes(&buffer[..size]) {
+            if !matches!(err, WiimoteError::Disconnected) {
+                log::warn!("Writing {output_report:?} to Wii remote failed: {err:?}");
+            }
+            return Err(err);
+        }
+        match output_report {
+            OutputReport::PlayerLed(flags) => {
+                self.leds.store(flags.bits(), Ordering::Relaxed);
+            }
+            OutputReport::DataReportingMode(mode) => {
+                *self.lock_reporting_mode() = Some(*mode);
+            }
+            OutputReport::IrCameraEnable(enabled) => {
+                self.ir_camera_enabled.store(*enabled, Ordering::Relaxed);
+            }
+            OutputReport::SpeakerEnable(enabled) => {
+                self.speaker_enabled.store(*enabled, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Writes a raw output report, the rumble bit is set from the current rumble state
+    /// unless the report is a rumble report (0x10) or the rumble tracking is disabled.
+    fn write_raw(&self, data: &[u8]) -> WiimoteResult<()> {
+        const RUMBLE_ID: u8 = 0x10;
+
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        let rumble_tracking = self.raw_rumble_tracking.load(Ordering::Relaxed);
+        let minimum_size = if rumble_tracking { 2 } else { 1 };
+        if data.len() < minimum_size || data.len() > buffer.len() {
+            return Err(WiimoteDeviceError::InvalidData.into());
+        }
+        buffer[..data.len()].copy_from_slice(data);
+        if rumble_tracking {
+            if buffer[0] == RUMBLE_ID {
+                self.rumble_enabled
+                    .store(buffer[1] & 1 != 0, Ordering::Relaxed);
+            } else if self.rumble_enabled.load(Ordering::Relaxed) {
+                buffer[1] |= 1;
+            }
+        }
+
+        if let Err(err) = self.write_bytes(&buffer[..data.len()]) {
+            if !matches!(err, WiimoteError::Disconnected) {
+                log::warn!("Writing raw output report to Wii remote failed: {err:?}");
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn lock_reporting_mode(&self) -> MutexGuard<'_, Option<DataReporingMode>> {
+        match self.reporting_mode.lock() {
+            Ok(reporting_mode) => reporting_mode,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Reads an input report once available, registers the waker of `cx` otherwise.
+    #[cfg(feature = "tokio")]
+    fn poll_read(&self, cx: &mut Context<'_>, buffer: &mut [u8]) -> Poll<WiimoteResult<usize>> {
+        let mut device_guard = self.lock();
+        let Some(device) = device_guard.as_mut() else {
+            return Poll::Ready(Err(WiimoteError::Disconnected));
+        };
+        let result = device.poll_read(cx, buffer);
+        if let Poll::Ready(Err(err)) = &result {
+            log::warn!("Reading from Wii remote failed: {err:?}");
+            self.close(&mut device_guard);
+        }
+        result
+    }
+
+    fn rumble_enabled(&self) -> bool {
+        self.rumble_enabled.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn last_received(&self) -> Option<Instant> {
+        self.lock()
+            .as_ref()
+            .and_then(|device| device.last_received())
+    }
+
+    /// Returns the last data reporting mode written to the Wii remote.
+    pub(crate) fn reporting_mode(&self) -> Option<DataReporingMode> {
+        *self.lock_reporting_mode()
+    }
+
+    /// Wakes reads blocked in the transport, which fail with `WiimoteError::Disconnected`.
+    /// Transports without a separate writer can not be cancelled.
+    pub(crate) fn cancel_pending_io(&self) {
+        if let Some(writer) = self.lock_writer().as_mut() {
+            writer.cancel_pending_io();
+        }
+    }
+
+    /// Closes the native device, following reads and writes fail with `WiimoteError::Disconnected`.
+    /// A blocked read is cancelled first, so closing does not wait for the next report.
+    pub(crate) fn disconnect(&self) {
+        self.cancel_pending_io();
+        self.close(&mut self.lock());
+        self.lost.store(false, Ordering::Relaxed);
+    }
+
+    /// Closes the native device unless a read is holding it, returns false if it is still open.
+    /// The writer is closed either way, so following writes fail with `WiimoteError::Disconnected`.
+    pub(crate) fn try_disconnect(&self) -> bool {
+        match self.device.try_lock() {
+            Ok(mut device_guard) => self.close(&mut device_guard),
+            Err(TryLockError::Poisoned(err)) => self.close(&mut err.into_inner()),
+            Err(TryLockError::WouldBlock) => {
+                _ = self.lock_writer().take();
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Replaces the connected transport with a wrapper around it.
+    pub(crate) fn wrap_transport(
+        &self,
+        wrap: impl FnOnce(Box<dyn NativeWiimote>) -> Box<dyn NativeWiimote>,
+    ) -> WiimoteResult<()> {
+        let mut device_guard = self.lock();
+        let device = device_guard.take().ok_or(WiimoteError::Disconnected)?;
+        let device = wrap(device);
+        *self.lock_writer() = device.try_clone_writer();
+        #[cfg(unix)]
+        self.set_readiness_fd(device.readiness_fd());
+        #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+        self.set_hid_info(device.hid_info());
+        *device_guard = Some(device);
+        Ok(())
+    }
+}
+
 /// A `WiimoteDevice` can be used to communicate with a Wii remote.
 pub struct WiimoteDevice {
-    device: Mutex<Option<NativeWiimoteDevice>>,
+    device: Arc<SharedDevice>,
     identifier: String,
+    model: WiimoteModel,
+    backend: Backend,
     calibration_data: AccelerometerCalibration,
-    motion_plus: Option<MotionPlus>,
-    extension: Option<WiimoteExtension>,
-    rumble_enabled: AtomicBool,
+    motion_plus: RwLock<Option<Arc<MotionPlus>>>,
+    motion_plus_policy: Mutex<MotionPlusPolicy>,
+    extension: RwLock<Option<Arc<ConnectedExtension>>>,
+    extension_connected: AtomicBool,
+    /// An extension or Motion Plus detection is running, status reports read by it do not start another one.
+    detecting_extension: AtomicBool,
+    /// A status report signaled an extension change, handled by [`WiimoteDevice::process_pending_changes`].
+    extension_refresh_pending: AtomicBool,
+    /// A status report was read, the IR camera and speaker are enabled again by
+    /// [`WiimoteDevice::process_pending_changes`] if the Wii remote reset them.
+    peripherals_check_pending: AtomicBool,
+    lenient_parsing: AtomicBool,
+    balance_board_temperature: Mutex<Option<u8>>,
+    rumble_scheduler: OnceLock<RumbleScheduler>,
+    battery: Mutex<Option<BatteryStatus>>,
+    low_battery_threshold: AtomicU8,
+    status_poller: OnceLock<StatusPoller>,
+    idle_watchdog: OnceLock<IdleWatchdog>,
+    liveness_watchdog: OnceLock<LivenessWatchdog>,
+    reconnect_policy: Mutex<Option<ReconnectPolicy>>,
+    peripherals: Mutex<PeripheralConfig>,
+    pub(crate) report_rate: ReportRateTracker,
+    pub(crate) peeked_report: Mutex<Option<PeekedReport>>,
+    pub(crate) report_subscribers: ReportSubscribers,
+    /// `None` for custom transports, which can not be opened again.
+    reopen: Option<ReopenTransport>,
+    progress: Option<ProgressObserver>,
+    events: Arc<EventPublisher>,
 }
 
-unsafe impl Sync for WiimoteDevice {}
-unsafe impl Send for WiimoteDevice {}
-
 impl WiimoteDevice {
     /// Wraps the `NativeWiimoteDevice` as a `WiimoteDevice`.
     ///
     /// # Errors
     ///
     /// This function will return an error if the device is not a recognized Wii remote or initialization failed.
-    pub(crate) fn new(device: NativeWiimoteDevice) -> WiimoteResult<Self> {
-        let identifier = device.identifier();
+    #[cfg(feature = "manager-thread")]
+    pub(crate) fn new(
+        device: NativeWiimoteDevice,
+        progress: Option<ProgressObserver>,
+    ) -> WiimoteResult<Self> {
+        let identifier = device.identifier().to_string();
+        let model = device.model();
+        enter_span!(INFO, "connect", identifier = %identifier);
+        let mut wiimote = Self::from_boxed(Box::new(device), progress)?;
+        if let Some(backend) = NATIVE_BACKEND {
+            wiimote.backend = backend;
+        }
+        wiimote.reopen = Some(Arc::new(move || {
+            wiimote_reopen(&identifier, model)
+                .map(|device| Box::new(device) as Box<dyn NativeWiimote>)
+        }));
+        Ok(wiimote)
+    }
+
+    /// Wraps a custom transport such as a [`ReplayWiimote`](crate::recording::ReplayWiimote)
+    /// and initializes the Wii remote through it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the initialization failed.
+    pub fn with_transport(transport: impl NativeWiimote + 'static) -> WiimoteResult<Self> {
+        enter_span!(INFO, "connect", identifier = %transport.identifier());
+        Self::from_boxed(Box::new(transport), None)
+    }
+
+    fn from_boxed(
+        device: Box<dyn NativeWiimote>,
+        progress: Option<ProgressObserver>,
+    ) -> WiimoteResult<Self> {
+        let identifier = device.identifier().to_string();
+        let model = device.model();
         let mut wiimote = Self {
-            device: Mutex::new(Some(device)),
+            device: Arc::new(SharedDevice::new(device)),
             identifier,
+            model,
+            backend: Backend::Custom,
             calibration_data: AccelerometerCalibration::default(),
-            motion_plus: None,
-            extension: None,
-            rumble_enabled: AtomicBool::new(false),
+            motion_plus: RwLock::new(None),
+            motion_plus_policy: Mutex::new(MotionPlusPolicy::default()),
+            extension: RwLock::new(None),
+            extension_connected: AtomicBool::new(false),
+            detecting_extension: AtomicBool::new(false),
+            extension_refresh_pending: AtomicBool::new(false),
+            peripherals_check_pending: AtomicBool::new(false),
+            lenient_parsing: AtomicBool::new(false),
+            balance_board_temperature: Mutex::new(None),
+            rumble_scheduler: OnceLock::new(),
+            battery: Mutex::new(None),
+            low_battery_threshold: AtomicU8::new(0),
+            status_poller: OnceLock::new(),
+            idle_watchdog: OnceLock::new(),
+            liveness_watchdog: OnceLock::new(),
+            reconnect_policy: Mutex::new(None),
+            peripherals: Mutex::new(PeripheralConfig::default()),
+            report_rate: ReportRateTracker::default(),
+            peeked_report: Mutex::new(None),
+            report_subscribers: ReportSubscribers::default(),
+            reopen: None,
+            progress,
+            events: Arc::default(),
         };
 
         wiimote.initialize()?;
@@ -100,11 +667,39 @@ impl WiimoteDevice {
     }
 
     /// Returns the unique identifier of the Wii remote.
+    /// The platform backends identify Wii remotes by their Bluetooth address in the form `00:19:1D:AA:BB:CC`
+    /// on all platforms, so identifiers can be persisted and compared across operating systems.
     #[must_use]
     pub fn identifier(&self) -> &str {
         &self.identifier
     }
 
+    /// Returns the Bluetooth address of the Wii remote parsed from its identifier,
+    /// all zeros for custom transports with an identifier that is not a Bluetooth address.
+    #[must_use]
+    pub fn bluetooth_address(&self) -> [u8; 6] {
+        parse_bluetooth_address(&self.identifier).unwrap_or_default()
+    }
+
+    /// Returns the hardware revision of the Wii remote, detected from the product ID or device name
+    /// and refined by the built-in Motion Plus or balance board extension found during initialization.
+    #[must_use]
+    pub const fn model(&self) -> WiimoteModel {
+        self.model
+    }
+
+    /// Returns the backend the Wii remote was found with, [`Backend::Custom`] for custom transports.
+    #[must_use]
+    pub const fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Returns the hardware features of the Wii remote, e.g. the balance board has neither speaker nor IR camera.
+    #[must_use]
+    pub const fn capabilities(&self) -> WiimoteCapabilities {
+        self.model.capabilities()
+    }
+
     /// Returns the accelerometer calibration data of the Wii remote.
     /// This data is used to convert raw accelerometer data to acceleration values.
     #[must_use]
@@ -113,25 +708,127 @@ impl WiimoteDevice {
     }
 
     /// Returns the `MotionPlus` extension of the Wii remote if connected.
+    /// An external Motion Plus plugged in or unplugged at runtime is detected when the
+    /// extension change is signaled, see [`WiimoteEvent::MotionPlusAttached`].
+    #[must_use]
+    pub fn motion_plus(&self) -> Option<Arc<MotionPlus>> {
+        match self.motion_plus.read() {
+            Ok(motion_plus) => motion_plus.clone(),
+            Err(motion_plus) => motion_plus.into_inner().clone(),
+        }
+    }
+
+    fn set_motion_plus(&self, motion_plus: Option<Arc<MotionPlus>>) {
+        match self.motion_plus.write() {
+            Ok(mut current) => *current = motion_plus,
+            Err(current) => *current.into_inner() = motion_plus,
+        }
+    }
+
+    /// Returns the policy used to manage the mode of the `MotionPlus` extension.
+    #[must_use]
+    pub fn motion_plus_policy(&self) -> MotionPlusPolicy {
+        match self.motion_plus_policy.lock() {
+            Ok(policy) => *policy,
+            Err(policy) => *policy.into_inner(),
+        }
+    }
+
+    /// Lets the device manage the mode of the `MotionPlus` extension.
+    /// The Motion Plus is initialized if necessary and the mode matching the connected extension is
+    /// activated. The mode is applied again whenever the extension changes or the Wii remote reconnects.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Motion Plus failed to initialize or change its mode.
+    pub fn enable_motion_plus(&self, policy: MotionPlusPolicy) -> WiimoteResult<()> {
+        self.set_motion_plus_policy(policy);
+        self.apply_motion_plus_policy()
+    }
+
+    pub(crate) fn set_motion_plus_policy(&self, policy: MotionPlusPolicy) {
+        match self.motion_plus_policy.lock() {
+            Ok(mut current) => *current = policy,
+            Err(current) => *current.into_inner() = policy,
+        }
+    }
+
+    fn apply_motion_plus_policy(&self) -> WiimoteResult<()> {
+        let Some(motion_plus) = self.motion_plus() else {
+            return Ok(());
+        };
+        let extension = self.extension().map(|extension| extension.extension());
+        let Some(mode) = self.motion_plus_policy().mode_for(extension) else {
+            return Ok(());
+        };
+
+        if !motion_plus.is_initialized() {
+            motion_plus.initialize(self)?;
+        }
+        motion_plus.change_mode(self, mode)
+    }
+
+    /// Returns the Wii remote extension and its calibration if connected.
+    /// The extension can be used to decode the extension bytes of data reports.
+    ///
+    /// The extension is detected again when a status report signals that an extension
+    /// was connected or disconnected.
+    #[must_use]
+    pub fn extension(&self) -> Option<Arc<ConnectedExtension>> {
+        match self.extension.read() {
+            Ok(extension) => extension.clone(),
+            Err(extension) => extension.into_inner().clone(),
+        }
+    }
+
+    /// Returns the calibration of the connected extension, read once when the extension was detected.
+    /// The calibration is read again after the extension was swapped, so decoding never needs to read it.
+    #[must_use]
+    pub fn extension_calibration(&self) -> Option<ExtensionCalibration> {
+        self.extension()
+            .map(|extension| extension.calibration().clone())
+    }
+
+    /// Receiver of events of the Wii remote, such as extension changes.
+    /// Events are published while reading input reports, each call creates a new subscription.
+    /// The channel is unbounded but only receives state changes, drop the receiver when it is no longer read.
     #[must_use]
-    pub const fn motion_plus(&self) -> Option<&MotionPlus> {
-        self.motion_plus.as_ref()
+    pub fn events_receiver(&self) -> crossbeam_channel::Receiver<WiimoteEvent> {
+        self.events.subscribe(false)
     }
 
-    /// Returns data about the Wii remote extension if connected.
+    /// Receiver of the events of the Wii remote like [`Self::events_receiver`], which also receives
+    /// every input report read as [`WiimoteEvent::Report`] before the events caused by it.
+    /// The receiver should be drained continuously, as reports arrive up to every few milliseconds.
+    /// Reports are dropped while 256 of them are queued, the other events are always delivered.
     #[must_use]
-    pub const fn extension(&self) -> Option<&WiimoteExtension> {
-        self.extension.as_ref()
+    pub fn events_receiver_with_reports(&self) -> crossbeam_channel::Receiver<WiimoteEvent> {
+        self.events.subscribe(true)
     }
 
     /// Returns whether the Wii remote is currently connected.
     /// The Wii remote is automatically re-assigned to this object when reconnected.
     #[must_use]
     pub fn is_connected(&self) -> bool {
-        self.device
-            .lock()
-            .map(|device| device.is_some())
-            .unwrap_or(false)
+        self.device.lock().is_some()
+    }
+
+    /// Returns a file descriptor that becomes readable once an input report can be read without waiting,
+    /// `None` if the transport does not support it or the Wii remote is disconnected.
+    ///
+    /// Register it with an external event loop (epoll, mio, calloop, ...) and read the queued reports
+    /// with [`Self::read_timeout`] and a timeout of 0 once it is readable.
+    /// The file descriptor changes when the Wii remote reconnects.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn readiness_fd(&self) -> Option<RawFd> {
+        self.device.readiness_fd()
+    }
+
+    /// Returns the HID device of the transport, see [`WiimoteDeviceExt`](crate::hid::WiimoteDeviceExt).
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    pub(crate) fn transport_hid_info(&self) -> Option<HidInfo> {
+        self.device.hid_info()
     }
 
     /// Reconnects the Wii remote from a `NativeWiimoteDevice`.
@@ -140,57 +837,447 @@ impl WiimoteDevice {
     ///
     /// This function will return an error if the device is not a recognized Wii remote or the Wii remote failed to initialize.
     pub fn reconnect(&mut self, device: NativeWiimoteDevice) -> WiimoteResult<()> {
+        enter_span!(INFO, "reconnect", identifier = %self.identifier);
         self.disconnected();
-        _ = self.device.lock().map(|mut d| d.replace(device));
-        self.initialize()
+        self.connect_transport(Box::new(device));
+        // The state is restored by the initialization below instead of the next read.
+        self.device.restore_pending.store(false, Ordering::Relaxed);
+        self.initialize()?;
+        self.restore_peripherals("reconnect")
     }
 
-    /// Writes the data to the connected Wii remote.
+    /// Replaces the observer notified while the Wii remote is initialized on reconnect.
+    #[cfg(feature = "manager-thread")]
+    pub(crate) fn set_progress_observer(&mut self, progress: Option<ProgressObserver>) {
+        self.progress = progress;
+    }
+
+    fn connect_transport(&self, device: Box<dyn NativeWiimote>) {
+        self.device.connect(device);
+        if let Some(liveness_watchdog) = self.liveness_watchdog.get() {
+            liveness_watchdog.reset();
+        }
+    }
+
+    /// Enables reconnecting to the Wii remote after reading or writing failed, `None` disables it (default).
     ///
-    /// # Errors
+    /// A read that finds the connection lost returns the error and starts opening the Wii remote again
+    /// with its identifier on a background thread, making up to `policy.max_attempts` attempts.
+    /// Once it is open, [`Self::process_pending_changes`] detects the extension again, restores the LEDs,
+    /// rumble, data reporting mode and Motion Plus mode and publishes [`WiimoteEvent::Reconnected`].
+    /// The lock of the device is not needed while reopening, and a Wii remote reconnected
+    /// by the [`WiimoteManager`] in the meantime is kept.
     ///
-    /// This function will return an error if the Wii remote is disconnected or write failed.
-    pub fn write(&self, output_report: &OutputReport) -> WiimoteResult<()> {
-        let mut device = match self.device.lock() {
-            Ok(device) => device,
-            Err(err) => err.into_inner(),
+    /// Only Wii remotes connected by the [`WiimoteManager`] can be opened again and async reads do not reconnect.
+    /// Closing the connection with [`IdleAction::Disconnect`] does not trigger a reconnect.
+    pub fn set_reconnect_policy(&self, policy: Option<ReconnectPolicy>) {
+        match self.reconnect_policy.lock() {
+            Ok(mut current) => *current = policy,
+            Err(current) => *current.into_inner() = policy,
+        }
+    }
+
+    /// Returns the policy set by [`Self::set_reconnect_policy`].
+    #[must_use]
+    pub fn reconnect_policy(&self) -> Option<ReconnectPolicy> {
+        match self.reconnect_policy.lock() {
+            Ok(policy) => *policy,
+            Err(policy) => *policy.into_inner(),
+        }
+    }
+
+    /// Starts opening the Wii remote again on a background thread after the connection was lost,
+    /// unless the reconnect policy is disabled or an attempt is already running.
+    fn start_auto_reconnect(&self) {
+        let (Some(policy), Some(reopen)) = (self.reconnect_policy(), &self.reopen) else {
+            return;
         };
-        if let Some(device) = device.as_mut() {
-            let rumble = if let OutputReport::Rumble(new_rumble) = output_report {
-                // Rumble is sent in every output report, so the new value needs to be stored.
-                self.rumble_enabled.store(*new_rumble, Ordering::Relaxed);
-                *new_rumble
-            } else {
-                self.rumble_enabled.load(Ordering::Relaxed)
-            };
-            let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
-            let size = output_report.fill_buffer(rumble, &mut buffer);
-            if device.write(&buffer[..size]).is_some() {
-                return Ok(());
-            }
+        if !self.device.is_lost() || self.device.reconnecting.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let device = Arc::clone(&self.device);
+        let reopen = Arc::clone(reopen);
+        let identifier = self.identifier.clone();
+        let spawn_result = std::thread::Builder::new()
+            .name("wiimote-reconnect".to_string())
+            .spawn(move || {
+                enter_span!(INFO, "auto_reconnect", identifier = %identifier);
+                for attempt in 1..=policy.max_attempts {
+                    std::thread::sleep(policy.retry_interval);
+                    // Reconnected by the manager or closed deliberately in the meantime.
+                    if !device.is_lost() {
+                        break;
+                    }
+                    let Some(transport) = reopen() else {
+                        log::debug!(
+                            "Reconnect attempt {attempt} of {} to Wii remote {identifier} failed",
+                            policy.max_attempts,
+                        );
+                        continue;
+                    };
+                    if device.connect_if_lost(transport) {
+                        log::info!("Reopened Wii remote {identifier}");
+                        device.restore_pending.store(true, Ordering::Release);
+                    }
+                    break;
+                }
+                device.reconnecting.store(false, Ordering::Release);
+            });
+        if let Err(err) = spawn_result {
+            log::warn!("Failed to spawn the reconnect thread: {err}");
+            self.device.reconnecting.store(false, Ordering::Release);
         }
-        _ = device.take();
-        Err(WiimoteError::Disconnected)
     }
 
-    /// Reads data from the connected Wii remote.
+    /// Applies the changes signaled by the reports read before, which exchange further reports
+    /// with the Wii remote and are therefore not done while reading:
+    ///
+    /// - restores the state of a Wii remote opened again by the [reconnect policy](Self::set_reconnect_policy),
+    /// - detects the extension and Motion Plus again after a status report signaled a change
+    ///   and publishes [`WiimoteEvent::ExtensionChanged`],
+    /// - enables the IR camera and speaker again if a status report showed that the Wii remote reset them.
+    ///
+    /// [`WiimoteSession`], [`Self::run_event_loop`] and [`ReportWorker`] call this after every read.
+    /// Applications reading the reports themselves should call it after status reports
+    /// or once per frame, it returns immediately if nothing is pending.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the Wii remote is disconnected or read failed.
-    pub fn read(&self) -> WiimoteResult<InputReport> {
-        let mut device = match self.device.lock() {
-            Ok(device) => device,
-            Err(err) => err.into_inner(),
-        };
-        if let Some(device) = device.as_mut() {
-            let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
-            if let Some(bytes_read) = device.read(&mut buffer) {
-                return InputReport::try_from(&buffer[..bytes_read]);
+    /// This function will return an error if communication to the Wii remote failed,
+    /// a failed extension detection is treated like a disconnected extension.
+    pub fn process_pending_changes(&self) -> WiimoteResult<()> {
+        if self.device.restore_pending.swap(false, Ordering::AcqRel) {
+            if let Err(err) = self.restore_state() {
+                log::warn!(
+                    "Failed to restore the state of Wii remote {}: {err:?}",
+                    self.identifier
+                );
+                return Err(err);
             }
+            log::info!("Reconnected Wii remote {}", self.identifier);
+            self.events.publish(&WiimoteEvent::Reconnected);
+            // The restored state includes the extension and the peripherals.
+            self.extension_refresh_pending
+                .store(false, Ordering::Relaxed);
+            self.peripherals_check_pending
+                .store(false, Ordering::Relaxed);
         }
-        _ = device.take();
-        Err(WiimoteError::Disconnected)
+        if self
+            .extension_refresh_pending
+            .swap(false, Ordering::Relaxed)
+        {
+            self.refresh_extension_state(self.extension_connected.load(Ordering::Relaxed));
+        }
+        if self
+            .peripherals_check_pending
+            .swap(false, Ordering::Relaxed)
+        {
+            self.restore_peripherals("status report")?;
+        }
+        Ok(())
+    }
+
+    /// Processes the pending changes after `input_report` was read by a read loop and sets the
+    /// data reporting mode again after a status report, which suspends the data reports.
+    pub(crate) fn process_report_changes(&self, input_report: &InputReport) -> WiimoteResult<()> {
+        self.process_pending_changes()?;
+        if matches!(input_report, InputReport::StatusInformation(_)) {
+            self.restore_reporting_mode()?;
+        }
+        Ok(())
+    }
+
+    /// Applies the state of the device to the reopened Wii remote, which starts out
+    /// without initialized extension, data reporting mode, LEDs and rumble.
+    fn restore_state(&self) -> WiimoteResult<()> {
+        let previous_extension = self.extension().map(|extension| extension.extension());
+        let extension = self.detecting(|| ConnectedExtension::detect(self))?;
+        self.extension_connected
+            .store(extension.is_some(), Ordering::Relaxed);
+        self.set_extension(extension.as_ref());
+        if extension.as_ref().map(ConnectedExtension::extension) != previous_extension {
+            self.publish_extension_changed(extension);
+        }
+
+        // Detecting the extension deactivates the Motion Plus, so it is restored afterwards.
+        if let Some(motion_plus) = self.motion_plus() {
+            motion_plus.reactivate(self, motion_plus.mode())?;
+            if !matches!(self.motion_plus_policy(), MotionPlusPolicy::Manual) {
+                self.apply_motion_plus_policy()?;
+            }
+        }
+
+        self.restore_peripherals("reconnect")?;
+        self.restore_reporting_mode()?;
+        self.write(&OutputReport::PlayerLed(self.leds()))?;
+        if self.device.rumble_enabled() {
+            self.write(&OutputReport::Rumble(true))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the data to the connected Wii remote.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or write failed.
+    pub fn write(&self, output_report: &OutputReport) -> WiimoteResult<()> {
+        self.device.write(output_report)
+    }
+
+    /// Returns the rumble state sent with every output report, set by rumble reports.
+    #[must_use]
+    pub fn rumble_enabled(&self) -> bool {
+        self.device.rumble_enabled()
+    }
+
+    /// Sends a raw output report starting with the report ID to the connected Wii remote,
+    /// e.g. to experiment with undocumented reports.
+    ///
+    /// The rumble bit of the first data byte is set if rumble is enabled and a rumble report (0x10)
+    /// updates the rumble state, unless disabled with [`Self::set_raw_rumble_tracking`].
+    /// Other state of the device, like the data reporting mode, is not updated by raw reports.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the report is empty or too long,
+    /// the Wii remote is disconnected or write failed.
+    pub fn write_raw(&self, data: &[u8]) -> WiimoteResult<()> {
+        self.device.write_raw(data)
+    }
+
+    /// Enables or disables lenient parsing of input reports, disabled by default.
+    ///
+    /// Some clone Wii remotes send reports with unexpected IDs or lengths, which fail to parse
+    /// with [`WiimoteDeviceError::InvalidData`]. While enabled, reads return these reports as
+    /// [`InputReport::Unknown`] instead, see [`InputReport::parse_lenient`].
+    pub fn set_lenient_parsing(&self, enabled: bool) {
+        self.lenient_parsing.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Enables or disables applying and updating the rumble state with raw output reports,
+    /// enabled by default. While disabled, [`Self::write_raw`] sends the bytes unchanged.
+    pub fn set_raw_rumble_tracking(&self, enabled: bool) {
+        self.device
+            .raw_rumble_tracking
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Enables or disables the rumble bit of the current rumble state in speaker data reports,
+    /// enabled by default. While disabled, the ~50 speaker data reports per second do not set the motor
+    /// again, the first one turns it off and clears the rumble state until the next rumble report.
+    pub fn set_speaker_rumble(&self, enabled: bool) {
+        self.device.speaker_rumble.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns whether speaker data reports get the rumble bit, see [`Self::set_speaker_rumble`].
+    #[must_use]
+    pub fn speaker_rumble(&self) -> bool {
+        self.device.speaker_rumble.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn wrap_transport(
+        &self,
+        wrap: impl FnOnce(Box<dyn NativeWiimote>) -> Box<dyn NativeWiimote>,
+    ) -> WiimoteResult<()> {
+        self.device.wrap_transport(wrap)
+    }
+
+    /// Returns the battery state from the last status report, `None` before the first status report.
+    /// Status reports can be requested periodically with [`Self::set_battery_poll_interval`].
+    #[must_use]
+    pub fn battery(&self) -> Option<BatteryStatus> {
+        match self.battery.lock() {
+            Ok(battery) => *battery,
+            Err(battery) => *battery.into_inner(),
+        }
+    }
+
+    /// Requests a status report every `interval` on a background thread, `None` stops the requests.
+    /// The status reports are processed while reading input reports.
+    pub fn set_battery_poll_interval(&self, interval: Option<Duration>) {
+        self.status_poller
+            .get_or_init(|| StatusPoller::new(Arc::clone(&self.device)))
+            .set_interval(interval);
+    }
+
+    /// Publishes [`WiimoteEvent::BatteryLow`] once the battery percentage drops below `percentage`,
+    /// in addition to the battery low flag of the Wii remote. Disabled with 0 (default).
+    pub fn set_low_battery_threshold(&self, percentage: u8) {
+        self.low_battery_threshold
+            .store(percentage, Ordering::Relaxed);
+    }
+
+    /// Enables the idle watchdog with the given configuration, `None` disables it.
+    /// Publishes [`WiimoteEvent::Idle`] after `config.timeout` without button or motion changes
+    /// and [`WiimoteEvent::Active`] once the Wii remote is used again.
+    /// Activity is detected while reading input reports.
+    pub fn set_idle_watchdog(&self, config: Option<IdleConfig>) {
+        self.idle_watchdog
+            .get_or_init(|| IdleWatchdog::new(Arc::clone(&self.device), Arc::clone(&self.events)))
+            .configure(config, &self.device);
+    }
+
+    /// Returns true if the idle watchdog detected that the Wii remote is idle.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.idle_watchdog.get().is_some_and(IdleWatchdog::is_idle)
+    }
+
+    /// Enables the liveness watchdog with the given configuration, `None` disables it.
+    /// A status report is requested after `config.probe_interval` without input reports and the
+    /// Wii remote is disconnected with [`WiimoteEvent::Disconnected`] after `config.deadline`.
+    ///
+    /// Input reports are observed while reading, so the Wii remote needs to be read from continuously.
+    /// Blocking reads wait in short time slices while the watchdog is enabled to notice the deadline.
+    pub fn set_liveness_watchdog(&self, config: Option<LivenessConfig>) {
+        self.liveness_watchdog
+            .get_or_init(|| {
+                LivenessWatchdog::new(Arc::clone(&self.device), Arc::clone(&self.events))
+            })
+            .configure(config);
+    }
+
+    /// Returns the last known state of the player LEDs,
+    /// updated when LEDs are written and from status reports.
+    #[must_use]
+    pub fn leds(&self) -> PlayerLedFlags {
+        PlayerLedFlags::from_bits_truncate(self.device.leds.load(Ordering::Relaxed))
+    }
+
+    /// Returns whether the IR camera is enabled,
+    /// updated when the IR camera is enabled or disabled and from status reports.
+    #[must_use]
+    pub fn is_ir_camera_enabled(&self) -> bool {
+        self.device.ir_camera_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the speaker is enabled,
+    /// updated when the speaker is enabled or disabled and from status reports.
+    #[must_use]
+    pub fn is_speaker_enabled(&self) -> bool {
+        self.device.speaker_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Records the configuration of the IR camera enabled by [`IrCamera::enable`], `None` once disabled.
+    pub(crate) fn set_ir_camera_config(&self, config: Option<(IrMode, IrSensitivity)>) {
+        self.lock_peripherals().ir_camera = config;
+    }
+
+    /// Records the configuration of the speaker initialized by [`Speaker::initialize`], `None` once disabled.
+    pub(crate) fn set_speaker_config(&self, config: Option<SpeakerConfig>) {
+        self.lock_peripherals().speaker = config;
+    }
+
+    fn lock_peripherals(&self) -> MutexGuard<'_, PeripheralConfig> {
+        match self.peripherals.lock() {
+            Ok(peripherals) => peripherals,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Enables the IR camera and the speaker again if they were enabled by the application
+    /// but the Wii remote reset them, e.g. after reconnecting.
+    fn restore_peripherals(&self, cause: &str) -> WiimoteResult<()> {
+        let peripherals = *self.lock_peripherals();
+        if let Some((mode, sensitivity)) = peripherals.ir_camera {
+            if !self.is_ir_camera_enabled() {
+                log::warn!(
+                    "The IR camera of Wii remote {} was reset by the {cause}, enabling it again",
+                    self.identifier
+                );
+                IrCamera::enable(self, mode, sensitivity)?;
+            }
+        }
+        if let Some(config) = peripherals.speaker {
+            if !self.is_speaker_enabled() {
+                log::warn!(
+                    "The speaker of Wii remote {} was reset by the {cause}, enabling it again",
+                    self.identifier
+                );
+                Speaker::initialize(self, config)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Toggles the player LED `led` from 1 to 4, keeping the other LEDs unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `led` is not between 1 and 4 or the write failed.
+    pub fn toggle_led(&self, led: u8) -> WiimoteResult<()> {
+        let flag = match led {
+            1 => PlayerLedFlags::LED_1,
+            2 => PlayerLedFlags::LED_2,
+            3 => PlayerLedFlags::LED_3,
+            4 => PlayerLedFlags::LED_4,
+            _ => return Err(WiimoteDeviceError::InvalidData.into()),
+        };
+        self.write(&OutputReport::PlayerLed(
+            self.leds().symmetric_difference(flag),
+        ))
+    }
+
+    /// Turns the rumble on for `duration`, see [`Self::play_rumble_pattern`].
+    pub fn rumble_for(&self, duration: Duration) {
+        self.play_rumble_pattern(RumblePattern::pulse(duration));
+    }
+
+    /// Plays the rumble pattern on a background thread, replacing the pattern currently playing.
+    /// The rumble returns to the intensity set by [`Self::set_rumble_intensity`] when the pattern
+    /// finished, off by default.
+    ///
+    /// Rumble output reports written while a pattern is playing are overwritten by the pattern.
+    pub fn play_rumble_pattern(&self, pattern: RumblePattern) {
+        self.rumble_scheduler().play(pattern);
+    }
+
+    /// Stops the rumble pattern currently playing, the rumble returns to the set intensity.
+    pub fn stop_rumble_pattern(&self) {
+        if let Some(scheduler) = self.rumble_scheduler.get() {
+            scheduler.play(RumblePattern::new());
+        }
+    }
+
+    /// Sets the perceived rumble intensity from 0 (off) to 1 (always on).
+    ///
+    /// The rumble motor can only be turned on or off, so intensities in between are approximated
+    /// by toggling the motor on a background thread. Rumble patterns temporarily replace the intensity.
+    pub fn set_rumble_intensity(&self, intensity: f32) {
+        self.rumble_scheduler().set_intensity(intensity);
+    }
+
+    fn rumble_scheduler(&self) -> &RumbleScheduler {
+        self.rumble_scheduler
+            .get_or_init(|| RumbleScheduler::new(Arc::clone(&self.device)))
+    }
+
+    /// Cancels the pending I/O of the Wii remote, so threads blocked in [`Self::read`]
+    /// wake with `WiimoteError::Disconnected` instead of waiting for the next report,
+    /// e.g. before exiting the process. The Wii remote reconnects on the next scan.
+    ///
+    /// Custom transports without a separate writer are not cancelled.
+    pub fn cancel_pending_io(&self) {
+        self.device.cancel_pending_io();
+    }
+
+    /// Returns the state shared with background threads, usable while a read holds the device.
+    #[cfg(feature = "manager-thread")]
+    pub(crate) fn shared_device(&self) -> Arc<SharedDevice> {
+        Arc::clone(&self.device)
+    }
+
+    /// Reads data from the connected Wii remote.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or read failed.
+    pub fn read(&self) -> WiimoteResult<InputReport> {
+        let input_report = self.read_report(None)?;
+        self.handle_report(&input_report);
+        Ok(input_report)
     }
 
     /// Reads data from the connected Wii remote waiting for a maximum of `timeout_millis`.
@@ -199,57 +1286,615 @@ impl WiimoteDevice {
     ///
     /// This function will return an error if the Wii remote is disconnected or read failed.
     pub fn read_timeout(&self, timeout_millis: usize) -> WiimoteResult<InputReport> {
-        let mut device = match self.device.lock() {
-            Ok(device) => device,
-            Err(err) => err.into_inner(),
+        let input_report = self.read_report(Some(timeout_millis))?;
+        self.handle_report(&input_report);
+        Ok(input_report)
+    }
+
+    /// Reads data from the connected Wii remote without blocking the thread while waiting.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or read failed.
+    #[cfg(feature = "tokio")]
+    pub async fn read_async(&self) -> WiimoteResult<InputReport> {
+        std::future::poll_fn(|cx| self.poll_read(cx)).await
+    }
+
+    /// Returns a `Stream` of the input reports of the Wii remote,
+    /// the stream ends after the Wii remote disconnected.
+    #[cfg(feature = "tokio")]
+    #[must_use]
+    pub const fn report_stream(&self) -> ReportStream<'_> {
+        ReportStream::new(self)
+    }
+
+    /// Reads and processes an input report once available, registers the waker of `cx` otherwise.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn poll_read(&self, cx: &mut Context<'_>) -> Poll<WiimoteResult<InputReport>> {
+        if let Some(peeked) = self.take_peeked_report() {
+            let (report, bytes_read) = peeked?;
+            let input_report = self.parse_report(&report[..bytes_read])?;
+            self.handle_report(&input_report);
+            return Poll::Ready(Ok(input_report));
+        }
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        let was_connected = self.is_connected();
+        let bytes_read = match std::task::ready!(self.device.poll_read(cx, &mut buffer)) {
+            Ok(bytes_read) => {
+                if let Some(liveness_watchdog) = self.liveness_watchdog.get() {
+                    liveness_watchdog.observe();
+                }
+                self.observe_arrival(&buffer[..bytes_read], self.device.last_received());
+                bytes_read
+            }
+            Err(err) => {
+                if was_connected {
+                    self.events.publish(&WiimoteEvent::Disconnected);
+                }
+                return Poll::Ready(Err(err));
+            }
         };
-        if let Some(device) = device.as_mut() {
-            let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
-            if let Some(bytes_read) = device.read_timeout(&mut buffer, timeout_millis) {
-                return InputReport::try_from(&buffer[..bytes_read]);
+        let input_report = self.parse_report(&buffer[..bytes_read])?;
+        self.handle_report(&input_report);
+        Poll::Ready(Ok(input_report))
+    }
+
+    /// Sends data to the connected Wii remote.
+    /// Output reports are only a few bytes and are written without waiting for a response,
+    /// so this completes after the report was handed to the operating system.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or write failed.
+    #[cfg(feature = "tokio")]
+    #[allow(clippy::unused_async)]
+    pub async fn write_async(&self, output_report: &OutputReport) -> WiimoteResult<()> {
+        self.write(output_report)
+    }
+
+    /// Sends the last data reporting mode again, the Wii remote stops sending
+    /// data reports after a status report until the data reporting mode is set.
+    pub(crate) fn restore_reporting_mode(&self) -> WiimoteResult<()> {
+        match self.device.reporting_mode() {
+            Some(mode) => self.write(&OutputReport::DataReportingMode(mode)),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads data from the connected Wii remote without updating the device state.
+    pub(crate) fn read_report(&self, timeout_millis: Option<usize>) -> WiimoteResult<InputReport> {
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        let bytes_read = self.read_bytes(&mut buffer, timeout_millis)?;
+        self.parse_report(&buffer[..bytes_read])
+    }
+
+    /// Parses the report strictly or leniently, see [`Self::set_lenient_parsing`].
+    fn parse_report(&self, bytes: &[u8]) -> WiimoteResult<InputReport> {
+        if self.lenient_parsing.load(Ordering::Relaxed) {
+            InputReport::parse_lenient(bytes)
+        } else {
+            InputReport::try_from(bytes)
+        }
+    }
+
+    /// Reads the raw bytes of an input report starting with the report ID into `buffer`,
+    /// waiting for a maximum of `timeout_millis` if set. Reports longer than `buffer` are truncated.
+    ///
+    /// Returns the number of bytes copied, 0 if no report was received within `timeout_millis`.
+    /// Known reports update the device state like [`Self::read`], unknown reports are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or read failed.
+    pub fn read_raw(
+        &self,
+        buffer: &mut [u8],
+        timeout_millis: Option<usize>,
+    ) -> WiimoteResult<usize> {
+        let mut report = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        let bytes_read = self.read_bytes(&mut report, timeout_millis)?;
+        if let Ok(input_report) = InputReport::try_from(&report[..bytes_read]) {
+            self.handle_report(&input_report);
+        }
+        let size = usize::min(bytes_read, buffer.len());
+        buffer[..size].copy_from_slice(&report[..size]);
+        Ok(size)
+    }
+
+    fn read_bytes(&self, buffer: &mut [u8], timeout_millis: Option<usize>) -> WiimoteResult<usize> {
+        if let Some(peeked) = self.take_peeked_report() {
+            let (report, bytes_read) = peeked?;
+            let size = usize::min(bytes_read, buffer.len());
+            buffer[..size].copy_from_slice(&report[..size]);
+            return Ok(size);
+        }
+        self.read_unpeeked(buffer, timeout_millis)
+    }
+
+    pub(crate) fn read_unpeeked(
+        &self,
+        buffer: &mut [u8],
+        timeout_millis: Option<usize>,
+    ) -> WiimoteResult<usize> {
+        let result = self.read_watched(buffer, timeout_millis);
+        if result.is_err() {
+            self.start_auto_reconnect();
+        }
+        result
+    }
+
+    fn read_watched(
+        &self,
+        buffer: &mut [u8],
+        timeout_millis: Option<usize>,
+    ) -> WiimoteResult<usize> {
+        let Some(liveness_watchdog) = self
+            .liveness_watchdog
+            .get()
+            .filter(|watchdog| watchdog.is_enabled())
+        else {
+            return self.read_transport(buffer, timeout_millis);
+        };
+
+        // Reads are split into time slices, so the transport can be closed once the deadline passed
+        // even if the transport never returns from a blocking read of a stalled Wii remote.
+        let deadline =
+            timeout_millis.map(|millis| Instant::now() + Duration::from_millis(millis as u64));
+        loop {
+            let slice_millis = deadline.map_or(LIVENESS_READ_SLICE_MILLIS, |deadline| {
+                let remaining = deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_millis();
+                usize::try_from(remaining).map_or(LIVENESS_READ_SLICE_MILLIS, |remaining| {
+                    remaining.min(LIVENESS_READ_SLICE_MILLIS)
+                })
+            });
+            let bytes_read = self.read_transport(buffer, Some(slice_millis))?;
+            if bytes_read > 0 {
+                liveness_watchdog.observe();
+                return Ok(bytes_read);
+            }
+            if liveness_watchdog.take_close_pending() {
+                self.device.close(&mut self.device.lock());
+                self.events.publish(&WiimoteEvent::Disconnected);
+                return Err(WiimoteError::Disconnected);
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok(0);
+            }
+        }
+    }
+
+    /// Records the arrival of a data report for [`Self::report_rate`],
+    /// at the time the transport received it if it is known.
+    fn observe_arrival(&self, report: &[u8], received: Option<Instant>) {
+        if matches!(report.first(), Some(0x30..=0x3F)) {
+            self.report_rate
+                .observe(received.unwrap_or_else(Instant::now));
+        }
+    }
+
+    fn read_transport(
+        &self,
+        buffer: &mut [u8],
+        timeout_millis: Option<usize>,
+    ) -> WiimoteResult<usize> {
+        let mut device_guard = self.device.lock();
+        if let Some(device) = device_guard.as_mut() {
+            let bytes_read = match timeout_millis {
+                Some(timeout_millis) => device.read_timeout(buffer, timeout_millis),
+                None => device.read(buffer),
+            };
+            match bytes_read {
+                Ok(bytes_read) => {
+                    self.observe_arrival(&buffer[..bytes_read], device.last_received());
+                    return Ok(bytes_read);
+                }
+                Err(err) => {
+                    log::warn!(
+                        "Reading from Wii remote {} failed: {err:?}",
+                        self.identifier
+                    );
+                    self.device.close(&mut device_guard);
+                    drop(device_guard);
+                    self.events.publish(&WiimoteEvent::Disconnected);
+                    return Err(err);
+                }
             }
         }
-        _ = device.take();
         Err(WiimoteError::Disconnected)
     }
 
-    fn initialize(&mut self) -> WiimoteResult<()> {
-        self.motion_plus = None;
-        self.extension = None;
+    /// Updates the device state from the report, changes that exchange further reports
+    /// with the Wii remote are left to [`Self::process_pending_changes`].
+    pub(crate) fn handle_report(&self, input_report: &InputReport) {
+        self.events.publish(&WiimoteEvent::Report(*input_report));
+        match input_report {
+            InputReport::StatusInformation(status) => {
+                // The LED flags of the status report use the same bits as the output report.
+                let leds = status.flags().bits() & PlayerLedFlags::all().bits();
+                self.device.leds.store(leds, Ordering::Relaxed);
+                self.handle_battery_status(status);
 
-        self.calibration_data = self.read_calibration_data()?;
-        self.motion_plus = MotionPlus::detect(self)?;
-        self.extension = WiimoteExtension::detect(self)?;
-        Ok(())
+                let flags = status.flags();
+                self.device.ir_camera_enabled.store(
+                    flags.contains(StatusFlags::IR_CAMERA_ENABLED),
+                    Ordering::Relaxed,
+                );
+                self.device.speaker_enabled.store(
+                    flags.contains(StatusFlags::SPEAKER_ENABLED),
+                    Ordering::Relaxed,
+                );
+                self.peripherals_check_pending
+                    .store(true, Ordering::Relaxed);
+
+                let connected = status
+                    .flags()
+                    .contains(StatusFlags::EXTENSION_CONTROLLER_CONNECTED);
+                // A detection reading this status report determines the extension itself.
+                if !self.detecting_extension.load(Ordering::Relaxed)
+                    && self.extension_connected.swap(connected, Ordering::Relaxed) != connected
+                {
+                    self.extension_refresh_pending
+                        .store(true, Ordering::Relaxed);
+                }
+
+                // The status report requested by the liveness watchdog suspended the data reports.
+                let probed = self
+                    .liveness_watchdog
+                    .get()
+                    .is_some_and(LivenessWatchdog::take_probe_response);
+                if probed {
+                    _ = self.restore_reporting_mode();
+                }
+            }
+            InputReport::DataReport(_, wiimote_data) => {
+                if let Some(idle_watchdog) = self.idle_watchdog.get() {
+                    idle_watchdog.observe(wiimote_data, &self.device, &self.events);
+                }
+                self.handle_motion_plus_data(wiimote_data);
+                self.handle_balance_board_status(wiimote_data);
+            }
+            _ => {}
+        }
+        self.report_subscribers.publish(input_report);
     }
 
-    fn read_calibration_data(&mut self) -> WiimoteResult<AccelerometerCalibration> {
-        // https://www.wiibrew.org/wiki/Wiimote#EEPROM_Memory
-        // The four bytes starting at 0x0016 and 0x0020 store the calibrated zero offsets for the accelerometer
-        // (high 8 bits of X,Y,Z in the first three bytes, low 2 bits packed in the fourth byte as --XXYYZZ).
-        // The four bytes at 0x001A and 0x24 store the force of gravity on those axes.
-        let data = simple_io::read_16_bytes_sync_checked(self, Addressing::eeprom(0x0016, 10))?;
+    fn handle_motion_plus_data(&self, wiimote_data: &WiimoteData) {
+        let Some(motion_plus) = self.motion_plus() else {
+            return;
+        };
+        if matches!(motion_plus.mode(), MotionPlusMode::Inactive)
+            || !motion_plus.is_auto_calibrating()
+        {
+            return;
+        }
 
-        let mut checksum = 0x55u8;
-        for byte in &data[..9] {
-            checksum = checksum.wrapping_add(*byte);
+        let mut motion_plus_buffer = [0u8; 6];
+        match wiimote_data.extension_data().and_then(|data| data.get(..6)) {
+            Some(data) => motion_plus_buffer.copy_from_slice(data),
+            None => return,
         }
-        if checksum != data[9] {
-            return Err(WiimoteDeviceError::InvalidChecksum.into());
+        if let Ok(motion_plus_data) = MotionPlusData::try_from(motion_plus_buffer) {
+            motion_plus.process_auto_calibration(&motion_plus_data);
         }
+    }
 
-        Ok(AccelerometerCalibration {
-            x_zero_offset: ((data[0] as u16) << 2) | ((data[3] as u16) >> 4 & 0b11),
-            y_zero_offset: ((data[1] as u16) << 2) | ((data[3] as u16) >> 2 & 0b11),
-            z_zero_offset: ((data[2] as u16) << 2) | ((data[3] as u16) & 0b11),
-            x_gravity: ((data[4] as u16) << 2) | ((data[7] as u16) >> 4 & 0b11),
-            y_gravity: ((data[5] as u16) << 2) | ((data[7] as u16) >> 2 & 0b11),
-            z_gravity: ((data[6] as u16) << 2) | ((data[7] as u16) & 0b11),
+    fn handle_battery_status(&self, status: &StatusData) {
+        let battery = BatteryStatus {
+            level: status.battery_level(),
+            low: status.flags().contains(StatusFlags::BATTERY_LOW),
+        };
+        let threshold = self.low_battery_threshold.load(Ordering::Relaxed);
+        let is_low = |battery: &BatteryStatus| battery.low || battery.percentage() < threshold;
+
+        let mut last_battery = match self.battery.lock() {
+            Ok(last_battery) => last_battery,
+            Err(last_battery) => last_battery.into_inner(),
+        };
+        let became_low = is_low(&battery) && !last_battery.as_ref().is_some_and(is_low);
+        *last_battery = Some(battery);
+        drop(last_battery);
+
+        if became_low {
+            self.events.publish(&WiimoteEvent::BatteryLow(battery));
+        }
+    }
+
+    fn handle_balance_board_status(&self, wiimote_data: &WiimoteData) {
+        // Only the status bytes are parsed, this runs for every data report.
+        let is_balance_board = self
+            .extension()
+            .is_some_and(|extension| extension.extension() == WiimoteExtension::BalanceBoard);
+        if !is_balance_board {
+            return;
+        }
+        let Some(status) = wiimote_data
+            .extension_data()
+            .and_then(BalanceBoardStatus::from_extension_data)
+        else {
+            return;
+        };
+
+        let mut last_temperature = match self.balance_board_temperature.lock() {
+            Ok(temperature) => temperature,
+            Err(temperature) => temperature.into_inner(),
+        };
+        let changed = last_temperature.map_or(true, |last| {
+            last.abs_diff(status.temperature) >= TEMPERATURE_CHANGE_THRESHOLD
+        });
+        if changed {
+            *last_temperature = Some(status.temperature);
+            drop(last_temperature);
+            self.events
+                .publish(&WiimoteEvent::BalanceBoardTemperatureChanged(status));
+        }
+    }
+
+    fn refresh_extension_state(&self, connected: bool) {
+        // A failed detection is treated like a disconnected extension,
+        // the status report is still returned so the data reporting mode can be restored.
+        let extension = if connected {
+            self.detecting(|| ConnectedExtension::detect(self))
+                .unwrap_or(None)
+        } else {
+            None
+        };
+
+        self.set_extension(extension.as_ref());
+        self.detecting(|| self.refresh_motion_plus(connected));
+
+        // Extension detection deactivates the Motion Plus, so the mode is applied again.
+        if let Some(motion_plus) = self.motion_plus() {
+            let mode = motion_plus.mode();
+            if matches!(self.motion_plus_policy(), MotionPlusPolicy::Manual) {
+                if !matches!(mode, MotionPlusMode::Inactive) {
+                    _ = motion_plus.change_mode(self, mode);
+                }
+            } else {
+                _ = self.apply_motion_plus_policy();
+            }
+        }
+
+        self.publish_extension_changed(extension);
+    }
+
+    /// Detects an external Motion Plus plugged in or unplugged at runtime,
+    /// which is signaled by the status report like an extension change.
+    fn refresh_motion_plus(&self, connected: bool) {
+        let previous = self.motion_plus();
+        let detected = match &previous {
+            Some(motion_plus) if motion_plus.motion_plus_type() == MotionPlusType::Builtin => {
+                return;
+            }
+            // An active Motion Plus identifies as the extension, the extension flag is only
+            // cleared once it was unplugged.
+            Some(motion_plus) if !matches!(motion_plus.mode(), MotionPlusMode::Inactive) => {
+                if connected {
+                    return;
+                }
+                None
+            }
+            _ => match MotionPlus::detect(self) {
+                Ok(detected) => detected.map(Arc::new),
+                Err(_) => return,
+            },
+        };
+        if previous.is_some() != detected.is_some() {
+            self.set_motion_plus(detected.clone());
+            self.publish_motion_plus_changed(previous.as_ref(), detected.as_ref());
+        }
+    }
+
+    fn publish_motion_plus_changed(
+        &self,
+        previous: Option<&Arc<MotionPlus>>,
+        current: Option<&Arc<MotionPlus>>,
+    ) {
+        match (previous, current) {
+            (None, Some(motion_plus)) => self.events.publish(&WiimoteEvent::MotionPlusAttached(
+                motion_plus.motion_plus_type(),
+            )),
+            (Some(_), None) => self.events.publish(&WiimoteEvent::MotionPlusDetached),
+            _ => {}
+        }
+    }
+
+    /// Detects the Motion Plus and the extension again, e.g. for applications that do not read the
+    /// status reports signaling extension changes. Publishes [`WiimoteEvent::ExtensionChanged`]
+    /// if the extension changed.
+    ///
+    /// An active Motion Plus is deactivated for the detection, afterwards the mode is applied
+    /// by the [`MotionPlusPolicy`] or the previous mode is activated again with [`MotionPlusPolicy::Manual`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if communication to the Wii remote failed.
+    pub fn refresh_extension(&mut self) -> WiimoteResult<ExtensionState> {
+        // An active Motion Plus is only visible at the extension address.
+        let previous_mode = self.motion_plus().map(|motion_plus| motion_plus.mode());
+        if let Some(motion_plus) = self.motion_plus() {
+            if !matches!(motion_plus.mode(), MotionPlusMode::Inactive) {
+                motion_plus.change_mode(self, MotionPlusMode::Inactive)?;
+            }
+        }
+        let detected = self.detecting(|| MotionPlus::detect(self))?;
+        // The same Motion Plus keeps its calibration.
+        let previous = self.motion_plus();
+        let motion_plus = match (previous.clone(), detected.map(Arc::new)) {
+            (Some(current), Some(detected))
+                if current.motion_plus_type() == detected.motion_plus_type() =>
+            {
+                Some(current)
+            }
+            (_, detected) => detected,
+        };
+        self.set_motion_plus(motion_plus.clone());
+        self.publish_motion_plus_changed(previous.as_ref(), motion_plus.as_ref());
+
+        let previous_extension = self.extension().map(|extension| extension.extension());
+        let extension = self.detecting(|| ConnectedExtension::detect(self))?;
+        self.extension_connected
+            .store(extension.is_some(), Ordering::Relaxed);
+        // A Wii remote Plus or balance board found only now, e.g. by a transport without product ID.
+        self.model = self.model.detected(
+            motion_plus
+                .as_ref()
+                .map(|motion_plus| motion_plus.motion_plus_type()),
+            extension.as_ref().map(ConnectedExtension::extension),
+        );
+        self.set_extension(extension.as_ref());
+        if extension.as_ref().map(ConnectedExtension::extension) != previous_extension {
+            self.publish_extension_changed(extension.clone());
+        }
+
+        if let Some(motion_plus) = self.motion_plus() {
+            if matches!(self.motion_plus_policy(), MotionPlusPolicy::Manual) {
+                if let Some(mode) = previous_mode {
+                    motion_plus.reactivate(self, mode)?;
+                }
+            } else {
+                self.apply_motion_plus_policy()?;
+            }
+        }
+
+        Ok(ExtensionState {
+            extension,
+            motion_plus: self
+                .motion_plus()
+                .map(|motion_plus| motion_plus.motion_plus_type()),
         })
     }
 
+    /// Detects the extension again without changing the mode of the Motion Plus.
+    pub(crate) fn redetect_extension(&self) -> WiimoteResult<()> {
+        let extension = self.detecting(|| ConnectedExtension::detect(self))?;
+        self.extension_connected
+            .store(extension.is_some(), Ordering::Relaxed);
+        self.set_extension(extension.as_ref());
+        self.publish_extension_changed(extension);
+        Ok(())
+    }
+
+    /// Runs an extension or Motion Plus detection, status reports read meanwhile do not start another one.
+    fn detecting<T>(&self, detect: impl FnOnce() -> T) -> T {
+        let nested = self.detecting_extension.swap(true, Ordering::Relaxed);
+        let result = detect();
+        self.detecting_extension.store(nested, Ordering::Relaxed);
+        result
+    }
+
+    fn set_extension(&self, extension: Option<&ConnectedExtension>) {
+        let extension = extension.cloned().map(Arc::new);
+        match self.extension.write() {
+            Ok(mut current) => *current = extension,
+            Err(current) => *current.into_inner() = extension,
+        }
+    }
+
+    fn publish_extension_changed(&self, extension: Option<ConnectedExtension>) {
+        _ = self
+            .balance_board_temperature
+            .lock()
+            .map(|mut temperature| temperature.take());
+        self.events
+            .publish(&WiimoteEvent::ExtensionChanged(extension));
+    }
+
+    fn initialize(&mut self) -> WiimoteResult<()> {
+        enter_span!(INFO, "initialize", identifier = %self.identifier, model = ?self.model);
+        *self.detecting_extension.get_mut() = true;
+        let result = self.initialize_detecting();
+        *self.detecting_extension.get_mut() = false;
+        result
+    }
+
+    fn initialize_detecting(&mut self) -> WiimoteResult<()> {
+        self.set_motion_plus(None);
+        self.set_extension(None);
+
+        // The extension is initialized while the calibration is read, so it is ready to be identified
+        // after the Motion Plus detection without waiting for the acknowledgements in between.
+        self.report_progress(InitPhase::ReadingCalibration);
+        let (calibration, acknowledgements) = self
+            .read_calibration_data()
+            .map_err(|err| err.during(InitPhase::ReadingCalibration))?;
+        self.calibration_data = calibration;
+        self.report_progress(InitPhase::DetectingMotionPlus);
+        // The Wii remote Plus always has a built-in Motion Plus, which saves the detection round trip.
+        let motion_plus = if self.model == WiimoteModel::Plus {
+            Some(MotionPlus::new(MotionPlusType::Builtin))
+        } else {
+            MotionPlus::detect(self).map_err(|err| err.during(InitPhase::DetectingMotionPlus))?
+        };
+        self.set_motion_plus(motion_plus.map(Arc::new));
+        self.report_progress(InitPhase::DetectingExtension);
+        let extension = ConnectedExtension::detect_initialized(self, &acknowledgements)
+            .map_err(|err| err.during(InitPhase::DetectingExtension))?;
+        self.extension_connected
+            .store(extension.is_some(), Ordering::Relaxed);
+        self.model = self.model.detected(
+            self.motion_plus()
+                .map(|motion_plus| motion_plus.motion_plus_type()),
+            extension.as_ref().map(ConnectedExtension::extension),
+        );
+        self.set_extension(extension.as_ref());
+        self.report_progress(InitPhase::ActivatingMotionPlus);
+        self.apply_motion_plus_policy()
+            .map_err(|err| err.during(InitPhase::ActivatingMotionPlus))?;
+        self.report_progress(InitPhase::Ready);
+        Ok(())
+    }
+
+    fn report_progress(&self, progress: InitPhase) {
+        progress::report(self.progress.as_ref(), &self.identifier, progress);
+    }
+
+    /// Reads the accelerometer calibration and initializes the extension in the same exchange,
+    /// returns the acknowledgements of the extension initialization.
+    fn read_calibration_data(
+        &mut self,
+    ) -> WiimoteResult<(AccelerometerCalibration, Vec<AcknowledgeData>)> {
+        let addressing = Addressing::eeprom(CalibrationBlock::Primary.address(), 10);
+        let mut requests = vec![MemoryRequest::Read(addressing)];
+        requests.extend(WiimoteExtension::initialization_requests());
+
+        let mut primary = Err(WiimoteDeviceError::MissingData.into());
+        let mut acknowledgements = Vec::new();
+        for response in simple_io::exchange(self, &requests)? {
+            match response {
+                MemoryResponse::Read(memory_data) => {
+                    primary =
+                        simple_io::check_memory_data(&memory_data, addressing).and_then(|data| {
+                            AccelerometerCalibration::from_eeprom(&data, CalibrationBlock::Primary)
+                        });
+                }
+                MemoryResponse::Write(ack) => acknowledgements.push(ack),
+            }
+        }
+
+        // The calibration is stored twice, the block at 0x0020 is used if the checksum of the block at 0x0016 fails.
+        let calibration = match primary {
+            Err(WiimoteError::WiimoteDeviceError(WiimoteDeviceError::InvalidChecksum)) => {
+                self.read_calibration_block(CalibrationBlock::Secondary)?
+            }
+            result => result?,
+        };
+        Ok((calibration, acknowledgements))
+    }
+
+    fn read_calibration_block(
+        &self,
+        block: CalibrationBlock,
+    ) -> WiimoteResult<AccelerometerCalibration> {
+        let data =
+            simple_io::read_16_bytes_sync_checked(self, Addressing::eeprom(block.address(), 10))?;
+        AccelerometerCalibration::from_eeprom(&data, block)
+    }
+
     fn disconnected(&self) {
-        _ = self.device.lock().map(|mut device| device.take());
+        self.device.disconnect();
     }
 }
 
@@ -258,3 +1903,528 @@ impl Drop for WiimoteDevice {
         self.disconnected();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<WiimoteDevice>();
+        assert_send_sync::<SharedDevice>();
+    }
+
+    #[test]
+    fn test_calibration_block() {
+        let mut data = [
+            0x80,
+            0x80,
+            0x80,
+            0b0001_1011,
+            0x9A,
+            0x9A,
+            0x9A,
+            0x00,
+            0x00,
+            0x00,
+        ];
+        data[9] = data[..9]
+            .iter()
+            .fold(0x55u8, |checksum, byte| checksum.wrapping_add(*byte));
+
+        let calibration = AccelerometerCalibration::from_eeprom(&data, CalibrationBlock::Secondary);
+        let Ok(calibration) = calibration else {
+            panic!("valid checksum rejected");
+        };
+        assert_eq!(calibration.block(), CalibrationBlock::Secondary);
+        assert_eq!(calibration.x_zero_offset, 0x201);
+        assert_eq!(calibration.y_zero_offset, 0x202);
+        assert_eq!(calibration.z_zero_offset, 0x203);
+        assert_eq!(calibration.x_gravity, 0x268);
+
+        data[9] = data[9].wrapping_add(1);
+        assert!(AccelerometerCalibration::from_eeprom(&data, CalibrationBlock::Primary).is_err());
+    }
+
+    /// Blocks reads until released and writes through a separate writer.
+    struct SplitTransport {
+        inner: Box<dyn NativeWiimote>,
+        release: crossbeam_channel::Receiver<()>,
+        written: crossbeam_channel::Sender<Vec<u8>>,
+    }
+
+    struct ChannelWriter(crossbeam_channel::Sender<Vec<u8>>);
+
+    impl NativeWiimoteWriter for ChannelWriter {
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            _ = self.0.send(buffer.to_vec());
+            Ok(buffer.len())
+        }
+    }
+
+    impl NativeWiimote for SplitTransport {
+        fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+            _ = self.release.recv();
+            self.inner.read(buffer)
+        }
+
+        fn read_timeout(&mut self, buffer: &mut [u8], _: usize) -> WiimoteResult<usize> {
+            self.read(buffer)
+        }
+
+        #[cfg(feature = "tokio")]
+        fn poll_read(
+            &mut self,
+            _: &mut std::task::Context<'_>,
+            buffer: &mut [u8],
+        ) -> std::task::Poll<WiimoteResult<usize>> {
+            std::task::Poll::Ready(self.read(buffer))
+        }
+
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            self.inner.write(buffer)
+        }
+
+        fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+            Some(Box::new(ChannelWriter(self.written.clone())))
+        }
+
+        fn identifier(&self) -> &str {
+            self.inner.identifier()
+        }
+
+        fn model(&self) -> WiimoteModel {
+            self.inner.model()
+        }
+    }
+
+    #[test]
+    fn test_write_during_blocking_read() {
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let wiimote = Arc::new(WiimoteDevice::with_transport(simulator.clone()).unwrap());
+        let (release_sender, release) = crossbeam_channel::unbounded();
+        let (written, written_receiver) = crossbeam_channel::unbounded();
+        wiimote
+            .wrap_transport(|inner| {
+                Box::new(SplitTransport {
+                    inner,
+                    release,
+                    written,
+                })
+            })
+            .unwrap();
+
+        let reader = {
+            let wiimote = Arc::clone(&wiimote);
+            std::thread::spawn(move || wiimote.read())
+        };
+        std::thread::sleep(Duration::from_millis(20));
+
+        wiimote
+            .write(&OutputReport::PlayerLed(PlayerLedFlags::LED_2))
+            .unwrap();
+        assert_eq!(written_receiver.try_recv().unwrap(), [0x11, 0x20]);
+        assert!(!reader.is_finished());
+
+        simulator.disconnect();
+        release_sender.send(()).unwrap();
+        assert!(reader.join().unwrap().is_err());
+    }
+
+    /// A transport whose writer fails with a timeout while `full` is set, like a full send buffer.
+    struct FullSendBufferTransport {
+        inner: crate::simulator::SimulatedWiimote,
+        full: Arc<AtomicBool>,
+    }
+
+    struct FullSendBufferWriter {
+        inner: crate::simulator::SimulatedWiimote,
+        full: Arc<AtomicBool>,
+    }
+
+    impl NativeWiimoteWriter for FullSendBufferWriter {
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            if self.full.load(Ordering::Relaxed) {
+                return Err(WiimoteError::WriteTimedOut);
+            }
+            self.inner.write(buffer)
+        }
+    }
+
+    impl NativeWiimote for FullSendBufferTransport {
+        fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+            self.inner.read(buffer)
+        }
+
+        fn read_timeout(
+            &mut self,
+            buffer: &mut [u8],
+            timeout_millis: usize,
+        ) -> WiimoteResult<usize> {
+            self.inner.read_timeout(buffer, timeout_millis)
+        }
+
+        #[cfg(feature = "tokio")]
+        fn poll_read(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+            buffer: &mut [u8],
+        ) -> std::task::Poll<WiimoteResult<usize>> {
+            self.inner.poll_read(cx, buffer)
+        }
+
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            self.inner.write(buffer)
+        }
+
+        fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+            Some(Box::new(FullSendBufferWriter {
+                inner: self.inner.clone(),
+                full: Arc::clone(&self.full),
+            }))
+        }
+
+        fn identifier(&self) -> &str {
+            self.inner.identifier()
+        }
+
+        fn model(&self) -> WiimoteModel {
+            self.inner.model()
+        }
+    }
+
+    #[test]
+    fn test_write_timeout_keeps_connection() {
+        let full = Arc::new(AtomicBool::new(false));
+        let wiimote = WiimoteDevice::with_transport(FullSendBufferTransport {
+            inner: crate::simulator::SimulatedWiimote::default(),
+            full: Arc::clone(&full),
+        })
+        .unwrap();
+
+        full.store(true, Ordering::Relaxed);
+        let result = wiimote.write(&OutputReport::StatusRequest);
+        assert!(matches!(result, Err(WiimoteError::WriteTimedOut)));
+        assert!(wiimote.is_connected());
+
+        full.store(false, Ordering::Relaxed);
+        wiimote.write(&OutputReport::StatusRequest).unwrap();
+        assert!(matches!(
+            wiimote.read_timeout(0),
+            Ok(InputReport::StatusInformation(_))
+        ));
+    }
+
+    /// Blocks reads until the pending I/O is cancelled through the writer.
+    struct CancellableTransport {
+        inner: Box<dyn NativeWiimote>,
+        cancel: crossbeam_channel::Sender<()>,
+        cancelled: crossbeam_channel::Receiver<()>,
+    }
+
+    struct CancellingWriter(crossbeam_channel::Sender<()>);
+
+    impl NativeWiimoteWriter for CancellingWriter {
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            Ok(buffer.len())
+        }
+
+        fn cancel_pending_io(&mut self) {
+            _ = self.0.send(());
+        }
+    }
+
+    impl NativeWiimote for CancellableTransport {
+        fn read(&mut self, _: &mut [u8]) -> WiimoteResult<usize> {
+            _ = self.cancelled.recv();
+            Err(WiimoteError::Disconnected)
+        }
+
+        fn read_timeout(&mut self, buffer: &mut [u8], _: usize) -> WiimoteResult<usize> {
+            self.read(buffer)
+        }
+
+        #[cfg(feature = "tokio")]
+        fn poll_read(
+            &mut self,
+            _: &mut std::task::Context<'_>,
+            buffer: &mut [u8],
+        ) -> std::task::Poll<WiimoteResult<usize>> {
+            std::task::Poll::Ready(self.read(buffer))
+        }
+
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            self.inner.write(buffer)
+        }
+
+        fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+            Some(Box::new(CancellingWriter(self.cancel.clone())))
+        }
+
+        fn identifier(&self) -> &str {
+            self.inner.identifier()
+        }
+
+        fn model(&self) -> WiimoteModel {
+            self.inner.model()
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_poll_read_returns_peeked_report() {
+        struct NoopWaker;
+
+        impl std::task::Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let wiimote =
+            WiimoteDevice::with_transport(crate::simulator::SimulatedWiimote::default()).unwrap();
+        wiimote.write(&OutputReport::StatusRequest).unwrap();
+        assert!(wiimote.peek_report());
+
+        let waker = std::task::Waker::from(Arc::new(NoopWaker));
+        let result = wiimote.poll_read(&mut Context::from_waker(&waker));
+        assert!(matches!(
+            result,
+            Poll::Ready(Ok(InputReport::StatusInformation(_)))
+        ));
+        assert!(wiimote.take_peeked_report().is_none());
+    }
+
+    #[test]
+    fn test_cancel_pending_io() {
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let wiimote = Arc::new(WiimoteDevice::with_transport(simulator).unwrap());
+        let (cancel, cancelled) = crossbeam_channel::unbounded();
+        wiimote
+            .wrap_transport(|inner| {
+                Box::new(CancellableTransport {
+                    inner,
+                    cancel,
+                    cancelled,
+                })
+            })
+            .unwrap();
+
+        let reader = {
+            let wiimote = Arc::clone(&wiimote);
+            std::thread::spawn(move || wiimote.read())
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!reader.is_finished());
+
+        wiimote.cancel_pending_io();
+        assert!(matches!(
+            reader.join().unwrap(),
+            Err(WiimoteError::Disconnected)
+        ));
+        assert!(!wiimote.is_connected());
+    }
+
+    /// Never returns an input report, blocking reads wait forever like a stalled Wii remote.
+    struct StalledTransport {
+        inner: Box<dyn NativeWiimote>,
+    }
+
+    impl NativeWiimote for StalledTransport {
+        fn read(&mut self, _: &mut [u8]) -> WiimoteResult<usize> {
+            loop {
+                std::thread::park();
+            }
+        }
+
+        fn read_timeout(&mut self, _: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize> {
+            std::thread::sleep(Duration::from_millis(timeout_millis as u64));
+            Ok(0)
+        }
+
+        #[cfg(feature = "tokio")]
+        fn poll_read(
+            &mut self,
+            _: &mut std::task::Context<'_>,
+            _: &mut [u8],
+        ) -> std::task::Poll<WiimoteResult<usize>> {
+            std::task::Poll::Pending
+        }
+
+        fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+            self.inner.write(buffer)
+        }
+
+        fn identifier(&self) -> &str {
+            self.inner.identifier()
+        }
+
+        fn model(&self) -> WiimoteModel {
+            self.inner.model()
+        }
+    }
+
+    #[test]
+    fn test_liveness_deadline() {
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let wiimote = WiimoteDevice::with_transport(simulator.clone()).unwrap();
+        wiimote
+            .wrap_transport(|inner| Box::new(StalledTransport { inner }))
+            .unwrap();
+        let events = wiimote.events_receiver();
+
+        wiimote.set_liveness_watchdog(Some(LivenessConfig {
+            probe_interval: Duration::from_millis(20),
+            deadline: Duration::from_millis(150),
+        }));
+        assert!(matches!(wiimote.read(), Err(WiimoteError::Disconnected)));
+        assert!(!wiimote.is_connected());
+        assert!(matches!(events.try_recv(), Ok(WiimoteEvent::Disconnected)));
+        assert!(events.try_recv().is_err());
+
+        let status_request = [0x15, 0x00];
+        assert!(simulator
+            .written_reports()
+            .iter()
+            .any(|report| report.as_slice() == status_request));
+    }
+
+    #[test]
+    fn test_auto_reconnect() {
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let mut wiimote = WiimoteDevice::with_transport(simulator.clone()).unwrap();
+        let replacement = crate::simulator::SimulatedWiimote::default();
+        let reopened = replacement.clone();
+        wiimote.reopen = Some(Arc::new(move || {
+            Some(Box::new(reopened.clone()) as Box<dyn NativeWiimote>)
+        }));
+        wiimote.set_reconnect_policy(Some(ReconnectPolicy {
+            max_attempts: 1,
+            retry_interval: Duration::ZERO,
+        }));
+
+        let mode = DataReporingMode {
+            continuous: true,
+            mode: 0x31,
+        };
+        wiimote
+            .write(&OutputReport::PlayerLed(PlayerLedFlags::LED_3))
+            .unwrap();
+        wiimote
+            .write(&OutputReport::DataReportingMode(mode))
+            .unwrap();
+        let events = wiimote.events_receiver();
+
+        simulator.disconnect();
+        // The read returns the error, the Wii remote is opened again on a background thread.
+        assert!(matches!(wiimote.read(), Err(WiimoteError::Disconnected)));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !wiimote.is_connected() {
+            assert!(Instant::now() < deadline, "Wii remote was not reopened");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        wiimote.process_pending_changes().unwrap();
+        assert!(matches!(
+            wiimote.read(),
+            Ok(InputReport::DataReport(0x31, _))
+        ));
+        assert!(matches!(events.try_recv(), Ok(WiimoteEvent::Disconnected)));
+        assert!(matches!(events.try_recv(), Ok(WiimoteEvent::Reconnected)));
+        assert!(replacement
+            .reporting_mode()
+            .is_some_and(|restored| restored.mode == 0x31 && restored.continuous));
+        assert!(replacement
+            .written_reports()
+            .iter()
+            .any(|report| report.as_slice() == [0x11, 0x40]));
+
+        // A deliberately closed connection is not opened again.
+        wiimote.disconnected();
+        assert!(matches!(wiimote.read(), Err(WiimoteError::Disconnected)));
+    }
+
+    #[test]
+    fn test_initialization_progress() {
+        let steps = Arc::new(Mutex::new(Vec::new()));
+        let observed = Arc::clone(&steps);
+        let observer: ProgressObserver = Arc::new(move |identifier: &str, step| {
+            observed
+                .lock()
+                .unwrap()
+                .push((identifier.to_string(), step));
+        });
+
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let wiimote = WiimoteDevice::from_boxed(Box::new(simulator), Some(observer)).unwrap();
+        let steps = steps.lock().unwrap();
+        assert!(steps
+            .iter()
+            .all(|(identifier, _)| identifier == wiimote.identifier()));
+        assert_eq!(
+            steps.iter().map(|(_, step)| *step).collect::<Vec<_>>(),
+            [
+                InitPhase::ReadingCalibration,
+                InitPhase::DetectingMotionPlus,
+                InitPhase::DetectingExtension,
+                InitPhase::ActivatingMotionPlus,
+                InitPhase::Ready,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_reports() {
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let wiimote = WiimoteDevice::with_transport(simulator.clone()).unwrap();
+
+        wiimote.write_raw(&[0x10, 0x01]).unwrap();
+        wiimote.write_raw(&[0x11, 0x10]).unwrap();
+        wiimote.set_raw_rumble_tracking(false);
+        wiimote.write_raw(&[0x11, 0x20]).unwrap();
+        assert!(wiimote.write_raw(&[]).is_err());
+        let written = simulator.written_reports();
+        assert_eq!(
+            written[written.len() - 2..],
+            [vec![0x11, 0x11], vec![0x11, 0x20]]
+        );
+
+        wiimote.write_raw(&[0x15, 0x00]).unwrap();
+        let mut buffer = [0u8; 3];
+        let size = loop {
+            let size = wiimote.read_raw(&mut buffer, Some(100)).unwrap();
+            if buffer[0] == 0x20 {
+                break size;
+            }
+        };
+        assert_eq!(size, 3);
+        assert!(wiimote.battery().is_some());
+    }
+
+    #[test]
+    fn test_speaker_rumble() {
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let wiimote = WiimoteDevice::with_transport(simulator.clone()).unwrap();
+        let speaker_data = OutputReport::SpeakerData(20, [0; 20]);
+
+        wiimote.write(&OutputReport::Rumble(true)).unwrap();
+        wiimote.write(&speaker_data).unwrap();
+        assert!(wiimote.rumble_enabled());
+        assert_eq!(
+            simulator.written_reports().last().unwrap()[..2],
+            [0x18, 0xA1]
+        );
+
+        wiimote.set_speaker_rumble(false);
+        assert!(!wiimote.speaker_rumble());
+        wiimote.write(&speaker_data).unwrap();
+        assert_eq!(
+            simulator.written_reports().last().unwrap()[..2],
+            [0x18, 0xA0]
+        );
+        // The motor was turned off by the speaker data, so the state follows it.
+        assert!(!wiimote.rumble_enabled());
+        wiimote
+            .write(&OutputReport::PlayerLed(PlayerLedFlags::LED_1))
+            .unwrap();
+        assert_eq!(simulator.written_reports().last().unwrap(), &[0x11, 0x10]);
+    }
+}
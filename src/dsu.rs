@@ -0,0 +1,657 @@
+//! Server of the DSU (cemuhook) protocol to use Wii remotes as motion controllers in emulators
+//! such as Dolphin, Cemu or yuzu.
+//!
+//! Protocol Documentation: <https://v1993.github.io/cemuhook-protocol/>
+
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::battery::BatteryStatus;
+use crate::device::{AccelerometerData, WiimoteDevice};
+use crate::extensions::motion_plus::MotionPlusData;
+use crate::input::{ButtonData, WiimoteData};
+
+/// The default port of DSU servers.
+pub const DSU_DEFAULT_PORT: u16 = 26760;
+/// The number of controller slots of the protocol.
+pub const DSU_SLOT_COUNT: usize = 4;
+
+const PROTOCOL_VERSION: u16 = 1001;
+const HEADER_SIZE: usize = 16;
+const SERVER_MAGIC: &[u8; 4] = b"DSUS";
+const CLIENT_MAGIC: &[u8; 4] = b"DSUC";
+
+const MESSAGE_VERSION: u32 = 0x10_0000;
+const MESSAGE_PORT_INFO: u32 = 0x10_0001;
+const MESSAGE_DATA: u32 = 0x10_0002;
+
+/// Interval between data packets, clients expect about 100 packets per second.
+const SEND_INTERVAL: Duration = Duration::from_millis(10);
+/// Clients repeat their data requests, subscriptions without a request in this duration are dropped.
+const SUBSCRIPTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The input state of a Wii remote sent to DSU clients.
+#[derive(Debug, Clone, Copy)]
+pub struct DsuControllerState {
+    /// Identifies the controller for the clients, derived from the Bluetooth address if possible.
+    pub mac: [u8; 6],
+    pub buttons: ButtonData,
+    /// The acceleration in g of the Wii remote axes (X, Y, Z).
+    pub acceleration: (f64, f64, f64),
+    /// The angular velocity in degrees per second (yaw, roll, pitch), `None` without Motion Plus.
+    pub angular_velocity: Option<(f64, f64, f64)>,
+    pub battery: Option<BatteryStatus>,
+}
+
+impl DsuControllerState {
+    /// Decodes the state from a data report of `device`.
+    /// The data reporting mode should include the accelerometer and, for the gyroscope,
+    /// at least 6 extension bytes with an active Motion Plus (e.g. `DataReporingMode::ButtonsAccelerometerExtension16`).
+    #[must_use]
+    pub fn from_report(device: &WiimoteDevice, data: &WiimoteData) -> Self {
+        let acceleration = if matches!(data.report_id(), 0x31 | 0x33 | 0x35 | 0x37) {
+            let accelerometer_data = AccelerometerData::from_normal_reporting(&data.data);
+            device
+                .accelerometer_calibration()
+                .get_acceleration(&accelerometer_data)
+        } else {
+            (0.0, 0.0, 0.0)
+        };
+
+        let angular_velocity = device.motion_plus().and_then(|motion_plus| {
+            let extension_data = data.extension_data()?;
+            let bytes: [u8; 6] = extension_data.get(..6)?.try_into().ok()?;
+            let motion_plus_data = MotionPlusData::try_from(bytes).ok()?;
+            Some(
+                motion_plus
+                    .calibration()
+                    .get_angular_velocity(&motion_plus_data),
+            )
+        });
+
+        Self {
+            mac: mac_from_identifier(device.identifier()),
+            buttons: data.buttons(),
+            acceleration,
+            angular_velocity,
+            battery: device.battery(),
+        }
+    }
+}
+
+/// Parses the Bluetooth address of the identifier,
+/// other identifiers are hashed to get a stable address.
+fn mac_from_identifier(identifier: &str) -> [u8; 6] {
+    let bytes: Vec<u8> = identifier
+        .split(':')
+        .filter_map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect();
+    if let Ok(mac) = bytes.try_into() {
+        return mac;
+    }
+
+    let [a, b, c, d] = crc32fast::hash(identifier.as_bytes()).to_be_bytes();
+    // Locally administered address
+    [0x02, 0x00, a, b, c, d]
+}
+
+/// A UDP server of the DSU protocol that streams the state of up to 4 Wii remotes to subscribed clients.
+/// The server stops when dropped.
+pub struct DsuServer {
+    shared: Arc<ServerState>,
+    local_addr: SocketAddr,
+    started: Instant,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct ServerState {
+    slots: Mutex<[Option<SlotState>; DSU_SLOT_COUNT]>,
+    stopped: AtomicBool,
+}
+
+#[derive(Clone, Copy)]
+struct SlotState {
+    controller: DsuControllerState,
+    /// Time of the last update in microseconds since the server started.
+    timestamp: u64,
+}
+
+impl ServerState {
+    fn slots(&self) -> MutexGuard<'_, [Option<SlotState>; DSU_SLOT_COUNT]> {
+        match self.slots.lock() {
+            Ok(slots) => slots,
+            Err(slots) => slots.into_inner(),
+        }
+    }
+}
+
+impl DsuServer {
+    /// Binds the server to `address`, usually `("127.0.0.1", DSU_DEFAULT_PORT)`,
+    /// and starts answering clients on a background thread.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the UDP socket could not be bound.
+    pub fn bind(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_read_timeout(Some(SEND_INTERVAL))?;
+        let local_addr = socket.local_addr()?;
+
+        let shared = Arc::new(ServerState {
+            slots: Mutex::new([None; DSU_SLOT_COUNT]),
+            stopped: AtomicBool::new(false),
+        });
+        let thread = {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || ServerThread::new(socket, shared).run())
+        };
+
+        Ok(Self {
+            shared,
+            local_addr,
+            started: Instant::now(),
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the address the server is bound to.
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Sets the state of the controller in `slot`, which is sent to clients from now on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slot` is not below `DSU_SLOT_COUNT`.
+    pub fn update(&self, slot: usize, controller: DsuControllerState) {
+        let timestamp = self.elapsed_micros();
+        self.shared.slots()[slot] = Some(SlotState {
+            controller,
+            timestamp,
+        });
+    }
+
+    /// Marks the controller in `slot` as disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slot` is not below `DSU_SLOT_COUNT`.
+    pub fn remove(&self, slot: usize) {
+        self.shared.slots()[slot] = None;
+    }
+
+    /// Sets the state of the controller in the slot assigned to its `mac`,
+    /// a newly connected controller is assigned the first free slot.
+    /// Returns the slot, `None` if all slots are assigned to other controllers.
+    pub fn update_controller(&self, controller: DsuControllerState) -> Option<usize> {
+        let timestamp = self.elapsed_micros();
+        let mut slots = self.shared.slots();
+        let slot = slots
+            .iter()
+            .position(|state| state.is_some_and(|state| state.controller.mac == controller.mac))
+            .or_else(|| slots.iter().position(Option::is_none))?;
+        slots[slot] = Some(SlotState {
+            controller,
+            timestamp,
+        });
+        Some(slot)
+    }
+
+    /// Marks the controller with `mac` as disconnected, its slot is assigned to the next new controller.
+    pub fn remove_controller(&self, mac: [u8; 6]) {
+        for state in self.shared.slots().iter_mut() {
+            if state.is_some_and(|state| state.controller.mac == mac) {
+                *state = None;
+            }
+        }
+    }
+
+    fn elapsed_micros(&self) -> u64 {
+        u64::try_from(self.started.elapsed().as_micros()).unwrap_or(u64::MAX)
+    }
+}
+
+impl Drop for DsuServer {
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+/// A client that requested controller data.
+struct Subscription {
+    address: SocketAddr,
+    filter: SlotFilter,
+    last_request: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotFilter {
+    All,
+    Slot(u8),
+    Mac([u8; 6]),
+}
+
+impl SlotFilter {
+    fn matches(self, slot: usize, controller: &DsuControllerState) -> bool {
+        match self {
+            Self::All => true,
+            Self::Slot(requested) => usize::from(requested) == slot,
+            Self::Mac(mac) => mac == controller.mac,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Request {
+    Version,
+    PortInfo(Vec<u8>),
+    Data(SlotFilter),
+}
+
+struct ServerThread {
+    socket: UdpSocket,
+    shared: Arc<ServerState>,
+    server_id: u32,
+    subscriptions: Vec<Subscription>,
+    packet_numbers: [u32; DSU_SLOT_COUNT],
+}
+
+impl ServerThread {
+    fn new(socket: UdpSocket, shared: Arc<ServerState>) -> Self {
+        Self {
+            socket,
+            shared,
+            server_id: std::process::id(),
+            subscriptions: Vec::new(),
+            packet_numbers: [0; DSU_SLOT_COUNT],
+        }
+    }
+
+    fn run(mut self) {
+        let mut buffer = [0u8; 1024];
+        let mut next_send = Instant::now();
+        while !self.shared.stopped.load(Ordering::Relaxed) {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((size, address)) => self.handle_packet(&buffer[..size], address),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                // Windows reports ICMP port unreachable of clients that went away as an error
+                Err(e) if e.kind() == ErrorKind::ConnectionReset => {}
+                Err(e) => {
+                    warn!("DSU server stopped: {e}");
+                    return;
+                }
+            }
+
+            let now = Instant::now();
+            if now >= next_send {
+                self.subscriptions
+                    .retain(|subscription| now - subscription.last_request < SUBSCRIPTION_TIMEOUT);
+                self.send_data();
+                next_send = now + SEND_INTERVAL;
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, packet: &[u8], address: SocketAddr) {
+        let Some(request) = parse_request(packet) else {
+            debug!("Ignoring invalid DSU packet from {address}");
+            return;
+        };
+
+        match request {
+            Request::Version => {
+                let payload = version_payload();
+                self.send(&payload, address);
+            }
+            Request::PortInfo(slots) => {
+                let states = *self.shared.slots();
+                for slot in slots {
+                    let state = states.get(usize::from(slot)).copied().flatten();
+                    let payload = port_info_payload(slot, state.map(|state| state.controller));
+                    self.send(&payload, address);
+                }
+            }
+            Request::Data(filter) => {
+                let now = Instant::now();
+                if let Some(subscription) = self
+                    .subscriptions
+                    .iter_mut()
+                    .find(|s| s.address == address && s.filter == filter)
+                {
+                    subscription.last_request = now;
+                } else {
+                    debug!("DSU client {address} subscribed to {filter:?}");
+                    self.subscriptions.push(Subscription {
+                        address,
+                        filter,
+                        last_request: now,
+                    });
+                }
+            }
+        }
+    }
+
+    fn send_data(&mut self) {
+        if self.subscriptions.is_empty() {
+            return;
+        }
+
+        let states = *self.shared.slots();
+        for (slot, state) in states.iter().enumerate() {
+            let Some(state) = state else {
+                continue;
+            };
+
+            let packet_number = self.packet_numbers[slot];
+            self.packet_numbers[slot] = packet_number.wrapping_add(1);
+            let payload = data_payload(slot as u8, state, packet_number);
+
+            let addresses: Vec<SocketAddr> = self
+                .subscriptions
+                .iter()
+                .filter(|s| s.filter.matches(slot, &state.controller))
+                .map(|s| s.address)
+                .collect();
+            for address in addresses {
+                self.send(&payload, address);
+            }
+        }
+    }
+
+    fn send(&self, payload: &[u8], address: SocketAddr) {
+        let packet = encode_packet(self.server_id, payload);
+        if let Err(e) = self.socket.send_to(&packet, address) {
+            debug!("Failed to send DSU packet to {address}: {e}");
+        }
+    }
+}
+
+/// Prepends the header with the checksum of the whole packet to the payload.
+fn encode_packet(server_id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(HEADER_SIZE + payload.len());
+    packet.extend_from_slice(SERVER_MAGIC);
+    packet.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+    packet.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    packet.extend_from_slice(&[0; 4]);
+    packet.extend_from_slice(&server_id.to_le_bytes());
+    packet.extend_from_slice(payload);
+
+    let checksum = crc32fast::hash(&packet);
+    packet[8..12].copy_from_slice(&checksum.to_le_bytes());
+    packet
+}
+
+/// Validates the header of a client packet and decodes the request.
+fn parse_request(packet: &[u8]) -> Option<Request> {
+    if packet.len() < HEADER_SIZE + 4 || &packet[..4] != CLIENT_MAGIC {
+        return None;
+    }
+    let length = usize::from(u16::from_le_bytes([packet[6], packet[7]]));
+    let packet = packet.get(..HEADER_SIZE + length)?;
+
+    let checksum = u32::from_le_bytes(packet[8..12].try_into().ok()?);
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&packet[..8]);
+    hasher.update(&[0; 4]);
+    hasher.update(&packet[12..]);
+    if hasher.finalize() != checksum {
+        return None;
+    }
+
+    let payload = &packet[HEADER_SIZE..];
+    // The length field may be shorter than the message type even though the packet is not.
+    let message_type = u32::from_le_bytes(payload.get(..4)?.try_into().ok()?);
+    let payload = &payload[4..];
+    match message_type {
+        MESSAGE_VERSION => Some(Request::Version),
+        MESSAGE_PORT_INFO => {
+            let count = i32::from_le_bytes(payload.get(..4)?.try_into().ok()?);
+            let count = usize::try_from(count).ok()?.min(DSU_SLOT_COUNT);
+            Some(Request::PortInfo(payload.get(4..4 + count)?.to_vec()))
+        }
+        MESSAGE_DATA => {
+            let flags = *payload.first()?;
+            let filter = if flags & 0b01 != 0 {
+                SlotFilter::Slot(*payload.get(1)?)
+            } else if flags & 0b10 != 0 {
+                SlotFilter::Mac(payload.get(2..8)?.try_into().ok()?)
+            } else {
+                SlotFilter::All
+            };
+            Some(Request::Data(filter))
+        }
+        _ => None,
+    }
+}
+
+fn version_payload() -> Vec<u8> {
+    let mut payload = MESSAGE_VERSION.to_le_bytes().to_vec();
+    payload.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+    payload
+}
+
+fn port_info_payload(slot: u8, controller: Option<DsuControllerState>) -> Vec<u8> {
+    let mut payload = MESSAGE_PORT_INFO.to_le_bytes().to_vec();
+    write_slot_header(&mut payload, slot, controller.as_ref());
+    payload.push(0);
+    payload
+}
+
+/// The information about the controller shared by the port info and data messages.
+fn write_slot_header(payload: &mut Vec<u8>, slot: u8, controller: Option<&DsuControllerState>) {
+    const CONNECTED: u8 = 2;
+    const FULL_GYRO: u8 = 2;
+    const PARTIAL_GYRO: u8 = 1;
+    const BLUETOOTH: u8 = 2;
+
+    payload.push(slot);
+    let Some(controller) = controller else {
+        payload.extend_from_slice(&[0; 10]);
+        return;
+    };
+
+    payload.push(CONNECTED);
+    payload.push(if controller.angular_velocity.is_some() {
+        FULL_GYRO
+    } else {
+        PARTIAL_GYRO
+    });
+    payload.push(BLUETOOTH);
+    payload.extend_from_slice(&controller.mac);
+    payload.push(battery_state(controller.battery));
+}
+
+fn battery_state(battery: Option<BatteryStatus>) -> u8 {
+    match battery.map(|battery| (battery.low, battery.percentage())) {
+        None => 0x00,
+        Some((true, _)) => 0x01,
+        Some((false, 0..=25)) => 0x02,
+        Some((false, 26..=60)) => 0x03,
+        Some((false, 61..=90)) => 0x04,
+        Some((false, _)) => 0x05,
+    }
+}
+
+fn data_payload(slot: u8, state: &SlotState, packet_number: u32) -> Vec<u8> {
+    const STICK_CENTER: u8 = 128;
+
+    let controller = &state.controller;
+    let buttons = controller.buttons;
+    let analog = |button: ButtonData| if buttons.contains(button) { 0xFF } else { 0 };
+
+    let mut payload = MESSAGE_DATA.to_le_bytes().to_vec();
+    write_slot_header(&mut payload, slot, Some(controller));
+    payload.push(1);
+    payload.extend_from_slice(&packet_number.to_le_bytes());
+    let (buttons1, buttons2) = dsu_buttons(buttons);
+    payload.extend_from_slice(&[buttons1, buttons2, analog(ButtonData::HOME), 0]);
+    payload.extend_from_slice(&[STICK_CENTER; 4]);
+    #[rustfmt::skip]
+    payload.extend_from_slice(&[
+        analog(ButtonData::LEFT), analog(ButtonData::DOWN),
+        analog(ButtonData::RIGHT), analog(ButtonData::UP),
+        analog(ButtonData::TWO), 0, analog(ButtonData::A), analog(ButtonData::ONE),
+        0, 0, analog(ButtonData::B), 0,
+    ]);
+    // Two inactive touches
+    payload.extend_from_slice(&[0; 12]);
+    payload.extend_from_slice(&state.timestamp.to_le_bytes());
+
+    // The Wii remote points along its Y axis with Z up, DSU clients expect Z to point to the player.
+    let (x, y, z) = controller.acceleration;
+    for value in [x, z, -y] {
+        payload.extend_from_slice(&(value as f32).to_le_bytes());
+    }
+    let (yaw, roll, pitch) = controller.angular_velocity.unwrap_or_default();
+    for value in [pitch, yaw, roll] {
+        payload.extend_from_slice(&(value as f32).to_le_bytes());
+    }
+    payload
+}
+
+/// Maps the Wii remote buttons to the DualShock 4 button bytes of the protocol.
+fn dsu_buttons(buttons: ButtonData) -> (u8, u8) {
+    let bit = |button: ButtonData, mask: u8| if buttons.contains(button) { mask } else { 0 };
+
+    let buttons1 = bit(ButtonData::MINUS, 0x01)
+        | bit(ButtonData::PLUS, 0x08)
+        | bit(ButtonData::UP, 0x10)
+        | bit(ButtonData::RIGHT, 0x20)
+        | bit(ButtonData::DOWN, 0x40)
+        | bit(ButtonData::LEFT, 0x80);
+    let buttons2 = bit(ButtonData::B, 0x02)
+        | bit(ButtonData::TWO, 0x10)
+        | bit(ButtonData::A, 0x40)
+        | bit(ButtonData::ONE, 0x80);
+    (buttons1, buttons2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_packet(payload: &[u8]) -> Vec<u8> {
+        let mut packet = encode_packet(1, payload);
+        packet[..4].copy_from_slice(CLIENT_MAGIC);
+        packet[8..12].fill(0);
+        let checksum = crc32fast::hash(&packet);
+        packet[8..12].copy_from_slice(&checksum.to_le_bytes());
+        packet
+    }
+
+    fn controller() -> DsuControllerState {
+        DsuControllerState {
+            mac: [1, 2, 3, 4, 5, 6],
+            buttons: ButtonData::A | ButtonData::UP,
+            acceleration: (0.0, 0.0, 1.0),
+            angular_velocity: Some((10.0, 20.0, 30.0)),
+            battery: None,
+        }
+    }
+
+    #[test]
+    fn test_encode_packet_checksum() {
+        let packet = encode_packet(7, &version_payload());
+        assert_eq!(&packet[..4], SERVER_MAGIC);
+        assert_eq!(u16::from_le_bytes([packet[6], packet[7]]), 6);
+
+        let checksum = u32::from_le_bytes(packet[8..12].try_into().unwrap());
+        let mut zeroed = packet.clone();
+        zeroed[8..12].fill(0);
+        assert_eq!(crc32fast::hash(&zeroed), checksum);
+    }
+
+    #[test]
+    fn test_parse_requests() {
+        let mut data = MESSAGE_DATA.to_le_bytes().to_vec();
+        data.extend_from_slice(&[0b01, 2, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            parse_request(&client_packet(&data)),
+            Some(Request::Data(SlotFilter::Slot(2)))
+        );
+
+        let mut port_info = MESSAGE_PORT_INFO.to_le_bytes().to_vec();
+        port_info.extend_from_slice(&2i32.to_le_bytes());
+        port_info.extend_from_slice(&[0, 1]);
+        assert_eq!(
+            parse_request(&client_packet(&port_info)),
+            Some(Request::PortInfo(vec![0, 1]))
+        );
+
+        let mut corrupted = client_packet(&MESSAGE_VERSION.to_le_bytes());
+        assert_eq!(parse_request(&corrupted), Some(Request::Version));
+        corrupted[8] ^= 0xFF;
+        assert_eq!(parse_request(&corrupted), None);
+
+        // A valid checksum of the header alone, followed by a message type outside of the length.
+        let mut empty = client_packet(&[]);
+        empty.extend_from_slice(&MESSAGE_VERSION.to_le_bytes());
+        assert_eq!(parse_request(&empty), None);
+    }
+
+    #[test]
+    fn test_data_payload() {
+        let state = SlotState {
+            controller: controller(),
+            timestamp: 1234,
+        };
+        let payload = data_payload(1, &state, 5);
+        assert_eq!(payload.len(), 84);
+        assert_eq!(&payload[4..10], &[1, 2, 2, 2, 1, 2]);
+        assert_eq!(u32::from_le_bytes(payload[16..20].try_into().unwrap()), 5);
+        assert_eq!(payload[20..22], [0x10, 0x40]);
+        assert_eq!(
+            u64::from_le_bytes(payload[52..60].try_into().unwrap()),
+            1234
+        );
+        let accel_y = f32::from_le_bytes(payload[64..68].try_into().unwrap());
+        let gyro_pitch = f32::from_le_bytes(payload[72..76].try_into().unwrap());
+        assert!((accel_y - 1.0).abs() < f32::EPSILON);
+        assert!((gyro_pitch - 30.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_slot_assignment() {
+        let server = DsuServer::bind(("127.0.0.1", 0)).unwrap();
+        let first = controller();
+        let second = DsuControllerState {
+            mac: [6, 5, 4, 3, 2, 1],
+            ..controller()
+        };
+
+        assert_eq!(server.update_controller(first), Some(0));
+        assert_eq!(server.update_controller(second), Some(1));
+        assert_eq!(server.update_controller(first), Some(0));
+
+        server.remove_controller(first.mac);
+        let slots: Vec<_> = (0..4)
+            .map(|index| {
+                server.update_controller(DsuControllerState {
+                    mac: [0, 0, 0, 0, 0, index],
+                    ..controller()
+                })
+            })
+            .collect();
+        assert_eq!(slots, [Some(0), Some(2), Some(3), None]);
+    }
+
+    #[test]
+    fn test_mac_from_identifier() {
+        assert_eq!(
+            mac_from_identifier("00:1F:32:AB:CD:EF"),
+            [0x00, 0x1F, 0x32, 0xAB, 0xCD, 0xEF]
+        );
+        assert_eq!(mac_from_identifier(r"\\?\HID#{...}")[0], 0x02);
+    }
+}
@@ -0,0 +1,42 @@
+//! The I/O of the user region through a `WiimoteDevice`.
+
+use super::UserRegion;
+use crate::output::Addressing;
+use crate::prelude::*;
+use crate::simple_io;
+
+impl UserRegion {
+    /// Reads `buffer.len()` bytes starting at `offset` in the user region.
+    /// Discards reports other than the memory data, do not read input reports at the same time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the range is outside of the user region or on I/O error.
+    pub fn read(
+        &self,
+        wiimote: &WiimoteDevice,
+        offset: usize,
+        buffer: &mut [u8],
+    ) -> WiimoteResult<()> {
+        let address = self.address(offset, buffer.len())?;
+        for (address, chunk) in (address..).step_by(16).zip(buffer.chunks_mut(16)) {
+            #[allow(clippy::cast_possible_truncation)] // At most 16 bytes
+            let addressing = Addressing::eeprom(address, chunk.len() as u16);
+            let data = simple_io::read_16_bytes_sync_checked(wiimote, addressing)?;
+            chunk.copy_from_slice(&data[..chunk.len()]);
+        }
+        Ok(())
+    }
+
+    /// Writes `data` starting at `offset` in the user region.
+    /// Discards reports other than the acknowledge result, do not read input reports at the same time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the range is outside of the user region,
+    /// on I/O error or if a write is not acknowledged.
+    pub fn write(&self, wiimote: &WiimoteDevice, offset: usize, data: &[u8]) -> WiimoteResult<()> {
+        let address = self.address(offset, data.len())?;
+        simple_io::write_memory_checked(wiimote, Addressing::eeprom(address, 0), data)
+    }
+}
@@ -0,0 +1,103 @@
+//! Access to the EEPROM of the Wii remote.
+//!
+//! WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#EEPROM_Memory>
+
+use std::ops::Range;
+
+use crate::prelude::*;
+
+mod io;
+
+/// The EEPROM address of the user region.
+const USER_REGION_START: u32 = 0x002A;
+/// The size of the user region in bytes.
+const USER_REGION_SIZE: usize = 0x0FA0;
+
+/// EEPROM regions written by the Wii remote or the Wii that must not be overwritten.
+const PROTECTED_REGIONS: [Range<u32>; 3] = [
+    // Accelerometer calibration blocks and the IR camera calibration
+    0x0000..0x002A,
+    // Mii data blocks
+    0x0FCA..0x15AA,
+    // Unknown data
+    0x15AA..0x1700,
+];
+
+/// The writable area of the EEPROM, used by games to store save-like data.
+#[derive(Debug, Clone, Copy)]
+pub struct UserRegion {
+    start: u32,
+    size: usize,
+}
+
+/// Returns the 0x0FA0-byte user region of the EEPROM.
+#[must_use]
+pub const fn user_region() -> UserRegion {
+    UserRegion {
+        start: USER_REGION_START,
+        size: USER_REGION_SIZE,
+    }
+}
+
+impl UserRegion {
+    /// Returns the size of the user region in bytes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.size
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the EEPROM address of `offset` if `size` bytes starting at `offset` are inside the
+    /// user region and do not overlap a protected region.
+    pub(crate) fn address(&self, offset: usize, size: usize) -> WiimoteResult<u32> {
+        let end = offset
+            .checked_add(size)
+            .filter(|end| *end <= self.size)
+            .ok_or(WiimoteDeviceError::InvalidData)?;
+        let to_address = |offset: usize| {
+            u32::try_from(offset)
+                .ok()
+                .and_then(|offset| self.start.checked_add(offset))
+                .ok_or(WiimoteDeviceError::InvalidData)
+        };
+        let (start, end) = (to_address(offset)?, to_address(end)?);
+        if PROTECTED_REGIONS
+            .iter()
+            .any(|region| start < region.end && region.start < end)
+        {
+            return Err(WiimoteDeviceError::InvalidData.into());
+        }
+        Ok(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_region_bounds() {
+        let region = user_region();
+
+        assert_eq!(region.address(0, 16).ok(), Some(0x002A));
+        assert_eq!(region.address(0x0F90, 0x10).ok(), Some(0x0FBA));
+        assert!(region.address(0x0F90, 0x11).is_err());
+        assert!(region.address(usize::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_protected_regions() {
+        let calibration = UserRegion {
+            start: 0x0020,
+            size: 0x20,
+        };
+
+        assert!(calibration.address(0, 1).is_err());
+        assert!(calibration.address(9, 2).is_err());
+        assert_eq!(calibration.address(10, 2).ok(), Some(0x002A));
+    }
+}
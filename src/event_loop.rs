@@ -0,0 +1,51 @@
+use std::ops::ControlFlow;
+
+use crate::prelude::*;
+
+/// The read timeout of the event loop, events published by background threads
+/// are passed to the handler at least this often.
+const EVENT_POLL_MILLIS: usize = 50;
+
+impl WiimoteDevice {
+    /// Reads from the Wii remote until the handler breaks or the Wii remote disconnects.
+    /// Every input report is passed to `handler` as [`WiimoteEvent::Report`],
+    /// followed by the events published while processing it.
+    ///
+    /// The Wii remote stops sending data reports after a status report until the data
+    /// reporting mode is set again, so the last data reporting mode is restored after each status report.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the error that disconnected the Wii remote,
+    /// after passing [`WiimoteEvent::Disconnected`] to the handler.
+    pub fn run_event_loop(
+        &self,
+        mut handler: impl FnMut(WiimoteEvent) -> ControlFlow<()>,
+    ) -> WiimoteResult<()> {
+        let events = self.events_receiver_with_reports();
+        loop {
+            let error = match self.read_timeout(EVENT_POLL_MILLIS) {
+                Ok(input_report) => self.process_report_changes(&input_report).err(),
+                Err(WiimoteError::WiimoteDeviceError(WiimoteDeviceError::MissingData)) => None,
+                // Skip reports with an unknown report id
+                Err(WiimoteError::WiimoteDeviceError(WiimoteDeviceError::InvalidData)) => None,
+                Err(error) => Some(error),
+            };
+
+            let mut disconnect_handled = false;
+            for event in events.try_iter() {
+                disconnect_handled |= matches!(event, WiimoteEvent::Disconnected);
+                if handler(event).is_break() {
+                    return Ok(());
+                }
+            }
+            if let Some(error) = error {
+                // The device may have been disconnected by a failed write without an event.
+                if !disconnect_handled {
+                    _ = handler(WiimoteEvent::Disconnected);
+                }
+                return Err(error);
+            }
+        }
+    }
+}
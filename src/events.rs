@@ -0,0 +1,112 @@
+use std::sync::Mutex;
+
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::battery::BatteryStatus;
+use crate::extensions::{BalanceBoardStatus, ConnectedExtension, MotionPlusType};
+use crate::input::InputReport;
+
+/// An event published by a `WiimoteDevice` while processing input reports.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum WiimoteEvent {
+    /// An extension was connected, disconnected or replaced.
+    /// Contains the newly detected extension, `None` if no extension is connected.
+    ExtensionChanged(Option<ConnectedExtension>),
+    /// The temperature reported by the balance board changed enough to affect the
+    /// temperature compensation of the weights, previously measured weights should be re-scaled.
+    /// Also published for the first status received after the balance board is connected.
+    BalanceBoardTemperatureChanged(BalanceBoardStatus),
+    /// An external Motion Plus was plugged into the Wii remote after it connected.
+    MotionPlusAttached(MotionPlusType),
+    /// The external Motion Plus was unplugged, [`WiimoteDevice::motion_plus`](crate::prelude::WiimoteDevice::motion_plus) returns `None`.
+    MotionPlusDetached,
+    /// The battery of the Wii remote became low, either signaled by the Wii remote
+    /// or the percentage dropped below the configured threshold.
+    BatteryLow(BatteryStatus),
+    /// The Wii remote had no button or motion changes for the configured idle timeout.
+    Idle,
+    /// The Wii remote became active again after being idle.
+    Active,
+    /// Reading from the Wii remote failed and it was disconnected.
+    Disconnected,
+    /// The Wii remote was opened again by the reconnect policy after the connection was lost,
+    /// see [`WiimoteDevice::set_reconnect_policy`](crate::prelude::WiimoteDevice::set_reconnect_policy).
+    Reconnected,
+    /// An input report read from the Wii remote, published before the events it caused.
+    /// Only published to the receivers of
+    /// [`WiimoteDevice::events_receiver_with_reports`](crate::prelude::WiimoteDevice::events_receiver_with_reports).
+    Report(InputReport),
+}
+
+/// The maximum number of [`WiimoteEvent::Report`] queued for a subscriber,
+/// further reports are dropped until the subscriber received some of them.
+const REPORT_EVENT_CAPACITY: usize = 256;
+
+#[derive(Debug)]
+struct Subscriber {
+    sender: Sender<WiimoteEvent>,
+    /// Whether the subscriber also receives [`WiimoteEvent::Report`].
+    reports: bool,
+}
+
+/// Distributes events to all subscribed receivers.
+///
+/// The channels are unbounded, as events other than reports only occur on state changes,
+/// and the reports are limited to [`REPORT_EVENT_CAPACITY`] per subscriber.
+#[derive(Debug, Default)]
+pub(crate) struct EventPublisher {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl EventPublisher {
+    pub(crate) fn subscribe(&self, reports: bool) -> Receiver<WiimoteEvent> {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        self.lock_subscribers().push(Subscriber { sender, reports });
+        receiver
+    }
+
+    pub(crate) fn publish(&self, event: &WiimoteEvent) {
+        let is_report = matches!(event, WiimoteEvent::Report(_));
+        // Subscribers whose receiver has been dropped are removed.
+        self.lock_subscribers().retain(|subscriber| {
+            if is_report
+                && (!subscriber.reports || subscriber.sender.len() >= REPORT_EVENT_CAPACITY)
+            {
+                return true;
+            }
+            subscriber.sender.send(event.clone()).is_ok()
+        });
+    }
+
+    fn lock_subscribers(&self) -> std::sync::MutexGuard<'_, Vec<Subscriber>> {
+        match self.subscribers.lock() {
+            Ok(subscribers) => subscribers,
+            Err(subscribers) => subscribers.into_inner(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_events_bounded() {
+        let publisher = EventPublisher::default();
+        let receiver = publisher.subscribe(true);
+        let report = InputReport::try_from([0x30, 0x00, 0x00].as_slice()).unwrap();
+        for _ in 0..=REPORT_EVENT_CAPACITY {
+            publisher.publish(&WiimoteEvent::Report(report));
+        }
+        assert_eq!(receiver.len(), REPORT_EVENT_CAPACITY);
+
+        // Other events are still delivered.
+        publisher.publish(&WiimoteEvent::Idle);
+        assert_eq!(receiver.len(), REPORT_EVENT_CAPACITY + 1);
+        assert!(matches!(
+            receiver.try_iter().last(),
+            Some(WiimoteEvent::Idle)
+        ));
+    }
+}
@@ -0,0 +1,434 @@
+use crate::input::{ButtonData, WiimoteData};
+use crate::output::PlayerLedFlags;
+use crate::prelude::*;
+
+/// Helpers for the Wii Balance Board, which connects as a Wii remote with the balance board extension.
+///
+/// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wii_Balance_Board>
+pub struct BalanceBoard;
+
+impl BalanceBoard {
+    /// The power button on the front of the balance board is reported as the A button.
+    pub const FRONT_BUTTON: ButtonData = ButtonData::A;
+
+    /// The blue LED of the front button is controlled by the first player LED.
+    pub const LED: PlayerLedFlags = PlayerLedFlags::LED_1;
+
+    /// Returns whether the front button is pressed based on the core button data.
+    #[must_use]
+    pub const fn is_front_button_pressed(buttons: ButtonData) -> bool {
+        buttons.contains(Self::FRONT_BUTTON)
+    }
+}
+
+/// The raw sensor values of the four corners of the balance board.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BalanceBoardSensors {
+    pub top_right: u16,
+    pub bottom_right: u16,
+    pub top_left: u16,
+    pub bottom_left: u16,
+}
+
+impl BalanceBoardSensors {
+    const fn from_be_bytes(data: &[u8]) -> Self {
+        Self {
+            top_right: u16::from_be_bytes([data[0], data[1]]),
+            bottom_right: u16::from_be_bytes([data[2], data[3]]),
+            top_left: u16::from_be_bytes([data[4], data[5]]),
+            bottom_left: u16::from_be_bytes([data[6], data[7]]),
+        }
+    }
+}
+
+/// The weights in kilograms measured at the four corners of the balance board.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BalanceBoardWeights {
+    pub top_right: f64,
+    pub bottom_right: f64,
+    pub top_left: f64,
+    pub bottom_left: f64,
+}
+
+impl BalanceBoardWeights {
+    /// Returns the sum of the weights of all four corners.
+    #[must_use]
+    pub fn total(&self) -> f64 {
+        self.top_right + self.bottom_right + self.top_left + self.bottom_left
+    }
+}
+
+/// The calibration data of the balance board.
+/// Can be used to convert raw sensor values to weights.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BalanceBoardCalibration {
+    kg_0: BalanceBoardSensors,
+    kg_17: BalanceBoardSensors,
+    kg_34: BalanceBoardSensors,
+    reference_temperature: u8,
+    tare: BalanceBoardWeights,
+}
+
+impl BalanceBoardCalibration {
+    /// Returns the temperature at which the balance board was calibrated.
+    #[must_use]
+    pub const fn reference_temperature(&self) -> u8 {
+        self.reference_temperature
+    }
+
+    /// Parses the 32 bytes of calibration data read from register 0xA40020
+    /// and the reference temperature read from register 0xA40060.
+    #[must_use]
+    pub const fn from_calibration_data(data: &[u8; 32], reference_temperature: u8) -> Self {
+        // https://www.wiibrew.org/wiki/Wii_Balance_Board#Calibration_Data
+        // 0x24: sensor values at 0 kg, 0x2C: at 17 kg, 0x34: at 34 kg.
+        // Each block contains the top right, bottom right, top left and bottom left sensor.
+        let (_, data) = data.split_at(4);
+        let (kg_0, data) = data.split_at(8);
+        let (kg_17, kg_34) = data.split_at(8);
+        Self {
+            kg_0: BalanceBoardSensors::from_be_bytes(kg_0),
+            kg_17: BalanceBoardSensors::from_be_bytes(kg_17),
+            kg_34: BalanceBoardSensors::from_be_bytes(kg_34),
+            reference_temperature,
+            tare: BalanceBoardWeights {
+                top_right: 0.0,
+                bottom_right: 0.0,
+                top_left: 0.0,
+                bottom_left: 0.0,
+            },
+        }
+    }
+
+    /// Captures a new zero reference from samples of the unloaded balance board.
+    /// The zero reference compensates for drift and offsets remaining after the factory calibration
+    /// and is subtracted from all weights returned by [`Self::get_weights`].
+    /// Returns the captured offsets in kilograms, `None` if no samples were provided.
+    pub fn tare(&mut self, samples: &[BalanceBoardData]) -> Option<BalanceBoardWeights> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sum = BalanceBoardWeights::default();
+        for sample in samples {
+            let weights = self.get_uncompensated_weights(&sample.sensors);
+            sum.top_right += weights.top_right;
+            sum.bottom_right += weights.bottom_right;
+            sum.top_left += weights.top_left;
+            sum.bottom_left += weights.bottom_left;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let count = samples.len() as f64;
+        self.tare = BalanceBoardWeights {
+            top_right: sum.top_right / count,
+            bottom_right: sum.bottom_right / count,
+            top_left: sum.top_left / count,
+            bottom_left: sum.bottom_left / count,
+        };
+        Some(self.tare)
+    }
+
+    /// Removes the zero reference captured by [`Self::tare`].
+    pub fn reset_tare(&mut self) {
+        self.tare = BalanceBoardWeights::default();
+    }
+
+    /// Returns the zero reference in kilograms captured by [`Self::tare`].
+    #[must_use]
+    pub const fn tare_offsets(&self) -> &BalanceBoardWeights {
+        &self.tare
+    }
+
+    /// Returns the weights in kilograms from the raw data using the current calibration.
+    /// If the data contains the temperature, the weights are compensated for the temperature drift
+    /// relative to the reference temperature of the calibration.
+    #[must_use]
+    pub fn get_weights(&self, data: &BalanceBoardData) -> BalanceBoardWeights {
+        let weights = self.get_uncompensated_weights(&data.sensors);
+        let weights = BalanceBoardWeights {
+            top_right: weights.top_right - self.tare.top_right,
+            bottom_right: weights.bottom_right - self.tare.bottom_right,
+            top_left: weights.top_left - self.tare.top_left,
+            bottom_left: weights.bottom_left - self.tare.bottom_left,
+        };
+        self.compensate_temperature(weights, data.status.map(|status| status.temperature))
+    }
+
+    fn get_uncompensated_weights(&self, sensors: &BalanceBoardSensors) -> BalanceBoardWeights {
+        BalanceBoardWeights {
+            top_right: Self::interpolate(
+                sensors.top_right,
+                self.kg_0.top_right,
+                self.kg_17.top_right,
+                self.kg_34.top_right,
+            ),
+            bottom_right: Self::interpolate(
+                sensors.bottom_right,
+                self.kg_0.bottom_right,
+                self.kg_17.bottom_right,
+                self.kg_34.bottom_right,
+            ),
+            top_left: Self::interpolate(
+                sensors.top_left,
+                self.kg_0.top_left,
+                self.kg_17.top_left,
+                self.kg_34.top_left,
+            ),
+            bottom_left: Self::interpolate(
+                sensors.bottom_left,
+                self.kg_0.bottom_left,
+                self.kg_17.bottom_left,
+                self.kg_34.bottom_left,
+            ),
+        }
+    }
+
+    fn interpolate(value: u16, kg_0: u16, kg_17: u16, kg_34: u16) -> f64 {
+        const KG_17: f64 = 17.0;
+        let (lower, upper, base) = if value < kg_17 {
+            (kg_0, kg_17, 0.0)
+        } else {
+            (kg_17, kg_34, KG_17)
+        };
+        if upper == lower {
+            return base;
+        }
+        base + KG_17 * (f64::from(value) - f64::from(lower)) / (f64::from(upper) - f64::from(lower))
+    }
+
+    fn compensate_temperature(
+        &self,
+        weights: BalanceBoardWeights,
+        temperature: Option<u8>,
+    ) -> BalanceBoardWeights {
+        // The sensors drift by roughly 0.07% per degree of difference to the calibration temperature.
+        const TEMPERATURE_CORRECTION: f64 = 0.0007;
+
+        let Some(temperature) = temperature else {
+            return weights;
+        };
+        let factor = 1.0
+            - TEMPERATURE_CORRECTION
+                * (f64::from(temperature) - f64::from(self.reference_temperature));
+        BalanceBoardWeights {
+            top_right: weights.top_right * factor,
+            bottom_right: weights.bottom_right * factor,
+            top_left: weights.top_left * factor,
+            bottom_left: weights.bottom_left * factor,
+        }
+    }
+}
+
+/// A temperature change of this many units warrants re-scaling previously measured weights.
+pub(crate) const TEMPERATURE_CHANGE_THRESHOLD: u8 = 2;
+
+/// The temperature and battery level reported by the balance board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BalanceBoardStatus {
+    /// The raw temperature, compared to the reference temperature of the calibration
+    /// to compensate the temperature drift of the sensors.
+    pub temperature: u8,
+    /// The raw battery level.
+    pub battery: u8,
+}
+
+impl BalanceBoardStatus {
+    /// Parses the status following the 8 bytes of sensor data,
+    /// `None` if the data reporting mode contains less than 11 extension bytes.
+    #[must_use]
+    pub fn from_extension_data(data: &[u8]) -> Option<Self> {
+        // https://www.wiibrew.org/wiki/Wii_Balance_Board#Data_Format
+        // 8 bytes of sensor data, followed by the temperature, padding and the battery level.
+        match data {
+            [_, _, _, _, _, _, _, _, temperature, _, battery, ..] => Some(Self {
+                temperature: *temperature,
+                battery: *battery,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the battery level as the 0 to 4 bars shown by the Wii system menu.
+    #[must_use]
+    pub const fn battery_bars(&self) -> u8 {
+        // https://www.wiibrew.org/wiki/Wii_Balance_Board#Data_Format
+        match self.battery {
+            0x83.. => 4,
+            0x82 => 3,
+            0x7D..=0x81 => 2,
+            0x78..=0x7C => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// The raw data of the balance board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BalanceBoardData {
+    pub sensors: BalanceBoardSensors,
+    /// Only available with 11 or more extension bytes (e.g. reporting mode 0x34).
+    pub status: Option<BalanceBoardStatus>,
+}
+
+impl BalanceBoardData {
+    /// Parses the 8 extension bytes of data reporting mode 0x32, which only contain the sensors.
+    #[must_use]
+    pub const fn from_8_bytes(data: &[u8; 8]) -> Self {
+        Self {
+            sensors: BalanceBoardSensors::from_be_bytes(data),
+            status: None,
+        }
+    }
+
+    /// Parses the 19 extension bytes of data reporting mode 0x34,
+    /// which contain the sensors, the temperature and the battery level.
+    #[must_use]
+    pub const fn from_19_bytes(data: &[u8; 19]) -> Self {
+        Self {
+            sensors: BalanceBoardSensors::from_be_bytes(data),
+            status: Some(BalanceBoardStatus {
+                temperature: data[8],
+                battery: data[10],
+            }),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for BalanceBoardData {
+    type Error = WiimoteError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        // https://www.wiibrew.org/wiki/Wii_Balance_Board#Data_Format
+        // 8 bytes of sensor data, followed by the temperature, padding and the battery level.
+        if value.len() < 8 {
+            return Err(WiimoteDeviceError::MissingData.into());
+        }
+
+        Ok(Self {
+            sensors: BalanceBoardSensors::from_be_bytes(value),
+            status: BalanceBoardStatus::from_extension_data(value),
+        })
+    }
+}
+
+/// The decoded data of a balance board data report, see [`BalanceBoardSession::read_frame`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceBoardFrame {
+    pub front_button: bool,
+    pub data: BalanceBoardData,
+    /// The weights calibrated with the calibration of the session.
+    pub weights: BalanceBoardWeights,
+}
+
+impl BalanceBoardFrame {
+    /// Decodes the extension bytes of a data report, `None` if the data reporting mode
+    /// contains less than the 8 bytes of sensor data.
+    #[must_use]
+    pub fn from_data_report(
+        wiimote_data: &WiimoteData,
+        calibration: &BalanceBoardCalibration,
+    ) -> Option<Self> {
+        let bytes = wiimote_data.extension_data().unwrap_or_default();
+        let data = match (bytes.first_chunk(), bytes.first_chunk()) {
+            (Some(bytes), _) => BalanceBoardData::from_19_bytes(bytes),
+            (None, Some(bytes)) => BalanceBoardData::from_8_bytes(bytes),
+            (None, None) => return None,
+        };
+        Some(Self {
+            front_button: BalanceBoard::is_front_button_pressed(wiimote_data.buttons()),
+            data,
+            weights: calibration.get_weights(&data),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balance_board_weights() {
+        let mut calibration_data = [0u8; 32];
+        for corner in 0..4 {
+            calibration_data[4 + corner * 2..6 + corner * 2]
+                .copy_from_slice(&1000u16.to_be_bytes());
+            calibration_data[12 + corner * 2..14 + corner * 2]
+                .copy_from_slice(&2700u16.to_be_bytes());
+            calibration_data[20 + corner * 2..22 + corner * 2]
+                .copy_from_slice(&4400u16.to_be_bytes());
+        }
+        let calibration = BalanceBoardCalibration::from_calibration_data(&calibration_data, 20);
+
+        let mut data = [0u8; 11];
+        data[0..2].copy_from_slice(&1850u16.to_be_bytes()); // 8.5 kg
+        data[2..4].copy_from_slice(&2700u16.to_be_bytes()); // 17 kg
+        data[4..6].copy_from_slice(&3550u16.to_be_bytes()); // 25.5 kg
+        data[6..8].copy_from_slice(&1000u16.to_be_bytes()); // 0 kg
+        data[8] = 20; // Temperature equal to the reference
+        data[10] = 0x83; // Battery
+
+        let data = BalanceBoardData::try_from(data.as_slice()).unwrap();
+        let weights = calibration.get_weights(&data);
+
+        assert_eq!(data.status.map(|status| status.battery), Some(0x83));
+        assert_eq!(data.status.map(|status| status.battery_bars()), Some(4));
+        assert!((weights.top_right - 8.5).abs() < 1e-9);
+        assert!((weights.bottom_right - 17.0).abs() < 1e-9);
+        assert!((weights.top_left - 25.5).abs() < 1e-9);
+        assert!(weights.bottom_left.abs() < 1e-9);
+        assert!((weights.total() - 51.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_balance_board_layouts() {
+        let mut data = [0u8; 19];
+        data[0..2].copy_from_slice(&1850u16.to_be_bytes());
+        data[6..8].copy_from_slice(&1000u16.to_be_bytes());
+        data[8] = 20;
+        data[10] = 0x82;
+        data[11..].fill(0xFF); // Padding
+
+        let short = BalanceBoardData::from_8_bytes(data.first_chunk().unwrap());
+        let full = BalanceBoardData::from_19_bytes(&data);
+        assert_eq!(short.sensors, full.sensors);
+        assert_eq!(short.sensors.bottom_left, 1000);
+        assert_eq!(short.status, None);
+        assert_eq!(full.status.map(|status| status.battery_bars()), Some(3));
+        assert_eq!(BalanceBoardData::try_from(data.as_slice()).unwrap(), full);
+    }
+
+    #[test]
+    fn test_balance_board_tare() {
+        let mut calibration_data = [0u8; 32];
+        for corner in 0..4 {
+            calibration_data[4 + corner * 2..6 + corner * 2]
+                .copy_from_slice(&1000u16.to_be_bytes());
+            calibration_data[12 + corner * 2..14 + corner * 2]
+                .copy_from_slice(&2700u16.to_be_bytes());
+            calibration_data[20 + corner * 2..22 + corner * 2]
+                .copy_from_slice(&4400u16.to_be_bytes());
+        }
+        let mut calibration = BalanceBoardCalibration::from_calibration_data(&calibration_data, 20);
+
+        let unloaded = |top_right: u16| BalanceBoardData {
+            sensors: BalanceBoardSensors {
+                top_right,
+                bottom_right: 1000,
+                top_left: 1000,
+                bottom_left: 1000,
+            },
+            status: None,
+        };
+        assert!(calibration.tare(&[]).is_none());
+        let offsets = calibration.tare(&[unloaded(1090), unloaded(1110)]).unwrap();
+        assert!((offsets.top_right - 1.0).abs() < 1e-9);
+
+        let weights = calibration.get_weights(&unloaded(1100));
+        assert!(weights.total().abs() < 1e-9);
+
+        calibration.reset_tare();
+        let weights = calibration.get_weights(&unloaded(1100));
+        assert!((weights.top_right - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,344 @@
+use bitflags::bitflags;
+
+use crate::calibration::{calibration_checksum_valid, normalize_stick};
+
+bitflags! {
+    /// Buttons of the Classic Controller (Pro).
+    ///
+    /// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Classic_Controller#Data_Format>
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ClassicControllerButtons: u16 {
+        const R = 1 << 1;
+        const PLUS = 1 << 2;
+        const HOME = 1 << 3;
+        const MINUS = 1 << 4;
+        const L = 1 << 5;
+        const DOWN = 1 << 6;
+        const RIGHT = 1 << 7;
+
+        const UP = 1 << 8;
+        const LEFT = 1 << 9;
+        const ZR = 1 << 10;
+        const X = 1 << 11;
+        const A = 1 << 12;
+        const Y = 1 << 13;
+        const B = 1 << 14;
+        const ZL = 1 << 15;
+    }
+}
+
+/// A button of the Classic Controller (Pro).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClassicControllerButton {
+    A,
+    B,
+    X,
+    Y,
+    L,
+    R,
+    ZL,
+    ZR,
+    Plus,
+    Minus,
+    Home,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ClassicControllerButton {
+    pub const ALL: [Self; 15] = [
+        Self::A,
+        Self::B,
+        Self::X,
+        Self::Y,
+        Self::L,
+        Self::R,
+        Self::ZL,
+        Self::ZR,
+        Self::Plus,
+        Self::Minus,
+        Self::Home,
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+    ];
+
+    /// Returns the flag of the button in the button data.
+    #[must_use]
+    pub const fn flag(self) -> ClassicControllerButtons {
+        match self {
+            Self::A => ClassicControllerButtons::A,
+            Self::B => ClassicControllerButtons::B,
+            Self::X => ClassicControllerButtons::X,
+            Self::Y => ClassicControllerButtons::Y,
+            Self::L => ClassicControllerButtons::L,
+            Self::R => ClassicControllerButtons::R,
+            Self::ZL => ClassicControllerButtons::ZL,
+            Self::ZR => ClassicControllerButtons::ZR,
+            Self::Plus => ClassicControllerButtons::PLUS,
+            Self::Minus => ClassicControllerButtons::MINUS,
+            Self::Home => ClassicControllerButtons::HOME,
+            Self::Up => ClassicControllerButtons::UP,
+            Self::Down => ClassicControllerButtons::DOWN,
+            Self::Left => ClassicControllerButtons::LEFT,
+            Self::Right => ClassicControllerButtons::RIGHT,
+        }
+    }
+}
+
+/// The calibration data of the Classic Controller (Pro).
+/// Can be used to convert raw stick and trigger data.
+///
+/// The values are stored with 8 bits, the sticks and triggers report fewer bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassicControllerCalibration {
+    left_stick_x: StickAxisCalibration,
+    left_stick_y: StickAxisCalibration,
+    right_stick_x: StickAxisCalibration,
+    right_stick_y: StickAxisCalibration,
+    left_trigger_zero: u8,
+    right_trigger_zero: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StickAxisCalibration {
+    max: u8,
+    min: u8,
+    center: u8,
+}
+
+impl StickAxisCalibration {
+    const fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            max: bytes[0],
+            min: bytes[1],
+            center: bytes[2],
+        }
+    }
+
+    /// Normalizes a value with `bits` bits, the calibration has 8 bits.
+    fn normalize(self, value: u8, bits: u32) -> f64 {
+        normalize_stick(value << (8 - bits), self.min, self.center, self.max)
+    }
+}
+
+impl Default for ClassicControllerCalibration {
+    /// Typical values of an original Classic Controller, used when the calibration data is invalid.
+    fn default() -> Self {
+        let axis = StickAxisCalibration {
+            max: 0xE0,
+            min: 0x20,
+            center: 0x80,
+        };
+        Self {
+            left_stick_x: axis,
+            left_stick_y: axis,
+            right_stick_x: axis,
+            right_stick_y: axis,
+            left_trigger_zero: 0x00,
+            right_trigger_zero: 0x00,
+        }
+    }
+}
+
+impl ClassicControllerCalibration {
+    /// Parses the 16 bytes of calibration data read from register 0xA40020.
+    /// Returns `None` if the checksum does not match.
+    #[must_use]
+    pub fn from_calibration_data(data: &[u8; 16]) -> Option<Self> {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Classic_Controller#Calibration_data
+        // The last two bytes are a checksum of the first 14 bytes like for the Nunchuck.
+        if !calibration_checksum_valid(data) {
+            return None;
+        }
+
+        Some(Self {
+            left_stick_x: StickAxisCalibration::from_bytes(&data[0..3]),
+            left_stick_y: StickAxisCalibration::from_bytes(&data[3..6]),
+            right_stick_x: StickAxisCalibration::from_bytes(&data[6..9]),
+            right_stick_y: StickAxisCalibration::from_bytes(&data[9..12]),
+            left_trigger_zero: data[12],
+            right_trigger_zero: data[13],
+        })
+    }
+
+    /// Returns the position of the left stick in the range -1.0 to 1.0 from the 6 bit raw values.
+    #[must_use]
+    pub fn get_left_stick(&self, x: u8, y: u8) -> (f64, f64) {
+        (
+            self.left_stick_x.normalize(x, 6),
+            self.left_stick_y.normalize(y, 6),
+        )
+    }
+
+    /// Returns the position of the right stick in the range -1.0 to 1.0 from the 5 bit raw values.
+    #[must_use]
+    pub fn get_right_stick(&self, x: u8, y: u8) -> (f64, f64) {
+        (
+            self.right_stick_x.normalize(x, 5),
+            self.right_stick_y.normalize(y, 5),
+        )
+    }
+
+    /// Returns the position of the analog triggers in the range 0.0 to 1.0.
+    #[must_use]
+    pub fn get_triggers(&self, data: &ClassicControllerData) -> (f64, f64) {
+        let normalize = |value: u8, zero: u8| {
+            let range = f64::from(0xF8u8.saturating_sub(zero).max(1));
+            (f64::from((value << 3).saturating_sub(zero)) / range).clamp(0.0, 1.0)
+        };
+        (
+            normalize(data.left_trigger, self.left_trigger_zero),
+            normalize(data.right_trigger, self.right_trigger_zero),
+        )
+    }
+}
+
+/// The raw data of a Classic Controller with analog triggers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClassicControllerData {
+    pub buttons: ClassicControllerButtons,
+    /// 6 bits, 0-63.
+    pub left_stick_x: u8,
+    /// 6 bits, 0-63.
+    pub left_stick_y: u8,
+    /// 5 bits, 0-31.
+    pub right_stick_x: u8,
+    /// 5 bits, 0-31.
+    pub right_stick_y: u8,
+    /// 5 bits, 0-31.
+    pub left_trigger: u8,
+    /// 5 bits, 0-31.
+    pub right_trigger: u8,
+}
+
+impl From<[u8; 6]> for ClassicControllerData {
+    fn from(value: [u8; 6]) -> Self {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Classic_Controller#Data_Format
+        // Buttons are active low, a pressed button is reported as 0.
+        let buttons = !u16::from_le_bytes([value[4], value[5]]);
+
+        Self {
+            buttons: ClassicControllerButtons::from_bits_truncate(buttons),
+            left_stick_x: value[0] & 0b0011_1111,
+            left_stick_y: value[1] & 0b0011_1111,
+            right_stick_x: ((value[0] >> 3) & 0b1_1000)
+                | ((value[1] >> 5) & 0b0_0110)
+                | (value[2] >> 7),
+            right_stick_y: value[2] & 0b1_1111,
+            left_trigger: ((value[2] >> 2) & 0b1_1000) | (value[3] >> 5),
+            right_trigger: value[3] & 0b1_1111,
+        }
+    }
+}
+
+/// The raw data of a Classic Controller Pro.
+///
+/// The Classic Controller Pro and the NES/SNES Classic Mini controllers have no analog triggers,
+/// `L` and `R` are only reported as digital buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClassicControllerProData {
+    pub buttons: ClassicControllerButtons,
+    /// 6 bits, 0-63.
+    pub left_stick_x: u8,
+    /// 6 bits, 0-63.
+    pub left_stick_y: u8,
+    /// 5 bits, 0-31.
+    pub right_stick_x: u8,
+    /// 5 bits, 0-31.
+    pub right_stick_y: u8,
+}
+
+impl From<ClassicControllerData> for ClassicControllerProData {
+    fn from(value: ClassicControllerData) -> Self {
+        Self {
+            buttons: value.buttons,
+            left_stick_x: value.left_stick_x,
+            left_stick_y: value.left_stick_y,
+            right_stick_x: value.right_stick_x,
+            right_stick_y: value.right_stick_y,
+        }
+    }
+}
+
+impl From<[u8; 6]> for ClassicControllerProData {
+    fn from(value: [u8; 6]) -> Self {
+        ClassicControllerData::from(value).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classic_controller_data() {
+        let data = [
+            0b1010_0001, // RX<4:3>, LX = 33
+            0b0101_1111, // RX<2:1>, LY = 31
+            0b1010_0011, // RX<0>, LT<4:3>, RY = 3
+            0b1110_0101, // LT<2:0>, RT = 5
+            0b1111_1001, // Plus and R pressed
+            0b1110_1111, // A pressed
+        ];
+
+        let data = ClassicControllerData::from(data);
+
+        assert_eq!(data.left_stick_x, 33);
+        assert_eq!(data.left_stick_y, 31);
+        assert_eq!(data.right_stick_x, 0b10011);
+        assert_eq!(data.right_stick_y, 3);
+        assert_eq!(data.left_trigger, 0b01111);
+        assert_eq!(data.right_trigger, 5);
+        assert_eq!(
+            data.buttons.bits(),
+            ClassicControllerButtons::PLUS
+                .union(ClassicControllerButtons::R)
+                .union(ClassicControllerButtons::A)
+                .bits()
+        );
+    }
+
+    #[test]
+    fn test_classic_controller_pro_data() {
+        let data = [0x20, 0x20, 0x10, 0x00, 0xFF, 0b0111_1111];
+
+        let data = ClassicControllerProData::from(data);
+
+        assert_eq!(data.left_stick_x, 32);
+        assert_eq!(data.left_stick_y, 32);
+        assert_eq!(data.right_stick_x, 0);
+        assert_eq!(data.right_stick_y, 16);
+        assert_eq!(data.buttons.bits(), ClassicControllerButtons::ZL.bits());
+    }
+
+    #[test]
+    fn test_classic_controller_calibration() {
+        let mut data = [
+            0xFC, 0x04, 0x80, // Left stick X
+            0xE0, 0x20, 0x7C, // Left stick Y
+            0xF8, 0x08, 0x80, // Right stick X
+            0xF8, 0x08, 0x80, // Right stick Y
+            0x10, 0x08, // Triggers
+            0, 0,
+        ];
+        let checksum = data[..14]
+            .iter()
+            .fold(0x55u8, |sum, byte| sum.wrapping_add(*byte));
+        data[14] = checksum;
+        data[15] = checksum.wrapping_add(0xAA);
+
+        let calibration = ClassicControllerCalibration::from_calibration_data(&data).unwrap();
+        assert_eq!(calibration.get_left_stick(63, 31), (1.0, 0.0));
+        assert_eq!(calibration.get_right_stick(1, 16), (-1.0, 0.0));
+
+        let triggers = ClassicControllerData::from([0, 0, 0, 0b0101_1111, 0xFF, 0xFF]);
+        assert_eq!(calibration.get_triggers(&triggers), (0.0, 1.0));
+
+        data[15] ^= 1;
+        assert!(ClassicControllerCalibration::from_calibration_data(&data).is_none());
+    }
+}
@@ -0,0 +1,37 @@
+//! Legacy encrypted extension mode.
+//!
+//! Before the new initialization sequence (0x55 to 0xA400F0, 0x00 to 0xA400FB) was introduced,
+//! extensions were initialized by writing 0x00 to 0xA40040. Extensions initialized this way
+//! encrypt all data, including their identifier and calibration.
+//! Some third-party extensions only respond correctly to this legacy initialization,
+//! see [`WiimoteExtension::enable_encryption`](super::WiimoteExtension::enable_encryption).
+//!
+//! WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers#The_Old_Way>
+
+/// Decrypts a single byte of data encrypted with the key of all zeros.
+#[must_use]
+pub const fn decrypt_byte(value: u8) -> u8 {
+    (value ^ 0x17).wrapping_add(0x17)
+}
+
+/// Decrypts data encrypted with the key of all zeros in place.
+pub fn decrypt(data: &mut [u8]) {
+    for byte in data {
+        *byte = decrypt_byte(*byte);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decrypt() {
+        // Nunchuck identifier 0000 A420 0000 as received in encrypted mode.
+        let mut data = [0xFE, 0xFE, 0x9A, 0x1E, 0xFE, 0xFE];
+
+        decrypt(&mut data);
+
+        assert_eq!(data, [0x00, 0x00, 0xA4, 0x20, 0x00, 0x00]);
+    }
+}
@@ -0,0 +1,468 @@
+//! The I/O of the extensions through a `WiimoteDevice`: detection, initialization,
+//! reading the calibration and managing their data reporting.
+//!
+//! The identifiers, calibration blocks and data of the extensions are decoded by the other
+//! extension modules, which never touch a transport and can be used without a Wii remote.
+
+use super::encryption;
+use crate::input::AcknowledgeData;
+use crate::output::{Addressing, OutputReport, PlayerLedFlags};
+use crate::prelude::*;
+use crate::simple_io::{self, MemoryRequest, MemoryResponse};
+
+/// Start of the 16 byte encryption key, writing 0x00 uses a key of all zeros.
+const ENCRYPTION_KEY_ADDRESS: u32 = 0xA4_0040;
+
+// https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Classic_Controller#Data_Format
+// The data format of the Classic Controller is stored in register 0xA400FE.
+const DATA_FORMAT_ADDRESS: u32 = 0xA4_00FE;
+const DATA_FORMAT_HIGH_RESOLUTION: u8 = 0x03;
+const DATA_FORMAT_DEFAULT: u8 = 0x01;
+
+impl WiimoteExtension {
+    /// Detects the extension (except for Motion Plus) connected to the Wii remote.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or if invalid data is received.
+    pub fn detect(wiimote: &WiimoteDevice) -> WiimoteResult<Option<Self>> {
+        Ok(Self::detect_with_encryption(wiimote)?.map(|(extension, _)| extension))
+    }
+
+    /// Detects the extension and whether it had to be initialized in the legacy encrypted mode.
+    pub(crate) fn detect_with_encryption(
+        wiimote: &WiimoteDevice,
+    ) -> WiimoteResult<Option<(Self, bool)>> {
+        let responses = simple_io::exchange(wiimote, &Self::initialization_requests())?;
+        let acknowledgements: Vec<_> = responses
+            .into_iter()
+            .filter_map(|response| match response {
+                MemoryResponse::Write(ack) => Some(ack),
+                MemoryResponse::Read(_) => None,
+            })
+            .collect();
+        Self::detect_initialized(wiimote, &acknowledgements)
+    }
+
+    /// The writes initializing the extension without encryption, they can be sent together with other requests.
+    pub(crate) fn initialization_requests() -> [MemoryRequest; 2] {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers#Identification
+        // The new way to initialize the extension is by writing 0x55 to 0x(4)A400F0, then writing 0x00 to 0x(4)A400FB.
+        let mut enable = [0u8; 16];
+        enable[0] = 0x55;
+        [
+            MemoryRequest::Write(Addressing::control_registers(0xA4_00F0, 1), enable),
+            MemoryRequest::Write(Addressing::control_registers(0xA4_00FB, 1), [0u8; 16]),
+        ]
+    }
+
+    /// Detects the extension after the [initialization requests](Self::initialization_requests)
+    /// were acknowledged with `acknowledgements`.
+    pub(crate) fn detect_initialized(
+        wiimote: &WiimoteDevice,
+        acknowledgements: &[AcknowledgeData],
+    ) -> WiimoteResult<Option<(Self, bool)>> {
+        let Some((identifier, encrypted)) = Self::identify_extension(wiimote, acknowledgements)?
+        else {
+            return Ok(None);
+        };
+
+        let extension = match Self::from_identifier(identifier) {
+            Self::ClassicController if !encrypted && Self::is_classic_controller_mini(wiimote)? => {
+                Self::set_classic_controller_data_format(wiimote, DATA_FORMAT_DEFAULT)?;
+                Self::ClassicControllerPro
+            }
+            extension => extension,
+        };
+        Ok(Some((extension, encrypted)))
+    }
+
+    /// The NES/SNES Classic Mini controllers identify as a regular Classic Controller,
+    /// but have no analog sticks or triggers and default to the high resolution data format 3.
+    fn is_classic_controller_mini(wiimote: &WiimoteDevice) -> WiimoteResult<bool> {
+        let addressing = Addressing::control_registers(DATA_FORMAT_ADDRESS, 1);
+        let read_result = simple_io::read_16_bytes_sync(wiimote, addressing)?;
+        Ok(read_result.is_success() && read_result.data[0] == DATA_FORMAT_HIGH_RESOLUTION)
+    }
+
+    /// Switches the data format of a Classic Controller, the Classic Mini controllers are switched
+    /// to data format 1 so the data is compatible with the Classic Controller Pro.
+    fn set_classic_controller_data_format(
+        wiimote: &WiimoteDevice,
+        data_format: u8,
+    ) -> WiimoteResult<()> {
+        simple_io::write_verified(wiimote, DATA_FORMAT_ADDRESS, &[data_format])
+    }
+
+    fn identify_extension(
+        wiimote: &WiimoteDevice,
+        acknowledgements: &[AcknowledgeData],
+    ) -> WiimoteResult<Option<([u8; 6], bool)>> {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers#Identification
+        // Once initialized, the last six bytes of the register block identify the connected Extension Controller.
+        // A six-byte read of register 0xA400FA will return these bytes.
+        // The Extension Controller must have been initialized prior to this.
+        if acknowledgements
+            .iter()
+            .any(|ack| ack.error() == Some(MemoryError::WriteOnly))
+        {
+            return Ok(None);
+        }
+
+        match Self::read_identifier(wiimote)? {
+            Some(identifier) if identifier != [0xFF; 6] => Ok(Some((identifier, false))),
+            // The extension did not respond to the new initialization, try the legacy encrypted mode.
+            _ => Self::identify_encrypted_extension(wiimote),
+        }
+    }
+
+    /// Initializes the extension the legacy way, which enables encryption with a key of all zeros.
+    /// The data read from the extension afterwards needs to be decrypted with [`decrypt`](super::encryption::decrypt).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or if no extension acknowledged the write.
+    pub fn enable_encryption(wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+        let addressing = Addressing::control_registers(ENCRYPTION_KEY_ADDRESS, 1);
+        let ack = simple_io::write_16_bytes_sync(wiimote, addressing, &[0u8; 16])?;
+        simple_io::check_acknowledge(&ack, ENCRYPTION_KEY_ADDRESS)
+    }
+
+    fn identify_encrypted_extension(
+        wiimote: &WiimoteDevice,
+    ) -> WiimoteResult<Option<([u8; 6], bool)>> {
+        if Self::enable_encryption(wiimote).is_err() {
+            return Ok(None);
+        }
+
+        Ok(Self::read_identifier(wiimote)?.map(|mut identifier| {
+            encryption::decrypt(&mut identifier);
+            (identifier, true)
+        }))
+    }
+
+    fn read_identifier(wiimote: &WiimoteDevice) -> WiimoteResult<Option<[u8; 6]>> {
+        let addressing = Addressing::control_registers(0xA4_00FA, 6);
+        let read_result = simple_io::read_16_bytes_sync(wiimote, addressing)?;
+        // Address is actually 0xA4_00FA, but only the lower 2 bytes are returned
+        if read_result.address_offset() != 0x00FA || read_result.size() < 6 {
+            Err(WiimoteDeviceError::InvalidData.into())
+        } else if read_result.error() == Some(MemoryError::WriteOnly) {
+            Ok(None)
+        } else {
+            let mut extension_info = [0u8; 6];
+            extension_info.copy_from_slice(&read_result.data[..6]);
+            Ok(Some(extension_info))
+        }
+    }
+}
+
+impl ConnectedExtension {
+    /// Detects the extension (except for Motion Plus) connected to the Wii remote and reads its calibration.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or if invalid data is received.
+    pub fn detect(wiimote: &WiimoteDevice) -> WiimoteResult<Option<Self>> {
+        let detected = WiimoteExtension::detect_with_encryption(wiimote)?;
+        Self::with_calibration(wiimote, detected)
+    }
+
+    /// Detects the extension after the [initialization requests](WiimoteExtension::initialization_requests)
+    /// were acknowledged with `acknowledgements` and reads its calibration.
+    pub(crate) fn detect_initialized(
+        wiimote: &WiimoteDevice,
+        acknowledgements: &[AcknowledgeData],
+    ) -> WiimoteResult<Option<Self>> {
+        let detected = WiimoteExtension::detect_initialized(wiimote, acknowledgements)?;
+        Self::with_calibration(wiimote, detected)
+    }
+
+    fn with_calibration(
+        wiimote: &WiimoteDevice,
+        detected: Option<(WiimoteExtension, bool)>,
+    ) -> WiimoteResult<Option<Self>> {
+        let Some((extension, encrypted)) = detected else {
+            return Ok(None);
+        };
+
+        let calibration = match extension {
+            WiimoteExtension::Nunchuck => {
+                ExtensionCalibration::Nunchuck(Self::read_nunchuck_calibration(wiimote, encrypted)?)
+            }
+            WiimoteExtension::ClassicController | WiimoteExtension::ClassicControllerPro => {
+                ExtensionCalibration::ClassicController(Self::read_classic_controller_calibration(
+                    wiimote, encrypted,
+                )?)
+            }
+            WiimoteExtension::BalanceBoard => ExtensionCalibration::BalanceBoard(
+                Self::read_balance_board_calibration(wiimote, encrypted)?,
+            ),
+            WiimoteExtension::Unknown(_) => ExtensionCalibration::None,
+        };
+
+        Ok(Some(Self::new(extension, calibration, encrypted)))
+    }
+
+    fn read_nunchuck_calibration(
+        wiimote: &WiimoteDevice,
+        encrypted: bool,
+    ) -> WiimoteResult<NunchuckCalibration> {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Nunchuck#Calibration_data
+        let data = Self::read_register_block(wiimote, 0xA4_0020, 16, encrypted)?;
+        // Many third-party Nunchucks do not store valid calibration data.
+        Ok(NunchuckCalibration::from_calibration_data(&data).unwrap_or_default())
+    }
+
+    fn read_classic_controller_calibration(
+        wiimote: &WiimoteDevice,
+        encrypted: bool,
+    ) -> WiimoteResult<ClassicControllerCalibration> {
+        let data = Self::read_register_block(wiimote, 0xA4_0020, 16, encrypted)?;
+        Ok(ClassicControllerCalibration::from_calibration_data(&data).unwrap_or_default())
+    }
+
+    fn read_balance_board_calibration(
+        wiimote: &WiimoteDevice,
+        encrypted: bool,
+    ) -> WiimoteResult<BalanceBoardCalibration> {
+        // https://www.wiibrew.org/wiki/Wii_Balance_Board#Calibration_Data
+        let mut data = [0u8; 32];
+        data[..16].copy_from_slice(&Self::read_register_block(
+            wiimote, 0xA4_0020, 16, encrypted,
+        )?);
+        data[16..].copy_from_slice(&Self::read_register_block(
+            wiimote, 0xA4_0030, 16, encrypted,
+        )?);
+        let reference_temperature = Self::read_register_block(wiimote, 0xA4_0060, 1, encrypted)?[0];
+
+        Ok(BalanceBoardCalibration::from_calibration_data(
+            &data,
+            reference_temperature,
+        ))
+    }
+
+    fn read_register_block(
+        wiimote: &WiimoteDevice,
+        address: u32,
+        size: u16,
+        encrypted: bool,
+    ) -> WiimoteResult<[u8; 16]> {
+        let addressing = Addressing::control_registers(address, size);
+        let mut data = simple_io::read_16_bytes_sync_checked(wiimote, addressing)?;
+        if encrypted {
+            encryption::decrypt(&mut data);
+        }
+        Ok(data)
+    }
+}
+
+// https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Wii_Motion_Plus
+impl MotionPlus {
+    /// Detects if the Wii remote has a Motion Plus extension.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if communication to the Wii remote failed.
+    pub(crate) fn detect(wiimote: &WiimoteDevice) -> WiimoteResult<Option<Self>> {
+        let address = Addressing::control_registers(0xA6_00FA, 6);
+        let memory_data = simple_io::read_16_bytes_sync(wiimote, address)?;
+        Ok(memory_data
+            .data
+            .first_chunk()
+            .and_then(|identifier| MotionPlusType::from_identifier(*identifier))
+            .map(Self::new))
+    }
+
+    /// Tries to initialize the Motion Plus extension and read its calibration.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or when receiving invalid data.
+    pub fn initialize(&self, wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+        Self::write_single_control_byte(wiimote, 0xA6_00F0, 0x55)?;
+        self.read_calibration_data(wiimote)?;
+        self.set_initialized(true);
+        Ok(())
+    }
+
+    /// Initializes the Motion Plus again after it was reset, e.g. by a reconnect, and activates `mode`,
+    /// keeping the current calibration.
+    pub(crate) fn reactivate(
+        &self,
+        wiimote: &WiimoteDevice,
+        mode: MotionPlusMode,
+    ) -> WiimoteResult<()> {
+        self.set_initialized(false);
+        self.set_mode(MotionPlusMode::Inactive);
+        if matches!(mode, MotionPlusMode::Inactive) {
+            return Ok(());
+        }
+        let calibration = self.calibration();
+        self.initialize(wiimote)?;
+        self.set_calibration(calibration);
+        self.change_mode(wiimote, mode)
+    }
+
+    /// Changes the mode of the Motion Plus extension.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or when receiving invalid data.
+    pub fn change_mode(&self, wiimote: &WiimoteDevice, mode: MotionPlusMode) -> WiimoteResult<()> {
+        let (address, value) = mode.register_write();
+        Self::write_single_control_byte(wiimote, address, value)?;
+        self.set_mode(mode);
+        Ok(())
+    }
+
+    /// Deactivates the Motion Plus and restores the extension connected to it to normal operation.
+    /// The extension is detected again and published as [`WiimoteEvent::ExtensionChanged`],
+    /// so [`WiimoteDevice::extension`] returns the extension afterwards.
+    ///
+    /// The policy of the device is reset to [`MotionPlusPolicy::Manual`],
+    /// otherwise the Motion Plus would be activated again on the next extension change.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or when receiving invalid data.
+    pub fn deactivate(&self, wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+        wiimote.set_motion_plus_policy(MotionPlusPolicy::Manual);
+        self.change_mode(wiimote, MotionPlusMode::Inactive)?;
+        wiimote.redetect_extension()
+    }
+
+    fn write_single_control_byte(
+        wiimote: &WiimoteDevice,
+        address: u32,
+        value: u8,
+    ) -> WiimoteResult<()> {
+        let addressing = Addressing::control_registers(address, 1);
+        let mut memory_write_buffer = [0u8; 16];
+        memory_write_buffer[0] = value;
+        let ack = simple_io::write_16_bytes_sync(wiimote, addressing, &memory_write_buffer)?;
+        if let Some(error @ MemoryError::WriteOnly) = ack.error() {
+            return Err(WiimoteDeviceError::Memory { address, error }.into());
+        }
+
+        Ok(())
+    }
+
+    fn read_calibration_data(&self, wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+        let mut data = [0u8; 32];
+        for (address, block) in (0xA6_0020..).step_by(16).zip(data.chunks_mut(16)) {
+            let addressing = Addressing::control_registers(address, 16);
+            block.copy_from_slice(&simple_io::read_16_bytes_sync_checked(wiimote, addressing)?);
+        }
+        self.set_factory_calibration(MotionPlusCalibration::from_calibration_data(&data)?);
+        Ok(())
+    }
+}
+
+impl BalanceBoard {
+    /// Turns the LED of the front button on or off.
+    ///
+    /// ```no_run
+    /// use wiimote_rs::prelude::*;
+    ///
+    /// fn indicate_ready(balance_board: &WiimoteDevice) -> WiimoteResult<()> {
+    ///     BalanceBoard::set_led(balance_board, true)
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the balance board is disconnected or write failed.
+    pub fn set_led(wiimote: &WiimoteDevice, on: bool) -> WiimoteResult<()> {
+        let flags = if on {
+            Self::LED
+        } else {
+            PlayerLedFlags::empty()
+        };
+        wiimote.write(&OutputReport::PlayerLed(flags))
+    }
+}
+
+/// Manages the data reporting of a balance board through a [`WiimoteSession`]
+/// started with [`SessionConfig::balance_board`], and decodes the weights.
+///
+/// With the status, data reporting mode 0x34 is used, which contains the temperature
+/// to compensate the drift of the sensors and the battery level.
+/// Otherwise mode 0x32 with only the 8 bytes of sensor data is used.
+///
+/// ```no_run
+/// use wiimote_rs::prelude::*;
+///
+/// fn print_weight(balance_board: &WiimoteDevice) -> WiimoteResult<()> {
+///     let session = BalanceBoardSession::start(balance_board, true)?;
+///     loop {
+///         let frame = session.read_frame()?;
+///         println!("{:.1} kg", frame.weights.total());
+///     }
+/// }
+/// ```
+pub struct BalanceBoardSession<'a> {
+    session: WiimoteSession<'a>,
+    calibration: BalanceBoardCalibration,
+}
+
+impl<'a> BalanceBoardSession<'a> {
+    /// Sets the data reporting mode for the balance board, reporting its temperature
+    /// and battery level if `with_status` is set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no balance board is connected
+    /// or the data reporting mode could not be written.
+    pub fn start(wiimote: &'a WiimoteDevice, with_status: bool) -> WiimoteResult<Self> {
+        let Some(ExtensionCalibration::BalanceBoard(calibration)) = wiimote.extension_calibration()
+        else {
+            return Err(WiimoteDeviceError::MissingData.into());
+        };
+        let session = WiimoteSession::start(wiimote, SessionConfig::balance_board(with_status))?;
+        Ok(Self {
+            session,
+            calibration,
+        })
+    }
+
+    /// Returns the data reporting mode used by the session.
+    #[must_use]
+    pub const fn reporting_mode(&self) -> u8 {
+        self.session.config().reporting_mode().0
+    }
+
+    /// Returns the calibration used for the weights, e.g. to [tare](BalanceBoardCalibration::tare) it.
+    pub fn calibration_mut(&mut self) -> &mut BalanceBoardCalibration {
+        &mut self.calibration
+    }
+
+    /// Reads until the next data report arrives and decodes it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the balance board is disconnected or read failed.
+    pub fn read_frame(&self) -> WiimoteResult<BalanceBoardFrame> {
+        loop {
+            let data = self.session.read_data()?;
+            if let Some(frame) = BalanceBoardFrame::from_data_report(&data, &self.calibration) {
+                return Ok(frame);
+            }
+        }
+    }
+
+    /// Reads the next input report waiting for a maximum of `timeout_millis`,
+    /// returns `None` if it is not a data report of the balance board.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the balance board is disconnected, read failed
+    /// or no report was received within the timeout.
+    pub fn read_frame_timeout(
+        &self,
+        timeout_millis: usize,
+    ) -> WiimoteResult<Option<BalanceBoardFrame>> {
+        let data = self.session.read_data_timeout(timeout_millis)?;
+        Ok(data.and_then(|data| BalanceBoardFrame::from_data_report(&data, &self.calibration)))
+    }
+}
@@ -1,73 +1,250 @@
+//! The extension controllers and the decoding of their identifiers, calibration and data.
+//!
+//! Apart from the `io` module, which detects and initializes the extensions through a `WiimoteDevice`,
+//! the extension modules do not depend on a transport, e.g. to decode captured reports or fuzz the decoders.
+
+pub(crate) mod balance_board;
+pub(crate) mod classic_controller;
+pub mod encryption;
+mod io;
 pub(crate) mod motion_plus;
+pub(crate) mod nunchuck;
+pub(crate) mod passthrough;
+pub(crate) mod registry;
+pub(crate) mod stillness;
+pub(crate) mod weight;
 
-use crate::output::Addressing;
-use crate::prelude::*;
-use crate::simple_io;
+use crate::calibration::StickProcessing;
+use crate::input::WiimoteData;
 
+pub use balance_board::*;
+pub use classic_controller::*;
+pub use io::BalanceBoardSession;
 pub use motion_plus::*;
+pub use nunchuck::*;
+pub use passthrough::*;
+pub use registry::*;
+pub use stillness::*;
+pub use weight::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WiimoteExtension {
     Nunchuck,
     ClassicController,
+    /// Classic Controller Pro or NES/SNES Classic Mini controller, both without analog triggers.
     ClassicControllerPro,
     BalanceBoard,
+    /// An extension with an identifier unknown to the library, its data can be decoded
+    /// by an [`ExtensionDecoder`] registered for the identifier.
     Unknown([u8; 6]),
 }
 
 impl WiimoteExtension {
-    /// Detects the extension (except for Motion Plus) connected to the Wii remote.
-    ///
-    /// # Errors
+    /// Returns the extension with the (decrypted) identifier read from register 0xA400FA.
     ///
-    /// This function will return an error on I/O error or if invalid data is received.
-    pub fn detect(wiimote: &WiimoteDevice) -> WiimoteResult<Option<Self>> {
-        let identifier = Self::identify_extension(wiimote)?;
-
+    /// The NES/SNES Classic Mini controllers cannot be told apart from a Classic Controller
+    /// by their identifier and are returned as [`Self::ClassicController`].
+    #[must_use]
+    pub const fn from_identifier(identifier: [u8; 6]) -> Self {
         // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers#Identification
-        Ok(match identifier {
-            Some([_, _, 0xA4, 0x20, 0x00, 0x00]) => Some(Self::Nunchuck),
-            Some([0x01, 0x00, 0xA4, 0x20, 0x01, 0x01]) => Some(Self::ClassicControllerPro),
-            Some([_, _, 0xA4, 0x20, 0x01, 0x01]) => Some(Self::ClassicController),
-            Some([_, _, 0xA4, 0x20, 0x04, 0x02]) => Some(Self::BalanceBoard),
-            Some(identifier) => Some(Self::Unknown(identifier)),
-            None => None,
-        })
+        match identifier {
+            [_, _, 0xA4, 0x20, 0x00, 0x00] => Self::Nunchuck,
+            [0x01, _, 0xA4, 0x20, 0x01, 0x01] => Self::ClassicControllerPro,
+            [_, _, 0xA4, 0x20, 0x01, 0x01] => Self::ClassicController,
+            [_, _, 0xA4, 0x20, 0x04, 0x02] => Self::BalanceBoard,
+            identifier => Self::Unknown(identifier),
+        }
     }
 
-    fn identify_extension(wiimote: &WiimoteDevice) -> WiimoteResult<Option<[u8; 6]>> {
-        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers#Identification
-        // The new way to initialize the extension is by writing 0x55 to 0x(4)A400F0, then writing 0x00 to 0x(4)A400FB.
-        // Once initialized, the last six bytes of the register block identify the connected Extension Controller.
-        // A six-byte read of register 0xA400FA will return these bytes.
-        // The Extension Controller must have been initialized prior to this.
-        let mut memory_write_buffer = [0u8; 16];
-
-        memory_write_buffer[0] = 0x55;
-        let addressing = Addressing::control_registers(0xA4_00F0, 1);
-        let ack = simple_io::write_16_bytes_sync(wiimote, addressing, &memory_write_buffer)?;
-        if ack.error_code() == 7 {
-            return Ok(None);
+    /// Returns the identifier of the extension, the identifier read from the extension for
+    /// [`Self::Unknown`] and the identifier of the original extension otherwise.
+    #[must_use]
+    pub const fn identifier(&self) -> [u8; 6] {
+        match self {
+            Self::Nunchuck => [0x00, 0x00, 0xA4, 0x20, 0x00, 0x00],
+            Self::ClassicController => [0x00, 0x00, 0xA4, 0x20, 0x01, 0x01],
+            Self::ClassicControllerPro => [0x01, 0x00, 0xA4, 0x20, 0x01, 0x01],
+            Self::BalanceBoard => [0x00, 0x00, 0xA4, 0x20, 0x04, 0x02],
+            Self::Unknown(identifier) => *identifier,
+        }
+    }
+}
+
+/// The calibration data of a connected extension.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionCalibration {
+    Nunchuck(NunchuckCalibration),
+    /// The calibration of the Classic Controller and the Classic Controller Pro.
+    ClassicController(ClassicControllerCalibration),
+    BalanceBoard(BalanceBoardCalibration),
+    /// The extension has no calibration data or it is not used for decoding.
+    None,
+}
+
+/// The decoded data of a connected extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtensionInput {
+    Nunchuck(NunchuckData),
+    ClassicController(ClassicControllerData),
+    ClassicControllerPro(ClassicControllerProData),
+    BalanceBoard(BalanceBoardData),
+    /// Decoded by the [`ExtensionDecoder`] registered for an unknown extension.
+    Custom(CustomExtensionData),
+}
+
+/// The calibrated analog inputs of an extension, see [`ConnectedExtension::axes`].
+/// Sticks range from -1.0 to 1.0 with up and right being positive, triggers from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtensionAxes {
+    /// The stick of the Nunchuck or the left stick of the Classic Controller.
+    pub left_stick: (f64, f64),
+    pub right_stick: Option<(f64, f64)>,
+    /// The analog triggers of the Classic Controller.
+    pub triggers: Option<(f64, f64)>,
+}
+
+/// The extensions found by [`WiimoteDevice::refresh_extension`](crate::prelude::WiimoteDevice::refresh_extension).
+#[derive(Debug, Clone)]
+pub struct ExtensionState {
+    /// The extension connected to the Wii remote or the pass-through port of the Motion Plus.
+    pub extension: Option<ConnectedExtension>,
+    /// The type of the Motion Plus if connected or built in.
+    pub motion_plus: Option<MotionPlusType>,
+}
+
+/// An extension connected to the Wii remote together with its calibration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectedExtension {
+    extension: WiimoteExtension,
+    calibration: ExtensionCalibration,
+    encrypted: bool,
+}
+
+impl ConnectedExtension {
+    pub(crate) const fn new(
+        extension: WiimoteExtension,
+        calibration: ExtensionCalibration,
+        encrypted: bool,
+    ) -> Self {
+        Self {
+            extension,
+            calibration,
+            encrypted,
         }
+    }
+
+    /// Returns the type of the connected extension.
+    #[must_use]
+    pub const fn extension(&self) -> WiimoteExtension {
+        self.extension
+    }
+
+    /// Returns the calibration data of the connected extension.
+    #[must_use]
+    pub const fn calibration(&self) -> &ExtensionCalibration {
+        &self.calibration
+    }
+
+    /// Returns whether the extension was initialized in the legacy encrypted mode.
+    /// The extension bytes are decrypted automatically by [`Self::decode`].
+    #[must_use]
+    pub const fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
 
-        memory_write_buffer[0] = 0x00;
-        let addressing = Addressing::control_registers(0xA4_00FB, 1);
-        let ack = simple_io::write_16_bytes_sync(wiimote, addressing, &memory_write_buffer)?;
-        if ack.error_code() == 7 {
-            return Ok(None);
+    /// Decodes the extension bytes of a data report.
+    ///
+    /// Returns `None` if the data reporting mode does not contain enough extension bytes
+    /// for the connected extension or the extension is unknown and no [`ExtensionDecoder`]
+    /// is registered for its identifier.
+    #[must_use]
+    pub fn decode(&self, wiimote_data: &WiimoteData) -> Option<ExtensionInput> {
+        let raw_data = wiimote_data.extension_data()?;
+        let mut buffer = [0u8; 21];
+        let extension_data = &mut buffer[..raw_data.len()];
+        extension_data.copy_from_slice(raw_data);
+        if self.encrypted {
+            encryption::decrypt(extension_data);
+        }
+
+        let mut six_bytes = [0u8; 6];
+        if let Some(data) = extension_data.get(..6) {
+            six_bytes.copy_from_slice(data);
+        }
+
+        match self.extension {
+            WiimoteExtension::Nunchuck if extension_data.len() >= 6 => {
+                Some(ExtensionInput::Nunchuck(NunchuckData::from(six_bytes)))
+            }
+            WiimoteExtension::ClassicController if extension_data.len() >= 6 => Some(
+                ExtensionInput::ClassicController(ClassicControllerData::from(six_bytes)),
+            ),
+            WiimoteExtension::ClassicControllerPro if extension_data.len() >= 6 => Some(
+                ExtensionInput::ClassicControllerPro(ClassicControllerProData::from(six_bytes)),
+            ),
+            WiimoteExtension::BalanceBoard => BalanceBoardData::try_from(&*extension_data)
+                .ok()
+                .map(ExtensionInput::BalanceBoard),
+            WiimoteExtension::Unknown(identifier) => {
+                registry::extension_decoder(identifier)?.decode(extension_data)
+            }
+            _ => None,
         }
+    }
+
+    /// Returns the sticks and triggers of the decoded input calibrated with the calibration
+    /// of the extension, with the deadzone and response curve of `processing` applied if set.
+    ///
+    /// Returns `None` for extensions without sticks.
+    #[must_use]
+    pub fn axes(
+        &self,
+        input: &ExtensionInput,
+        processing: Option<&StickProcessing>,
+    ) -> Option<ExtensionAxes> {
+        let classic_controller = match &self.calibration {
+            ExtensionCalibration::ClassicController(calibration) => calibration.clone(),
+            _ => ClassicControllerCalibration::default(),
+        };
+        let mut axes = match input {
+            ExtensionInput::Nunchuck(data) => {
+                let left_stick = match &self.calibration {
+                    ExtensionCalibration::Nunchuck(calibration) => calibration.get_stick(data),
+                    _ => NunchuckCalibration::default().get_stick(data),
+                };
+                ExtensionAxes {
+                    left_stick,
+                    right_stick: None,
+                    triggers: None,
+                }
+            }
+            ExtensionInput::ClassicController(data) => ExtensionAxes {
+                left_stick: classic_controller.get_left_stick(data.left_stick_x, data.left_stick_y),
+                right_stick: Some(
+                    classic_controller.get_right_stick(data.right_stick_x, data.right_stick_y),
+                ),
+                triggers: Some(classic_controller.get_triggers(data)),
+            },
+            ExtensionInput::ClassicControllerPro(data) => ExtensionAxes {
+                left_stick: classic_controller.get_left_stick(data.left_stick_x, data.left_stick_y),
+                right_stick: Some(
+                    classic_controller.get_right_stick(data.right_stick_x, data.right_stick_y),
+                ),
+                triggers: None,
+            },
+            ExtensionInput::BalanceBoard(_) | ExtensionInput::Custom(_) => return None,
+        };
 
-        let addressing = Addressing::control_registers(0xA4_00FA, 6);
-        let read_result = simple_io::read_16_bytes_sync(wiimote, addressing)?;
-        // Address is actually 0xA4_00FA, but only the lower 2 bytes are returned
-        if read_result.address_offset() != 0x00FA || read_result.size() < 6 {
-            Err(WiimoteDeviceError::InvalidData.into())
-        } else if read_result.error_flag() == 7 {
-            Ok(None)
-        } else {
-            let mut extension_info = [0u8; 6];
-            extension_info.copy_from_slice(&read_result.data[..6]);
-            Ok(Some(extension_info))
+        if let Some(processing) = processing {
+            axes.left_stick = processing.apply_stick(axes.left_stick);
+            axes.right_stick = axes.right_stick.map(|stick| processing.apply_stick(stick));
+            axes.triggers = axes.triggers.map(|(left, right)| {
+                (
+                    processing.apply_trigger(left),
+                    processing.apply_trigger(right),
+                )
+            });
         }
+        Some(axes)
     }
 }
@@ -1,10 +1,9 @@
-use std::cell::RefCell;
 use std::sync::atomic::AtomicBool;
+use std::sync::{Mutex, PoisonError, RwLock};
 
 use crate::calibration::normalize;
-use crate::output::Addressing;
+use crate::extensions::StillnessDetector;
 use crate::prelude::*;
-use crate::simple_io;
 
 #[derive(Debug, Clone, Copy)]
 pub enum MotionPlusMode {
@@ -14,12 +13,143 @@ pub enum MotionPlusMode {
     ClassicControllerPassthrough,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl MotionPlusMode {
+    /// Returns the control register and the value written to it to switch to the mode.
+    pub(crate) const fn register_write(self) -> (u32, u8) {
+        match self {
+            Self::Inactive => (0xA4_00F0, 0x55),
+            Self::Active => (0xA6_00FE, 0x04),
+            Self::NunchuckPassthrough => (0xA6_00FE, 0x05),
+            Self::ClassicControllerPassthrough => (0xA6_00FE, 0x07),
+        }
+    }
+}
+
+/// Determines how the Motion Plus mode is managed by the `WiimoteDevice`.
+#[derive(Debug, Default, Clone, Copy)]
+pub enum MotionPlusPolicy {
+    /// The Motion Plus mode is only changed by calling `MotionPlus::change_mode`.
+    #[default]
+    Manual,
+    /// The Motion Plus is always active, a connected extension is not passed through.
+    Active,
+    /// The Motion Plus passes through the data of a connected Nunchuck or Classic Controller
+    /// and is active without pass-through otherwise.
+    Passthrough,
+}
+
+impl MotionPlusPolicy {
+    /// Returns the Motion Plus mode for the connected extension, `None` if the mode is managed manually.
+    #[must_use]
+    pub const fn mode_for(self, extension: Option<WiimoteExtension>) -> Option<MotionPlusMode> {
+        match (self, extension) {
+            (Self::Manual, _) => None,
+            (Self::Passthrough, Some(WiimoteExtension::Nunchuck)) => {
+                Some(MotionPlusMode::NunchuckPassthrough)
+            }
+            (
+                Self::Passthrough,
+                Some(WiimoteExtension::ClassicController | WiimoteExtension::ClassicControllerPro),
+            ) => Some(MotionPlusMode::ClassicControllerPassthrough),
+            (Self::Active | Self::Passthrough, _) => Some(MotionPlusMode::Active),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MotionPlusType {
     External,
     Builtin,
 }
 
+impl MotionPlusType {
+    /// Returns the type of the Motion Plus with the identifier read from register 0xA600FA,
+    /// `None` if it does not identify an inactive Motion Plus.
+    #[must_use]
+    pub const fn from_identifier(identifier: [u8; 6]) -> Option<Self> {
+        match identifier {
+            [0x00, 0x00, 0xA6, 0x20, _, 0x05] => Some(Self::External),
+            [_, 0x00, 0xA6, 0x20, _, 0x05] => Some(Self::Builtin),
+            _ => None,
+        }
+    }
+}
+
+/// The unit of an [`AngularVelocity`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AngularVelocityUnit {
+    #[default]
+    DegreesPerSecond,
+    RadiansPerSecond,
+}
+
+/// The angular velocity measured by the Motion Plus, with the axes named like on WiiBrew.
+///
+/// Holding the Wii remote pointed at the screen with the buttons facing up:
+/// - `yaw` is the rotation around the vertical axis, turning the tip left or right
+/// - `roll` is the rotation around the long axis of the Wii remote, twisting it
+/// - `pitch` is the rotation around the horizontal axis through its sides, tilting the tip up or down
+///
+/// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Wii_Motion_Plus#Data_Format>
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AngularVelocity {
+    pub yaw: f64,
+    pub roll: f64,
+    pub pitch: f64,
+    pub unit: AngularVelocityUnit,
+}
+
+impl AngularVelocity {
+    /// Creates an angular velocity in degrees per second.
+    #[must_use]
+    pub const fn from_degrees_per_second(yaw: f64, roll: f64, pitch: f64) -> Self {
+        Self {
+            yaw,
+            roll,
+            pitch,
+            unit: AngularVelocityUnit::DegreesPerSecond,
+        }
+    }
+
+    /// Returns the angular velocity converted to `unit`.
+    #[must_use]
+    pub fn to_unit(self, unit: AngularVelocityUnit) -> Self {
+        let convert = |value: f64| match (self.unit, unit) {
+            (AngularVelocityUnit::DegreesPerSecond, AngularVelocityUnit::RadiansPerSecond) => {
+                value.to_radians()
+            }
+            (AngularVelocityUnit::RadiansPerSecond, AngularVelocityUnit::DegreesPerSecond) => {
+                value.to_degrees()
+            }
+            _ => value,
+        };
+        Self {
+            yaw: convert(self.yaw),
+            roll: convert(self.roll),
+            pitch: convert(self.pitch),
+            unit,
+        }
+    }
+
+    /// Returns the angular velocity in radians per second.
+    #[must_use]
+    pub fn to_radians_per_second(self) -> Self {
+        self.to_unit(AngularVelocityUnit::RadiansPerSecond)
+    }
+
+    /// Returns the angular velocity in degrees per second.
+    #[must_use]
+    pub fn to_degrees_per_second(self) -> Self {
+        self.to_unit(AngularVelocityUnit::DegreesPerSecond)
+    }
+
+    /// Returns the yaw, roll and pitch in the order of [`MotionPlusCalibration::get_angular_velocity`].
+    #[must_use]
+    pub const fn as_tuple(&self) -> (f64, f64, f64) {
+        (self.yaw, self.roll, self.pitch)
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct MotionPlusCalibration {
     fast: MotionPlusCalibrationData,
@@ -27,6 +157,55 @@ pub struct MotionPlusCalibration {
 }
 
 impl MotionPlusCalibration {
+    /// Creates a calibration from the calibration data used in fast and slow mode,
+    /// e.g. to restore a persisted calibration or replace a corrupted calibration block.
+    #[must_use]
+    pub const fn new(fast: MotionPlusCalibrationData, slow: MotionPlusCalibrationData) -> Self {
+        Self { fast, slow }
+    }
+
+    /// Decodes the 32 bytes of calibration stored at 0xA60020, the fast mode block followed by the slow mode block.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the CRC32 checksum in the last two bytes of each block does not match.
+    pub fn from_calibration_data(data: &[u8; 32]) -> WiimoteResult<Self> {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&data[0..14]);
+        hasher.update(&data[16..30]);
+        let checksum = u32::from_be_bytes([data[14], data[15], data[30], data[31]]);
+        if hasher.finalize() != checksum {
+            return Err(WiimoteDeviceError::InvalidChecksum.into());
+        }
+
+        let mut fast = [0u8; 16];
+        let mut slow = [0u8; 16];
+        fast.copy_from_slice(&data[..16]);
+        slow.copy_from_slice(&data[16..]);
+        Ok(Self::new(fast.into(), slow.into()))
+    }
+
+    /// Returns the calibration data used while an axis is in fast mode (slow bit not set).
+    #[must_use]
+    pub const fn fast(&self) -> &MotionPlusCalibrationData {
+        &self.fast
+    }
+
+    /// Returns the calibration data used while an axis is in slow mode (slow bit set).
+    #[must_use]
+    pub const fn slow(&self) -> &MotionPlusCalibrationData {
+        &self.slow
+    }
+
+    /// Returns the calibrated angular velocity with named axes in degrees per second.
+    #[must_use]
+    pub fn angular_velocity(&self, data: &MotionPlusData) -> AngularVelocity {
+        let (yaw, roll, pitch) = self.get_angular_velocity(data);
+        AngularVelocity::from_degrees_per_second(yaw, roll, pitch)
+    }
+
+    /// Returns the calibrated angular velocity (yaw, roll, pitch) in degrees per second,
+    /// see [`Self::angular_velocity`] for named axes.
     #[must_use]
     pub fn get_angular_velocity(&self, data: &MotionPlusData) -> (f64, f64, f64) {
         // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Wii_Motion_Plus#Data_Format
@@ -77,8 +256,10 @@ impl MotionPlusCalibration {
     }
 }
 
+/// The calibration of the three gyroscope axes in either fast or slow mode.
+/// Zero values and scales have 16 bits, while the data of the gyroscope only has 14 bits.
 #[derive(Debug, Default, Clone)]
-struct MotionPlusCalibrationData {
+pub struct MotionPlusCalibrationData {
     yaw_zero_value: u16,
     roll_zero_value: u16,
     pitch_zero_value: u16,
@@ -88,6 +269,54 @@ struct MotionPlusCalibrationData {
     degrees_div_6: u8,
 }
 
+impl MotionPlusCalibrationData {
+    /// Creates calibration data from the `(yaw, roll, pitch)` zero values and scales.
+    /// The scales are the raw values measured at `degrees_div_6 * 6` deg/s.
+    #[must_use]
+    pub const fn new(
+        zero_values: (u16, u16, u16),
+        scales: (u16, u16, u16),
+        degrees_div_6: u8,
+    ) -> Self {
+        Self {
+            yaw_zero_value: zero_values.0,
+            roll_zero_value: zero_values.1,
+            pitch_zero_value: zero_values.2,
+            yaw_scale: scales.0,
+            roll_scale: scales.1,
+            pitch_scale: scales.2,
+            degrees_div_6,
+        }
+    }
+
+    /// Returns the `(yaw, roll, pitch)` values measured without rotation.
+    #[must_use]
+    pub const fn zero_values(&self) -> (u16, u16, u16) {
+        (
+            self.yaw_zero_value,
+            self.roll_zero_value,
+            self.pitch_zero_value,
+        )
+    }
+
+    /// Returns the `(yaw, roll, pitch)` values measured at [`Self::degrees`] deg/s.
+    #[must_use]
+    pub const fn scales(&self) -> (u16, u16, u16) {
+        (self.yaw_scale, self.roll_scale, self.pitch_scale)
+    }
+
+    #[must_use]
+    pub const fn degrees_div_6(&self) -> u8 {
+        self.degrees_div_6
+    }
+
+    /// Returns the angular velocity in deg/s the scales were measured at.
+    #[must_use]
+    pub const fn degrees(&self) -> u16 {
+        self.degrees_div_6 as u16 * 6
+    }
+}
+
 impl From<[u8; 16]> for MotionPlusCalibrationData {
     fn from(value: [u8; 16]) -> Self {
         Self {
@@ -103,7 +332,7 @@ impl From<[u8; 16]> for MotionPlusCalibrationData {
 }
 
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct MotionPlusData {
     pub yaw: u16,
     pub roll: u16,
@@ -142,32 +371,27 @@ impl TryFrom<[u8; 6]> for MotionPlusData {
 pub struct MotionPlus {
     motion_plus_type: MotionPlusType,
     initialized: AtomicBool,
-    mode: RefCell<MotionPlusMode>,
-    calibration: RefCell<MotionPlusCalibration>,
+    mode: RwLock<MotionPlusMode>,
+    calibration: RwLock<MotionPlusCalibration>,
+    factory_calibration: RwLock<MotionPlusCalibration>,
+    stillness_detector: Mutex<Option<StillnessDetector>>,
+    drift: RwLock<Option<(f64, f64, f64)>>,
 }
 
 // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Wii_Motion_Plus
 impl MotionPlus {
-    /// Detects if the Wii remote has a Motion Plus extension.
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if communication to the Wii remote failed.
-    pub(crate) fn detect(wiimote: &WiimoteDevice) -> WiimoteResult<Option<Self>> {
-        let address = Addressing::control_registers(0xA6_00FA, 6);
-        let memory_data = simple_io::read_16_bytes_sync(wiimote, address)?;
-        let motion_plus_type = match memory_data.data[0..6] {
-            [0x00, 0x00, 0xA6, 0x20, _, 0x05] => MotionPlusType::External,
-            [_, 0x00, 0xA6, 0x20, _, 0x05] => MotionPlusType::Builtin,
-            _ => return Ok(None),
-        };
-
-        Ok(Some(Self {
+    /// Creates an uninitialized and inactive Motion Plus of a known type without detecting it,
+    /// e.g. the built-in Motion Plus of a Wii remote Plus.
+    pub(crate) fn new(motion_plus_type: MotionPlusType) -> Self {
+        Self {
             motion_plus_type,
             initialized: AtomicBool::new(false),
-            mode: RefCell::new(MotionPlusMode::Inactive),
-            calibration: RefCell::new(MotionPlusCalibration::default()),
-        }))
+            mode: RwLock::new(MotionPlusMode::Inactive),
+            calibration: RwLock::new(MotionPlusCalibration::default()),
+            factory_calibration: RwLock::new(MotionPlusCalibration::default()),
+            stillness_detector: Mutex::new(None),
+            drift: RwLock::new(None),
+        }
     }
 
     #[must_use]
@@ -182,25 +406,36 @@ impl MotionPlus {
 
     #[must_use]
     pub fn mode(&self) -> MotionPlusMode {
-        *self.mode.borrow()
+        *self.mode.read().unwrap_or_else(PoisonError::into_inner)
     }
 
     #[must_use]
     pub fn calibration(&self) -> MotionPlusCalibration {
-        self.calibration.borrow().clone()
+        self.calibration
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
 
-    /// Tries to initialize the Motion Plus extension and read its calibration.
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error on I/O error or when receiving invalid data.
-    pub fn initialize(&self, wiimote: &WiimoteDevice) -> WiimoteResult<()> {
-        Self::write_single_control_byte(wiimote, 0xA6_00F0, 0x55)?;
-        self.read_calibration_data(wiimote)?;
+    pub(super) fn set_initialized(&self, initialized: bool) {
         self.initialized
-            .store(true, std::sync::atomic::Ordering::Relaxed);
-        Ok(())
+            .store(initialized, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub(super) fn set_mode(&self, mode: MotionPlusMode) {
+        replace(&self.mode, mode);
+    }
+
+    /// Replaces the factory calibration and the calibration used to convert the gyroscope data.
+    pub(super) fn set_factory_calibration(&self, calibration: MotionPlusCalibration) {
+        replace(&self.factory_calibration, calibration.clone());
+        replace(&self.calibration, calibration);
+    }
+
+    /// Replaces the calibration used to convert the gyroscope data,
+    /// e.g. with a persisted calibration or for Motion Plus extensions with a corrupted calibration block.
+    pub fn set_calibration(&self, calibration: MotionPlusCalibration) {
+        replace(&self.calibration, calibration);
     }
 
     /// Calibrates the slow zero values of the Motion Plus extension using multiple data readings.
@@ -226,88 +461,169 @@ impl MotionPlus {
             pitch_sum += reading.pitch as u64;
         }
 
-        #[allow(clippy::cast_sign_loss, clippy::cast_precision_loss)]
+        #[allow(clippy::cast_precision_loss)]
         if read_count >= 8 {
-            let average_yaw = ((yaw_sum as f64 / read_count as f64).round() as u16) << 2; // Calibration has 16 bits, values only 14
-            let average_roll = ((roll_sum as f64 / read_count as f64).round() as u16) << 2;
-            let average_pitch = ((pitch_sum as f64 / read_count as f64).round() as u16) << 2;
-
-            let mut calibration = self.calibration.borrow_mut();
-
-            calibration.slow.yaw_zero_value = average_yaw;
-            calibration.slow.roll_zero_value = average_roll;
-            calibration.slow.pitch_zero_value = average_pitch;
-
-            calibration.fast.yaw_zero_value = average_yaw;
-            calibration.fast.roll_zero_value = average_roll;
-            calibration.fast.pitch_zero_value = average_pitch;
-            Some(calibration.clone())
+            let read_count = read_count as f64;
+            Some(self.set_zero_values([
+                yaw_sum as f64 / read_count,
+                roll_sum as f64 / read_count,
+                pitch_sum as f64 / read_count,
+            ]))
         } else {
             None
         }
     }
 
-    /// Changes the mode of the Motion Plus extension.
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error on I/O error or when receiving invalid data.
-    pub fn change_mode(&self, wiimote: &WiimoteDevice, mode: MotionPlusMode) -> WiimoteResult<()> {
-        let (address, value) = match mode {
-            MotionPlusMode::Inactive => (0xA4_00F0, 0x55),
-            MotionPlusMode::Active => (0xA6_00FE, 0x04),
-            MotionPlusMode::NunchuckPassthrough => (0xA6_00FE, 0x05),
-            MotionPlusMode::ClassicControllerPassthrough => (0xA6_00FE, 0x07),
+    /// Enables or disables the automatic calibration of the zero values.
+    /// While enabled, the zero values are refined with every window of readings in which the
+    /// Wii remote is detected to be still, see [`StillnessDetector`].
+    /// The readings are processed when data reports are read from the `WiimoteDevice`.
+    pub fn set_auto_calibration(&self, detector: Option<StillnessDetector>) {
+        *self
+            .stillness_detector
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = detector;
+    }
+
+    #[must_use]
+    pub fn is_auto_calibrating(&self) -> bool {
+        self.stillness_detector
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Returns the angular velocity in deg/s the factory calibration reported during the last
+    /// still window detected by the automatic calibration, which is the estimated gyroscope drift.
+    #[must_use]
+    pub fn drift(&self) -> Option<(f64, f64, f64)> {
+        *self.drift.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds a reading to the automatic calibration if enabled.
+    pub(crate) fn process_auto_calibration(&self, data: &MotionPlusData) {
+        let average = match self
+            .stillness_detector
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+        {
+            Some(detector) => detector.push(data),
+            None => return,
+        };
+        let Some(average) = average else {
+            return;
         };
-        Self::write_single_control_byte(wiimote, address, value)?;
-        self.mode.replace(mode);
-        Ok(())
-    }
-
-    fn write_single_control_byte(
-        wiimote: &WiimoteDevice,
-        address: u32,
-        value: u8,
-    ) -> WiimoteResult<()> {
-        let addressing = Addressing::control_registers(address, 1);
-        let mut memory_write_buffer = [0u8; 16];
-        memory_write_buffer[0] = value;
-        let ack = simple_io::write_16_bytes_sync(wiimote, addressing, &memory_write_buffer)?;
-        if ack.error_code() == 7 {
-            return Err(WiimoteDeviceError::InvalidData.into());
-        }
 
-        Ok(())
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let still_data = MotionPlusData {
+            yaw: average[0].round() as u16,
+            roll: average[1].round() as u16,
+            pitch: average[2].round() as u16,
+            yaw_slow: true,
+            roll_slow: true,
+            pitch_slow: true,
+            extension_connected: data.extension_connected,
+        };
+        let drift = self
+            .factory_calibration
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_angular_velocity(&still_data);
+        replace(&self.drift, Some(drift));
+        self.set_zero_values(average);
     }
 
-    fn read_calibration_data(&self, wiimote: &WiimoteDevice) -> WiimoteResult<()> {
-        let mut hasher = crc32fast::Hasher::new();
-        let mut checksum = [0u8; 4];
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn set_zero_values(&self, average: [f64; 3]) -> MotionPlusCalibration {
+        // Calibration has 16 bits, values only 14
+        let average_yaw = (average[0].round() as u16) << 2;
+        let average_roll = (average[1].round() as u16) << 2;
+        let average_pitch = (average[2].round() as u16) << 2;
+
+        let mut calibration = self
+            .calibration
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        calibration.slow.yaw_zero_value = average_yaw;
+        calibration.slow.roll_zero_value = average_roll;
+        calibration.slow.pitch_zero_value = average_pitch;
+        // The readings are all taken in slow mode, the fast mode keeps its factory zero values.
+        calibration.clone()
+    }
+}
+
+/// Replaces the value behind the lock, ignoring a poisoned lock as the value is always replaced whole.
+fn replace<T>(lock: &RwLock<T>, value: T) {
+    *lock.write().unwrap_or_else(PoisonError::into_inner) = value;
+}
 
-        let fast =
-            Self::read_calibration_part(wiimote, 0xA6_0020, &mut hasher, &mut checksum[0..2])?;
-        let slow =
-            Self::read_calibration_part(wiimote, 0xA6_0020 + 16, &mut hasher, &mut checksum[2..4])?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if hasher.finalize() != u32::from_be_bytes(checksum) {
-            return Err(WiimoteDeviceError::InvalidChecksum.into());
-        }
+    #[test]
+    fn test_motion_plus_identifier() {
+        assert_eq!(
+            MotionPlusType::from_identifier([0x00, 0x00, 0xA6, 0x20, 0x00, 0x05]),
+            Some(MotionPlusType::External)
+        );
+        assert_eq!(
+            MotionPlusType::from_identifier([0x01, 0x00, 0xA6, 0x20, 0x00, 0x05]),
+            Some(MotionPlusType::Builtin)
+        );
+        // An active Motion Plus identifies at the extension registers instead.
+        assert_eq!(
+            MotionPlusType::from_identifier([0x00, 0x00, 0xA4, 0x20, 0x04, 0x05]),
+            None
+        );
+    }
 
-        self.calibration
-            .replace(MotionPlusCalibration { fast, slow });
-        Ok(())
-    }
-
-    fn read_calibration_part(
-        wiimote: &WiimoteDevice,
-        address: u32,
-        hasher: &mut crc32fast::Hasher,
-        checksum_buffer: &mut [u8],
-    ) -> WiimoteResult<MotionPlusCalibrationData> {
-        let addressing = Addressing::control_registers(address, 16);
-        let data = simple_io::read_16_bytes_sync_checked(wiimote, addressing)?;
-        hasher.update(&data[0..14]);
-        checksum_buffer.copy_from_slice(&data[14..16]);
-        Ok(MotionPlusCalibrationData::from(data))
+    #[test]
+    fn test_calibration_checksum() {
+        let mut data = crate::mock::motion_plus_calibration();
+        let calibration = MotionPlusCalibration::from_calibration_data(&data).unwrap();
+        assert_eq!(calibration.fast().zero_values(), (0x7C00, 0x7C00, 0x7C00));
+        assert_eq!(calibration.slow().degrees(), 240);
+
+        data[3] ^= 0x01;
+        assert!(MotionPlusCalibration::from_calibration_data(&data).is_err());
+    }
+
+    #[test]
+    fn test_calibrate_zero_values() {
+        let motion_plus = MotionPlus::new(MotionPlusType::Builtin);
+        let fast_zero_values = motion_plus.calibration().fast().zero_values();
+        let reading = MotionPlusData {
+            yaw: 0x1F00,
+            roll: 0x1F10,
+            pitch: 0x1F20,
+            yaw_slow: true,
+            roll_slow: true,
+            pitch_slow: true,
+            extension_connected: false,
+        };
+
+        let calibration = motion_plus.calibrate_zero_values(&[reading; 8]).unwrap();
+        assert_eq!(
+            calibration.slow().zero_values(),
+            (0x1F00 << 2, 0x1F10 << 2, 0x1F20 << 2)
+        );
+        assert_eq!(calibration.fast().zero_values(), fast_zero_values);
+    }
+
+    #[test]
+    fn test_angular_velocity_units() {
+        let velocity = AngularVelocity::from_degrees_per_second(180.0, -90.0, 0.0);
+        let radians = velocity.to_radians_per_second();
+        assert_eq!(radians.unit, AngularVelocityUnit::RadiansPerSecond);
+        assert!((radians.yaw - std::f64::consts::PI).abs() < 1e-12);
+        assert!((radians.roll + std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert_eq!(radians.to_radians_per_second(), radians);
+
+        let degrees = radians.to_degrees_per_second();
+        assert!((degrees.yaw - 180.0).abs() < 1e-9 && (degrees.roll + 90.0).abs() < 1e-9);
+        assert_eq!(velocity.as_tuple(), (180.0, -90.0, 0.0));
     }
 }
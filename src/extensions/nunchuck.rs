@@ -0,0 +1,171 @@
+use bitflags::bitflags;
+
+use crate::calibration::{calibration_checksum_valid, normalize, normalize_stick};
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct NunchuckButtons: u8 {
+        const Z = 1 << 0;
+        const C = 1 << 1;
+    }
+}
+
+/// A button of the Nunchuck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NunchuckButton {
+    C,
+    Z,
+}
+
+impl NunchuckButton {
+    pub const ALL: [Self; 2] = [Self::C, Self::Z];
+
+    /// Returns the flag of the button in the button data.
+    #[must_use]
+    pub const fn flag(self) -> NunchuckButtons {
+        match self {
+            Self::C => NunchuckButtons::C,
+            Self::Z => NunchuckButtons::Z,
+        }
+    }
+}
+
+/// The calibration data of the Nunchuck.
+/// Can be used to convert raw accelerometer and stick data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NunchuckCalibration {
+    x_zero_offset: u16,
+    y_zero_offset: u16,
+    z_zero_offset: u16,
+    x_gravity: u16,
+    y_gravity: u16,
+    z_gravity: u16,
+    stick_x_min: u8,
+    stick_x_max: u8,
+    stick_x_center: u8,
+    stick_y_min: u8,
+    stick_y_max: u8,
+    stick_y_center: u8,
+}
+
+impl Default for NunchuckCalibration {
+    /// Typical values of an original Nunchuck, used when the calibration data is invalid.
+    fn default() -> Self {
+        Self {
+            x_zero_offset: 0x200,
+            y_zero_offset: 0x200,
+            z_zero_offset: 0x200,
+            x_gravity: 0x2C0,
+            y_gravity: 0x2C0,
+            z_gravity: 0x2C0,
+            stick_x_min: 0x20,
+            stick_x_max: 0xE0,
+            stick_x_center: 0x80,
+            stick_y_min: 0x20,
+            stick_y_max: 0xE0,
+            stick_y_center: 0x80,
+        }
+    }
+}
+
+impl NunchuckCalibration {
+    /// Parses the 16 bytes of calibration data read from register 0xA40020.
+    /// Returns `None` if the checksum does not match.
+    #[must_use]
+    pub fn from_calibration_data(data: &[u8; 16]) -> Option<Self> {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Nunchuck#Calibration_data
+        // The last two bytes are a checksum of the first 14 bytes.
+        if !calibration_checksum_valid(data) {
+            return None;
+        }
+
+        Some(Self {
+            x_zero_offset: ((data[0] as u16) << 2) | ((data[3] as u16) >> 4 & 0b11),
+            y_zero_offset: ((data[1] as u16) << 2) | ((data[3] as u16) >> 2 & 0b11),
+            z_zero_offset: ((data[2] as u16) << 2) | ((data[3] as u16) & 0b11),
+            x_gravity: ((data[4] as u16) << 2) | ((data[7] as u16) >> 4 & 0b11),
+            y_gravity: ((data[5] as u16) << 2) | ((data[7] as u16) >> 2 & 0b11),
+            z_gravity: ((data[6] as u16) << 2) | ((data[7] as u16) & 0b11),
+            stick_x_max: data[8],
+            stick_x_min: data[9],
+            stick_x_center: data[10],
+            stick_y_max: data[11],
+            stick_y_min: data[12],
+            stick_y_center: data[13],
+        })
+    }
+
+    /// Returns the acceleration values from the raw data using the current calibration.
+    #[must_use]
+    pub fn get_acceleration(&self, data: &NunchuckData) -> (f64, f64, f64) {
+        let x = normalize(
+            data.accelerometer_x,
+            10,
+            self.x_zero_offset,
+            self.x_gravity,
+            10,
+        );
+        let y = normalize(
+            data.accelerometer_y,
+            10,
+            self.y_zero_offset,
+            self.y_gravity,
+            10,
+        );
+        let z = normalize(
+            data.accelerometer_z,
+            10,
+            self.z_zero_offset,
+            self.z_gravity,
+            10,
+        );
+        (x, y, z)
+    }
+
+    /// Returns the stick position in the range -1.0 to 1.0 using the current calibration.
+    #[must_use]
+    pub fn get_stick(&self, data: &NunchuckData) -> (f64, f64) {
+        let x = normalize_stick(
+            data.stick_x,
+            self.stick_x_min,
+            self.stick_x_center,
+            self.stick_x_max,
+        );
+        let y = normalize_stick(
+            data.stick_y,
+            self.stick_y_min,
+            self.stick_y_center,
+            self.stick_y_max,
+        );
+        (x, y)
+    }
+}
+
+/// The raw data of the Nunchuck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NunchuckData {
+    pub buttons: NunchuckButtons,
+    pub stick_x: u8,
+    pub stick_y: u8,
+    /// 10 bits, 0-1023.
+    pub accelerometer_x: u16,
+    /// 10 bits, 0-1023.
+    pub accelerometer_y: u16,
+    /// 10 bits, 0-1023.
+    pub accelerometer_z: u16,
+}
+
+impl From<[u8; 6]> for NunchuckData {
+    fn from(value: [u8; 6]) -> Self {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Nunchuck#Data_Format
+        // Buttons are active low, a pressed button is reported as 0.
+        Self {
+            buttons: NunchuckButtons::from_bits_truncate(!value[5]),
+            stick_x: value[0],
+            stick_y: value[1],
+            accelerometer_x: ((value[2] as u16) << 2) | ((value[5] as u16) >> 2 & 0b11),
+            accelerometer_y: ((value[3] as u16) << 2) | ((value[5] as u16) >> 4 & 0b11),
+            accelerometer_z: ((value[4] as u16) << 2) | ((value[5] as u16) >> 6 & 0b11),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::prelude::*;
+
+/// Data received while the Motion Plus is active, either gyroscope data or extension data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PassthroughData {
+    MotionPlus(MotionPlusData),
+    Nunchuck(NunchuckData),
+    ClassicController(ClassicControllerData),
+}
+
+/// Decodes the extension bytes of a Motion Plus in active or pass-through mode.
+///
+/// In pass-through mode the extension bytes alternate between Motion Plus data and the data
+/// of the extension connected to the Motion Plus. The extension data is rearranged to make room
+/// for the flag identifying the data, losing the least significant bits of some values.
+///
+/// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Wii_Motion_Plus#Passthrough_modes>
+#[derive(Debug, Clone, Copy)]
+pub struct PassthroughDecoder {
+    mode: MotionPlusMode,
+}
+
+impl PassthroughDecoder {
+    #[must_use]
+    pub const fn new(mode: MotionPlusMode) -> Self {
+        Self { mode }
+    }
+
+    #[must_use]
+    pub const fn mode(&self) -> MotionPlusMode {
+        self.mode
+    }
+
+    /// Decodes the 6 extension bytes.
+    /// Returns `None` if the Motion Plus is inactive.
+    #[must_use]
+    pub fn decode(&self, data: [u8; 6]) -> Option<PassthroughData> {
+        if matches!(self.mode, MotionPlusMode::Inactive) {
+            return None;
+        }
+
+        if let Ok(motion_plus_data) = MotionPlusData::try_from(data) {
+            return Some(PassthroughData::MotionPlus(motion_plus_data));
+        }
+
+        match self.mode {
+            MotionPlusMode::NunchuckPassthrough => Some(PassthroughData::Nunchuck(
+                NunchuckData::from(Self::unmangle_nunchuck(data)),
+            )),
+            MotionPlusMode::ClassicControllerPassthrough => {
+                Some(PassthroughData::ClassicController(
+                    ClassicControllerData::from(Self::unmangle_classic_controller(data)),
+                ))
+            }
+            // Without pass-through, all data is Motion Plus data.
+            MotionPlusMode::Active | MotionPlusMode::Inactive => None,
+        }
+    }
+
+    /// Restores the regular Nunchuck data format.
+    /// The least significant bit of all three accelerometer axes is lost and set to 0.
+    const fn unmangle_nunchuck(data: [u8; 6]) -> [u8; 6] {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Wii_Motion_Plus#Nunchuck_pass-through_mode
+        // Byte 4: AZ<9:3>, extension connected
+        // Byte 5: AZ<2:1>, AY<1>, AX<1>, BC, BZ, 0, 0
+        let accelerometer_z = ((data[4] >> 1) as u16) << 3 | ((data[5] >> 6) as u16 & 0b11) << 1;
+        let accelerometer_y_low = (data[5] >> 5) & 0b1;
+        let accelerometer_x_low = (data[5] >> 4) & 0b1;
+        let button_c = (data[5] >> 3) & 0b1;
+        let button_z = (data[5] >> 2) & 0b1;
+
+        [
+            data[0],
+            data[1],
+            data[2],
+            data[3],
+            (accelerometer_z >> 2) as u8,
+            ((accelerometer_z as u8 & 0b11) << 6)
+                | (accelerometer_y_low << 5)
+                | (accelerometer_x_low << 3)
+                | (button_c << 1)
+                | button_z,
+        ]
+    }
+
+    /// Restores the regular Classic Controller data format.
+    /// The least significant bit of both left stick axes is lost and set to 0.
+    const fn unmangle_classic_controller(data: [u8; 6]) -> [u8; 6] {
+        // https://www.wiibrew.org/wiki/Wiimote/Extension_Controllers/Wii_Motion_Plus#Classic_Controller_pass-through_mode
+        // Byte 0: RX<4:3>, LX<5:1>, BDU
+        // Byte 1: RX<2:1>, LY<5:1>, BDL
+        // Byte 4: buttons, extension connected
+        // Byte 5: buttons, 0, 0
+        let button_up = data[0] & 0b1;
+        let button_left = data[1] & 0b1;
+
+        [
+            data[0] & !0b1,
+            data[1] & !0b1,
+            data[2],
+            data[3],
+            data[4] | 0b1,
+            (data[5] & !0b11) | (button_left << 1) | button_up,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_motion_plus_frame() {
+        let decoder = PassthroughDecoder::new(MotionPlusMode::NunchuckPassthrough);
+
+        let data = decoder.decode([0x12, 0x34, 0x56, 0b1000_0011, 0b0100_0010, 0b1100_0010]);
+
+        assert!(matches!(data, Some(PassthroughData::MotionPlus(_))));
+        if let Some(PassthroughData::MotionPlus(data)) = data {
+            assert_eq!(data.yaw, 0x2012);
+            assert_eq!(data.roll, 0x1034);
+            assert_eq!(data.pitch, 0x3056);
+            assert!(data.yaw_slow && data.roll_slow && data.pitch_slow);
+            assert!(!data.extension_connected);
+        }
+    }
+
+    #[test]
+    fn test_nunchuck_frame() {
+        let decoder = PassthroughDecoder::new(MotionPlusMode::NunchuckPassthrough);
+
+        let data = decoder.decode([
+            0x80,
+            0x7F,
+            0x40,
+            0x41,
+            0b1010_1011, // AZ<9:3>, extension connected
+            0b1011_0100, // AZ<2:1>, AY<1>, AX<1>, C pressed, Z released
+        ]);
+
+        assert!(matches!(data, Some(PassthroughData::Nunchuck(_))));
+        if let Some(PassthroughData::Nunchuck(data)) = data {
+            assert_eq!(data.stick_x, 0x80);
+            assert_eq!(data.stick_y, 0x7F);
+            assert_eq!(data.accelerometer_x, 0x40 << 2 | 0b10);
+            assert_eq!(data.accelerometer_y, 0x41 << 2 | 0b10);
+            assert_eq!(data.accelerometer_z, 0b10_1010_1100);
+            assert_eq!(data.buttons.bits(), NunchuckButtons::C.bits());
+        }
+    }
+
+    #[test]
+    fn test_classic_controller_frame() {
+        let decoder = PassthroughDecoder::new(MotionPlusMode::ClassicControllerPassthrough);
+
+        let data = decoder.decode([
+            0b0010_0000, // LX<5:1> = 32, D-Pad up pressed
+            0b0010_0001, // LY<5:1> = 32, D-Pad left released
+            0x10,
+            0x00,
+            0xFF,
+            0b1111_1100,
+        ]);
+
+        assert!(matches!(data, Some(PassthroughData::ClassicController(_))));
+        if let Some(PassthroughData::ClassicController(data)) = data {
+            assert_eq!(data.left_stick_x, 32);
+            assert_eq!(data.left_stick_y, 32);
+            assert_eq!(data.buttons.bits(), ClassicControllerButtons::UP.bits());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use once_cell::sync::Lazy;
+
+use crate::prelude::*;
+
+/// Decodes the extension bytes of an extension unknown to the library,
+/// registered for its identifier with [`register_extension_decoder`].
+pub trait ExtensionDecoder: Send + Sync {
+    /// Decodes the (decrypted) extension bytes of a data report, their number depends on the
+    /// data reporting mode. Returns `None` if the bytes are not enough for the extension.
+    ///
+    /// Extensions compatible with a known extension can return its input, so bindings and
+    /// gamepad mappings apply, others return [`ExtensionInput::Custom`].
+    fn decode(&self, data: &[u8]) -> Option<ExtensionInput>;
+}
+
+/// The input of an extension decoded by a custom [`ExtensionDecoder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CustomExtensionData {
+    /// The identifier of the extension.
+    pub identifier: [u8; 6],
+    /// The pressed buttons as bit flags defined by the decoder.
+    pub buttons: u32,
+    /// The analog inputs as defined by the decoder.
+    pub axes: [i16; 6],
+}
+
+type DecoderRegistry = HashMap<[u8; 6], Arc<dyn ExtensionDecoder>>;
+
+static DECODERS: Lazy<RwLock<DecoderRegistry>> = Lazy::new(RwLock::default);
+
+/// Registers the decoder for extensions with the `identifier`, replacing a previously registered decoder.
+///
+/// Decoders are only used for identifiers detected as [`WiimoteExtension::Unknown`],
+/// the data of known extensions is always decoded by the library.
+pub fn register_extension_decoder(identifier: [u8; 6], decoder: Arc<dyn ExtensionDecoder>) {
+    DECODERS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(identifier, decoder);
+}
+
+/// Removes the decoder registered for the `identifier`, returns it if one was registered.
+pub fn unregister_extension_decoder(identifier: [u8; 6]) -> Option<Arc<dyn ExtensionDecoder>> {
+    DECODERS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&identifier)
+}
+
+/// Returns the decoder registered for the `identifier`.
+pub(crate) fn extension_decoder(identifier: [u8; 6]) -> Option<Arc<dyn ExtensionDecoder>> {
+    DECODERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&identifier)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputReport;
+
+    struct TestDecoder;
+
+    impl ExtensionDecoder for TestDecoder {
+        fn decode(&self, data: &[u8]) -> Option<ExtensionInput> {
+            let bytes = data.get(..2)?;
+            Some(ExtensionInput::Custom(CustomExtensionData {
+                identifier: IDENTIFIER,
+                buttons: u32::from(bytes[0]),
+                axes: [i16::from(bytes[1]), 0, 0, 0, 0, 0],
+            }))
+        }
+    }
+
+    const IDENTIFIER: [u8; 6] = [0x00, 0x00, 0xA4, 0x20, 0x01, 0x03];
+
+    #[test]
+    fn test_identifiers() {
+        for extension in [
+            WiimoteExtension::Nunchuck,
+            WiimoteExtension::ClassicController,
+            WiimoteExtension::ClassicControllerPro,
+            WiimoteExtension::BalanceBoard,
+            WiimoteExtension::Unknown(IDENTIFIER),
+        ] {
+            assert_eq!(
+                WiimoteExtension::from_identifier(extension.identifier()),
+                extension
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_decoder() {
+        let extension = ConnectedExtension::new(
+            WiimoteExtension::Unknown(IDENTIFIER),
+            ExtensionCalibration::None,
+            false,
+        );
+        let report = [0x32, 0x00, 0x00, 0x05, 0x7F, 0, 0, 0, 0, 0, 0];
+        let InputReport::DataReport(_, data) = InputReport::try_from(&report[..]).unwrap() else {
+            unreachable!();
+        };
+        assert_eq!(extension.decode(&data), None);
+
+        register_extension_decoder(IDENTIFIER, Arc::new(TestDecoder));
+        let Some(ExtensionInput::Custom(input)) = extension.decode(&data) else {
+            panic!("Expected custom extension input");
+        };
+        assert_eq!(input.buttons, 0x05);
+        assert_eq!(input.axes[0], 0x7F);
+        assert!(unregister_extension_decoder(IDENTIFIER).is_some());
+        assert_eq!(extension.decode(&data), None);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/// Detects windows of Motion Plus readings where the Wii remote is not moving.
+/// Used to continuously refine the zero values of the gyroscope while data is received.
+///
+/// A window is considered still when all readings are in slow mode and the variance of
+/// every axis stays below the configured maximum.
+#[derive(Debug, Clone)]
+pub struct StillnessDetector {
+    window_size: usize,
+    max_variance: f64,
+    readings: VecDeque<[u16; 3]>,
+}
+
+impl Default for StillnessDetector {
+    /// About half a second of readings at 100 reports per second, allowing a standard deviation
+    /// of roughly 1 deg/s in slow mode.
+    fn default() -> Self {
+        Self::new(50, 16.0)
+    }
+}
+
+impl StillnessDetector {
+    /// Creates a detector averaging `window_size` readings (at least 8) with a maximum
+    /// variance in raw units for each axis.
+    #[must_use]
+    pub fn new(window_size: usize, max_variance: f64) -> Self {
+        let window_size = usize::max(window_size, 8);
+        Self {
+            window_size,
+            max_variance,
+            readings: VecDeque::with_capacity(window_size),
+        }
+    }
+
+    /// Adds a reading to the current window.
+    /// Returns the average yaw, roll and pitch of the window when a still window was completed.
+    pub fn push(&mut self, data: &MotionPlusData) -> Option<[f64; 3]> {
+        if !data.yaw_slow || !data.roll_slow || !data.pitch_slow {
+            // Too much movement, start a new window
+            self.readings.clear();
+            return None;
+        }
+
+        if self.readings.len() == self.window_size {
+            self.readings.pop_front();
+        }
+        self.readings.push_back([data.yaw, data.roll, data.pitch]);
+        if self.readings.len() < self.window_size {
+            return None;
+        }
+
+        let (average, variance) = self.statistics();
+        if variance
+            .iter()
+            .all(|variance| *variance <= self.max_variance)
+        {
+            self.readings.clear();
+            Some(average)
+        } else {
+            None
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn statistics(&self) -> ([f64; 3], [f64; 3]) {
+        let count = self.readings.len() as f64;
+        let mut average = [0.0; 3];
+        for reading in &self.readings {
+            for (sum, value) in average.iter_mut().zip(reading) {
+                *sum += f64::from(*value) / count;
+            }
+        }
+
+        let mut variance = [0.0; 3];
+        for reading in &self.readings {
+            for ((sum, value), average) in variance.iter_mut().zip(reading).zip(&average) {
+                *sum += (f64::from(*value) - average).powi(2) / count;
+            }
+        }
+        (average, variance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(yaw: u16, slow: bool) -> MotionPlusData {
+        MotionPlusData {
+            yaw,
+            roll: 0x2000,
+            pitch: 0x1FF0,
+            yaw_slow: slow,
+            roll_slow: true,
+            pitch_slow: true,
+            extension_connected: false,
+        }
+    }
+
+    #[test]
+    fn test_still_window() {
+        let mut detector = StillnessDetector::new(10, 4.0);
+
+        for i in 0..9 {
+            assert!(detector.push(&reading(0x2000 + i % 2, true)).is_none());
+        }
+        let average = detector.push(&reading(0x2001, true)).unwrap();
+
+        assert!((average[0] - 8192.5).abs() < 1e-9);
+        assert!((average[1] - 8192.0).abs() < 1e-9);
+        assert!((average[2] - 8176.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_movement_resets_window() {
+        let mut detector = StillnessDetector::new(10, 4.0);
+
+        for _ in 0..9 {
+            assert!(detector.push(&reading(0x2000, true)).is_none());
+        }
+        assert!(detector.push(&reading(0x2000, false)).is_none());
+        assert!(detector.push(&reading(0x2000, true)).is_none());
+
+        for i in 0..20 {
+            // Variance too high
+            assert!(detector
+                .push(&reading(0x2000 + (i % 2) * 20, true))
+                .is_none());
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/// An event of a person on the balance board, see [`WeightTracker`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightEvent {
+    /// The total weight rose above [`WeightTrackerConfig::step_on_kg`].
+    StepOn,
+    /// The total weight fell below [`WeightTrackerConfig::step_off_kg`].
+    StepOff,
+    /// The total weight in kilograms settled, reported again once it settles at a different weight.
+    StableWeight(f64),
+    /// The center of pressure moved, from -1.0 to 1.0 with right and top (away from the power button) being positive.
+    CenterOfPressure(f64, f64),
+}
+
+/// The thresholds of a [`WeightTracker`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightTrackerConfig {
+    /// The total weight in kilograms above which a person is on the board.
+    pub step_on_kg: f64,
+    /// The total weight in kilograms below which the person left the board, lower than
+    /// [`Self::step_on_kg`] so the noise of the sensors does not toggle the state.
+    pub step_off_kg: f64,
+    /// The number of samples the weight has to stay within [`Self::stable_tolerance_kg`].
+    pub stable_samples: usize,
+    /// The maximum difference in kilograms of the samples of a stable weight,
+    /// also the change required to report another stable weight.
+    pub stable_tolerance_kg: f64,
+    /// The distance the center of pressure has to move before it is reported again.
+    pub center_of_pressure_threshold: f64,
+}
+
+impl Default for WeightTrackerConfig {
+    /// About half a second of samples at 100 reports per second.
+    fn default() -> Self {
+        Self {
+            step_on_kg: 10.0,
+            step_off_kg: 5.0,
+            stable_samples: 50,
+            stable_tolerance_kg: 0.5,
+            center_of_pressure_threshold: 0.05,
+        }
+    }
+}
+
+/// Turns the weights measured by a balance board into [`WeightEvent`]s.
+///
+/// ```no_run
+/// use wiimote_rs::prelude::*;
+///
+/// fn weigh(balance_board: &WiimoteDevice) -> WiimoteResult<f64> {
+///     let session = BalanceBoardSession::start(balance_board, true)?;
+///     let mut tracker = WeightTracker::new(WeightTrackerConfig::default());
+///     loop {
+///         let frame = session.read_frame()?;
+///         for event in tracker.push(&frame.weights) {
+///             if let WeightEvent::StableWeight(weight) = event {
+///                 return Ok(weight);
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WeightTracker {
+    config: WeightTrackerConfig,
+    on_board: bool,
+    samples: VecDeque<f64>,
+    stable_weight: Option<f64>,
+    center_of_pressure: Option<(f64, f64)>,
+}
+
+impl Default for WeightTracker {
+    fn default() -> Self {
+        Self::new(WeightTrackerConfig::default())
+    }
+}
+
+impl WeightTracker {
+    #[must_use]
+    pub fn new(config: WeightTrackerConfig) -> Self {
+        Self {
+            config,
+            on_board: false,
+            samples: VecDeque::with_capacity(config.stable_samples),
+            stable_weight: None,
+            center_of_pressure: None,
+        }
+    }
+
+    /// Returns whether a person is on the board.
+    #[must_use]
+    pub const fn is_on_board(&self) -> bool {
+        self.on_board
+    }
+
+    /// Adds the weights of a data report, returns the events caused by it.
+    pub fn push(&mut self, weights: &BalanceBoardWeights) -> Vec<WeightEvent> {
+        let mut events = Vec::new();
+        let total = weights.total();
+        if !self.on_board {
+            if total < self.config.step_on_kg {
+                return events;
+            }
+            self.on_board = true;
+            events.push(WeightEvent::StepOn);
+        } else if total < self.config.step_off_kg {
+            self.reset();
+            events.push(WeightEvent::StepOff);
+            return events;
+        }
+
+        if self.samples.len() >= self.config.stable_samples.max(1) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(total);
+        if let Some(weight) = self.settled_weight() {
+            let changed = self.stable_weight.map_or(true, |stable_weight| {
+                (weight - stable_weight).abs() > self.config.stable_tolerance_kg
+            });
+            if changed {
+                self.stable_weight = Some(weight);
+                events.push(WeightEvent::StableWeight(weight));
+            }
+        }
+
+        let (x, y) = Self::center_of_pressure(weights, total);
+        let moved = self.center_of_pressure.map_or(true, |(last_x, last_y)| {
+            (x - last_x).hypot(y - last_y) > self.config.center_of_pressure_threshold
+        });
+        if moved {
+            self.center_of_pressure = Some((x, y));
+            events.push(WeightEvent::CenterOfPressure(x, y));
+        }
+        events
+    }
+
+    /// Forgets the person on the board without emitting [`WeightEvent::StepOff`].
+    pub fn reset(&mut self) {
+        self.on_board = false;
+        self.samples.clear();
+        self.stable_weight = None;
+        self.center_of_pressure = None;
+    }
+
+    /// Returns the mean of the samples if the window is full and within the tolerance.
+    fn settled_weight(&self) -> Option<f64> {
+        if self.samples.len() < self.config.stable_samples.max(1) {
+            return None;
+        }
+        let (min, max) = self
+            .samples
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), &sample| {
+                (min.min(sample), max.max(sample))
+            });
+        #[allow(clippy::cast_precision_loss)]
+        let mean = self.samples.iter().sum::<f64>() / self.samples.len() as f64;
+        (max - min <= self.config.stable_tolerance_kg).then_some(mean)
+    }
+
+    fn center_of_pressure(weights: &BalanceBoardWeights, total: f64) -> (f64, f64) {
+        let right = weights.top_right + weights.bottom_right;
+        let left = weights.top_left + weights.bottom_left;
+        let top = weights.top_right + weights.top_left;
+        let bottom = weights.bottom_right + weights.bottom_left;
+        (
+            ((right - left) / total).clamp(-1.0, 1.0),
+            ((top - bottom) / total).clamp(-1.0, 1.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights(
+        top_right: f64,
+        bottom_right: f64,
+        top_left: f64,
+        bottom_left: f64,
+    ) -> BalanceBoardWeights {
+        BalanceBoardWeights {
+            top_right,
+            bottom_right,
+            top_left,
+            bottom_left,
+        }
+    }
+
+    #[test]
+    fn test_weight_events() {
+        let mut tracker = WeightTracker::new(WeightTrackerConfig {
+            stable_samples: 3,
+            ..WeightTrackerConfig::default()
+        });
+        assert!(tracker.push(&weights(1.0, 1.0, 1.0, 1.0)).is_empty());
+
+        let events = tracker.push(&weights(20.0, 20.0, 20.0, 20.0));
+        assert_eq!(
+            events,
+            [WeightEvent::StepOn, WeightEvent::CenterOfPressure(0.0, 0.0)]
+        );
+        assert!(tracker.push(&weights(20.1, 20.0, 20.0, 20.0)).is_empty());
+        let events = tracker.push(&weights(20.0, 20.0, 20.0, 20.1));
+        assert_eq!(events.len(), 1);
+        assert!(
+            matches!(events[0], WeightEvent::StableWeight(weight) if (weight - 80.0).abs() < 0.1)
+        );
+        // Still the same weight.
+        assert!(tracker.push(&weights(20.0, 20.0, 20.0, 20.0)).is_empty());
+
+        // Leaning to the right within the hysteresis of stepping off.
+        let events = tracker.push(&weights(30.0, 30.0, 10.0, 10.0));
+        assert_eq!(events, [WeightEvent::CenterOfPressure(0.5, 0.0)]);
+        assert!(tracker.is_on_board());
+        assert_eq!(
+            tracker.push(&weights(2.0, 2.0, 2.0, 2.0)),
+            [WeightEvent::CenterOfPressure(0.0, 0.0)]
+        );
+
+        assert_eq!(
+            tracker.push(&weights(1.0, 1.0, 1.0, 1.0)),
+            [WeightEvent::StepOff]
+        );
+        assert!(!tracker.is_on_board());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use crate::extensions::BalanceBoardWeights;
+
+/// A filter smoothing a single stream of values.
+pub trait Filter {
+    /// Adds a value to the filter and returns the filtered value.
+    fn filter(&mut self, value: f64) -> f64;
+
+    /// Discards the history of the filter, the next value is returned unfiltered.
+    fn reset(&mut self);
+}
+
+/// Exponential moving average, a first order low-pass filter.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialFilter {
+    alpha: f64,
+    state: Option<f64>,
+}
+
+impl ExponentialFilter {
+    /// Creates a filter with the smoothing factor `alpha`, clamped between 0 and 1.
+    /// A value of 1 disables the filtering, smaller values smooth more.
+    #[must_use]
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            state: None,
+        }
+    }
+
+    /// Creates a filter with the cutoff frequency `cutoff_hz` for values received at `sample_rate_hz`.
+    /// The Wii remote sends data reports at about 100 Hz in continuous reporting mode.
+    #[must_use]
+    pub fn from_cutoff(cutoff_hz: f64, sample_rate_hz: f64) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff_hz);
+        let dt = 1.0 / sample_rate_hz;
+        Self::new(dt / (rc + dt))
+    }
+
+    #[must_use]
+    pub const fn alpha(&self) -> f64 {
+        self.alpha
+    }
+}
+
+impl Filter for ExponentialFilter {
+    fn filter(&mut self, value: f64) -> f64 {
+        let filtered = match self.state {
+            Some(state) => state + self.alpha * (value - state),
+            None => value,
+        };
+        self.state = Some(filtered);
+        filtered
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Averages the last values received.
+#[derive(Debug, Clone)]
+pub struct MovingAverageFilter {
+    window_size: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+}
+
+impl MovingAverageFilter {
+    /// Creates a filter averaging the last `window_size` values (at least 1).
+    #[must_use]
+    pub fn new(window_size: usize) -> Self {
+        let window_size = usize::max(window_size, 1);
+        Self {
+            window_size,
+            values: VecDeque::with_capacity(window_size),
+            sum: 0.0,
+        }
+    }
+
+    #[must_use]
+    pub const fn window_size(&self) -> usize {
+        self.window_size
+    }
+}
+
+impl Filter for MovingAverageFilter {
+    #[allow(clippy::cast_precision_loss)]
+    fn filter(&mut self, value: f64) -> f64 {
+        if self.values.len() == self.window_size {
+            if let Some(oldest) = self.values.pop_front() {
+                self.sum -= oldest;
+            }
+        }
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum / self.values.len() as f64
+    }
+
+    fn reset(&mut self) {
+        self.values.clear();
+        self.sum = 0.0;
+    }
+}
+
+/// A sample of sensor data with multiple axes that can be filtered axis by axis.
+pub trait FilterSample: Sized {
+    /// Returns the sample with `filter` applied to every axis, called with the index of the axis.
+    #[must_use]
+    fn map_axes(self, filter: impl FnMut(usize, f64) -> f64) -> Self;
+}
+
+/// A point of the IR camera or any other 2D value, `(x, y)`.
+impl FilterSample for (f64, f64) {
+    fn map_axes(self, mut filter: impl FnMut(usize, f64) -> f64) -> Self {
+        (filter(0, self.0), filter(1, self.1))
+    }
+}
+
+/// Acceleration `(x, y, z)` or angular velocity `(yaw, roll, pitch)`.
+impl FilterSample for (f64, f64, f64) {
+    fn map_axes(self, mut filter: impl FnMut(usize, f64) -> f64) -> Self {
+        (filter(0, self.0), filter(1, self.1), filter(2, self.2))
+    }
+}
+
+/// The weights in the order top right, bottom right, top left, bottom left.
+impl FilterSample for BalanceBoardWeights {
+    fn map_axes(self, mut filter: impl FnMut(usize, f64) -> f64) -> Self {
+        Self {
+            top_right: filter(0, self.top_right),
+            bottom_right: filter(1, self.bottom_right),
+            top_left: filter(2, self.top_left),
+            bottom_left: filter(3, self.bottom_left),
+        }
+    }
+}
+
+/// Filters a stream of multi-axis sensor samples with a separate filter for each axis.
+///
+/// # Example
+///
+/// ```
+/// use wiimote_rs::filters::{ExponentialFilter, SensorFilter};
+///
+/// // Stronger smoothing on the z axis
+/// let mut filter = SensorFilter::new(vec![
+///     ExponentialFilter::from_cutoff(5.0, 100.0),
+///     ExponentialFilter::from_cutoff(5.0, 100.0),
+///     ExponentialFilter::from_cutoff(1.0, 100.0),
+/// ]);
+/// let acceleration = filter.filter((0.0, 0.0, 1.0));
+/// assert_eq!(acceleration, (0.0, 0.0, 1.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SensorFilter<F: Filter> {
+    axes: Vec<F>,
+}
+
+impl<F: Filter> SensorFilter<F> {
+    /// Creates a filter with one filter per axis.
+    /// Axes without a filter are passed through unchanged.
+    #[must_use]
+    pub const fn new(axes: Vec<F>) -> Self {
+        Self { axes }
+    }
+
+    /// Creates a filter using the same configuration for `axis_count` axes.
+    #[must_use]
+    pub fn uniform(filter: F, axis_count: usize) -> Self
+    where
+        F: Clone,
+    {
+        Self::new(vec![filter; axis_count])
+    }
+
+    /// Returns the filter of the axis at `index` to change its configuration.
+    pub fn axis_mut(&mut self, index: usize) -> Option<&mut F> {
+        self.axes.get_mut(index)
+    }
+
+    /// Adds a sample to the filter and returns the filtered sample.
+    pub fn filter<T: FilterSample>(&mut self, sample: T) -> T {
+        sample.map_axes(|index, value| match self.axes.get_mut(index) {
+            Some(filter) => filter.filter(value),
+            None => value,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.axes.iter_mut().for_each(Filter::reset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_filter() {
+        let mut filter = ExponentialFilter::new(0.5);
+
+        assert!((filter.filter(10.0) - 10.0).abs() < 1e-9);
+        assert!((filter.filter(20.0) - 15.0).abs() < 1e-9);
+        assert!((filter.filter(20.0) - 17.5).abs() < 1e-9);
+
+        filter.reset();
+        assert!((filter.filter(0.0) - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_moving_average_filter() {
+        let mut filter = MovingAverageFilter::new(3);
+
+        assert!((filter.filter(3.0) - 3.0).abs() < 1e-9);
+        assert!((filter.filter(6.0) - 4.5).abs() < 1e-9);
+        assert!((filter.filter(9.0) - 6.0).abs() < 1e-9);
+        assert!((filter.filter(12.0) - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sensor_filter_per_axis() {
+        let mut filter = SensorFilter::new(vec![
+            MovingAverageFilter::new(1),
+            MovingAverageFilter::new(2),
+        ]);
+
+        filter.filter((1.0, 1.0, 1.0));
+        let (x, y, z) = filter.filter((3.0, 3.0, 3.0));
+
+        assert!((x - 3.0).abs() < 1e-9);
+        assert!((y - 2.0).abs() < 1e-9);
+        // No filter configured for the third axis
+        assert!((z - 3.0).abs() < 1e-9);
+    }
+}
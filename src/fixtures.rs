@@ -0,0 +1,387 @@
+//! Test fixtures from captured report traces.
+//!
+//! A [`Fixture`] is a plain text trace of the reports exchanged with a Wii remote,
+//! for example exported from a Bluetooth capture of misbehaving third-party hardware.
+//! Each line contains the timestamp in seconds, the direction (`in` or `out`) and the report as hex bytes:
+//!
+//! ```text
+//! # Nunchuck clone, identifier read after initialization
+//! 0.000000 out 17 04 a4 00 fa 00 06
+//! 0.004120 in  21 00 00 50 00 fa 00 00 a4 20 00 00 00 00 00 00 00 00 00 00 00 00
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored. The hex bytes may be separated by spaces or colons
+//! and may start with the HID transaction byte (0xA1 for input, 0xA2 for output reports) included in captures.
+//!
+//! [`Fixture::decode`] parses the input reports with [`InputReport`] and decodes the extension data
+//! of data reports using the extension identified by the register reads contained in the trace.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::extensions::{encryption, ExtensionCalibration};
+use crate::input::{InputReport, StatusFlags};
+use crate::prelude::*;
+
+const WRITE_MEMORY_ID: u8 = ReportId::WriteMemory.to_u8();
+const READ_MEMORY_ID: u8 = ReportId::ReadMemory.to_u8();
+const CONTROL_REGISTERS_FLAG: u8 = 0x04;
+
+/// Register writes that select the initialization of the extension.
+const ENCRYPTION_KEY_ADDRESS: u32 = 0xA4_0040;
+const EXTENSION_INIT_ADDRESS: u32 = 0xA4_00F0;
+
+/// Whether a report was sent by the Wii remote or to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportDirection {
+    Input,
+    Output,
+}
+
+/// A single report of a fixture.
+#[derive(Debug, Clone)]
+pub struct FixtureEntry {
+    /// The line of the entry in the fixture, starting at 1.
+    pub line: usize,
+    pub timestamp: Duration,
+    pub direction: ReportDirection,
+    /// The report starting with the report ID.
+    pub report: Vec<u8>,
+}
+
+/// An input report of a fixture decoded with the state of the trace up to that report.
+#[derive(Debug)]
+pub struct DecodedReport {
+    /// The line of the report in the fixture, starting at 1.
+    pub line: usize,
+    pub timestamp: Duration,
+    pub report: WiimoteResult<InputReport>,
+    /// The extension identified by the trace at the time of the report.
+    pub extension: Option<WiimoteExtension>,
+    /// The decoded extension bytes, only for data reports with an identified extension.
+    pub extension_input: Option<ExtensionInput>,
+}
+
+/// A trace of reports exchanged with a Wii remote.
+#[derive(Debug, Clone, Default)]
+pub struct Fixture {
+    entries: Vec<FixtureEntry>,
+}
+
+impl Fixture {
+    /// Reads the fixture from the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read or is not a valid fixture.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the fixture from its text.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error naming the line if a line is not a valid entry.
+    pub fn parse(text: &str) -> std::io::Result<Self> {
+        let mut entries = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_entry(index + 1, line).map_err(|message| {
+                std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {}: {message}", index + 1),
+                )
+            })?;
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+
+    /// Returns all entries of the fixture in order.
+    #[must_use]
+    pub fn entries(&self) -> &[FixtureEntry] {
+        &self.entries
+    }
+
+    /// Decodes the input reports of the fixture.
+    ///
+    /// The connected extension is tracked through the register reads of its identifier and calibration,
+    /// whether it uses the legacy encrypted mode through the initialization writes,
+    /// and it is forgotten when a status report no longer reports an extension.
+    #[must_use]
+    pub fn decode(&self) -> Vec<DecodedReport> {
+        let mut state = TraceState::default();
+        let mut decoded = Vec::new();
+        for entry in &self.entries {
+            match entry.direction {
+                ReportDirection::Output => state.output_report(&entry.report),
+                ReportDirection::Input => {
+                    let report = InputReport::try_from(entry.report.as_slice());
+                    let extension = state.extension();
+                    let extension_input = match (&report, &extension) {
+                        (Ok(InputReport::DataReport(_, data)), Some(extension)) => {
+                            extension.decode(data)
+                        }
+                        _ => None,
+                    };
+                    if let Ok(report) = &report {
+                        state.input_report(report);
+                    }
+                    decoded.push(DecodedReport {
+                        line: entry.line,
+                        timestamp: entry.timestamp,
+                        report,
+                        extension: extension.map(|extension| extension.extension()),
+                        extension_input,
+                    });
+                }
+            }
+        }
+        decoded
+    }
+}
+
+fn parse_entry(line: usize, text: &str) -> Result<FixtureEntry, String> {
+    let mut fields = text.split_whitespace();
+    let timestamp = fields.next().unwrap_or_default();
+    let timestamp = timestamp
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("invalid timestamp `{timestamp}`"))?;
+    let direction = match fields.next() {
+        Some("in") => ReportDirection::Input,
+        Some("out") => ReportDirection::Output,
+        Some(direction) => return Err(format!("invalid direction `{direction}`")),
+        None => return Err("missing direction".to_owned()),
+    };
+
+    let digits: String = fields.flat_map(|field| field.split(':')).collect();
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.is_ascii() {
+        return Err("the report must consist of complete hex bytes".to_owned());
+    }
+    let mut report = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid hex bytes `{digits}`"))?;
+
+    let transaction_header = match direction {
+        ReportDirection::Input => 0xA1,
+        ReportDirection::Output => 0xA2,
+    };
+    if report.len() > 1 && report[0] == transaction_header {
+        report.remove(0);
+    }
+
+    Ok(FixtureEntry {
+        line,
+        timestamp,
+        direction,
+        report,
+    })
+}
+
+/// The extension registers known from a trace.
+#[derive(Default)]
+struct TraceState {
+    registers: HashMap<u32, u8>,
+    /// The register block of the last read request, the read responses only contain the lower 2 bytes.
+    read_block: Option<u32>,
+    encrypted: bool,
+}
+
+impl TraceState {
+    fn output_report(&mut self, report: &[u8]) {
+        let [id, flags, a2, a1, a0, rest @ ..] = report else {
+            return;
+        };
+        if flags & CONTROL_REGISTERS_FLAG == 0 {
+            if *id == READ_MEMORY_ID {
+                self.read_block = None;
+            }
+            return;
+        }
+        let address = u32::from_be_bytes([0, *a2, *a1, *a0]);
+        match *id {
+            READ_MEMORY_ID => self.read_block = Some(address & 0xFF_0000),
+            WRITE_MEMORY_ID => match (address, rest) {
+                (ENCRYPTION_KEY_ADDRESS, _) => self.encrypted = true,
+                (EXTENSION_INIT_ADDRESS, [_, 0x55, ..]) => self.encrypted = false,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn input_report(&mut self, report: &InputReport) {
+        match report {
+            InputReport::ReadMemory(data) => {
+                if let (Some(block), None) = (self.read_block, data.error()) {
+                    let address = block | u32::from(data.address_offset());
+                    let bytes = data.data;
+                    for (offset, byte) in bytes[..usize::from(data.size())].iter().enumerate() {
+                        self.registers.insert(address + offset as u32, *byte);
+                    }
+                }
+            }
+            InputReport::StatusInformation(data)
+                if !data
+                    .flags()
+                    .contains(StatusFlags::EXTENSION_CONTROLLER_CONNECTED) =>
+            {
+                self.registers
+                    .retain(|address, _| address & 0xFF_0000 != 0xA4_0000);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns `N` decrypted extension register bytes at `address` if all of them were read.
+    fn read<const N: usize>(&self, address: u32) -> Option<[u8; N]> {
+        let mut bytes = [0u8; N];
+        for (offset, byte) in bytes.iter_mut().enumerate() {
+            *byte = *self.registers.get(&(address + offset as u32))?;
+        }
+        if self.encrypted {
+            encryption::decrypt(&mut bytes);
+        }
+        Some(bytes)
+    }
+
+    fn extension(&self) -> Option<ConnectedExtension> {
+        let identifier = self.read::<6>(0xA4_00FA)?;
+        if identifier == [0xFF; 6] {
+            return None;
+        }
+        let extension = WiimoteExtension::from_identifier(identifier);
+        let calibration = match extension {
+            WiimoteExtension::Nunchuck => ExtensionCalibration::Nunchuck(
+                self.read::<16>(0xA4_0020)
+                    .and_then(|data| NunchuckCalibration::from_calibration_data(&data))
+                    .unwrap_or_default(),
+            ),
+            WiimoteExtension::ClassicController | WiimoteExtension::ClassicControllerPro => {
+                ExtensionCalibration::ClassicController(
+                    self.read::<16>(0xA4_0020)
+                        .and_then(|data| ClassicControllerCalibration::from_calibration_data(&data))
+                        .unwrap_or_default(),
+                )
+            }
+            WiimoteExtension::BalanceBoard => {
+                match (self.read::<32>(0xA4_0020), self.read::<1>(0xA4_0060)) {
+                    (Some(data), Some([reference_temperature])) => {
+                        ExtensionCalibration::BalanceBoard(
+                            BalanceBoardCalibration::from_calibration_data(
+                                &data,
+                                reference_temperature,
+                            ),
+                        )
+                    }
+                    _ => ExtensionCalibration::None,
+                }
+            }
+            _ => ExtensionCalibration::None,
+        };
+        Some(ConnectedExtension::new(
+            extension,
+            calibration,
+            self.encrypted,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NUNCHUCK_TRACE: &str = "
+        # Nunchuck identified in the new unencrypted mode
+        0.000 out 16 04 a4 00 f0 01 55 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        0.002 in  22 00 00 16 00
+        0.010 out a2:17:04:a4:00:fa:00:06
+        0.012 in  a1:21:00:00:50:00:fa:00:00:a4:20:00:00:00:00:00:00:00:00:00:00:00:00
+        0.020 in  32 00 00 e0 80 80 80 b3 fd
+    ";
+
+    #[test]
+    fn test_parse() {
+        let fixture = Fixture::parse(NUNCHUCK_TRACE).unwrap();
+
+        let entries = fixture.entries();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].line, 3);
+        assert_eq!(entries[0].direction, ReportDirection::Output);
+        assert_eq!(entries[1].timestamp, Duration::from_millis(2));
+        assert_eq!(
+            entries[2].report,
+            [0x17, 0x04, 0xA4, 0x00, 0xFA, 0x00, 0x06]
+        );
+        assert_eq!(entries[3].report[0], 0x21);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in [
+            "x in 30 00 00",
+            "0.1 sideways 30 00 00",
+            "0.1 in",
+            "0.1 in 30 0",
+            "0.1 in 30 zz",
+        ] {
+            let error = Fixture::parse(&format!("# comment\n{text}")).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+            assert!(error.to_string().starts_with("line 2:"));
+        }
+    }
+
+    #[test]
+    fn test_decode_nunchuck() {
+        let decoded = Fixture::parse(NUNCHUCK_TRACE).unwrap().decode();
+
+        assert_eq!(decoded.len(), 3);
+        assert!(matches!(decoded[0].report, Ok(InputReport::Acknowledge(_))));
+        assert!(decoded[1].extension.is_none());
+        assert!(matches!(
+            decoded[2].extension,
+            Some(WiimoteExtension::Nunchuck)
+        ));
+        let Some(ExtensionInput::Nunchuck(data)) = &decoded[2].extension_input else {
+            panic!("Expected Nunchuck data");
+        };
+        assert_eq!(data.stick_x, 0xE0);
+        assert!(data.buttons.contains(NunchuckButtons::C));
+    }
+
+    #[test]
+    fn test_decode_encrypted_and_unplugged() {
+        let decoded = Fixture::parse(
+            "
+            0.0 out 16 04 a4 00 40 01 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+            0.1 out 17 04 a4 00 fa 00 06
+            0.2 in  21 00 00 50 00 fa fe fe 9a 1e fe fe 00 00 00 00 00 00 00 00 00 00
+            0.3 in  32 00 00 00 00 00 00 00 00
+            0.4 in  20 00 00 00 00 00 60
+            0.5 in  32 00 00 00 00 00 00 00 00
+            0.6 in  20 00
+            ",
+        )
+        .unwrap()
+        .decode();
+
+        assert!(matches!(
+            decoded[1].extension,
+            Some(WiimoteExtension::Nunchuck)
+        ));
+        assert!(decoded[1].extension_input.is_some());
+        assert!(decoded[3].extension.is_none());
+        assert!(decoded[4].report.is_err());
+    }
+}
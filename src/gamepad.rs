@@ -0,0 +1,442 @@
+//! Maps the Wii remote and its extensions to a conventional gamepad.
+//!
+//! The buttons and axes are named like the ones of [gilrs](https://docs.rs/gilrs),
+//! the face buttons are named by their position (`South` is the bottom button).
+
+use crate::calibration::Deadzone;
+use crate::extensions::{
+    ClassicControllerButtons, ConnectedExtension, ExtensionCalibration, ExtensionInput,
+    NunchuckButtons, NunchuckCalibration,
+};
+use crate::input::{ButtonData, WiimoteData};
+use crate::prelude::*;
+
+/// A button of the standard gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub const ALL: [Self; 15] = [
+        Self::South,
+        Self::East,
+        Self::North,
+        Self::West,
+        Self::LeftTrigger,
+        Self::LeftTrigger2,
+        Self::RightTrigger,
+        Self::RightTrigger2,
+        Self::Select,
+        Self::Start,
+        Self::Mode,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+    ];
+
+    const fn mask(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// An axis of the standard gamepad.
+/// Sticks range from -1.0 to 1.0 with up and right being positive, triggers from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    /// The analog left trigger.
+    LeftZ,
+    /// The analog right trigger.
+    RightZ,
+}
+
+impl GamepadAxis {
+    pub const ALL: [Self; 6] = [
+        Self::LeftStickX,
+        Self::LeftStickY,
+        Self::RightStickX,
+        Self::RightStickY,
+        Self::LeftZ,
+        Self::RightZ,
+    ];
+}
+
+/// The direction of the D-pad as a hat switch.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadHat {
+    #[default]
+    Centered,
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl GamepadHat {
+    /// Returns the hat direction of the pressed D-pad buttons, opposite directions cancel each other.
+    #[must_use]
+    pub const fn from_dpad(up: bool, down: bool, left: bool, right: bool) -> Self {
+        match (up && !down, down && !up, left && !right, right && !left) {
+            (true, _, false, true) => Self::UpRight,
+            (true, _, true, false) => Self::UpLeft,
+            (true, _, _, _) => Self::Up,
+            (_, true, false, true) => Self::DownRight,
+            (_, true, true, false) => Self::DownLeft,
+            (_, true, _, _) => Self::Down,
+            (_, _, true, _) => Self::Left,
+            (_, _, _, true) => Self::Right,
+            _ => Self::Centered,
+        }
+    }
+}
+
+/// The state of the standard gamepad mapped from a data report.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GamepadState {
+    buttons: u16,
+    axes: [f64; 6],
+}
+
+impl GamepadState {
+    /// Returns whether the button is pressed.
+    #[must_use]
+    pub const fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons & button.mask() != 0
+    }
+
+    /// Returns the pressed buttons.
+    pub fn pressed_buttons(&self) -> impl Iterator<Item = GamepadButton> + '_ {
+        GamepadButton::ALL
+            .into_iter()
+            .filter(|button| self.is_pressed(*button))
+    }
+
+    /// Returns the value of the axis after applying the deadzone, 0.0 if the controller has no such axis.
+    #[must_use]
+    pub const fn axis(&self, axis: GamepadAxis) -> f64 {
+        self.axes[axis as usize]
+    }
+
+    /// Returns the direction of the D-pad.
+    #[must_use]
+    pub const fn hat(&self) -> GamepadHat {
+        GamepadHat::from_dpad(
+            self.is_pressed(GamepadButton::DPadUp),
+            self.is_pressed(GamepadButton::DPadDown),
+            self.is_pressed(GamepadButton::DPadLeft),
+            self.is_pressed(GamepadButton::DPadRight),
+        )
+    }
+
+    fn press(&mut self, button: GamepadButton, pressed: bool) {
+        if pressed {
+            self.buttons |= button.mask();
+        }
+    }
+
+    fn set_axis(&mut self, axis: GamepadAxis, value: f64) {
+        self.axes[axis as usize] = value;
+    }
+}
+
+/// Maps the Wii remote held upright and its extension to a standard gamepad.
+///
+/// | Wii remote | Gamepad |
+/// |---|---|
+/// | A, B, 1, 2 | `South`, `RightTrigger2`, `West`, `North` |
+/// | -, +, Home | `Select`, `Start`, `Mode` |
+/// | D-pad | D-pad |
+/// | Nunchuck stick, C, Z | Left stick, `LeftTrigger`, `LeftTrigger2` |
+/// | Classic Controller a, b, x, y | `East`, `South`, `North`, `West` |
+/// | Classic Controller L, R, ZL, ZR | `LeftTrigger2`, `RightTrigger2`, `LeftTrigger`, `RightTrigger` |
+/// | Classic Controller sticks, analog L and R | Sticks, `LeftZ`, `RightZ` |
+///
+/// The buttons of the Wii remote and the Classic Controller are combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadMapping {
+    /// Stick positions with a smaller distance from the center are reported as centered,
+    /// larger distances are rescaled to still reach 1.0.
+    pub stick_deadzone: f64,
+    /// Trigger values below the deadzone are reported as released.
+    pub trigger_deadzone: f64,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        Self {
+            stick_deadzone: 0.1,
+            trigger_deadzone: 0.05,
+        }
+    }
+}
+
+impl GamepadMapping {
+    /// Maps a data report of `device`, decoding the extension bytes with the connected extension.
+    #[must_use]
+    pub fn map_report(&self, device: &WiimoteDevice, data: &WiimoteData) -> GamepadState {
+        let extension = device.extension();
+        let input = extension
+            .as_ref()
+            .and_then(|extension| extension.decode(data));
+        let buttons = if data.report_id() == 0x3D {
+            ButtonData::empty()
+        } else {
+            data.buttons()
+        };
+        self.map(buttons, input.as_ref(), extension.as_deref())
+    }
+
+    /// Maps the Wii remote buttons and the decoded extension input.
+    /// The extension calibration is used for the Nunchuck stick, typical values are used without it.
+    #[must_use]
+    pub fn map(
+        &self,
+        buttons: ButtonData,
+        input: Option<&ExtensionInput>,
+        extension: Option<&ConnectedExtension>,
+    ) -> GamepadState {
+        let mut state = GamepadState::default();
+        state.press(GamepadButton::South, buttons.contains(ButtonData::A));
+        state.press(
+            GamepadButton::RightTrigger2,
+            buttons.contains(ButtonData::B),
+        );
+        state.press(GamepadButton::West, buttons.contains(ButtonData::ONE));
+        state.press(GamepadButton::North, buttons.contains(ButtonData::TWO));
+        state.press(GamepadButton::Select, buttons.contains(ButtonData::MINUS));
+        state.press(GamepadButton::Start, buttons.contains(ButtonData::PLUS));
+        state.press(GamepadButton::Mode, buttons.contains(ButtonData::HOME));
+        state.press(GamepadButton::DPadUp, buttons.contains(ButtonData::UP));
+        state.press(GamepadButton::DPadDown, buttons.contains(ButtonData::DOWN));
+        state.press(GamepadButton::DPadLeft, buttons.contains(ButtonData::LEFT));
+        state.press(
+            GamepadButton::DPadRight,
+            buttons.contains(ButtonData::RIGHT),
+        );
+
+        match input {
+            Some(ExtensionInput::Nunchuck(data)) => {
+                state.press(
+                    GamepadButton::LeftTrigger,
+                    data.buttons.contains(NunchuckButtons::C),
+                );
+                state.press(
+                    GamepadButton::LeftTrigger2,
+                    data.buttons.contains(NunchuckButtons::Z),
+                );
+
+                let stick = match extension.map(ConnectedExtension::calibration) {
+                    Some(ExtensionCalibration::Nunchuck(calibration)) => {
+                        calibration.get_stick(data)
+                    }
+                    _ => NunchuckCalibration::default().get_stick(data),
+                };
+                self.set_stick(&mut state, GamepadAxis::LeftStickX, stick);
+            }
+            Some(ExtensionInput::ClassicController(data)) => {
+                self.map_classic_controller(
+                    &mut state,
+                    data.buttons,
+                    [
+                        data.left_stick_x,
+                        data.left_stick_y,
+                        data.right_stick_x,
+                        data.right_stick_y,
+                    ],
+                );
+                let left = self.trigger(data.left_trigger);
+                let right = self.trigger(data.right_trigger);
+                state.set_axis(GamepadAxis::LeftZ, left);
+                state.set_axis(GamepadAxis::RightZ, right);
+            }
+            Some(ExtensionInput::ClassicControllerPro(data)) => {
+                self.map_classic_controller(
+                    &mut state,
+                    data.buttons,
+                    [
+                        data.left_stick_x,
+                        data.left_stick_y,
+                        data.right_stick_x,
+                        data.right_stick_y,
+                    ],
+                );
+                // The digital L and R buttons are reported as fully pressed triggers.
+                let left = f64::from(u8::from(data.buttons.contains(ClassicControllerButtons::L)));
+                let right = f64::from(u8::from(data.buttons.contains(ClassicControllerButtons::R)));
+                state.set_axis(GamepadAxis::LeftZ, left);
+                state.set_axis(GamepadAxis::RightZ, right);
+            }
+            Some(ExtensionInput::BalanceBoard(_) | ExtensionInput::Custom(_)) | None => {}
+        }
+
+        state
+    }
+
+    fn map_classic_controller(
+        &self,
+        state: &mut GamepadState,
+        buttons: ClassicControllerButtons,
+        sticks: [u8; 4],
+    ) {
+        let pressed = |button| buttons.contains(button);
+        state.press(GamepadButton::East, pressed(ClassicControllerButtons::A));
+        state.press(GamepadButton::South, pressed(ClassicControllerButtons::B));
+        state.press(GamepadButton::North, pressed(ClassicControllerButtons::X));
+        state.press(GamepadButton::West, pressed(ClassicControllerButtons::Y));
+        state.press(
+            GamepadButton::LeftTrigger,
+            pressed(ClassicControllerButtons::ZL),
+        );
+        state.press(
+            GamepadButton::RightTrigger,
+            pressed(ClassicControllerButtons::ZR),
+        );
+        state.press(
+            GamepadButton::LeftTrigger2,
+            pressed(ClassicControllerButtons::L),
+        );
+        state.press(
+            GamepadButton::RightTrigger2,
+            pressed(ClassicControllerButtons::R),
+        );
+        state.press(
+            GamepadButton::Select,
+            pressed(ClassicControllerButtons::MINUS),
+        );
+        state.press(
+            GamepadButton::Start,
+            pressed(ClassicControllerButtons::PLUS),
+        );
+        state.press(GamepadButton::Mode, pressed(ClassicControllerButtons::HOME));
+        state.press(GamepadButton::DPadUp, pressed(ClassicControllerButtons::UP));
+        state.press(
+            GamepadButton::DPadDown,
+            pressed(ClassicControllerButtons::DOWN),
+        );
+        state.press(
+            GamepadButton::DPadLeft,
+            pressed(ClassicControllerButtons::LEFT),
+        );
+        state.press(
+            GamepadButton::DPadRight,
+            pressed(ClassicControllerButtons::RIGHT),
+        );
+
+        // The left stick has 6 bits, the right stick 5 bits.
+        let [left_x, left_y, right_x, right_y] = sticks;
+        let left = (raw_stick(left_x, 63), raw_stick(left_y, 63));
+        let right = (raw_stick(right_x, 31), raw_stick(right_y, 31));
+        self.set_stick(state, GamepadAxis::LeftStickX, left);
+        self.set_stick(state, GamepadAxis::RightStickX, right);
+    }
+
+    /// Sets the X axis and the following Y axis of a stick after applying the radial deadzone.
+    fn set_stick(&self, state: &mut GamepadState, x_axis: GamepadAxis, (x, y): (f64, f64)) {
+        let (x, y) = Deadzone::Radial(self.stick_deadzone).apply_stick((x, y));
+        state.axes[x_axis as usize] = x;
+        state.axes[x_axis as usize + 1] = y;
+    }
+
+    /// Converts a 5-bit trigger value to the range 0.0 to 1.0 after applying the deadzone.
+    fn trigger(&self, value: u8) -> f64 {
+        Deadzone::Axial(self.trigger_deadzone).apply_trigger(f64::from(value) / 31.0)
+    }
+}
+
+/// Converts an uncalibrated stick value from 0 to `max` to the range -1.0 to 1.0.
+fn raw_stick(value: u8, max: u8) -> f64 {
+    let center = f64::from(max) / 2.0;
+    ((f64::from(value) - center) / center).clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wiimote_buttons() {
+        let state = GamepadMapping::default().map(ButtonData::A | ButtonData::UP, None, None);
+
+        assert!(state.is_pressed(GamepadButton::South));
+        assert!(!state.is_pressed(GamepadButton::East));
+        assert_eq!(
+            state.pressed_buttons().collect::<Vec<_>>(),
+            [GamepadButton::South, GamepadButton::DPadUp]
+        );
+        assert_eq!(state.hat(), GamepadHat::Up);
+    }
+
+    #[test]
+    fn test_hat() {
+        assert_eq!(
+            GamepadHat::from_dpad(true, false, false, true),
+            GamepadHat::UpRight
+        );
+        assert_eq!(
+            GamepadHat::from_dpad(false, true, true, false),
+            GamepadHat::DownLeft
+        );
+        assert_eq!(
+            GamepadHat::from_dpad(true, true, true, false),
+            GamepadHat::Left
+        );
+        assert_eq!(
+            GamepadHat::from_dpad(false, false, false, false),
+            GamepadHat::Centered
+        );
+    }
+
+    #[test]
+    fn test_classic_controller() {
+        // Left stick centered, right stick fully right, left trigger fully pressed, a pressed
+        let data = ClassicControllerData::from([0xE0, 0xE0, 0xF0, 0xE0, 0xFF, 0b1110_1111]);
+        let input = ExtensionInput::ClassicController(data);
+
+        let state = GamepadMapping::default().map(ButtonData::empty(), Some(&input), None);
+
+        assert!(state.is_pressed(GamepadButton::East));
+        assert_eq!(state.axis(GamepadAxis::LeftStickX), 0.0);
+        assert!((state.axis(GamepadAxis::RightStickX) - 1.0).abs() < 1e-3);
+        assert!((state.axis(GamepadAxis::LeftZ) - 1.0).abs() < 1e-9);
+        assert_eq!(state.axis(GamepadAxis::RightZ), 0.0);
+    }
+
+    #[test]
+    fn test_stick_deadzone() {
+        let mapping = GamepadMapping {
+            stick_deadzone: 0.2,
+            trigger_deadzone: 0.0,
+        };
+        let mut state = GamepadState::default();
+
+        mapping.set_stick(&mut state, GamepadAxis::LeftStickX, (0.1, 0.1));
+        assert_eq!(state.axis(GamepadAxis::LeftStickX), 0.0);
+
+        mapping.set_stick(&mut state, GamepadAxis::LeftStickX, (0.0, 0.6));
+        assert!((state.axis(GamepadAxis::LeftStickY) - 0.5).abs() < 1e-9);
+        assert_eq!(state.axis(GamepadAxis::LeftStickX), 0.0);
+    }
+}
@@ -0,0 +1,34 @@
+//! Windows specific information about the HID device of a Wii remote.
+
+use crate::prelude::*;
+
+/// The HID device a Wii remote was opened with on Windows, see [`WiimoteDeviceExt::hid_info`].
+///
+/// The device path and report lengths can be matched with other tools when debugging,
+/// e.g. the device instance in Device Manager or the captured packets in Wireshark.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HidInfo {
+    /// The device interface path the HID device was opened with.
+    pub device_path: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// The length of the input reports including the report ID, from the HID capabilities.
+    pub input_report_length: u16,
+    /// The length of the output reports including the report ID, from the HID capabilities.
+    pub output_report_length: u16,
+    /// The length of the feature reports including the report ID, from the HID capabilities.
+    pub feature_report_length: u16,
+}
+
+/// Windows specific extensions of [`WiimoteDevice`].
+pub trait WiimoteDeviceExt {
+    /// Returns the HID device of the transport opened last, also after it was closed.
+    /// `None` for custom transports that are not HID devices.
+    fn hid_info(&self) -> Option<HidInfo>;
+}
+
+impl WiimoteDeviceExt for WiimoteDevice {
+    fn hid_info(&self) -> Option<HidInfo> {
+        self.transport_hid_info()
+    }
+}
@@ -0,0 +1,268 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{RecvTimeoutError, Sender};
+
+use crate::device::SharedDevice;
+use crate::events::EventPublisher;
+use crate::input::{ButtonData, WiimoteData};
+use crate::output::{DataReporingMode, OutputReport};
+use crate::prelude::*;
+
+/// What happens when the Wii remote becomes idle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IdleAction {
+    /// Only publish [`WiimoteEvent::Idle`].
+    Notify,
+    /// Switch to the non-continuous buttons-only reporting mode 0x30 until a button is pressed,
+    /// the previous data reporting mode is restored when the Wii remote becomes active again.
+    #[default]
+    MinimalReporting,
+    /// Close the connection to the Wii remote, see [`WiimoteDevice::is_connected`].
+    Disconnect,
+}
+
+/// The configuration of the idle watchdog of a `WiimoteDevice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleConfig {
+    /// The time without button or motion changes after which the Wii remote is idle.
+    pub timeout: Duration,
+    pub action: IdleAction,
+    /// The minimum change of a raw 8-bit accelerometer value counted as motion.
+    pub motion_threshold: u8,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5 * 60),
+            action: IdleAction::default(),
+            motion_threshold: 8,
+        }
+    }
+}
+
+/// Detects button and motion changes in data reports.
+#[derive(Debug, Default)]
+struct ActivityDetector {
+    buttons: Option<ButtonData>,
+    acceleration: Option<[u8; 3]>,
+}
+
+impl ActivityDetector {
+    /// Returns true if the buttons changed or the acceleration moved more than `motion_threshold`
+    /// away from the acceleration at the last activity.
+    fn update(&mut self, wiimote_data: &WiimoteData, motion_threshold: u8) -> bool {
+        let mut active = false;
+        if wiimote_data.report_id() != 0x3D {
+            // The unused bits of the button bytes contain accelerometer data.
+            let buttons = wiimote_data.buttons() & ButtonData::all();
+            active |= self.buttons.is_some_and(|last| last != buttons);
+            self.buttons = Some(buttons);
+        }
+        if matches!(wiimote_data.report_id(), 0x31 | 0x33 | 0x35 | 0x37) {
+            let mut acceleration = [0u8; 3];
+            acceleration.copy_from_slice(&wiimote_data.data[2..5]);
+            match self.acceleration {
+                Some(last) => {
+                    let moved = last
+                        .iter()
+                        .zip(acceleration)
+                        .any(|(last, value)| last.abs_diff(value) > motion_threshold);
+                    if moved {
+                        self.acceleration = Some(acceleration);
+                        active = true;
+                    }
+                }
+                None => self.acceleration = Some(acceleration),
+            }
+        }
+        active
+    }
+}
+
+#[derive(Debug)]
+struct IdleState {
+    config: Option<IdleConfig>,
+    detector: ActivityDetector,
+    last_activity: Instant,
+    idle: bool,
+    restore_mode: Option<DataReporingMode>,
+}
+
+/// Detects when the Wii remote is idle on a background thread and applies the [`IdleAction`].
+/// Activity is detected while reading input reports from the `WiimoteDevice`.
+pub(crate) struct IdleWatchdog {
+    state: Arc<Mutex<IdleState>>,
+    wake: Sender<()>,
+}
+
+impl IdleWatchdog {
+    pub(crate) fn new(device: Arc<SharedDevice>, events: Arc<EventPublisher>) -> Self {
+        let state = Arc::new(Mutex::new(IdleState {
+            config: None,
+            detector: ActivityDetector::default(),
+            last_activity: Instant::now(),
+            idle: false,
+            restore_mode: None,
+        }));
+        let (wake, receiver) = crossbeam_channel::unbounded();
+
+        let thread_state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            // The thread ends when the watchdog is dropped together with the `WiimoteDevice`.
+            loop {
+                let timeout = {
+                    let state = lock(&thread_state);
+                    match state.config {
+                        Some(config) if !state.idle => {
+                            Some(config.timeout.saturating_sub(state.last_activity.elapsed()))
+                        }
+                        _ => None,
+                    }
+                };
+                let received = match timeout {
+                    Some(timeout) => receiver.recv_timeout(timeout),
+                    None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(()) => {}
+                    Err(RecvTimeoutError::Timeout) => {
+                        Self::enter_idle(&thread_state, &device, &events)
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+
+        Self { state, wake }
+    }
+
+    fn enter_idle(state: &Mutex<IdleState>, device: &SharedDevice, events: &EventPublisher) {
+        let mut state = lock(state);
+        let Some(config) = state.config else {
+            return;
+        };
+        if state.idle || state.last_activity.elapsed() < config.timeout {
+            return;
+        }
+        state.idle = true;
+        match config.action {
+            IdleAction::Notify => {}
+            IdleAction::MinimalReporting => {
+                state.restore_mode = device.reporting_mode();
+                _ = device.write(&OutputReport::DataReportingMode(DataReporingMode {
+                    continuous: false,
+                    mode: 0x30,
+                }));
+            }
+            IdleAction::Disconnect => device.disconnect(),
+        }
+        drop(state);
+        events.publish(&WiimoteEvent::Idle);
+    }
+
+    /// Replaces the configuration, `None` disables the watchdog.
+    /// Restores the data reporting mode if the Wii remote is currently idle.
+    pub(crate) fn configure(&self, config: Option<IdleConfig>, device: &SharedDevice) {
+        let mut state = lock(&self.state);
+        state.config = config;
+        state.detector = ActivityDetector::default();
+        state.last_activity = Instant::now();
+        state.idle = false;
+        let restore_mode = state.restore_mode.take();
+        drop(state);
+
+        if let Some(mode) = restore_mode {
+            _ = device.write(&OutputReport::DataReportingMode(mode));
+        }
+        _ = self.wake.send(());
+    }
+
+    pub(crate) fn is_idle(&self) -> bool {
+        lock(&self.state).idle
+    }
+
+    /// Checks the data report for activity and wakes the Wii remote up if it is idle.
+    pub(crate) fn observe(
+        &self,
+        wiimote_data: &WiimoteData,
+        device: &SharedDevice,
+        events: &EventPublisher,
+    ) {
+        let mut state = lock(&self.state);
+        let Some(config) = state.config else {
+            return;
+        };
+        if !state.detector.update(wiimote_data, config.motion_threshold) {
+            return;
+        }
+        state.last_activity = Instant::now();
+        if !std::mem::replace(&mut state.idle, false) {
+            return;
+        }
+        let restore_mode = state.restore_mode.take();
+        drop(state);
+
+        if let Some(mode) = restore_mode {
+            _ = device.write(&OutputReport::DataReportingMode(mode));
+        }
+        events.publish(&WiimoteEvent::Active);
+        _ = self.wake.send(());
+    }
+}
+
+fn lock(state: &Mutex<IdleState>) -> MutexGuard<'_, IdleState> {
+    match state.lock() {
+        Ok(state) => state,
+        Err(err) => err.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputReport;
+
+    fn report(report_id: u8, data: [u8; 21]) -> WiimoteData {
+        let mut buffer = [0u8; 22];
+        buffer[0] = report_id;
+        buffer[1..].copy_from_slice(&data);
+        match InputReport::try_from(buffer.as_slice()) {
+            Ok(InputReport::DataReport(_, wiimote_data)) => wiimote_data,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_button_activity() {
+        let mut detector = ActivityDetector::default();
+        let buttons = |bytes: [u8; 2]| {
+            let mut data = [0u8; 21];
+            data[..2].copy_from_slice(&bytes);
+            report(0x30, data)
+        };
+
+        assert!(!detector.update(&buttons([0, 0]), 8));
+        // Accelerometer bits in the button bytes are ignored
+        assert!(!detector.update(&buttons([0b0110_0000, 0]), 8));
+        assert!(detector.update(&buttons([0, 0b0000_1000]), 8));
+    }
+
+    #[test]
+    fn test_motion_activity() {
+        let mut detector = ActivityDetector::default();
+        let acceleration = |x: u8| {
+            let mut data = [0u8; 21];
+            data[2..5].copy_from_slice(&[x, 0x80, 0x98]);
+            report(0x31, data)
+        };
+
+        assert!(!detector.update(&acceleration(0x80), 8));
+        assert!(!detector.update(&acceleration(0x86), 8));
+        assert!(!detector.update(&acceleration(0x7A), 8));
+        assert!(detector.update(&acceleration(0x90), 8));
+        // The reference acceleration moved with the activity
+        assert!(!detector.update(&acceleration(0x8A), 8));
+    }
+}
@@ -1,12 +1,14 @@
+use std::fmt;
+
 use crate::prelude::*;
 use bitflags::bitflags;
 
-const STATUS_ID: u8 = 0x20;
-const READ_MEMORY_ID: u8 = 0x21;
-const ACKNOWLEDGE_ID: u8 = 0x22;
+const STATUS_ID: u8 = ReportId::Status.to_u8();
+const READ_MEMORY_ID: u8 = ReportId::ReadMemoryData.to_u8();
+const ACKNOWLEDGE_ID: u8 = ReportId::Acknowledge.to_u8();
 
 bitflags! {
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct StatusFlags: u8 {
         const BATTERY_LOW = 0b0000_0001;
         const EXTENSION_CONTROLLER_CONNECTED = 0b0000_0010;
@@ -20,7 +22,7 @@ bitflags! {
 }
 
 bitflags! {
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct ButtonData: u16 {
         const LEFT = 1 << 0;
         const RIGHT = 1 << 1;
@@ -38,8 +40,94 @@ bitflags! {
     }
 }
 
+/// A button of the Wii remote, see [`ButtonData::pressed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Left,
+    Right,
+    Down,
+    Up,
+    Plus,
+    Two,
+    One,
+    B,
+    A,
+    Minus,
+    Home,
+}
+
+impl Button {
+    /// All buttons in the order of their bits in the button data.
+    pub const ALL: [Self; 11] = [
+        Self::Left,
+        Self::Right,
+        Self::Down,
+        Self::Up,
+        Self::Plus,
+        Self::Two,
+        Self::One,
+        Self::B,
+        Self::A,
+        Self::Minus,
+        Self::Home,
+    ];
+
+    /// Returns the flag of the button in the button data.
+    #[must_use]
+    pub const fn flag(self) -> ButtonData {
+        match self {
+            Self::Left => ButtonData::LEFT,
+            Self::Right => ButtonData::RIGHT,
+            Self::Down => ButtonData::DOWN,
+            Self::Up => ButtonData::UP,
+            Self::Plus => ButtonData::PLUS,
+            Self::Two => ButtonData::TWO,
+            Self::One => ButtonData::ONE,
+            Self::B => ButtonData::B,
+            Self::A => ButtonData::A,
+            Self::Minus => ButtonData::MINUS,
+            Self::Home => ButtonData::HOME,
+        }
+    }
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Left => "Left",
+            Self::Right => "Right",
+            Self::Down => "Down",
+            Self::Up => "Up",
+            Self::Plus => "+",
+            Self::Two => "2",
+            Self::One => "1",
+            Self::B => "B",
+            Self::A => "A",
+            Self::Minus => "-",
+            Self::Home => "Home",
+        };
+        f.write_str(name)
+    }
+}
+
+impl ButtonData {
+    /// Returns the pressed buttons.
+    /// The unused bits are ignored, they carry accelerometer data and some clones set them at random.
+    pub fn pressed(self) -> impl Iterator<Item = Button> {
+        Button::ALL
+            .into_iter()
+            .filter(move |button| self.is_pressed(*button))
+    }
+
+    /// Returns whether the button is pressed.
+    #[must_use]
+    pub const fn is_pressed(self, button: Button) -> bool {
+        self.contains(button.flag())
+    }
+}
+
 #[repr(C, packed)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct StatusData {
     buttons: ButtonData,
     flags: StatusFlags,
@@ -68,7 +156,7 @@ impl StatusData {
 }
 
 #[repr(C, packed)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct MemoryData {
     buttons: ButtonData,
     size_error_flags: u8,
@@ -100,6 +188,18 @@ impl MemoryData {
         self.size_error_flags & 0x0F
     }
 
+    /// Returns the error flag as `MemoryError`, `None` if the read succeeded.
+    #[must_use]
+    pub const fn error(&self) -> Option<MemoryError> {
+        MemoryError::from_code(self.error_flag())
+    }
+
+    /// Returns true if the read succeeded.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.error_flag() == 0
+    }
+
     /// Returns the 2 least significant bytes of the address of the first byte.
     #[must_use]
     pub const fn address_offset(&self) -> u16 {
@@ -108,7 +208,7 @@ impl MemoryData {
 }
 
 #[repr(C, packed)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AcknowledgeData {
     buttons: ButtonData,
     report_number: u8,
@@ -133,15 +233,57 @@ impl AcknowledgeData {
     pub const fn error_code(&self) -> u8 {
         self.error_code
     }
+
+    /// Returns the error code as `MemoryError`, `None` if the output report succeeded.
+    /// Acknowledgements of memory writes use the codes of memory reads, e.g. 7 if no extension
+    /// is connected and 8 for an invalid address.
+    #[must_use]
+    pub const fn error(&self) -> Option<MemoryError> {
+        MemoryError::from_code(self.error_code)
+    }
+
+    /// Returns true if the output report was executed without error.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.error_code == 0
+    }
 }
 
 #[repr(C, packed)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct WiimoteData {
+    report_id: u8,
     pub data: [u8; 21],
 }
 
 impl WiimoteData {
+    /// Returns the ID of the data report, which determines the data reporting mode.
+    #[must_use]
+    pub const fn report_id(&self) -> u8 {
+        self.report_id
+    }
+
+    /// Returns the extension bytes of the data report,
+    /// or `None` if the data reporting mode does not include extension data.
+    ///
+    /// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Data_Reporting>
+    #[must_use]
+    pub fn extension_data(&self) -> Option<&[u8]> {
+        let (offset, length) = extension_data_range(self.report_id)?;
+        Some(&self.data[offset..offset + length])
+    }
+
+    /// Returns the IR camera bytes of the data report,
+    /// or `None` if the data reporting mode does not include IR data.
+    /// The reports 0x3E and 0x3F each contain half of the 36 bytes of the full IR mode.
+    ///
+    /// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Data_Reporting>
+    #[must_use]
+    pub fn ir_data(&self) -> Option<&[u8]> {
+        let (offset, length) = ir_data_range(self.report_id)?;
+        Some(&self.data[offset..offset + length])
+    }
+
     /// Returns the core button data.
     ///
     /// This is invalid for report type 0x3d that only contains extension data.
@@ -150,10 +292,44 @@ impl WiimoteData {
         let bits = u16::from_le_bytes([self.data[0], self.data[1]]);
         ButtonData::from_bits_retain(bits)
     }
+
+    /// Returns the accelerometer data of the data report,
+    /// or `None` if the data reporting mode does not include it or splits it across two reports (0x3E/0x3F).
+    #[must_use]
+    pub const fn accelerometer(&self) -> Option<AccelerometerData> {
+        match self.report_id {
+            0x31 | 0x33 | 0x35 | 0x37 => Some(AccelerometerData::from_normal_reporting(&self.data)),
+            _ => None,
+        }
+    }
+}
+
+/// The offset and length of the extension bytes in the data of the report.
+const fn extension_data_range(report_id: u8) -> Option<(usize, usize)> {
+    match report_id {
+        0x32 => Some((2, 8)),
+        0x34 => Some((2, 19)),
+        0x35 => Some((5, 16)),
+        0x36 => Some((12, 9)),
+        0x37 => Some((15, 6)),
+        0x3D => Some((0, 21)),
+        _ => None,
+    }
+}
+
+/// The offset and length of the IR camera bytes in the data of the report.
+const fn ir_data_range(report_id: u8) -> Option<(usize, usize)> {
+    match report_id {
+        0x33 => Some((5, 12)),
+        0x36 => Some((2, 10)),
+        0x37 => Some((5, 10)),
+        0x3E | 0x3F => Some((3, 18)),
+        _ => None,
+    }
 }
 
 /// An input report represents the data sent from the Wii remote to the computer.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InputReport {
     /// Status information report (ID 0x20).
     ///
@@ -182,22 +358,196 @@ pub enum InputReport {
     ///
     /// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Data_Reporting>
     DataReport(u8, WiimoteData),
+    /// A report with an unknown ID or too short for its ID, only returned by
+    /// [`InputReport::parse_lenient`]. Some clone Wii remotes send such reports.
+    ///
+    /// Contains the report ID and the bytes following it, truncated to 21 bytes and padded with zeros.
+    Unknown(u8, [u8; 21]),
+}
+
+/// A concise human-readable summary of an input report, see [`InputReport::describe`].
+///
+/// Data reports are summarized as `0x35 | A+B | acc (0.02,-0.98,0.10) g | ext 16B`,
+/// without calibration the raw accelerometer values are shown.
+#[derive(Debug, Clone, Copy)]
+pub struct ReportSummary<'a> {
+    report: &'a InputReport,
+    calibration: Option<&'a AccelerometerCalibration>,
+}
+
+impl fmt::Display for ReportSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.report {
+            InputReport::StatusInformation(data) => {
+                write!(f, "0x{STATUS_ID:02X} | ")?;
+                write_buttons(f, data.buttons())?;
+                write!(f, " | battery {}", data.battery_level())?;
+                let flags = data.flags();
+                if flags.contains(StatusFlags::BATTERY_LOW) {
+                    f.write_str(" low")?;
+                }
+                if flags.contains(StatusFlags::EXTENSION_CONTROLLER_CONNECTED) {
+                    f.write_str(" | ext")?;
+                }
+                if flags.contains(StatusFlags::SPEAKER_ENABLED) {
+                    f.write_str(" | speaker")?;
+                }
+                if flags.contains(StatusFlags::IR_CAMERA_ENABLED) {
+                    f.write_str(" | ir")?;
+                }
+                let leds = [
+                    StatusFlags::LED_1,
+                    StatusFlags::LED_2,
+                    StatusFlags::LED_3,
+                    StatusFlags::LED_4,
+                ];
+                f.write_str(" | leds ")?;
+                let mut separator = "";
+                for (index, _) in leds
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, led)| flags.contains(**led))
+                {
+                    write!(f, "{separator}{}", index + 1)?;
+                    separator = "+";
+                }
+                if separator.is_empty() {
+                    f.write_str("off")?;
+                }
+                Ok(())
+            }
+            InputReport::ReadMemory(data) => {
+                write!(f, "0x{READ_MEMORY_ID:02X} | ")?;
+                write_buttons(f, data.buttons())?;
+                match data.error() {
+                    Some(error) => write!(f, " | read 0x{:04X} {error:?}", data.address_offset()),
+                    None => write!(
+                        f,
+                        " | read 0x{:04X} {}B",
+                        data.address_offset(),
+                        data.size()
+                    ),
+                }
+            }
+            InputReport::Acknowledge(data) => {
+                write!(f, "0x{ACKNOWLEDGE_ID:02X} | ")?;
+                write_buttons(f, data.buttons())?;
+                write!(f, " | ack 0x{:02X}", data.report_number())?;
+                match data.error() {
+                    Some(error) => write!(f, " {error:?}"),
+                    None => f.write_str(" ok"),
+                }
+            }
+            InputReport::DataReport(report_id, data) => {
+                write!(f, "0x{report_id:02X}")?;
+                // Report 0x3D only contains extension data.
+                if *report_id != 0x3D {
+                    f.write_str(" | ")?;
+                    write_buttons(f, data.buttons())?;
+                }
+                if let Some(accelerometer) = data.accelerometer() {
+                    match self.calibration {
+                        Some(calibration) => {
+                            let (x, y, z) = calibration.get_acceleration(&accelerometer);
+                            write!(f, " | acc ({x:.2},{y:.2},{z:.2}) g")?;
+                        }
+                        None => {
+                            let (x, y, z) = accelerometer.raw();
+                            write!(f, " | acc ({x},{y},{z})")?;
+                        }
+                    }
+                }
+                if let Some(ir_data) = data.ir_data() {
+                    write!(f, " | ir {}B", ir_data.len())?;
+                }
+                if let Some(extension_data) = data.extension_data() {
+                    write!(f, " | ext {}B", extension_data.len())?;
+                }
+                Ok(())
+            }
+            InputReport::Unknown(report_id, _) => write!(f, "0x{report_id:02X} | unknown"),
+        }
+    }
+}
+
+/// Writes the pressed buttons joined with `+`, or `-` if no button is pressed.
+fn write_buttons(f: &mut fmt::Formatter<'_>, buttons: ButtonData) -> fmt::Result {
+    let mut separator = "";
+    for button in buttons.pressed() {
+        write!(f, "{separator}{button}")?;
+        separator = "+";
+    }
+    if separator.is_empty() {
+        f.write_str("-")?;
+    }
+    Ok(())
+}
+
+impl InputReport {
+    /// Returns a concise human-readable summary of the report for logging,
+    /// the acceleration is shown in g if the `calibration` of the Wii remote is given.
+    ///
+    /// ```
+    /// use wiimote_rs::input::InputReport;
+    ///
+    /// let report = InputReport::try_from(&[0x30u8, 0x00, 0x0C][..]).unwrap();
+    /// assert_eq!(report.describe(None).to_string(), "0x30 | B+A");
+    /// ```
+    #[must_use]
+    pub const fn describe<'a>(
+        &'a self,
+        calibration: Option<&'a AccelerometerCalibration>,
+    ) -> ReportSummary<'a> {
+        ReportSummary {
+            report: self,
+            calibration,
+        }
+    }
+}
+
+impl fmt::Display for InputReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.describe(None).fmt(f)
+    }
 }
 
 macro_rules! transmute_data {
     ($value:expr, $type:ident) => {{
         const DATA_SIZE: usize = std::mem::size_of::<$type>();
-        if $value.len() < DATA_SIZE {
+        if $value.len() <= DATA_SIZE {
             return Err(WiimoteDeviceError::InvalidData.into());
         }
-        let mut slice = [0u8; DATA_SIZE];
-        slice.copy_from_slice(&$value[1..=DATA_SIZE]);
-
-        unsafe { std::mem::transmute::<[u8; DATA_SIZE], $type>(slice) }
+        // The packed report structs are valid for any bytes and read directly from the report.
+        unsafe { std::ptr::read_unaligned($value[1..].as_ptr().cast::<$type>()) }
     }};
 }
 
 impl InputReport {
+    /// Parses a report like [`InputReport::try_from`], but returns reports with an unknown ID
+    /// or too short for their ID as [`InputReport::Unknown`] instead of an error.
+    ///
+    /// ```
+    /// use wiimote_rs::input::InputReport;
+    ///
+    /// let report = InputReport::parse_lenient(&[0x5A, 0x01, 0x02]).unwrap();
+    /// assert!(matches!(report, InputReport::Unknown(0x5A, [0x01, 0x02, 0x00, ..])));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the report is empty.
+    pub fn parse_lenient(value: &[u8]) -> WiimoteResult<Self> {
+        match Self::try_from(value) {
+            Err(WiimoteError::WiimoteDeviceError(WiimoteDeviceError::InvalidData)) => {
+                let mut data = [0u8; 21];
+                let bytes_to_copy = usize::min(value.len() - 1, data.len());
+                data[..bytes_to_copy].copy_from_slice(&value[1..=bytes_to_copy]);
+                Ok(Self::Unknown(value[0], data))
+            }
+            result => result,
+        }
+    }
+
     fn from_status_information(value: &[u8]) -> WiimoteResult<Self> {
         let data = transmute_data!(value, StatusData);
         Ok(Self::StatusInformation(data))
@@ -219,14 +569,21 @@ impl InputReport {
         let bytes_to_copy = usize::min(value.len() - 1, DATA_SIZE);
         data[..bytes_to_copy].copy_from_slice(&value[1..=bytes_to_copy]);
 
-        Self::DataReport(value[0], WiimoteData { data })
+        Self::DataReport(
+            value[0],
+            WiimoteData {
+                report_id: value[0],
+                data,
+            },
+        )
     }
 }
 
-impl TryFrom<&[u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE]> for InputReport {
+/// Parses a report from a buffer of any size, e.g. sized by the input report length of the HID device.
+impl<const N: usize> TryFrom<&[u8; N]> for InputReport {
     type Error = WiimoteError;
 
-    fn try_from(value: &[u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE]) -> Result<Self, Self::Error> {
+    fn try_from(value: &[u8; N]) -> Result<Self, Self::Error> {
         let slice_without_length: &[u8] = value.as_slice();
         Self::try_from(slice_without_length)
     }
@@ -249,6 +606,217 @@ impl TryFrom<&[u8]> for InputReport {
     }
 }
 
+/// A borrowed view of an input report that reads its fields directly from the received bytes.
+///
+/// Parsing validates the length like [`InputReport`] but does not copy the report,
+/// convert it with [`InputReport::from`] to keep it beyond the lifetime of the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputReportRef<'a> {
+    /// Status information report (ID 0x20), see [`InputReport::StatusInformation`].
+    StatusInformation(StatusDataRef<'a>),
+    /// Read memory data report (ID 0x21), see [`InputReport::ReadMemory`].
+    ReadMemory(MemoryDataRef<'a>),
+    /// Acknowledge report (ID 0x22), see [`InputReport::Acknowledge`].
+    Acknowledge(AcknowledgeDataRef<'a>),
+    /// Data report (IDs 0x30-0x3F), see [`InputReport::DataReport`].
+    DataReport(WiimoteDataRef<'a>),
+}
+
+impl<'a> TryFrom<&'a [u8]> for InputReportRef<'a> {
+    type Error = WiimoteError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(WiimoteDeviceError::MissingData.into());
+        }
+        let min_length = match value[0] {
+            STATUS_ID => std::mem::size_of::<StatusData>(),
+            READ_MEMORY_ID => std::mem::size_of::<MemoryData>(),
+            ACKNOWLEDGE_ID => std::mem::size_of::<AcknowledgeData>(),
+            0x30..=0x3F => return Ok(Self::DataReport(WiimoteDataRef(value))),
+            _ => return Err(WiimoteDeviceError::InvalidData.into()),
+        };
+        if value.len() <= min_length {
+            return Err(WiimoteDeviceError::InvalidData.into());
+        }
+        Ok(match value[0] {
+            STATUS_ID => Self::StatusInformation(StatusDataRef(value)),
+            READ_MEMORY_ID => Self::ReadMemory(MemoryDataRef(value)),
+            _ => Self::Acknowledge(AcknowledgeDataRef(value)),
+        })
+    }
+}
+
+impl From<InputReportRef<'_>> for InputReport {
+    fn from(report: InputReportRef<'_>) -> Self {
+        let bytes = match report {
+            InputReportRef::StatusInformation(StatusDataRef(bytes))
+            | InputReportRef::ReadMemory(MemoryDataRef(bytes))
+            | InputReportRef::Acknowledge(AcknowledgeDataRef(bytes))
+            | InputReportRef::DataReport(WiimoteDataRef(bytes)) => bytes,
+        };
+        // The length was validated when parsing the view.
+        Self::try_from(bytes).expect("Invalid input report view")
+    }
+}
+
+/// Reads the core buttons following the report ID, missing bytes are read as 0.
+fn buttons_from_report(report: &[u8]) -> ButtonData {
+    let byte = |index: usize| report.get(index).copied().unwrap_or_default();
+    ButtonData::from_bits_retain(u16::from_le_bytes([byte(1), byte(2)]))
+}
+
+/// A borrowed status information report, see [`StatusData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatusDataRef<'a>(&'a [u8]);
+
+impl StatusDataRef<'_> {
+    /// Returns the core button data.
+    #[must_use]
+    pub fn buttons(&self) -> ButtonData {
+        buttons_from_report(self.0)
+    }
+
+    /// Returns the status flags.
+    #[must_use]
+    pub const fn flags(&self) -> StatusFlags {
+        StatusFlags::from_bits_retain(self.0[3])
+    }
+
+    /// Returns the battery level.
+    #[must_use]
+    pub const fn battery_level(&self) -> u8 {
+        self.0[6]
+    }
+}
+
+/// A borrowed read memory data report, see [`MemoryData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemoryDataRef<'a>(&'a [u8]);
+
+impl<'a> MemoryDataRef<'a> {
+    /// Returns the core button data.
+    #[must_use]
+    pub fn buttons(&self) -> ButtonData {
+        buttons_from_report(self.0)
+    }
+
+    /// Returns the size of the data in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u8 {
+        (self.0[3] >> 4) + 1
+    }
+
+    /// Returns the error flag, see [`MemoryData::error_flag`].
+    #[must_use]
+    pub const fn error_flag(&self) -> u8 {
+        self.0[3] & 0x0F
+    }
+
+    /// Returns the error flag as `MemoryError`, `None` if the read succeeded.
+    #[must_use]
+    pub const fn error(&self) -> Option<MemoryError> {
+        MemoryError::from_code(self.error_flag())
+    }
+
+    /// Returns true if the read succeeded.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.error_flag() == 0
+    }
+
+    /// Returns the 2 least significant bytes of the address of the first byte.
+    #[must_use]
+    pub const fn address_offset(&self) -> u16 {
+        u16::from_be_bytes([self.0[4], self.0[5]])
+    }
+
+    /// Returns the 16 data bytes, of which the first [`Self::size`] bytes are valid.
+    #[must_use]
+    pub fn data(&self) -> &'a [u8] {
+        &self.0[6..22]
+    }
+}
+
+/// A borrowed acknowledge report, see [`AcknowledgeData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AcknowledgeDataRef<'a>(&'a [u8]);
+
+impl AcknowledgeDataRef<'_> {
+    /// Returns the core button data.
+    #[must_use]
+    pub fn buttons(&self) -> ButtonData {
+        buttons_from_report(self.0)
+    }
+
+    /// Returns the report number.
+    #[must_use]
+    pub const fn report_number(&self) -> u8 {
+        self.0[3]
+    }
+
+    /// Returns the error code.
+    #[must_use]
+    pub const fn error_code(&self) -> u8 {
+        self.0[4]
+    }
+
+    /// Returns the error code as `MemoryError`, see [`AcknowledgeData::error`].
+    #[must_use]
+    pub const fn error(&self) -> Option<MemoryError> {
+        MemoryError::from_code(self.error_code())
+    }
+
+    /// Returns true if the output report was executed without error.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.error_code() == 0
+    }
+}
+
+/// A borrowed data report, see [`WiimoteData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WiimoteDataRef<'a>(&'a [u8]);
+
+impl<'a> WiimoteDataRef<'a> {
+    /// Returns the ID of the data report, which determines the data reporting mode.
+    #[must_use]
+    pub const fn report_id(&self) -> u8 {
+        self.0[0]
+    }
+
+    /// Returns the received bytes following the report ID, at most 21 bytes.
+    #[must_use]
+    pub fn data(&self) -> &'a [u8] {
+        let end = usize::min(self.0.len(), 22);
+        &self.0[1..end]
+    }
+
+    /// Returns the extension bytes of the data report, or `None` if the data reporting mode
+    /// does not include extension data or the report is truncated.
+    #[must_use]
+    pub fn extension_data(&self) -> Option<&'a [u8]> {
+        let (offset, length) = extension_data_range(self.report_id())?;
+        self.data().get(offset..offset + length)
+    }
+
+    /// Returns the IR camera bytes of the data report, or `None` if the data reporting mode
+    /// does not include IR data or the report is truncated.
+    #[must_use]
+    pub fn ir_data(&self) -> Option<&'a [u8]> {
+        let (offset, length) = ir_data_range(self.report_id())?;
+        self.data().get(offset..offset + length)
+    }
+
+    /// Returns the core button data.
+    ///
+    /// This is invalid for report type 0x3d that only contains extension data.
+    #[must_use]
+    pub fn buttons(&self) -> ButtonData {
+        buttons_from_report(self.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -285,6 +853,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pressed_buttons() {
+        // The unused bits are set in addition to A, Home and D-Pad left
+        let buttons = ButtonData::from_bits_retain(0b1110_1000_0110_0001);
+        assert_eq!(
+            buttons.pressed().collect::<Vec<_>>(),
+            [Button::Left, Button::A, Button::Home]
+        );
+        assert!(buttons.is_pressed(Button::A));
+        assert!(!buttons.is_pressed(Button::B));
+        assert_eq!(ButtonData::empty().pressed().count(), 0);
+    }
+
+    #[test]
+    fn test_report_equality() {
+        let mut data = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        data[0] = 0x30;
+        data[2] = 0b0000_1000; // A
+        let report = InputReport::try_from(&data).unwrap();
+
+        assert_eq!(report, InputReport::try_from(&data).unwrap());
+        data[2] = 0;
+        assert_ne!(report, InputReport::try_from(&data).unwrap());
+    }
+
+    #[test]
+    fn test_describe_report() {
+        let mut data = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        data[0] = 0x35;
+        data[2] = 0b0000_1100; // A and B
+        data[3..6].copy_from_slice(&[0x80, 0x80, 0x9A]); // Accelerometer
+        let report = InputReport::try_from(&data).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "0x35 | B+A | acc (512,512,616) | ext 16B"
+        );
+
+        data[0] = 0x20;
+        data[3] = 0b0011_0010; // Extension connected, LED 1 and 2
+        data[6] = 0xC0; // Battery level
+        let report = InputReport::try_from(&data).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "0x20 | B+A | battery 192 | ext | leds 1+2"
+        );
+    }
+
     #[test]
     fn test_read_memory_report() {
         let mut data = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
@@ -303,6 +918,9 @@ mod tests {
             assert_eq!(data.buttons().bits(), ButtonData::HOME.bits());
             assert_eq!(data.size(), 16);
             assert_eq!(data.error_flag(), 7);
+            assert_eq!(data.error(), Some(MemoryError::WriteOnly));
+            assert_eq!(data.error().map(MemoryError::code), Some(7));
+            assert!(!data.is_success());
             assert_eq!(data.address_offset(), 0x12AB);
             assert_eq!(data.data, *b"1234567890123456");
         }
@@ -325,9 +943,23 @@ mod tests {
             assert_eq!(data.buttons().bits(), 0);
             assert_eq!(data.report_number(), 0x12);
             assert_eq!(data.error_code(), 0xAB);
+            assert_eq!(data.error(), Some(MemoryError::Unknown(0xAB)));
+            assert!(!data.is_success());
         }
     }
 
+    #[test]
+    fn test_acknowledge_error_codes() {
+        let error = |code: u8| match InputReport::try_from([0x22, 0, 0, 0x16, code].as_slice()) {
+            Ok(InputReport::Acknowledge(data)) => data.error(),
+            _ => unreachable!(),
+        };
+        assert_eq!(error(0), None);
+        assert_eq!(error(7), Some(MemoryError::WriteOnly));
+        assert_eq!(error(8), Some(MemoryError::NonexistentAddress));
+        assert_eq!(error(4), Some(MemoryError::Unknown(4)));
+    }
+
     #[test]
     fn test_buttons_mode_0x30() {
         let data: &[u8] = &[
@@ -344,6 +976,138 @@ mod tests {
                 data.buttons().bits(),
                 ButtonData::LEFT.union(ButtonData::ONE).bits()
             );
+            assert!(data.extension_data().is_none());
+        }
+    }
+
+    #[test]
+    fn test_extension_data_mode_0x35() {
+        let mut data = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        data[0] = 0x35;
+        data[6..22].copy_from_slice(b"1234567890123456"); // Extension bytes after accelerometer
+
+        let report = InputReport::try_from(&data).unwrap();
+
+        assert!(matches!(report, InputReport::DataReport(0x35, _)));
+        if let InputReport::DataReport(_, data) = report {
+            assert_eq!(data.report_id(), 0x35);
+            assert_eq!(data.extension_data(), Some(b"1234567890123456".as_slice()));
+        }
+    }
+
+    #[test]
+    fn test_truncated_status_report() {
+        // Report ID and 5 of the 6 data bytes
+        let data = [0x20, 0x00, 0x00, 0x02, 0x00, 0x00];
+
+        let result = InputReport::try_from(data.as_slice());
+
+        assert!(matches!(
+            result,
+            Err(WiimoteError::WiimoteDeviceError(
+                WiimoteDeviceError::InvalidData
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_lenient_parsing() {
+        let unknown_id = [0x5A, 0x01, 0x02];
+        assert!(InputReport::try_from(unknown_id.as_slice()).is_err());
+        let report = InputReport::parse_lenient(&unknown_id).unwrap();
+        let mut data = [0u8; 21];
+        data[..2].copy_from_slice(&[0x01, 0x02]);
+        assert_eq!(report, InputReport::Unknown(0x5A, data));
+        assert_eq!(report.to_string(), "0x5A | unknown");
+
+        let truncated_status = [0x20, 0x00, 0x00, 0x02, 0x00, 0x00];
+        assert!(matches!(
+            InputReport::parse_lenient(&truncated_status),
+            Ok(InputReport::Unknown(0x20, _))
+        ));
+
+        // Junk in unused bytes of known reports does not change the result.
+        let data_report = [0x30, 0x00, 0x08, 0xFF, 0xFF, 0xFF];
+        assert_eq!(
+            InputReport::parse_lenient(&data_report).unwrap(),
+            InputReport::try_from(data_report.as_slice()).unwrap()
+        );
+        assert!(InputReport::parse_lenient(&[]).is_err());
+    }
+
+    #[test]
+    fn test_report_from_sized_buffers() {
+        let mut exact = [0u8; 22];
+        exact[0] = 0x30;
+        exact[1] = 0b0000_0001; // D-Pad left
+        let mut oversized = [0u8; 64];
+        oversized[..22].copy_from_slice(&exact);
+
+        for report in [
+            InputReport::try_from(&exact).unwrap(),
+            InputReport::try_from(&oversized).unwrap(),
+        ] {
+            let InputReport::DataReport(_, data) = report else {
+                panic!("Expected a data report");
+            };
+            assert_eq!(data.buttons(), ButtonData::LEFT);
         }
     }
+
+    #[test]
+    fn test_report_ref_matches_owned_report() {
+        let mut data = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        data[0] = 0x21;
+        data[2] = 0b1000_0000; // Home
+        data[3] = 0xF7; // Size and error flags
+        data[4] = 0x12; // Address
+        data[5] = 0xAB; // Address
+        data[6..22].copy_from_slice(b"1234567890123456");
+
+        let Ok(InputReportRef::ReadMemory(memory)) = InputReportRef::try_from(&data[..]) else {
+            panic!("Expected a read memory report");
+        };
+        assert_eq!(memory.buttons(), ButtonData::HOME);
+        assert_eq!(memory.size(), 16);
+        assert_eq!(memory.error(), Some(MemoryError::WriteOnly));
+        assert_eq!(memory.address_offset(), 0x12AB);
+        assert_eq!(memory.data(), b"1234567890123456");
+        assert!(std::ptr::eq(memory.data(), &data[6..22]));
+
+        let report = InputReport::from(InputReportRef::ReadMemory(memory));
+        assert!(
+            matches!(report, InputReport::ReadMemory(owned) if owned.data == *b"1234567890123456")
+        );
+    }
+
+    #[test]
+    fn test_data_report_ref() {
+        let mut data = [0u8; 22];
+        data[0] = 0x35;
+        data[1] = 0b0000_0001; // D-Pad left
+        data[6..22].copy_from_slice(b"1234567890123456");
+
+        let Ok(InputReportRef::DataReport(report)) = InputReportRef::try_from(&data[..]) else {
+            panic!("Expected a data report");
+        };
+        assert_eq!(report.report_id(), 0x35);
+        assert_eq!(report.buttons(), ButtonData::LEFT);
+        assert_eq!(report.extension_data(), Some(&b"1234567890123456"[..]));
+        assert!(report.ir_data().is_none());
+
+        // A truncated report has no extension data instead of zero padding.
+        let Ok(InputReportRef::DataReport(truncated)) = InputReportRef::try_from(&data[..10])
+        else {
+            panic!("Expected a data report");
+        };
+        assert!(truncated.extension_data().is_none());
+    }
+
+    #[test]
+    fn test_truncated_report_ref() {
+        let data: &[u8] = &[0x20, 0x00, 0x00, 0x00];
+
+        assert!(InputReportRef::try_from(data).is_err());
+        assert!(InputReportRef::try_from(&[][..]).is_err());
+    }
 }
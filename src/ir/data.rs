@@ -0,0 +1,214 @@
+use crate::input::WiimoteData;
+
+use super::IrMode;
+
+/// The bounding box of an IR object in the full mode, with 7 bit precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IrBoundingBox {
+    pub x_min: u8,
+    pub y_min: u8,
+    pub x_max: u8,
+    pub y_max: u8,
+}
+
+/// An IR source seen by the camera.
+/// The position ranges from 0 to 1023 horizontally and 0 to 767 vertically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IrObject {
+    pub x: u16,
+    pub y: u16,
+    /// The rough size of the object from 0 to 15, `None` in basic mode.
+    pub size: Option<u8>,
+    /// Only available in full mode.
+    pub bounding_box: Option<IrBoundingBox>,
+    /// Only available in full mode.
+    pub intensity: Option<u8>,
+}
+
+/// The up to four objects tracked by the IR camera.
+/// Objects that are not seen are `None`, the slots keep the order reported by the camera.
+///
+/// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Data_Formats>
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IrData {
+    pub objects: [Option<IrObject>; 4],
+}
+
+impl IrData {
+    /// Decodes the IR bytes of a data report in the given mode.
+    /// Returns `None` if there are not enough bytes for the mode.
+    #[must_use]
+    pub fn decode(mode: IrMode, data: &[u8]) -> Option<Self> {
+        match mode {
+            IrMode::Basic => data.get(..10)?.try_into().ok().map(Self::from_basic),
+            IrMode::Extended => data.get(..12)?.try_into().ok().map(Self::from_extended),
+            IrMode::Full => data.get(..36)?.try_into().ok().map(Self::from_full),
+        }
+    }
+
+    /// Decodes the full mode data split across the interleaved reports 0x3E and 0x3F.
+    #[must_use]
+    #[allow(clippy::similar_names)]
+    pub fn from_interleaved(data_3e: &WiimoteData, data_3f: &WiimoteData) -> Option<Self> {
+        if data_3e.report_id() != 0x3E || data_3f.report_id() != 0x3F {
+            return None;
+        }
+
+        let mut data = [0u8; 36];
+        data[..18].copy_from_slice(data_3e.ir_data()?);
+        data[18..].copy_from_slice(data_3f.ir_data()?);
+        Some(Self::from_full(&data))
+    }
+
+    /// Decodes the 10 bytes of the basic mode, two groups of 5 bytes containing two objects each.
+    #[must_use]
+    pub fn from_basic(data: &[u8; 10]) -> Self {
+        // https://www.wiibrew.org/wiki/Wiimote#Basic_Mode
+        let mut objects = [None; 4];
+        for (pair, chunk) in data.chunks_exact(5).enumerate() {
+            let high_bits = chunk[2];
+            objects[pair * 2] = Self::decode_position(chunk[0], chunk[1], high_bits >> 4)
+                .map(|(x, y)| IrObject::new(x, y));
+            objects[pair * 2 + 1] = Self::decode_position(chunk[3], chunk[4], high_bits)
+                .map(|(x, y)| IrObject::new(x, y));
+        }
+        Self { objects }
+    }
+
+    /// Decodes the 12 bytes of the extended mode, 3 bytes per object.
+    #[must_use]
+    pub fn from_extended(data: &[u8; 12]) -> Self {
+        // https://www.wiibrew.org/wiki/Wiimote#Extended_Mode
+        let mut objects = [None; 4];
+        for (object, chunk) in objects.iter_mut().zip(data.chunks_exact(3)) {
+            *object = Self::decode_extended_object(chunk);
+        }
+        Self { objects }
+    }
+
+    /// Decodes the 36 bytes of the full mode, 9 bytes per object.
+    #[must_use]
+    pub fn from_full(data: &[u8; 36]) -> Self {
+        // https://www.wiibrew.org/wiki/Wiimote#Full_Mode
+        let mut objects = [None; 4];
+        for (object, chunk) in objects.iter_mut().zip(data.chunks_exact(9)) {
+            *object = Self::decode_extended_object(chunk).map(|object| IrObject {
+                bounding_box: Some(IrBoundingBox {
+                    x_min: chunk[3] & 0x7F,
+                    y_min: chunk[4] & 0x7F,
+                    x_max: chunk[5] & 0x7F,
+                    y_max: chunk[6] & 0x7F,
+                }),
+                intensity: Some(chunk[8]),
+                ..object
+            });
+        }
+        Self { objects }
+    }
+
+    /// Returns the objects currently seen by the camera.
+    pub fn visible_objects(&self) -> impl Iterator<Item = &IrObject> {
+        self.objects.iter().flatten()
+    }
+
+    fn decode_extended_object(chunk: &[u8]) -> Option<IrObject> {
+        let (x, y) = Self::decode_position(chunk[0], chunk[1], chunk[2] >> 4)?;
+        Some(IrObject {
+            size: Some(chunk[2] & 0x0F),
+            ..IrObject::new(x, y)
+        })
+    }
+
+    /// Combines the low bytes with the high bits `Y<9:8> X<9:8>` in the lower nibble of `high_bits`.
+    /// Returns `None` for the "not seen" sentinel, where all bits are set.
+    fn decode_position(x_low: u8, y_low: u8, high_bits: u8) -> Option<(u16, u16)> {
+        let x = u16::from(x_low) | (u16::from(high_bits & 0b0011) << 8);
+        let y = u16::from(y_low) | (u16::from((high_bits >> 2) & 0b0011) << 8);
+        if x == 0x3FF && y == 0x3FF {
+            None
+        } else {
+            Some((x, y))
+        }
+    }
+}
+
+impl IrObject {
+    const fn new(x: u16, y: u16) -> Self {
+        Self {
+            x,
+            y,
+            size: None,
+            bounding_box: None,
+            intensity: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_mode() {
+        let data = [
+            0x34,
+            0x12,
+            0b0110_0111,
+            0x78,
+            0x56, // objects 1 and 2
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF, // not seen
+        ];
+
+        let ir_data = IrData::from_basic(&data);
+
+        let object = ir_data.objects[0].unwrap();
+        assert_eq!((object.x, object.y), (0x234, 0x112));
+        assert_eq!(object.size, None);
+        let object = ir_data.objects[1].unwrap();
+        assert_eq!((object.x, object.y), (0x378, 0x156));
+        assert!(ir_data.objects[2].is_none());
+        assert!(ir_data.objects[3].is_none());
+        assert_eq!(ir_data.visible_objects().count(), 2);
+    }
+
+    #[test]
+    fn test_extended_mode() {
+        let mut data = [0xFF; 12];
+        data[3..6].copy_from_slice(&[0x00, 0xFF, 0b1001_0101]);
+
+        let ir_data = IrData::decode(IrMode::Extended, &data).unwrap();
+
+        assert!(ir_data.objects[0].is_none());
+        let object = ir_data.objects[1].unwrap();
+        assert_eq!((object.x, object.y), (0x100, 0x2FF));
+        assert_eq!(object.size, Some(5));
+        assert!(IrData::decode(IrMode::Extended, &data[..10]).is_none());
+    }
+
+    #[test]
+    fn test_full_mode() {
+        let mut data = [0xFF; 36];
+        data[..9].copy_from_slice(&[0x10, 0x20, 0b0000_0011, 0x01, 0x02, 0x7E, 0xFF, 0x00, 0x80]);
+
+        let ir_data = IrData::from_full(&data);
+
+        let object = ir_data.objects[0].unwrap();
+        assert_eq!((object.x, object.y), (0x10, 0x20));
+        assert_eq!(object.size, Some(3));
+        assert_eq!(
+            object.bounding_box,
+            Some(IrBoundingBox {
+                x_min: 0x01,
+                y_min: 0x02,
+                x_max: 0x7E,
+                y_max: 0x7F,
+            })
+        );
+        assert_eq!(object.intensity, Some(0x80));
+        assert_eq!(ir_data.visible_objects().count(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::input::{ButtonData, WiimoteData};
+use crate::prelude::*;
+
+use super::IrData;
+
+/// The report of the first half, also used as the interleaved data reporting mode.
+pub(crate) const FIRST_HALF_ID: u8 = 0x3E;
+const SECOND_HALF_ID: u8 = 0x3F;
+
+/// A frame of the interleaved data reporting mode combined from the reports 0x3E and 0x3F.
+#[derive(Debug, Clone, Copy)]
+pub struct FullIrFrame {
+    pub buttons: ButtonData,
+    pub accelerometer: AccelerometerData,
+    pub ir: IrData,
+}
+
+/// Combines the interleaved data reports 0x3E and 0x3F into frames.
+/// The accelerometer and the 36 bytes of the full IR mode are split across both reports.
+///
+/// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Interleaved_Core_Buttons_and_Accelerometer_with_36_IR_bytes>
+#[derive(Debug, Default)]
+pub struct InterleavedAssembler {
+    first_half: Option<[u8; 21]>,
+}
+
+impl InterleavedAssembler {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { first_half: None }
+    }
+
+    /// Adds a data report and returns the frame once both halves were received.
+    /// Reports of other data reporting modes are ignored.
+    pub fn push(&mut self, wiimote_data: &WiimoteData) -> Option<FullIrFrame> {
+        match wiimote_data.report_id() {
+            FIRST_HALF_ID => {
+                self.first_half = Some(wiimote_data.data);
+                None
+            }
+            SECOND_HALF_ID => {
+                // A second half without a first half is dropped, e.g. after a lost report.
+                let first_half = self.first_half.take()?;
+                let second_half = &wiimote_data.data;
+
+                let mut ir_data = [0u8; 36];
+                ir_data[..18].copy_from_slice(&first_half[3..21]);
+                ir_data[18..].copy_from_slice(&second_half[3..21]);
+
+                let buttons = u16::from_le_bytes([first_half[0], first_half[1]]);
+                Some(FullIrFrame {
+                    // The unused button bits contain the z axis of the accelerometer.
+                    buttons: ButtonData::from_bits_truncate(buttons),
+                    accelerometer: AccelerometerData::from_interleaved_reporting(
+                        &first_half,
+                        second_half,
+                    ),
+                    ir: IrData::from_full(&ir_data),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Discards a received first half.
+    pub fn reset(&mut self) {
+        self.first_half = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputReport;
+
+    fn report(report_id: u8, data: [u8; 21]) -> WiimoteData {
+        let mut buffer = [0u8; 22];
+        buffer[0] = report_id;
+        buffer[1..].copy_from_slice(&data);
+        match InputReport::try_from(buffer.as_slice()) {
+            Ok(InputReport::DataReport(_, wiimote_data)) => wiimote_data,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_assemble_frame() {
+        let mut assembler = InterleavedAssembler::new();
+        let mut first_half = [0xFF; 21];
+        first_half[..3].copy_from_slice(&[0b0110_0000, 0b0000_1000, 0x80]); // A pressed
+        first_half[3..12].copy_from_slice(&[0x10, 0x20, 0x00, 0, 0, 0, 0, 0, 0x40]);
+        let mut second_half = [0xFF; 21];
+        second_half[..3].copy_from_slice(&[0x00, 0x00, 0x7F]);
+
+        assert!(assembler.push(&report(0x3F, second_half)).is_none());
+        assert!(assembler.push(&report(0x3E, first_half)).is_none());
+        let frame = assembler.push(&report(0x3F, second_half)).unwrap();
+
+        assert_eq!(frame.buttons.bits(), ButtonData::A.bits());
+        let object = frame.ir.objects[0].unwrap();
+        assert_eq!((object.x, object.y), (0x10, 0x20));
+        assert_eq!(object.intensity, Some(0x40));
+        assert_eq!(frame.ir.visible_objects().count(), 1);
+    }
+}
@@ -0,0 +1,127 @@
+//! The I/O of the IR camera through a `WiimoteDevice`: enabling it and reading the interleaved reports.
+//!
+//! The IR data and the interleaved reports are decoded by the other IR modules,
+//! which never touch a transport and can be used without a Wii remote.
+
+use crate::input::InputReport;
+use crate::output::{DataReporingMode, OutputReport};
+use crate::prelude::*;
+use crate::simple_io;
+
+use super::interleaved::FIRST_HALF_ID;
+use super::{FullIrFrame, InterleavedAssembler, IrCamera, IrMode, IrSensitivity};
+
+impl IrCamera {
+    /// Enables the IR camera with the given data format and sensitivity.
+    /// The data reporting mode must be changed separately to a mode matching the [`IrMode`].
+    ///
+    /// The `WiimoteDevice` enables the IR camera again if a reconnect or a status report shows it was reset.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error, if a register write is not acknowledged
+    /// or the sensitivity and mode registers do not read back the written values.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use wiimote_rs::ir::{IrCamera, IrMode, IrSensitivity};
+    /// use wiimote_rs::output::{DataReporingMode, OutputReport};
+    /// use wiimote_rs::prelude::*;
+    ///
+    /// # fn enable(wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+    /// IrCamera::enable(wiimote, IrMode::Extended, IrSensitivity::Level3)?;
+    /// wiimote.write(&OutputReport::DataReportingMode(DataReporingMode {
+    ///     continuous: true,
+    ///     mode: 0x33,
+    /// }))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enable(
+        wiimote: &WiimoteDevice,
+        mode: IrMode,
+        sensitivity: IrSensitivity,
+    ) -> WiimoteResult<()> {
+        // https://www.wiibrew.org/wiki/Wiimote#Initialization
+        wiimote.write(&OutputReport::IrCameraEnable(true))?;
+        wiimote.write(&OutputReport::IrCameraEnable2(true))?;
+
+        let (block_1, block_2) = sensitivity.blocks();
+        simple_io::write_register_checked(wiimote, 0xB0_0030, &[0x08])?;
+        simple_io::write_verified(wiimote, 0xB0_0000, &block_1)?;
+        simple_io::write_verified(wiimote, 0xB0_001A, &block_2)?;
+        simple_io::write_verified(wiimote, 0xB0_0033, &[mode.mode_number()])?;
+        simple_io::write_register_checked(wiimote, 0xB0_0030, &[0x08])?;
+        wiimote.set_ir_camera_config(Some((mode, sensitivity)));
+        Ok(())
+    }
+
+    /// Disables the IR camera.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error.
+    pub fn disable(wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+        wiimote.set_ir_camera_config(None);
+        wiimote.write(&OutputReport::IrCameraEnable(false))?;
+        wiimote.write(&OutputReport::IrCameraEnable2(false))
+    }
+
+    /// Enables the IR camera in full mode and switches to the interleaved data reporting mode,
+    /// the only mode containing all 36 bytes of IR data.
+    /// Data reports should then only be read from the returned reader.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or if a register write is not acknowledged.
+    pub fn enable_full_ir(
+        wiimote: &WiimoteDevice,
+        sensitivity: IrSensitivity,
+    ) -> WiimoteResult<FullIrReader<'_>> {
+        Self::enable(wiimote, IrMode::Full, sensitivity)?;
+        Self::set_interleaved_reporting(wiimote)?;
+        Ok(FullIrReader {
+            wiimote,
+            assembler: InterleavedAssembler::new(),
+        })
+    }
+
+    fn set_interleaved_reporting(wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+        wiimote.write(&OutputReport::DataReportingMode(DataReporingMode {
+            continuous: true,
+            mode: FIRST_HALF_ID,
+        }))
+    }
+}
+
+/// Reads frames of the full IR mode from a Wii remote, see [`IrCamera::enable_full_ir`].
+pub struct FullIrReader<'a> {
+    wiimote: &'a WiimoteDevice,
+    assembler: InterleavedAssembler,
+}
+
+impl FullIrReader<'_> {
+    /// Reads reports until a complete frame was received.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or read failed.
+    pub fn read_frame(&mut self) -> WiimoteResult<FullIrFrame> {
+        loop {
+            match self.wiimote.read()? {
+                InputReport::DataReport(_, wiimote_data) => {
+                    if let Some(frame) = self.assembler.push(&wiimote_data) {
+                        return Ok(frame);
+                    }
+                }
+                InputReport::StatusInformation(_) => {
+                    // The data reporting mode must be set again after a status report.
+                    self.assembler.reset();
+                    IrCamera::set_interleaved_reporting(self.wiimote)?;
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+pub(crate) mod camera;
+pub(crate) mod data;
+pub(crate) mod interleaved;
+mod io;
+pub(crate) mod pointer;
+pub(crate) mod tracker;
+
+pub use camera::*;
+pub use data::*;
+pub use interleaved::*;
+pub use io::FullIrReader;
+pub use pointer::*;
+pub use tracker::*;
@@ -0,0 +1,317 @@
+use std::f64::consts::PI;
+
+use super::{IrObject, TrackedIrObject};
+
+const CAMERA_WIDTH: f64 = 1024.0;
+const CAMERA_HEIGHT: f64 = 768.0;
+/// The horizontal field of view of the IR camera, about 33 degrees.
+const CAMERA_HORIZONTAL_FOV: f64 = 33.0 * PI / 180.0;
+/// The vertical field of view of the IR camera, about 23 degrees.
+const CAMERA_VERTICAL_FOV: f64 = 23.0 * PI / 180.0;
+/// The vertical offset of the cursor in screen heights when the sensor bar is not centered.
+const SENSOR_BAR_VERTICAL_OFFSET: f64 = 0.25;
+
+/// Where the sensor bar is placed relative to the screen.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SensorBarPosition {
+    #[default]
+    AboveScreen,
+    BelowScreen,
+}
+
+/// The placement and size of the sensor bar.
+#[derive(Debug, Clone, Copy)]
+pub struct SensorBarConfig {
+    pub position: SensorBarPosition,
+    /// The distance between the two IR clusters of the sensor bar, about 0.2 meters for the
+    /// original sensor bar. The distance of [`PointerPosition`] has the same unit.
+    pub width: f64,
+}
+
+impl Default for SensorBarConfig {
+    fn default() -> Self {
+        Self {
+            position: SensorBarPosition::default(),
+            width: 0.2,
+        }
+    }
+}
+
+impl SensorBarConfig {
+    /// Estimates the distance and direction of the sensor bar from its two clusters.
+    ///
+    /// The distance follows from the known width of the sensor bar and the angle between the
+    /// clusters, the direction from the position of their center in the camera image.
+    /// The angles are relative to the camera and not compensated for the roll of the Wii remote.
+    #[must_use]
+    pub fn estimate_pose(&self, first: &IrObject, second: &IrObject) -> SensorBarPose {
+        let dx = f64::from(second.x) - f64::from(first.x);
+        let dy = f64::from(second.y) - f64::from(first.y);
+        let separation = dx.hypot(dy) * CAMERA_HORIZONTAL_FOV / CAMERA_WIDTH;
+
+        let center_x = (f64::from(first.x) + f64::from(second.x)) / 2.0;
+        let center_y = (f64::from(first.y) + f64::from(second.y)) / 2.0;
+
+        SensorBarPose {
+            distance: self.width / (2.0 * (separation / 2.0).tan()),
+            // The camera sees the sensor bar move in the opposite direction of the remote.
+            yaw: (CAMERA_WIDTH / 2.0 - center_x) * CAMERA_HORIZONTAL_FOV / CAMERA_WIDTH,
+            pitch: (CAMERA_HEIGHT / 2.0 - center_y) * CAMERA_VERTICAL_FOV / CAMERA_HEIGHT,
+        }
+    }
+}
+
+/// The position of the sensor bar relative to the Wii remote,
+/// e.g. for head tracking with the remote mounted to the screen.
+#[derive(Debug, Clone, Copy)]
+pub struct SensorBarPose {
+    /// The distance to the center of the sensor bar in the unit of [`SensorBarConfig::width`].
+    pub distance: f64,
+    /// The horizontal angle in radians between the pointing direction and the sensor bar,
+    /// positive if the remote points to the right of the bar.
+    pub yaw: f64,
+    /// The vertical angle in radians between the pointing direction and the sensor bar,
+    /// positive if the remote points above the bar.
+    pub pitch: f64,
+}
+
+/// The position the Wii remote points at.
+#[derive(Debug, Clone, Copy)]
+pub struct PointerPosition {
+    /// Horizontal position from 0 (left) to 1 (right) of the screen, may exceed the range.
+    pub x: f64,
+    /// Vertical position from 0 (top) to 1 (bottom) of the screen, may exceed the range.
+    pub y: f64,
+    /// The roll of the Wii remote in radians used to compensate the position.
+    pub roll: f64,
+    /// The distance to the sensor bar, `None` while only one cluster is seen.
+    pub distance: Option<f64>,
+}
+
+/// Computes the cursor position from the two clusters of the sensor bar seen by the IR camera.
+///
+/// The position is compensated for the roll of the Wii remote, so rotating the remote does not
+/// move the cursor. The roll measured from the IR clusters is ambiguous by 180 degrees,
+/// which is resolved by the accelerometer if available. While only one cluster is seen,
+/// the position of the other is estimated from the last frame both were seen in.
+#[derive(Debug, Clone)]
+pub struct Pointer {
+    config: SensorBarConfig,
+    /// The IDs of the two clusters and the vector from the first cluster to the center.
+    clusters: Option<(u32, u32, (f64, f64))>,
+    roll: f64,
+}
+
+impl Pointer {
+    #[must_use]
+    pub const fn new(config: SensorBarConfig) -> Self {
+        Self {
+            config,
+            clusters: None,
+            roll: 0.0,
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &SensorBarConfig {
+        &self.config
+    }
+
+    /// Computes the cursor position from the objects of an [`super::IrTracker`] and the
+    /// acceleration in g of the Wii remote.
+    /// Returns `None` if the sensor bar is not seen.
+    pub fn update(
+        &mut self,
+        tracked_objects: &[TrackedIrObject],
+        acceleration: Option<(f64, f64, f64)>,
+    ) -> Option<PointerPosition> {
+        let visible: Vec<&TrackedIrObject> = tracked_objects
+            .iter()
+            .filter(|tracked| tracked.is_visible())
+            .collect();
+        let find = |id: u32| visible.iter().find(|tracked| tracked.id == id).copied();
+
+        let (center, distance) = match (self.clusters, visible.as_slice()) {
+            (Some((first_id, second_id, _)), _)
+                if find(first_id).zip(find(second_id)).is_some() =>
+            {
+                self.update_clusters(find(first_id)?, find(second_id)?, acceleration)
+            }
+            (_, [first, second, ..]) => self.update_clusters(first, second, acceleration),
+            (Some((first_id, second_id, to_center)), [single]) => {
+                let position = Self::position(single);
+                let center = if single.id == first_id {
+                    (position.0 + to_center.0, position.1 + to_center.1)
+                } else if single.id == second_id {
+                    (position.0 - to_center.0, position.1 - to_center.1)
+                } else {
+                    return None;
+                };
+                (center, None)
+            }
+            _ => return None,
+        };
+
+        // Rotate around the center of the camera to undo the roll.
+        let (sin, cos) = self.roll.sin_cos();
+        let x = center.0 - CAMERA_WIDTH / 2.0;
+        let y = center.1 - CAMERA_HEIGHT / 2.0;
+        let x_rotated = x * cos + y * sin;
+        let y_rotated = -x * sin + y * cos;
+
+        // The camera sees the sensor bar move in the opposite direction of the pointer.
+        let offset = match self.config.position {
+            SensorBarPosition::AboveScreen => SENSOR_BAR_VERTICAL_OFFSET,
+            SensorBarPosition::BelowScreen => -SENSOR_BAR_VERTICAL_OFFSET,
+        };
+        Some(PointerPosition {
+            x: 0.5 - x_rotated / CAMERA_WIDTH,
+            y: 0.5 - y_rotated / CAMERA_HEIGHT + offset,
+            roll: self.roll,
+            distance,
+        })
+    }
+
+    /// Forgets the clusters of the sensor bar, e.g. after the IR camera was disabled.
+    pub fn reset(&mut self) {
+        self.clusters = None;
+    }
+
+    fn update_clusters(
+        &mut self,
+        first: &TrackedIrObject,
+        second: &TrackedIrObject,
+        acceleration: Option<(f64, f64, f64)>,
+    ) -> ((f64, f64), Option<f64>) {
+        let first_position = Self::position(first);
+        let second_position = Self::position(second);
+        let delta = (
+            second_position.0 - first_position.0,
+            second_position.1 - first_position.1,
+        );
+
+        // The clusters only give the roll modulo 180 degrees.
+        let mut roll = delta.1.atan2(delta.0);
+        if roll > PI / 2.0 {
+            roll -= PI;
+        } else if roll <= -PI / 2.0 {
+            roll += PI;
+        }
+        if let Some((x, _, z)) = acceleration {
+            let accelerometer_roll = x.atan2(z);
+            if Self::angle_difference(roll, accelerometer_roll) > PI / 2.0 {
+                roll += PI;
+            }
+        }
+        self.roll = roll;
+
+        let to_center = (delta.0 / 2.0, delta.1 / 2.0);
+        self.clusters = Some((first.id, second.id, to_center));
+
+        let pose = self.config.estimate_pose(&first.object, &second.object);
+        (
+            (
+                first_position.0 + to_center.0,
+                first_position.1 + to_center.1,
+            ),
+            Some(pose.distance),
+        )
+    }
+
+    fn position(tracked: &TrackedIrObject) -> (f64, f64) {
+        (f64::from(tracked.object.x), f64::from(tracked.object.y))
+    }
+
+    fn angle_difference(a: f64, b: f64) -> f64 {
+        let difference = (a - b).rem_euclid(2.0 * PI);
+        f64::min(difference, 2.0 * PI - difference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::IrObject;
+
+    fn tracked(id: u32, x: u16, y: u16, missed_frames: u32) -> TrackedIrObject {
+        TrackedIrObject {
+            id,
+            object: IrObject {
+                x,
+                y,
+                size: None,
+                bounding_box: None,
+                intensity: None,
+            },
+            missed_frames,
+        }
+    }
+
+    #[test]
+    fn test_centered_pointer() {
+        let mut pointer = Pointer::new(SensorBarConfig {
+            position: SensorBarPosition::BelowScreen,
+            width: 0.2,
+        });
+
+        let position = pointer
+            .update(&[tracked(0, 412, 384, 0), tracked(1, 612, 384, 0)], None)
+            .unwrap();
+
+        assert!((position.x - 0.5).abs() < 1e-9);
+        assert!((position.y - 0.25).abs() < 1e-9);
+        assert!(position.roll.abs() < 1e-9);
+        assert!(position.distance.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_roll_compensation() {
+        let mut pointer = Pointer::new(SensorBarConfig::default());
+
+        let level = pointer
+            .update(&[tracked(0, 312, 384, 0), tracked(1, 512, 384, 0)], None)
+            .unwrap();
+        // Rolled by 90 degrees around the center of the camera.
+        let rolled = pointer
+            .update(
+                &[tracked(0, 512, 184, 0), tracked(1, 512, 384, 0)],
+                Some((1.0, 0.0, 0.0)),
+            )
+            .unwrap();
+
+        assert!((rolled.roll - PI / 2.0).abs() < 1e-9);
+        assert!((level.x - rolled.x).abs() < 1e-9);
+        assert!((level.y - rolled.y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sensor_bar_pose() {
+        let config = SensorBarConfig::default();
+        // The clusters are a quarter of the field of view apart
+        let first = tracked(0, 0, 384, 0).object;
+        let second = tracked(1, 256, 384, 0).object;
+
+        let pose = config.estimate_pose(&first, &second);
+
+        let expected_distance = 0.2 / (2.0 * (CAMERA_HORIZONTAL_FOV / 8.0).tan());
+        assert!((pose.distance - expected_distance).abs() < 1e-9);
+        assert!((pose.yaw - CAMERA_HORIZONTAL_FOV * 0.375).abs() < 1e-9);
+        assert!(pose.pitch.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_single_cluster() {
+        let mut pointer = Pointer::new(SensorBarConfig::default());
+
+        let both = pointer
+            .update(&[tracked(0, 412, 384, 0), tracked(1, 612, 384, 0)], None)
+            .unwrap();
+        let single = pointer
+            .update(&[tracked(0, 412, 384, 1), tracked(1, 612, 384, 0)], None)
+            .unwrap();
+
+        assert!((both.x - single.x).abs() < 1e-9);
+        assert!(single.distance.is_none());
+        assert!(pointer.update(&[], None).is_none());
+    }
+}
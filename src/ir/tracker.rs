@@ -0,0 +1,179 @@
+use super::{IrData, IrObject};
+
+/// An IR object with an ID that stays the same across frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackedIrObject {
+    pub id: u32,
+    /// The last position seen, kept while the object is missing.
+    pub object: IrObject,
+    /// The number of consecutive frames the object was not seen, 0 if visible.
+    pub missed_frames: u32,
+}
+
+impl TrackedIrObject {
+    #[must_use]
+    pub const fn is_visible(&self) -> bool {
+        self.missed_frames == 0
+    }
+}
+
+/// Assigns stable IDs to the IR objects reported by the camera.
+///
+/// The camera may report the objects in a different slot from frame to frame and loses them for
+/// single frames, so objects are matched to the closest object of the previous frame instead.
+/// Objects that are not seen are kept for a few frames before their ID is dropped.
+#[derive(Debug, Clone)]
+pub struct IrTracker {
+    max_distance: f64,
+    max_missed_frames: u32,
+    next_id: u32,
+    tracked_objects: Vec<TrackedIrObject>,
+}
+
+impl Default for IrTracker {
+    fn default() -> Self {
+        Self::new(100.0, 5)
+    }
+}
+
+impl IrTracker {
+    /// Creates a tracker matching objects that moved at most `max_distance` camera units between
+    /// frames and keeping missing objects for `max_missed_frames` frames.
+    #[must_use]
+    pub const fn new(max_distance: f64, max_missed_frames: u32) -> Self {
+        Self {
+            max_distance,
+            max_missed_frames,
+            next_id: 0,
+            tracked_objects: Vec::new(),
+        }
+    }
+
+    /// Matches the objects of a new frame and returns all tracked objects ordered by ID,
+    /// including objects that are currently missing.
+    pub fn update(&mut self, data: &IrData) -> &[TrackedIrObject] {
+        let observations: Vec<IrObject> = data.visible_objects().copied().collect();
+
+        // Nearest-neighbor matching, the closest pairs are assigned first.
+        let mut pairs = Vec::new();
+        for (tracked_index, tracked) in self.tracked_objects.iter().enumerate() {
+            for (observation_index, observation) in observations.iter().enumerate() {
+                let distance = Self::distance(&tracked.object, observation);
+                if distance <= self.max_distance {
+                    pairs.push((distance, tracked_index, observation_index));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut tracked_matched = vec![false; self.tracked_objects.len()];
+        let mut observation_matched = vec![false; observations.len()];
+        for (_, tracked_index, observation_index) in pairs {
+            if tracked_matched[tracked_index] || observation_matched[observation_index] {
+                continue;
+            }
+            tracked_matched[tracked_index] = true;
+            observation_matched[observation_index] = true;
+
+            let tracked = &mut self.tracked_objects[tracked_index];
+            tracked.object = observations[observation_index];
+            tracked.missed_frames = 0;
+        }
+
+        for (tracked, matched) in self.tracked_objects.iter_mut().zip(tracked_matched) {
+            if !matched {
+                tracked.missed_frames += 1;
+            }
+        }
+        let max_missed_frames = self.max_missed_frames;
+        self.tracked_objects
+            .retain(|tracked| tracked.missed_frames <= max_missed_frames);
+
+        for (observation, matched) in observations.into_iter().zip(observation_matched) {
+            if !matched {
+                self.tracked_objects.push(TrackedIrObject {
+                    id: self.next_id,
+                    object: observation,
+                    missed_frames: 0,
+                });
+                self.next_id = self.next_id.wrapping_add(1);
+            }
+        }
+
+        self.tracked_objects.sort_by_key(|tracked| tracked.id);
+        &self.tracked_objects
+    }
+
+    /// Returns all tracked objects ordered by ID, including objects that are currently missing.
+    #[must_use]
+    pub fn tracked_objects(&self) -> &[TrackedIrObject] {
+        &self.tracked_objects
+    }
+
+    /// Drops all tracked objects, e.g. after the IR camera was disabled.
+    pub fn reset(&mut self) {
+        self.tracked_objects.clear();
+    }
+
+    fn distance(a: &IrObject, b: &IrObject) -> f64 {
+        let dx = f64::from(a.x) - f64::from(b.x);
+        let dy = f64::from(a.y) - f64::from(b.y);
+        dx.hypot(dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(positions: [Option<(u16, u16)>; 4]) -> IrData {
+        IrData {
+            objects: positions.map(|position| {
+                position.map(|(x, y)| IrObject {
+                    x,
+                    y,
+                    size: None,
+                    bounding_box: None,
+                    intensity: None,
+                })
+            }),
+        }
+    }
+
+    #[test]
+    fn test_reordered_objects_keep_ids() {
+        let mut tracker = IrTracker::default();
+
+        tracker.update(&frame([Some((100, 300)), Some((600, 300)), None, None]));
+        let tracked = tracker.update(&frame([Some((605, 302)), Some((98, 301)), None, None]));
+
+        assert_eq!(tracked.len(), 2);
+        assert_eq!(tracked[0].id, 0);
+        assert_eq!(tracked[0].object.x, 98);
+        assert_eq!(tracked[1].id, 1);
+        assert_eq!(tracked[1].object.x, 605);
+    }
+
+    #[test]
+    fn test_dropout_tolerance() {
+        let mut tracker = IrTracker::new(100.0, 1);
+
+        tracker.update(&frame([Some((100, 300)), Some((600, 300)), None, None]));
+        let tracked = tracker.update(&frame([Some((600, 300)), None, None, None]));
+        assert_eq!(tracked.len(), 2);
+        assert!(!tracked[0].is_visible());
+        assert!(tracked[1].is_visible());
+
+        let tracked = tracker.update(&frame([None, Some((102, 300)), Some((600, 300)), None]));
+        assert_eq!(tracked.len(), 2);
+        assert_eq!(tracked[0].id, 0);
+        assert!(tracked[0].is_visible());
+
+        tracker.update(&frame([None, None, None, None]));
+        let tracked = tracker.update(&frame([None, None, None, None]));
+        assert!(tracked.is_empty());
+
+        let tracked = tracker.update(&frame([Some((100, 300)), None, None, None]));
+        assert_eq!(tracked[0].id, 2);
+    }
+}
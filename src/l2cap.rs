@@ -0,0 +1,88 @@
+//! Linux specific tuning of the L2CAP channels and the Bluetooth connection of Wii remotes.
+
+use std::sync::{Mutex, PoisonError, RwLock};
+use std::time::Duration;
+
+/// The options applied to the channels of Wii remotes connected afterwards, see [`set_l2cap_config`].
+///
+/// The defaults leave the sockets and the connection as configured by the kernel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct L2capConfig {
+    /// The incoming and outgoing MTU of the control and data channels, set before connecting.
+    pub mtu: Option<u16>,
+    /// The time a write to the data channel waits for space in the send buffer before failing with
+    /// [`WiimoteError::WriteTimedOut`](crate::prelude::WiimoteError::WriteTimedOut),
+    /// so a Wii remote out of range does not block writes.
+    pub send_timeout: Option<Duration>,
+    /// Makes writes to the data channel fail with
+    /// [`WiimoteError::WriteTimedOut`](crate::prelude::WiimoteError::WriteTimedOut)
+    /// instead of waiting if the send buffer is full.
+    pub non_blocking: bool,
+    /// Removes sniff mode from the link policy of the connection, the controller then
+    /// no longer polls the Wii remote in longer intervals, which delays its reports.
+    ///
+    /// Sending HCI commands usually requires root or the `CAP_NET_RAW` capability,
+    /// failures are logged and otherwise ignored.
+    pub disable_sniff_mode: bool,
+}
+
+static CONFIG: RwLock<L2capConfig> = RwLock::new(L2capConfig {
+    mtu: None,
+    send_timeout: None,
+    non_blocking: false,
+    disable_sniff_mode: false,
+});
+
+/// The addresses of the Wii remotes whose output reports are written to the control channel.
+static CONTROL_PIPE_DEVICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Sets the options of the channels of Wii remotes connected or reconnected afterwards.
+pub fn set_l2cap_config(config: L2capConfig) {
+    *CONFIG.write().unwrap_or_else(PoisonError::into_inner) = config;
+}
+
+/// Returns the options of the channels of Wii remotes connected afterwards.
+pub fn l2cap_config() -> L2capConfig {
+    *CONFIG.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Writes the output reports of the Wii remote with the Bluetooth `address`, e.g. `00:19:FD:AA:BB:CC`
+/// as returned by [`WiimoteDevice::identifier`](crate::prelude::WiimoteDevice::identifier),
+/// to the control channel as SET_REPORT transactions. Some original Wii remotes require it.
+/// Applies to the channels opened afterwards, including reconnects.
+///
+/// Otherwise the control channel is only used after the data channel rejected a write.
+pub fn set_control_pipe_writes(address: &str, enabled: bool) {
+    let mut devices = CONTROL_PIPE_DEVICES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    devices.retain(|device| !device.eq_ignore_ascii_case(address));
+    if enabled {
+        devices.push(address.to_string());
+    }
+}
+
+/// Returns whether the output reports of the Wii remote with the Bluetooth `address`
+/// are written to the control channel from the start, see [`set_control_pipe_writes`].
+pub fn control_pipe_writes(address: &str) -> bool {
+    CONTROL_PIPE_DEVICES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .any(|device| device.eq_ignore_ascii_case(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_pipe_writes_per_device() {
+        set_control_pipe_writes("00:19:FD:AA:BB:CC", true);
+        assert!(control_pipe_writes("00:19:fd:aa:bb:cc"));
+        assert!(!control_pipe_writes("00:19:FD:AA:BB:CD"));
+
+        set_control_pipe_writes("00:19:fd:aa:bb:cc", false);
+        assert!(!control_pipe_writes("00:19:FD:AA:BB:CC"));
+    }
+}
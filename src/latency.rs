@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::input::InputReport;
+use crate::output::OutputReport;
+use crate::prelude::*;
+
+/// The number of data reports the report rate is computed from.
+const RATE_WINDOW: usize = 100;
+/// How long [`WiimoteDevice::measure_latency`] waits for the status report.
+const LATENCY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The rate of the data reports received recently, see [`WiimoteDevice::report_rate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportRate {
+    pub reports_per_second: f64,
+    pub mean_interval: Duration,
+    /// The longest time between two data reports, spikes indicate stalls of the Bluetooth stack.
+    pub max_interval: Duration,
+    /// The number of intervals the rate is computed from, at most 99.
+    pub samples: usize,
+}
+
+/// Records the arrival times of the last data reports.
+#[derive(Debug, Default)]
+pub(crate) struct ReportRateTracker {
+    arrivals: Mutex<VecDeque<Instant>>,
+}
+
+impl ReportRateTracker {
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Instant>> {
+        match self.arrivals.lock() {
+            Ok(arrivals) => arrivals,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Records a data report received by the transport at `received`.
+    pub(crate) fn observe(&self, received: Instant) {
+        let mut arrivals = self.lock();
+        if arrivals.len() >= RATE_WINDOW {
+            arrivals.pop_front();
+        }
+        arrivals.push_back(received);
+    }
+
+    fn rate(&self) -> Option<ReportRate> {
+        let arrivals = self.lock();
+        let (first, last) = (arrivals.front()?, arrivals.back()?);
+        let samples = arrivals.len() - 1;
+        let elapsed = last.duration_since(*first);
+        if samples == 0 || elapsed.is_zero() {
+            return None;
+        }
+        let max_interval = arrivals
+            .iter()
+            .zip(arrivals.iter().skip(1))
+            .map(|(previous, next)| next.duration_since(*previous))
+            .max()
+            .unwrap_or_default();
+        #[allow(clippy::cast_possible_truncation)] // At most `RATE_WINDOW`
+        let mean_interval = elapsed / samples as u32;
+        #[allow(clippy::cast_precision_loss)]
+        let reports_per_second = samples as f64 / elapsed.as_secs_f64();
+        Some(ReportRate {
+            reports_per_second,
+            mean_interval,
+            max_interval,
+            samples,
+        })
+    }
+
+    fn reset(&self) {
+        self.lock().clear();
+    }
+}
+
+impl WiimoteDevice {
+    /// Measures the time between sending a status request and receiving the status report,
+    /// e.g. to compare Bluetooth adapters and stacks.
+    ///
+    /// Other reports received while waiting are processed and discarded, do not read input reports at the same time.
+    /// The data reporting mode is set again afterwards, as the status report suspends the data reports.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected, the request could not be written
+    /// or no status report was received within a second.
+    pub fn measure_latency(&self) -> WiimoteResult<Duration> {
+        let sent = Instant::now();
+        let deadline = sent + LATENCY_TIMEOUT;
+        self.write(&OutputReport::StatusRequest)?;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(WiimoteDeviceError::MissingData.into());
+            }
+            #[allow(clippy::cast_possible_truncation)] // At most `LATENCY_TIMEOUT`
+            let timeout_millis = remaining.as_millis().max(1) as usize;
+            let input_report = match self.read_report(Some(timeout_millis)) {
+                Ok(input_report) => input_report,
+                Err(WiimoteError::WiimoteDeviceError(
+                    WiimoteDeviceError::MissingData | WiimoteDeviceError::InvalidData,
+                )) => continue,
+                Err(err) => return Err(err),
+            };
+            let received = Instant::now();
+            self.handle_report(&input_report);
+            self.process_report_changes(&input_report)?;
+            if matches!(input_report, InputReport::StatusInformation(_)) {
+                return Ok(received.duration_since(sent));
+            }
+        }
+    }
+
+    /// Returns the rate of the last 100 data reports read from the Wii remote,
+    /// `None` until at least two data reports were read.
+    /// The reports are timed when the backend received them, not when they were read.
+    ///
+    /// The Wii remote sends data reports every 10 ms in continuous reporting mode,
+    /// a lower rate or long maximum intervals point to an overloaded Bluetooth connection.
+    #[must_use]
+    pub fn report_rate(&self) -> Option<ReportRate> {
+        self.report_rate.rate()
+    }
+
+    /// Forgets the data reports recorded for the report rate, e.g. after changing the reporting mode.
+    pub fn reset_report_rate(&self) {
+        self.report_rate.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::DataReporingMode;
+    use crate::simulator::SimulatedWiimote;
+
+    #[test]
+    fn test_measure_latency() {
+        let simulator = SimulatedWiimote::default();
+        let wiimote = WiimoteDevice::with_transport(simulator.clone()).unwrap();
+        let reporting_mode = OutputReport::DataReportingMode(DataReporingMode {
+            continuous: true,
+            mode: 0x31,
+        });
+        wiimote.write(&reporting_mode).unwrap();
+
+        assert!(wiimote.measure_latency().unwrap() < Duration::from_secs(1));
+        let written = simulator.written_reports();
+        assert_eq!(written.last().map(|report| report[0]), Some(0x12));
+    }
+
+    #[test]
+    fn test_report_rate() {
+        let tracker = ReportRateTracker::default();
+        assert!(tracker.rate().is_none());
+        for _ in 0..3 {
+            tracker.observe(Instant::now());
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let rate = tracker.rate().unwrap();
+        assert_eq!(rate.samples, 2);
+        assert!(rate.max_interval >= rate.mean_interval);
+        assert!(rate.reports_per_second > 0.0 && rate.reports_per_second <= 200.0);
+
+        tracker.reset();
+        assert!(tracker.rate().is_none());
+    }
+}
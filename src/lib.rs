@@ -1,21 +1,111 @@
 #![allow(clippy::module_name_repetitions)]
 
+mod backend;
+mod battery;
+pub mod bindings;
+pub mod btsnoop;
 mod calibration;
 mod device;
+#[cfg(feature = "dsu")]
+pub mod dsu;
+pub mod eeprom;
+mod event_loop;
+mod events;
 pub mod extensions;
+pub mod filters;
+pub mod fixtures;
+pub mod gamepad;
+#[cfg(all(target_os = "windows", feature = "windows-backend"))]
+pub mod hid;
+mod idle;
 pub mod input;
+pub mod ir;
+#[cfg(all(target_os = "linux", feature = "linux-backend"))]
+pub mod l2cap;
+mod latency;
+mod liveness;
+#[cfg(feature = "manager-thread")]
 mod manager;
+mod memory_stream;
+pub mod mock;
 mod native;
 pub mod output;
+mod poll;
+mod progress;
+mod reconnect;
+pub mod recording;
+mod report_id;
+#[cfg(feature = "tokio")]
+mod report_stream;
 mod result;
+mod rumble;
+mod session;
 mod simple_io;
+pub mod simulator;
+pub mod speaker;
+mod subscription;
+mod trace;
+mod worker;
 
+pub use poll::poll_all;
+
+/// The size of the buffers for a single report, larger than any report of the protocol (at most 22 bytes).
+///
+/// The Windows backend sizes its read buffers by the input report length of the HID device,
+/// longer reports only contain padding that is cut off when copied into buffers of this size.
+/// The Linux backend receives the L2CAP packets, a HID prefix byte and the report, into buffers of this size.
 pub const WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE: usize = 32;
 
 pub mod prelude {
-    pub use crate::device::{AccelerometerCalibration, AccelerometerData, WiimoteDevice};
+    pub use crate::backend::{Backend, BackendPreference};
+    pub use crate::battery::BatteryStatus;
+    pub use crate::calibration::{Deadzone, ResponseCurve, StickProcessing};
+    pub use crate::device::{
+        AccelerometerCalibration, AccelerometerData, CalibrationBlock, WiimoteCapabilities,
+        WiimoteDevice, WiimoteModel,
+    };
+    pub use crate::events::WiimoteEvent;
+    pub use crate::extensions::balance_board::*;
+    pub use crate::extensions::classic_controller::*;
     pub use crate::extensions::motion_plus::*;
-    pub use crate::manager::WiimoteManager;
+    pub use crate::extensions::nunchuck::*;
+    pub use crate::extensions::passthrough::*;
+    pub use crate::extensions::registry::*;
+    pub use crate::extensions::stillness::*;
+    pub use crate::extensions::weight::*;
+    pub use crate::extensions::BalanceBoardSession;
+    pub use crate::extensions::{
+        ConnectedExtension, ExtensionAxes, ExtensionCalibration, ExtensionInput, ExtensionState,
+        WiimoteExtension,
+    };
+    pub use crate::idle::{IdleAction, IdleConfig};
+    pub use crate::ir::camera::*;
+    pub use crate::ir::data::*;
+    pub use crate::ir::interleaved::*;
+    pub use crate::ir::pointer::*;
+    pub use crate::ir::tracker::*;
+    pub use crate::latency::ReportRate;
+    pub use crate::liveness::LivenessConfig;
+    #[cfg(all(feature = "manager-thread", feature = "tokio"))]
+    pub use crate::manager::NewDevicesStream;
+    #[cfg(feature = "manager-thread")]
+    pub use crate::manager::{WiimoteManager, WiimoteManagerBuilder};
+    pub use crate::memory_stream::MemoryReadStream;
+    pub use crate::native::{NativeWiimote, NativeWiimoteWriter};
+    pub use crate::poll::poll_all;
+    pub use crate::progress::ProgressObserver;
+    pub use crate::reconnect::ReconnectPolicy;
+    pub use crate::report_id::ReportId;
+    #[cfg(feature = "tokio")]
+    pub use crate::report_stream::ReportStream;
     pub use crate::result::*;
+    pub use crate::rumble::RumblePattern;
+    pub use crate::session::{
+        DuplicateFilter, DuplicateSuppression, ReportFields, SessionConfig, SessionFrame,
+        WiimoteSession,
+    };
+    pub use crate::speaker::*;
+    pub use crate::subscription::{ReportKind, ReportSubscription};
+    pub use crate::worker::{BackpressurePolicy, DeliveryConfig, ReportWorker};
     pub use crate::WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE;
 }
@@ -0,0 +1,177 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{RecvTimeoutError, Sender};
+
+use crate::device::SharedDevice;
+use crate::events::{EventPublisher, WiimoteEvent};
+use crate::output::OutputReport;
+
+/// The configuration of the liveness watchdog of a `WiimoteDevice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivenessConfig {
+    /// The time without input reports after which a status report is requested.
+    /// No requests are sent while the Wii remote reports data continuously.
+    pub probe_interval: Duration,
+    /// The time without input reports after which the Wii remote is disconnected.
+    pub deadline: Duration,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            probe_interval: Duration::from_secs(1),
+            deadline: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LivenessState {
+    config: Option<LivenessConfig>,
+    last_report: Instant,
+    last_probe: Option<Instant>,
+    /// A status report was requested, the data reporting mode is restored once it arrives.
+    probe_pending: bool,
+    /// The deadline passed, no further probes are sent until the Wii remote reconnects.
+    stalled: bool,
+    /// The transport could not be closed by the watchdog because a read was holding it,
+    /// the reader closes it and publishes [`WiimoteEvent::Disconnected`] instead.
+    close_pending: bool,
+}
+
+impl LivenessState {
+    /// Returns the time until the next probe or the deadline, `None` if there is nothing to wait for.
+    fn next_timeout(&self) -> Option<Duration> {
+        let config = self.config.filter(|_| !self.stalled)?;
+        let last_contact = self
+            .last_probe
+            .map_or(self.last_report, |probe| probe.max(self.last_report));
+        let probe =
+            (last_contact + config.probe_interval).saturating_duration_since(Instant::now());
+        let deadline =
+            (self.last_report + config.deadline).saturating_duration_since(Instant::now());
+        Some(probe.min(deadline))
+    }
+}
+
+/// Requests status reports from a silent Wii remote on a background thread
+/// and disconnects it if no input report arrives within the deadline.
+/// Input reports are observed while reading from the `WiimoteDevice`.
+pub(crate) struct LivenessWatchdog {
+    state: Arc<Mutex<LivenessState>>,
+    wake: Sender<()>,
+}
+
+impl LivenessWatchdog {
+    pub(crate) fn new(device: Arc<SharedDevice>, events: Arc<EventPublisher>) -> Self {
+        let state = Arc::new(Mutex::new(LivenessState {
+            config: None,
+            last_report: Instant::now(),
+            last_probe: None,
+            probe_pending: false,
+            stalled: false,
+            close_pending: false,
+        }));
+        let (wake, receiver) = crossbeam_channel::unbounded();
+
+        let thread_state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            // The thread ends when the watchdog is dropped together with the `WiimoteDevice`.
+            loop {
+                let timeout = lock(&thread_state).next_timeout();
+                let received = match timeout {
+                    Some(timeout) => receiver.recv_timeout(timeout),
+                    None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(()) => {}
+                    Err(RecvTimeoutError::Timeout) => Self::check(&thread_state, &device, &events),
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+
+        Self { state, wake }
+    }
+
+    fn check(state_mutex: &Mutex<LivenessState>, device: &SharedDevice, events: &EventPublisher) {
+        let mut state = lock(state_mutex);
+        let Some(config) = state.config else {
+            return;
+        };
+        if state.stalled {
+            return;
+        }
+
+        let silence = state.last_report.elapsed();
+        if silence >= config.deadline {
+            state.stalled = true;
+            state.close_pending = true;
+            drop(state);
+            log::warn!("Wii remote did not respond for {silence:?}, disconnecting");
+
+            // A blocked read keeps the transport locked, the reader closes it after its next time slice.
+            if device.try_disconnect() && std::mem::take(&mut lock(state_mutex).close_pending) {
+                events.publish(&WiimoteEvent::Disconnected);
+            }
+            return;
+        }
+
+        let last_contact = state
+            .last_probe
+            .map_or(state.last_report, |probe| probe.max(state.last_report));
+        if last_contact.elapsed() >= config.probe_interval {
+            state.last_probe = Some(Instant::now());
+            state.probe_pending = true;
+            drop(state);
+            _ = device.write(&OutputReport::StatusRequest);
+        }
+    }
+
+    /// Replaces the configuration, `None` disables the watchdog.
+    pub(crate) fn configure(&self, config: Option<LivenessConfig>) {
+        lock(&self.state).config = config;
+        self.reset();
+    }
+
+    /// Restarts the deadline, e.g. after the Wii remote reconnected.
+    pub(crate) fn reset(&self) {
+        let mut state = lock(&self.state);
+        state.last_report = Instant::now();
+        state.last_probe = None;
+        state.probe_pending = false;
+        state.stalled = false;
+        state.close_pending = false;
+        drop(state);
+        _ = self.wake.send(());
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        lock(&self.state).config.is_some()
+    }
+
+    /// Records that an input report was received.
+    pub(crate) fn observe(&self) {
+        lock(&self.state).last_report = Instant::now();
+    }
+
+    /// Returns true if the status report was requested by the watchdog,
+    /// so the data reporting mode it suspended needs to be restored.
+    pub(crate) fn take_probe_response(&self) -> bool {
+        std::mem::take(&mut lock(&self.state).probe_pending)
+    }
+
+    /// Returns true once if the deadline passed while a read was holding the transport,
+    /// the caller closes the transport and publishes [`WiimoteEvent::Disconnected`].
+    pub(crate) fn take_close_pending(&self) -> bool {
+        std::mem::take(&mut lock(&self.state).close_pending)
+    }
+}
+
+fn lock(state: &Mutex<LivenessState>) -> MutexGuard<'_, LivenessState> {
+    match state.lock() {
+        Ok(state) => state,
+        Err(err) => err.into_inner(),
+    }
+}
@@ -1,28 +1,146 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
 use std::time::Duration;
 
+use log::{debug, info, warn};
 use once_cell::sync::Lazy;
 
-use crate::device::WiimoteDevice;
-use crate::native::{wiimotes_scan, wiimotes_scan_cleanup, NativeWiimote};
+use crate::backend::BackendPreference;
+use crate::device::{SharedDevice, WiimoteDevice};
+use crate::native::{
+    normalize_identifier, wiimotes_scan_cleanup, wiimotes_scan_with, NativeWiimote,
+};
+use crate::progress::{self, ProgressObserver};
+use crate::trace::enter_span;
 
 type MutexWiimoteDevice = Arc<Mutex<WiimoteDevice>>;
 
+/// Serializes the scans of all managers, the platform backends are not safe to scan concurrently.
+static SCAN_LOCK: Mutex<()> = Mutex::new(());
+
+/// The identifiers of the devices connected by any manager, so a Wii remote is only opened once.
+static CLAIMED_DEVICES: Lazy<Mutex<HashSet<String>>> = Lazy::new(Mutex::default);
+
+/// Claims `identifier` for a manager, returns `false` if another manager already connected it.
+fn claim_device(identifier: &str) -> bool {
+    CLAIMED_DEVICES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(identifier.to_string())
+}
+
+/// Releases the claims of `identifiers` so other managers can connect the Wii remotes.
+fn release_devices<'a>(identifiers: impl IntoIterator<Item = &'a String>) {
+    let mut claimed = CLAIMED_DEVICES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    for identifier in identifiers {
+        claimed.remove(identifier);
+    }
+}
+
 /// Manages connections to Wii remotes.
 /// Periodically checks for new connections of Wii remotes.
 pub struct WiimoteManager {
+    /// The devices by their identifier normalized with [`normalize_identifier`].
     seen_devices: HashMap<String, MutexWiimoteDevice>,
+    /// The shared state of the seen devices, to cancel reads holding the lock of a device.
+    shared_devices: Vec<Arc<SharedDevice>>,
     scan_interval: Duration,
+    backend: BackendPreference,
+    progress_observer: Option<ProgressObserver>,
     new_devices_receiver: crossbeam_channel::Receiver<MutexWiimoteDevice>,
+    #[cfg(feature = "tokio")]
+    new_devices_stream_senders: Vec<tokio::sync::mpsc::UnboundedSender<MutexWiimoteDevice>>,
+}
+
+/// Configures a [`WiimoteManager`] separate from the [`WiimoteManager::get_instance`] singleton.
+///
+/// Several managers can run at the same time, their scans take turns and each Wii remote is
+/// connected by the first manager that finds it. The other managers skip it until that manager
+/// is dropped or [cleaned up](WiimoteManager::cleanup).
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use wiimote_rs::prelude::*;
+///
+/// let manager = WiimoteManager::builder()
+///     .scan_interval(Duration::from_secs(1))
+///     .backend(BackendPreference::Auto)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WiimoteManagerBuilder {
+    scan_interval: Duration,
+    backend: BackendPreference,
+}
+
+impl Default for WiimoteManagerBuilder {
+    fn default() -> Self {
+        Self {
+            scan_interval: Duration::from_millis(500),
+            backend: BackendPreference::Auto,
+        }
+    }
+}
+
+impl WiimoteManagerBuilder {
+    /// Sets the interval at which the manager scans for Wii remotes, 500 ms by default.
+    #[must_use]
+    pub const fn scan_interval(mut self, scan_interval: Duration) -> Self {
+        self.scan_interval = scan_interval;
+        self
+    }
+
+    /// Sets the backends the manager scans with, [`BackendPreference::Auto`] by default.
+    #[must_use]
+    pub const fn backend(mut self, backend: BackendPreference) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Creates the manager and starts scanning for Wii remotes.
+    /// Scanning stops once the manager is dropped.
+    #[must_use]
+    pub fn build(self) -> Arc<Mutex<WiimoteManager>> {
+        WiimoteManager::from_builder(self)
+    }
+}
+
+/// A `Stream` of newly connected Wii remotes, see [`WiimoteManager::new_devices_stream`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct NewDevicesStream {
+    receiver: tokio::sync::mpsc::UnboundedReceiver<MutexWiimoteDevice>,
+}
+
+#[cfg(feature = "tokio")]
+impl futures_core::Stream for NewDevicesStream {
+    type Item = MutexWiimoteDevice;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
 }
 
 impl WiimoteManager {
     /// Get the Wii remote manager instance.
+    /// It does not connect Wii remotes connected by a manager created with [`WiimoteManager::builder`].
     pub fn get_instance() -> Arc<Mutex<Self>> {
-        static mut SINGLETON: Lazy<Arc<Mutex<WiimoteManager>>> =
-            Lazy::new(|| WiimoteManager::new_with_interval(Duration::from_millis(500)));
-        unsafe { SINGLETON.clone() }
+        static SINGLETON: Lazy<Arc<Mutex<WiimoteManager>>> =
+            Lazy::new(|| WiimoteManager::builder().build());
+        Arc::clone(&SINGLETON)
+    }
+
+    /// Returns a builder for a manager with its own scan interval and backends.
+    #[must_use]
+    pub fn builder() -> WiimoteManagerBuilder {
+        WiimoteManagerBuilder::default()
     }
 
     /// Cleanup the Wii remote manager instance and disconnect all Wii remotes.
@@ -33,7 +151,12 @@ impl WiimoteManager {
                 Ok(m) => m,
                 Err(m) => m.into_inner(),
             };
+            release_devices(manager.seen_devices.keys());
             manager.seen_devices.clear();
+            // Threads blocked in reads would otherwise keep the devices connected until the next report.
+            for device in manager.shared_devices.drain(..) {
+                device.disconnect();
+            }
         }
         wiimotes_scan_cleanup();
     }
@@ -43,6 +166,12 @@ impl WiimoteManager {
         self.scan_interval = scan_interval;
     }
 
+    /// Set the observer notified of each step of connecting and reconnecting Wii remotes,
+    /// e.g. to show feedback while a Wii remote is connecting. `None` removes the observer.
+    pub fn set_progress_observer(&mut self, observer: Option<ProgressObserver>) {
+        self.progress_observer = observer;
+    }
+
     /// Collection of Wii remotes that are connected or have been connected previously.
     #[must_use]
     pub fn seen_devices(&self) -> Vec<MutexWiimoteDevice> {
@@ -55,13 +184,27 @@ impl WiimoteManager {
         self.new_devices_receiver.clone()
     }
 
-    fn new_with_interval(scan_interval: Duration) -> Arc<Mutex<Self>> {
+    /// Stream of newly connected Wii remotes for async applications.
+    /// Only devices connected after the stream was created are yielded.
+    #[cfg(feature = "tokio")]
+    pub fn new_devices_stream(&mut self) -> NewDevicesStream {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.new_devices_stream_senders.push(sender);
+        NewDevicesStream { receiver }
+    }
+
+    fn from_builder(builder: WiimoteManagerBuilder) -> Arc<Mutex<Self>> {
         let (new_devices_sender, new_devices_receiver) = crossbeam_channel::unbounded();
 
         let manager = Arc::new(Mutex::new(Self {
             seen_devices: HashMap::new(),
-            scan_interval,
+            shared_devices: Vec::new(),
+            scan_interval: builder.scan_interval,
+            backend: builder.backend,
+            progress_observer: None,
             new_devices_receiver,
+            #[cfg(feature = "tokio")]
+            new_devices_stream_senders: Vec::new(),
         }));
 
         let weak_manager = Arc::downgrade(&manager);
@@ -76,11 +219,20 @@ impl WiimoteManager {
                         };
 
                         let new_devices = manager.scan();
+                        #[cfg(feature = "tokio")]
+                        // Dropped streams are removed on every scan, also without new devices.
+                        manager.new_devices_stream_senders.retain(|sender| {
+                            !sender.is_closed()
+                                && new_devices
+                                    .iter()
+                                    .all(|device| sender.send(Arc::clone(device)).is_ok())
+                        });
                         let send_result = new_devices
                             .into_iter()
                             .try_for_each(|device| new_devices_sender.send(device));
                         if send_result.is_err() {
                             // Channel is disconnected, end scan thread
+                            debug!("Stopping Wii remote scan, the manager was dropped");
                             return;
                         }
 
@@ -98,25 +250,46 @@ impl WiimoteManager {
     /// Scan for connected Wii remotes.
     fn scan(&mut self) -> Vec<MutexWiimoteDevice> {
         let mut native_devices = Vec::new();
-        wiimotes_scan(&mut native_devices);
+        let observer = self.progress_observer.as_ref();
+        {
+            let _scan_guard = SCAN_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            for backend in self.backend.backends() {
+                enter_span!(DEBUG, "scan", backend = ?backend);
+                debug!("Scanning for Wii remotes with {backend:?}");
+                wiimotes_scan_with(backend, &mut native_devices, &|identifier, step| {
+                    progress::report(observer, identifier, step);
+                });
+            }
+        }
+        debug!("Wii remote scan found {} device(s)", native_devices.len());
 
         let mut new_devices = Vec::new();
 
-        for native_wiimote in native_devices {
-            let identifier = native_wiimote.identifier();
+        for (identifier, native_wiimote) in deduplicate(native_devices) {
             if let Some(existing_device) = self.seen_devices.get(&identifier) {
-                let result = existing_device.lock().unwrap().reconnect(native_wiimote);
-                if let Err(error) = result {
-                    eprintln!("Failed to reconnect wiimote: {error:?}");
+                let mut existing_device = existing_device.lock().unwrap();
+                existing_device.set_progress_observer(self.progress_observer.clone());
+                let result = existing_device.reconnect(native_wiimote);
+                drop(existing_device);
+                match result {
+                    Ok(()) => info!("Reconnected Wii remote {identifier}"),
+                    Err(error) => warn!("Failed to reconnect Wii remote {identifier}: {error:?}"),
                 }
+            } else if !claim_device(&identifier) {
+                debug!("Skipping Wii remote {identifier}, another manager connected it");
             } else {
-                match WiimoteDevice::new(native_wiimote) {
+                match WiimoteDevice::new(native_wiimote, self.progress_observer.clone()) {
                     Ok(device) => {
+                        info!("Connected Wii remote {identifier} ({:?})", device.model());
+                        self.shared_devices.push(device.shared_device());
                         let new_device = Arc::new(Mutex::new(device));
                         new_devices.push(Arc::clone(&new_device));
                         self.seen_devices.insert(identifier, new_device);
                     }
-                    Err(error) => eprintln!("Failed to connect to wiimote: {error:?}"),
+                    Err(error) => {
+                        warn!("Failed to connect to Wii remote {identifier}: {error:?}");
+                        release_devices([&identifier]);
+                    }
                 }
             }
         }
@@ -124,3 +297,69 @@ impl WiimoteManager {
         new_devices
     }
 }
+
+impl Drop for WiimoteManager {
+    fn drop(&mut self) {
+        release_devices(self.seen_devices.keys());
+    }
+}
+
+/// Drops the devices with the same Bluetooth address as an earlier device of the scan,
+/// e.g. a Wii remote enumerated with two HID interfaces or found by several backends.
+/// Returns the remaining devices with their normalized identifiers.
+fn deduplicate<T: NativeWiimote>(devices: Vec<T>) -> Vec<(String, T)> {
+    let mut identifiers = HashSet::new();
+    devices
+        .into_iter()
+        .filter_map(|device| {
+            let identifier = normalize_identifier(device.identifier());
+            if identifiers.insert(identifier.clone()) {
+                Some((identifier, device))
+            } else {
+                debug!("Skipping duplicate of Wii remote {identifier}");
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::SimulatedWiimote;
+
+    #[test]
+    fn test_deduplicate() {
+        let simulator = |identifier: &str| {
+            let simulator = SimulatedWiimote::default();
+            simulator.set_identifier(identifier);
+            simulator
+        };
+        let devices = vec![
+            simulator("00:19:FD:AA:BB:CC"),
+            simulator("0019fdaabbcc"),
+            simulator("00-19-FD-AA-BB-CD"),
+            simulator("custom"),
+            simulator("custom"),
+        ];
+
+        let identifiers: Vec<_> = deduplicate(devices)
+            .into_iter()
+            .map(|(identifier, _)| identifier)
+            .collect();
+        assert_eq!(
+            identifiers,
+            ["00:19:FD:AA:BB:CC", "00:19:FD:AA:BB:CD", "custom"]
+        );
+    }
+
+    #[test]
+    fn test_claim_device() {
+        let identifier = "claim-test".to_string();
+        assert!(claim_device(&identifier));
+        assert!(!claim_device(&identifier));
+        release_devices([&identifier]);
+        assert!(claim_device(&identifier));
+        release_devices([&identifier]);
+    }
+}
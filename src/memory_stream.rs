@@ -0,0 +1,121 @@
+use crate::input::{InputReport, MemoryData};
+use crate::output::{Addressing, OutputReport};
+use crate::prelude::*;
+use crate::simple_io::{read_setup_report, RETRY_COUNT};
+
+/// The memory data reports of a read larger than 16 bytes in the order they arrive,
+/// see [`WiimoteDevice::read_memory_stream`].
+///
+/// The iterator ends after all requested bytes were received or with the first error.
+pub struct MemoryReadStream<'a> {
+    wiimote: &'a WiimoteDevice,
+    addressing: Addressing,
+    received: u32,
+    finished: bool,
+}
+
+impl<'a> MemoryReadStream<'a> {
+    pub(crate) fn new(wiimote: &'a WiimoteDevice, addressing: Addressing) -> WiimoteResult<Self> {
+        wiimote.write(&OutputReport::ReadMemory(addressing))?;
+        Ok(Self {
+            wiimote,
+            addressing,
+            received: 0,
+            finished: addressing.size == 0,
+        })
+    }
+
+    /// Returns the number of bytes received so far.
+    #[must_use]
+    pub const fn received(&self) -> usize {
+        self.received as usize
+    }
+
+    /// Returns the total number of bytes requested.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.addressing.size as usize
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.addressing.size == 0
+    }
+
+    fn receive(&mut self) -> WiimoteResult<MemoryData> {
+        let address = self.addressing.address + self.received;
+        for _ in 0..RETRY_COUNT {
+            let memory_data = match read_setup_report(self.wiimote) {
+                Ok(InputReport::ReadMemory(memory_data)) => memory_data,
+                // Timed out or received an unrelated report
+                Ok(_) | Err(WiimoteError::WiimoteDeviceError(_)) => continue,
+                Err(err) => return Err(err),
+            };
+            if let Some(error) = memory_data.error() {
+                return Err(WiimoteDeviceError::Memory { address, error }.into());
+            }
+            #[allow(clippy::cast_possible_truncation)] // The report only contains the low bytes
+            if memory_data.address_offset() == address as u16 {
+                self.received += u32::from(memory_data.size());
+                return Ok(memory_data);
+            }
+        }
+        Err(WiimoteDeviceError::MissingData.into())
+    }
+}
+
+impl Iterator for MemoryReadStream<'_> {
+    type Item = WiimoteResult<MemoryData>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.receive();
+        self.finished = result.is_err() || self.received >= u32::from(self.addressing.size);
+        Some(result)
+    }
+}
+
+impl WiimoteDevice {
+    /// Requests `addressing.size` bytes and returns the memory data reports as they arrive,
+    /// e.g. to show the progress of dumping a large EEPROM region.
+    /// The Wii remote answers with a report of up to 16 bytes for each part of the memory.
+    ///
+    /// Discards reports other than the memory data, status reports still update the device state.
+    /// Do not read input reports at the same time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or the request could not be written,
+    /// errors of the memory reads are returned by the iterator.
+    pub fn read_memory_stream(
+        &self,
+        addressing: Addressing,
+    ) -> WiimoteResult<MemoryReadStream<'_>> {
+        MemoryReadStream::new(self, addressing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::SimulatedWiimote;
+
+    #[test]
+    fn test_read_memory_stream() {
+        let wiimote = WiimoteDevice::with_transport(SimulatedWiimote::default()).unwrap();
+
+        let stream = wiimote
+            .read_memory_stream(Addressing::eeprom(0x0016, 40))
+            .unwrap();
+        let sizes: Vec<u8> = stream.map(|data| data.unwrap().size()).collect();
+        assert_eq!(sizes, [16, 16, 8]);
+
+        let mut stream = wiimote
+            .read_memory_stream(Addressing::eeprom(0xFFFF, 2))
+            .unwrap();
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+}
@@ -0,0 +1,480 @@
+//! A scripted Wii remote to test code using a `WiimoteDevice` without hardware.
+//!
+//! ```
+//! use wiimote_rs::mock::MockWiimote;
+//! use wiimote_rs::prelude::*;
+//!
+//! let mock = MockWiimote::new();
+//! mock.attach_extension([0x00, 0x00, 0xA4, 0x20, 0x00, 0x00]);
+//! let wiimote = WiimoteDevice::with_transport(mock.clone()).unwrap();
+//! assert!(matches!(
+//!     wiimote.extension().map(|extension| extension.extension()),
+//!     Some(WiimoteExtension::Nunchuck)
+//! ));
+//! ```
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::prelude::*;
+
+const STATUS_REQUEST_ID: u8 = ReportId::StatusRequest.to_u8();
+const WRITE_MEMORY_ID: u8 = ReportId::WriteMemory.to_u8();
+const READ_MEMORY_ID: u8 = ReportId::ReadMemory.to_u8();
+const PLAYER_LED_ID: u8 = ReportId::PlayerLed.to_u8();
+
+const EXTENSION_REGISTERS: u32 = 0xA4_0000;
+const MOTION_PLUS_REGISTERS: u32 = 0xA6_0000;
+
+/// The calibration block of a typical Wii remote, stored at 0x0016 and 0x0020.
+pub(crate) const ACCELEROMETER_CALIBRATION: [u8; 10] =
+    [0x80, 0x80, 0x80, 0x00, 0x9A, 0x9A, 0x9A, 0x00, 0x40, 0xE3];
+
+/// A transport answering output reports from scripted memory instead of a Wii remote.
+///
+/// - Memory reads are answered from the scripted EEPROM and control registers.
+///   Unset EEPROM bytes read as 0, reading unset control registers fails with `MemoryError::WriteOnly`
+///   like reading the registers of a missing extension.
+/// - Memory writes are stored and acknowledged, status requests are answered with a status report.
+/// - Scripted failures are reported for reads and writes starting at the scripted address.
+/// - Reads return the queued input reports and the responses in order,
+///   0 bytes (no report) if the queue is empty instead of blocking.
+///
+/// Clones share their state, so a clone can script and inspect the mock after the original
+/// was passed to [`WiimoteDevice::with_transport`].
+#[derive(Clone, Default)]
+pub struct MockWiimote {
+    state: Arc<Mutex<MockState>>,
+}
+
+struct MockState {
+    identifier: &'static str,
+    model: WiimoteModel,
+    connected: bool,
+    eeprom: BTreeMap<u32, u8>,
+    registers: BTreeMap<u32, u8>,
+    read_errors: HashMap<u32, MemoryError>,
+    write_errors: HashMap<u32, MemoryError>,
+    battery_level: u8,
+    leds: u8,
+    input_reports: VecDeque<Vec<u8>>,
+    written_reports: Vec<Vec<u8>>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        let mut state = Self {
+            identifier: "00:00:00:00:00:00",
+            model: WiimoteModel::Original,
+            connected: true,
+            eeprom: BTreeMap::new(),
+            registers: BTreeMap::new(),
+            read_errors: HashMap::new(),
+            write_errors: HashMap::new(),
+            battery_level: 0xC8,
+            leds: 0,
+            input_reports: VecDeque::new(),
+            written_reports: Vec::new(),
+        };
+        store(&mut state.eeprom, 0x0016, &ACCELEROMETER_CALIBRATION);
+        store(&mut state.eeprom, 0x0020, &ACCELEROMETER_CALIBRATION);
+        state
+    }
+}
+
+/// The calibration of a typical Motion Plus stored at 0xA60020 with a valid checksum.
+/// The zero values are 0x7C00, the scales 0x3400 for 240 degrees in both the fast and slow mode.
+pub(crate) fn motion_plus_calibration() -> [u8; 32] {
+    let mut calibration = [0u8; 32];
+    for part in calibration.chunks_mut(16) {
+        part[..12].copy_from_slice(&[
+            0x7C, 0x00, 0x7C, 0x00, 0x7C, 0x00, 0x34, 0x00, 0x34, 0x00, 0x34, 0x00,
+        ]);
+        part[12] = 40;
+    }
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&calibration[0..14]);
+    hasher.update(&calibration[16..30]);
+    let checksum = hasher.finalize().to_be_bytes();
+    calibration[14..16].copy_from_slice(&checksum[0..2]);
+    calibration[30..32].copy_from_slice(&checksum[2..4]);
+    calibration
+}
+
+fn store(memory: &mut BTreeMap<u32, u8>, address: u32, data: &[u8]) {
+    for (offset, byte) in (0u32..).zip(data) {
+        memory.insert(address + offset, *byte);
+    }
+}
+
+impl MockWiimote {
+    /// Creates an original Wii remote without extension with a valid accelerometer calibration.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Sets the identifier of the mock and its clones. Each identifier set is kept until the program
+    /// exits, so the transport can hand out references to it. Meant for tests setting a few identifiers.
+    pub fn set_identifier(&self, identifier: &str) {
+        self.state().identifier = Box::leak(identifier.into());
+    }
+
+    pub fn set_model(&self, model: WiimoteModel) {
+        self.state().model = model;
+    }
+
+    /// Sets the raw battery level reported in status reports.
+    pub fn set_battery_level(&self, level: u8) {
+        self.state().battery_level = level;
+    }
+
+    /// Sets the EEPROM bytes starting at `address`.
+    pub fn set_eeprom(&self, address: u32, data: &[u8]) {
+        store(&mut self.state().eeprom, address, data);
+    }
+
+    /// Sets the control register bytes starting at `address`, e.g. 0xA400FA for the extension identifier.
+    pub fn set_registers(&self, address: u32, data: &[u8]) {
+        store(&mut self.state().registers, address, data);
+    }
+
+    /// Returns the control register byte at `address`, `None` if it was never set or written.
+    #[must_use]
+    pub fn register(&self, address: u32) -> Option<u8> {
+        self.state().registers.get(&address).copied()
+    }
+
+    /// Fails memory reads starting at `address` with `error`.
+    pub fn fail_reads(&self, address: u32, error: MemoryError) {
+        self.state().read_errors.insert(address, error);
+    }
+
+    /// Fails memory writes starting at `address` with `error`.
+    pub fn fail_writes(&self, address: u32, error: MemoryError) {
+        self.state().write_errors.insert(address, error);
+    }
+
+    /// Attaches an extension with the 6-byte identifier read from 0xA400FA.
+    /// The other extension registers, including the calibration data, are set to 0.
+    pub fn attach_extension(&self, identifier: [u8; 6]) {
+        let mut state = self.state();
+        store(&mut state.registers, EXTENSION_REGISTERS, &[0; 0x100]);
+        store(
+            &mut state.registers,
+            EXTENSION_REGISTERS + 0xFA,
+            &identifier,
+        );
+    }
+
+    /// Removes the extension registers, so the extension is no longer detected.
+    pub fn detach_extension(&self) {
+        self.state()
+            .registers
+            .retain(|address, _| address & 0xFF_0000 != EXTENSION_REGISTERS);
+    }
+
+    /// Attaches an external Motion Plus with a valid calibration.
+    /// Activating it does not move it to the extension registers.
+    pub fn attach_motion_plus(&self) {
+        let calibration = motion_plus_calibration();
+        let mut state = self.state();
+        store(&mut state.registers, MOTION_PLUS_REGISTERS, &[0; 0x100]);
+        store(
+            &mut state.registers,
+            MOTION_PLUS_REGISTERS + 0x20,
+            &calibration,
+        );
+        store(
+            &mut state.registers,
+            MOTION_PLUS_REGISTERS + 0xFA,
+            &[0x00, 0x00, 0xA6, 0x20, 0x00, 0x05],
+        );
+    }
+
+    /// Queues an input report returned by a following read, starting with the report ID.
+    pub fn push_input(&self, report: &[u8]) {
+        self.state().input_reports.push_back(report.to_vec());
+    }
+
+    /// Returns the output reports written to the mock, starting with the report ID.
+    #[must_use]
+    pub fn written_reports(&self) -> Vec<Vec<u8>> {
+        self.state().written_reports.clone()
+    }
+
+    /// Disconnects the mock, following reads and writes fail with `WiimoteError::Disconnected`.
+    pub fn disconnect(&self) {
+        self.state().connected = false;
+    }
+}
+
+impl MockState {
+    fn status_report(&self) -> Vec<u8> {
+        let extension_connected = self.registers.contains_key(&(EXTENSION_REGISTERS + 0xFA));
+        let flags = self.leds & 0xF0 | if extension_connected { 0x02 } else { 0x00 };
+        vec![0x20, 0, 0, flags, 0, 0, self.battery_level]
+    }
+
+    fn read_memory(&mut self, control_registers: bool, address: u32, size: u16) {
+        let mut offset = 0;
+        while offset < u32::from(size) {
+            let chunk_address = address + offset;
+            let chunk_size = (u32::from(size) - offset).min(16);
+            let mut data = [0u8; 16];
+            let mut error = self.read_errors.get(&chunk_address).copied();
+            for (index, byte) in (0..chunk_size).zip(&mut data) {
+                let memory_byte = if control_registers {
+                    self.registers.get(&(chunk_address + index)).copied()
+                } else {
+                    Some(
+                        self.eeprom
+                            .get(&(chunk_address + index))
+                            .copied()
+                            .unwrap_or(0),
+                    )
+                };
+                match memory_byte {
+                    Some(memory_byte) => *byte = memory_byte,
+                    None => error = error.or(Some(MemoryError::WriteOnly)),
+                }
+            }
+
+            let mut report = vec![
+                0x21,
+                0,
+                0,
+                ((chunk_size as u8 - 1) << 4) | error.map_or(0, MemoryError::code),
+            ];
+            report.extend_from_slice(&(chunk_address as u16).to_be_bytes());
+            report.extend_from_slice(&data);
+            self.input_reports.push_back(report);
+
+            if error.is_some() {
+                break;
+            }
+            offset += chunk_size;
+        }
+    }
+
+    fn write_memory(&mut self, control_registers: bool, address: u32, data: &[u8]) {
+        let error = self.write_errors.get(&address).copied();
+        if error.is_none() {
+            let memory = if control_registers {
+                &mut self.registers
+            } else {
+                &mut self.eeprom
+            };
+            store(memory, address, data);
+        }
+        let report = vec![
+            0x22,
+            0,
+            0,
+            WRITE_MEMORY_ID,
+            error.map_or(0, MemoryError::code),
+        ];
+        self.input_reports.push_back(report);
+    }
+
+    fn handle_output_report(&mut self, buffer: &[u8]) {
+        self.written_reports.push(buffer.to_vec());
+        let Some((&report_id, data)) = buffer.split_first() else {
+            return;
+        };
+        match report_id {
+            PLAYER_LED_ID if !data.is_empty() => self.leds = data[0],
+            STATUS_REQUEST_ID => self.input_reports.push_back(self.status_report()),
+            WRITE_MEMORY_ID if data.len() >= 21 => {
+                let control_registers = data[0] & 0x04 != 0;
+                let address = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+                let size = usize::from(data[4]).min(16);
+                self.write_memory(control_registers, address, &data[5..5 + size]);
+            }
+            READ_MEMORY_ID if data.len() >= 6 => {
+                let control_registers = data[0] & 0x04 != 0;
+                let address = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+                let size = u16::from_be_bytes([data[4], data[5]]);
+                self.read_memory(control_registers, address, size);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl NativeWiimote for MockWiimote {
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        let mut state = self.state();
+        if !state.connected {
+            return Err(WiimoteError::Disconnected);
+        }
+        let Some(report) = state.input_reports.pop_front() else {
+            return Ok(0);
+        };
+        let size = report.len().min(buffer.len());
+        buffer[..size].copy_from_slice(&report[..size]);
+        Ok(size)
+    }
+
+    fn read_timeout(&mut self, buffer: &mut [u8], _timeout_millis: usize) -> WiimoteResult<usize> {
+        self.read(buffer)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll_read(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+        buffer: &mut [u8],
+    ) -> std::task::Poll<WiimoteResult<usize>> {
+        // Reads never wait, a report is either queued or not available.
+        std::task::Poll::Ready(self.read(buffer))
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        let mut state = self.state();
+        if !state.connected {
+            return Err(WiimoteError::Disconnected);
+        }
+        state.handle_output_report(buffer);
+        Ok(buffer.len())
+    }
+
+    fn identifier(&self) -> &str {
+        self.state().identifier
+    }
+
+    fn model(&self) -> WiimoteModel {
+        self.state().model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize_without_extension() {
+        let mock = MockWiimote::new();
+        let wiimote = WiimoteDevice::with_transport(mock.clone()).unwrap();
+
+        assert_eq!(wiimote.identifier(), "00:00:00:00:00:00");
+        assert_eq!(
+            wiimote.accelerometer_calibration().block(),
+            CalibrationBlock::Primary
+        );
+        assert!(wiimote.motion_plus().is_none());
+        assert!(wiimote.extension().is_none());
+        assert!(!mock.written_reports().is_empty());
+    }
+
+    #[test]
+    fn test_clones_share_identifier() {
+        let mock = MockWiimote::new();
+        let transport = mock.clone();
+        mock.set_identifier("00:19:FD:AA:BB:CC");
+        assert_eq!(transport.identifier(), "00:19:FD:AA:BB:CC");
+    }
+
+    #[test]
+    fn test_secondary_calibration_block() {
+        let mock = MockWiimote::new();
+        mock.set_eeprom(0x0016 + 9, &[0x00]);
+        let wiimote = WiimoteDevice::with_transport(mock).unwrap();
+
+        assert_eq!(
+            wiimote.accelerometer_calibration().block(),
+            CalibrationBlock::Secondary
+        );
+    }
+
+    #[test]
+    fn test_calibration_read_error() {
+        let mock = MockWiimote::new();
+        // Only a failed checksum falls back to the block at 0x0020.
+        mock.fail_reads(0x0016, MemoryError::NonexistentAddress);
+
+        let result = WiimoteDevice::with_transport(mock);
+        assert!(matches!(
+            result,
+            Err(WiimoteError::InitError {
+                phase: InitPhase::ReadingCalibration,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_detect_extension() {
+        let mock = MockWiimote::new();
+        mock.attach_extension([0x00, 0x00, 0xA4, 0x20, 0x01, 0x01]);
+        let wiimote = WiimoteDevice::with_transport(mock.clone()).unwrap();
+
+        assert!(matches!(
+            wiimote.extension().map(|extension| extension.extension()),
+            Some(WiimoteExtension::ClassicController)
+        ));
+        assert_eq!(mock.register(0xA4_00F0), Some(0x55));
+    }
+
+    #[test]
+    fn test_motion_plus() {
+        let mock = MockWiimote::new();
+        mock.attach_motion_plus();
+        let wiimote = WiimoteDevice::with_transport(mock.clone()).unwrap();
+
+        let motion_plus = wiimote.motion_plus().unwrap();
+        assert!(matches!(
+            motion_plus.motion_plus_type(),
+            MotionPlusType::External
+        ));
+
+        wiimote
+            .enable_motion_plus(MotionPlusPolicy::Active)
+            .unwrap();
+        assert!(wiimote.motion_plus().unwrap().is_initialized());
+        assert_eq!(mock.register(0xA6_00FE), Some(0x04));
+    }
+
+    #[test]
+    fn test_exchange_skips_stale_responses() {
+        use crate::output::Addressing;
+        use crate::simple_io::{exchange, MemoryRequest, MemoryResponse};
+
+        let mock = MockWiimote::new();
+        let wiimote = WiimoteDevice::with_transport(mock.clone()).unwrap();
+        // The response to an earlier read of 0x0020 and the acknowledgement of another report.
+        let mut stale_read = vec![0x21, 0x00, 0x00, 0xF0, 0x00, 0x20];
+        stale_read.extend([0xFF; 16]);
+        mock.push_input(&stale_read);
+        mock.push_input(&[0x22, 0x00, 0x00, 0x12, 0x00]);
+
+        let requests = [
+            MemoryRequest::Read(Addressing::eeprom(0x0016, 10)),
+            MemoryRequest::Write(Addressing::control_registers(0xA4_00F0, 1), [0x55; 16]),
+        ];
+        let responses = exchange(&wiimote, &requests).unwrap();
+        assert!(matches!(
+            responses.as_slice(),
+            [MemoryResponse::Read(data), MemoryResponse::Write(ack)]
+                if data.address_offset() == 0x0016 && ack.report_number() == 0x16
+        ));
+        assert_eq!(mock.register(0xA4_00F0), Some(0x55));
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let mock = MockWiimote::new();
+        let wiimote = WiimoteDevice::with_transport(mock.clone()).unwrap();
+        mock.disconnect();
+
+        assert!(matches!(wiimote.read(), Err(WiimoteError::Disconnected)));
+        assert!(!wiimote.is_connected());
+    }
+}
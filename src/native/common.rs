@@ -1,6 +1,14 @@
 // Some functions are unused on certain platforms
 #![allow(dead_code)]
 
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+
+use crate::device::WiimoteModel;
+
 const WIIMOTE_VENDOR_ID: u16 = 0x057E;
 const WIIMOTE_PRODUCT_ID: u16 = 0x0306;
 const WIIMOTE_PLUS_PRODUCT_ID: u16 = 0x0330;
@@ -11,5 +19,379 @@ pub(super) const fn is_wiimote(vendor_id: u16, product_id: u16) -> bool {
 }
 
 pub(super) fn is_wiimote_device_name(name: &str) -> bool {
-    name == "Nintendo RVL-CNT-01" || name == "Nintendo RVL-CNT-01-TR"
+    matches!(
+        name,
+        "Nintendo RVL-CNT-01" | "Nintendo RVL-CNT-01-TR" | "Nintendo RVL-WBC-01"
+    )
+}
+
+/// Returns whether a device with the Bluetooth class of device can be a Wii remote,
+/// which reports the peripheral major device class (0x002504 or 0x000508).
+/// Devices without a meaningful major class are not ruled out.
+pub(super) const fn is_wiimote_device_class(class_of_device: u32) -> bool {
+    const MISCELLANEOUS: u32 = 0x00;
+    const PERIPHERAL: u32 = 0x05;
+    const UNCATEGORIZED: u32 = 0x1F;
+
+    let major_class = (class_of_device >> 8) & 0x1F;
+    matches!(major_class, PERIPHERAL | MISCELLANEOUS | UNCATEGORIZED)
+}
+
+/// Formats the Bluetooth address as the identifier of a Wii remote on all platforms,
+/// uppercase hexadecimal bytes separated by colons, e.g. `00:19:1D:AA:BB:CC`.
+pub(super) fn format_bluetooth_address(address: [u8; 6]) -> String {
+    let [a, b, c, d, e, f] = address;
+    format!("{a:02X}:{b:02X}:{c:02X}:{d:02X}:{e:02X}:{f:02X}")
+}
+
+/// Parses a Bluetooth address with `:` or `-` separators or without separators in any case,
+/// such as the HID serial number of a Wii remote on Windows. Trailing NUL bytes are ignored.
+pub(crate) fn parse_bluetooth_address(text: &str) -> Option<[u8; 6]> {
+    let digits: Vec<u8> = text
+        .trim_end_matches('\0')
+        .bytes()
+        .filter(|byte| !matches!(byte, b':' | b'-'))
+        .collect();
+    if digits.len() != 12 {
+        return None;
+    }
+
+    let mut address = [0u8; 6];
+    for (byte, pair) in address.iter_mut().zip(digits.chunks_exact(2)) {
+        let pair = std::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    Some(address)
+}
+
+/// Returns the identifier in the form of [`format_bluetooth_address`] if it contains a Bluetooth address.
+pub(crate) fn normalize_identifier(identifier: &str) -> String {
+    parse_bluetooth_address(identifier)
+        .map_or_else(|| identifier.to_string(), format_bluetooth_address)
+}
+
+pub(super) const fn model_from_product_id(product_id: u16) -> WiimoteModel {
+    if product_id == WIIMOTE_PLUS_PRODUCT_ID {
+        WiimoteModel::Plus
+    } else {
+        WiimoteModel::Original
+    }
+}
+
+pub(super) fn model_from_device_name(name: &str) -> WiimoteModel {
+    if name.ends_with("-TR") {
+        WiimoteModel::Plus
+    } else if name.ends_with("RVL-WBC-01") {
+        WiimoteModel::BalanceBoard
+    } else {
+        WiimoteModel::Original
+    }
+}
+
+/// The number of input reports queued per Wii remote by the I/O thread,
+/// about a third of a second at the 100 reports per second of continuous reporting.
+pub(super) const REPORT_QUEUE_CAPACITY: usize = 32;
+
+/// Counts the reports queued by the I/O threads for all devices,
+/// so a thread can wait for the next report of any of several devices.
+struct ReportSignal {
+    queued: Mutex<u64>,
+    changed: Condvar,
+}
+
+static REPORT_SIGNAL: ReportSignal = ReportSignal {
+    queued: Mutex::new(0),
+    changed: Condvar::new(),
+};
+
+impl ReportSignal {
+    fn lock(&self) -> MutexGuard<'_, u64> {
+        self.queued.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn notify(&self) {
+        let mut queued = self.lock();
+        *queued = queued.wrapping_add(1);
+        drop(queued);
+        self.changed.notify_all();
+    }
+}
+
+/// Returns the number of reports and disconnects queued for all devices so far.
+pub(crate) fn queued_report_count() -> u64 {
+    *REPORT_SIGNAL.lock()
+}
+
+/// Waits at most `timeout` until a report or disconnect was queued for any device
+/// after [`queued_report_count`] returned `count`.
+pub(crate) fn wait_for_queued_report(count: u64, timeout: Duration) {
+    let queued = REPORT_SIGNAL.lock();
+    _ = REPORT_SIGNAL
+        .changed
+        .wait_timeout_while(queued, timeout, |queued| *queued == count);
+}
+
+/// Creates a queue of at most `capacity` reports, which drops the oldest report when it is full.
+///
+/// The I/O threads of the backends never wait for a device, so a device that is not read
+/// receives the most recent reports once it is read again instead of growing the queue.
+pub(super) fn report_queue<T>(capacity: usize) -> (ReportSender<T>, ReportReceiver<T>) {
+    let queue = Arc::new(ReportQueue {
+        state: Mutex::new(ReportQueueState {
+            reports: VecDeque::with_capacity(capacity),
+            last_received: None,
+            sender_connected: true,
+            receiver_connected: true,
+        }),
+        available: Condvar::new(),
+        capacity: usize::max(capacity, 1),
+    });
+    (ReportSender(Arc::clone(&queue)), ReportReceiver(queue))
+}
+
+struct ReportQueue<T> {
+    state: Mutex<ReportQueueState<T>>,
+    /// Notified when a report was queued or the sender was dropped.
+    available: Condvar,
+    capacity: usize,
+}
+
+struct ReportQueueState<T> {
+    /// The reports with the time they were queued by the I/O thread.
+    reports: VecDeque<(Instant, T)>,
+    /// The time the report returned last was queued.
+    last_received: Option<Instant>,
+    sender_connected: bool,
+    receiver_connected: bool,
+}
+
+impl<T> ReportQueueState<T> {
+    fn pop(&mut self) -> Option<T> {
+        let (received, report) = self.reports.pop_front()?;
+        self.last_received = Some(received);
+        Some(report)
+    }
+}
+
+impl<T> ReportQueue<T> {
+    fn lock(&self) -> MutexGuard<'_, ReportQueueState<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The sending end of a [`report_queue`], disconnects the receiver when dropped.
+pub(super) struct ReportSender<T>(Arc<ReportQueue<T>>);
+
+impl<T> ReportSender<T> {
+    /// Queues the report and returns the oldest report if it was dropped to make room.
+    ///
+    /// # Errors
+    ///
+    /// Returns the report if the receiver was dropped.
+    pub(super) fn send(&self, report: T) -> Result<Option<T>, T> {
+        let mut state = self.0.lock();
+        if !state.receiver_connected {
+            return Err(report);
+        }
+        let dropped = if state.reports.len() >= self.0.capacity {
+            state.reports.pop_front().map(|(_, dropped)| dropped)
+        } else {
+            None
+        };
+        state.reports.push_back((Instant::now(), report));
+        drop(state);
+        self.0.available.notify_one();
+        REPORT_SIGNAL.notify();
+        Ok(dropped)
+    }
+}
+
+impl<T> Drop for ReportSender<T> {
+    fn drop(&mut self) {
+        self.0.lock().sender_connected = false;
+        self.0.available.notify_all();
+        REPORT_SIGNAL.notify();
+    }
+}
+
+/// The receiving end of a [`report_queue`].
+/// Queued reports are still received after the sender was dropped.
+pub(super) struct ReportReceiver<T>(Arc<ReportQueue<T>>);
+
+impl<T> ReportReceiver<T> {
+    /// Waits for the next report.
+    pub(super) fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.0.lock();
+        loop {
+            if let Some(report) = state.pop() {
+                return Ok(report);
+            }
+            if !state.sender_connected {
+                return Err(RecvError);
+            }
+            state = self
+                .0
+                .available
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Waits at most `timeout` for the next report.
+    pub(super) fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.0.lock();
+        loop {
+            if let Some(report) = state.pop() {
+                return Ok(report);
+            }
+            if !state.sender_connected {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                // The deadline is too far in the future to be represented.
+                None => timeout,
+            };
+            if remaining.is_zero() {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            state = self
+                .0
+                .available
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Returns the next report without waiting.
+    pub(super) fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.0.lock();
+        match state.pop() {
+            Some(report) => Ok(report),
+            None if state.sender_connected => Err(TryRecvError::Empty),
+            None => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.lock().reports.is_empty()
+    }
+
+    /// Returns when the report received last was queued by the I/O thread.
+    pub(super) fn last_received(&self) -> Option<Instant> {
+        self.0.lock().last_received
+    }
+
+    /// Returns whether the sender was dropped, the queued reports can still be received.
+    #[cfg(test)]
+    pub(super) fn is_disconnected(&self) -> bool {
+        !self.0.lock().sender_connected
+    }
+}
+
+impl<T> Drop for ReportReceiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.receiver_connected = false;
+        state.reports.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_identifier() {
+        assert_eq!(normalize_identifier("0019fdaabbcc"), "00:19:FD:AA:BB:CC");
+        assert_eq!(
+            normalize_identifier("00:19:FD:AA:BB:CC\0\0"),
+            "00:19:FD:AA:BB:CC"
+        );
+        assert_eq!(
+            normalize_identifier("00-19-fd-aa-bb-cc"),
+            "00:19:FD:AA:BB:CC"
+        );
+        assert_eq!(normalize_identifier("custom"), "custom");
+        assert_eq!(parse_bluetooth_address("0019fdaabbc"), None);
+        assert_eq!(parse_bluetooth_address("0019fdaabbcg"), None);
+        assert_eq!(
+            parse_bluetooth_address("00:19:FD:AA:BB:CC"),
+            Some([0x00, 0x19, 0xFD, 0xAA, 0xBB, 0xCC])
+        );
+    }
+
+    #[test]
+    fn test_report_queue_drops_oldest_reports() {
+        let (sender, receiver) = report_queue(2);
+        assert_eq!(sender.send(1), Ok(None));
+        assert_eq!(sender.send(2), Ok(None));
+        assert_eq!(sender.send(3), Ok(Some(1)));
+        assert_eq!(receiver.try_recv(), Ok(2));
+        assert_eq!(receiver.try_recv(), Ok(3));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_millis(1)),
+            Err(RecvTimeoutError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_wait_for_queued_report() {
+        let (sender, _receiver) = report_queue(2);
+        let count = queued_report_count();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            sender.send(1).unwrap();
+        });
+        let start = Instant::now();
+        wait_for_queued_report(count, Duration::from_secs(5));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_ne!(queued_report_count(), count);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_report_queue_timestamps() {
+        let (sender, receiver) = report_queue(2);
+        assert_eq!(receiver.last_received(), None);
+        let before = Instant::now();
+        sender.send(1).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(receiver.recv(), Ok(1));
+        // The time the report was queued, not the time it was received.
+        let queued = receiver.last_received().unwrap();
+        assert!(queued >= before && queued.elapsed() >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_report_queue_disconnect() {
+        let (sender, receiver) = report_queue(4);
+        sender.send(1).unwrap();
+        drop(sender);
+        assert_eq!(receiver.recv(), Ok(1));
+        assert_eq!(receiver.recv(), Err(RecvError));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+
+        let (sender, receiver) = report_queue(4);
+        drop(receiver);
+        assert_eq!(sender.send(1), Err(1));
+    }
+
+    #[test]
+    fn test_report_queue_wakes_receiver() {
+        let (sender, receiver) = report_queue(4);
+        let thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            sender.send(1).unwrap();
+        });
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(1));
+        thread.join().unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
 }
@@ -1,42 +1,83 @@
 mod bindings;
+mod multiplexer;
 
+use std::collections::HashMap;
 use std::ffi::c_int;
-
+#[cfg(feature = "tokio")]
+use std::os::fd::AsRawFd;
+use std::os::fd::RawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+#[cfg(feature = "tokio")]
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+
+use log::{debug, error, info, warn};
 use nix::errno::Errno;
 use nix::libc::{
-    connect, poll, pollfd, sockaddr, socket, write, AF_BLUETOOTH, POLLIN, SOCK_SEQPACKET,
+    connect, dup, fcntl, getsockopt, ioctl, poll, pollfd, recv, setsockopt, shutdown, sockaddr,
+    socket, socklen_t, timeval, write, AF_BLUETOOTH, F_GETFL, F_SETFL, MSG_DONTWAIT, O_NONBLOCK,
+    POLLIN, SHUT_RDWR, SOCK_SEQPACKET, SOL_SOCKET, SO_SNDTIMEO,
 };
-use nix::unistd::{close, read};
-
+use nix::unistd::close;
+use once_cell::sync::Lazy;
+#[cfg(feature = "tokio")]
+use tokio::io::unix::AsyncFd;
+#[cfg(feature = "tokio")]
+use tokio::io::Interest;
+
+use crate::device::WiimoteModel;
+use crate::l2cap::{control_pipe_writes, l2cap_config, L2capConfig};
+use crate::result::{InitPhase, OsOperation, WiimoteError, WiimoteResult};
 use crate::WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE;
 
 use self::bindings::{
-    ba2str, bdaddr_t, hci_get_route, hci_inquiry, hci_open_dev, hci_read_remote_name, inquiry_info,
-    sockaddr_l2, BTPROTO_L2CAP, IREQ_CACHE_FLUSH,
+    bdaddr_t, hci_conn_info, hci_get_route, hci_inquiry, hci_open_dev, hci_read_link_policy,
+    hci_read_remote_name, hci_write_link_policy, inquiry_info, l2cap_options, sockaddr_l2,
+    ACL_LINK, BTPROTO_L2CAP, HCI_LP_SNIFF, IREQ_CACHE_FLUSH, L2CAP_OPTIONS, SOL_L2CAP,
 };
 
-use super::common::is_wiimote_device_name;
-use super::NativeWiimote;
+use self::multiplexer::ReadRegistration;
+use super::common::{
+    format_bluetooth_address, is_wiimote_device_class, is_wiimote_device_name,
+    model_from_device_name, parse_bluetooth_address,
+};
+use super::{NativeWiimote, NativeWiimoteWriter};
 
 const MAX_INQUIRIES: i32 = 255;
 const SCAN_SECONDS: i32 = 6;
 const MAX_NAME_LENGTH: i32 = 250;
 
+/// How long the result of a remote name lookup is reused before the name is read again.
+const REMOTE_NAME_EXPIRY: Duration = Duration::from_secs(10 * 60);
+
 const CONTROL_PIPE_ID: u16 = 0x0011;
 const DATA_PIPE_ID: u16 = 0x0013;
 
-unsafe fn connect_socket(address: sockaddr_l2) -> Option<c_int> {
+/// `_IOR('H', 213, int)` from `hci.h`, a function-like macro without a binding.
+const HCIGETCONNINFO: u32 = 0x8004_48D5;
+/// The timeout of HCI commands sent to the local adapter.
+const HCI_COMMAND_TIMEOUT_MILLIS: c_int = 1000;
+
+unsafe fn connect_socket(address: sockaddr_l2, config: &L2capConfig) -> Option<c_int> {
     let socket_fd = socket(AF_BLUETOOTH as _, SOCK_SEQPACKET as _, BTPROTO_L2CAP as _);
     if socket_fd < 0 {
-        eprintln!("Unable to open socket to Wiimote: {}", Errno::last().desc());
+        warn!(
+            "Unable to open socket to Wii remote: {}",
+            Errno::last().desc()
+        );
         return None;
     }
+    if let Some(mtu) = config.mtu {
+        set_mtu(socket_fd, mtu);
+    }
 
     let address_ptr = std::ptr::addr_of!(address).cast::<sockaddr>();
     let address_size = std::mem::size_of_val(&address);
     if connect(socket_fd, address_ptr, address_size as _) < 0 {
-        eprintln!(
-            "Unable to connect channel of Wiimote: {}",
+        let psm = address.l2_psm;
+        warn!(
+            "Unable to connect channel {psm:#06x} of Wii remote: {}",
             Errno::last().desc()
         );
         _ = close(socket_fd);
@@ -45,33 +86,175 @@ unsafe fn connect_socket(address: sockaddr_l2) -> Option<c_int> {
     Some(socket_fd)
 }
 
-unsafe fn handle_wiimote(bdaddr: bdaddr_t) -> Option<LinuxNativeWiimote> {
+/// Sets the incoming and outgoing MTU of an unconnected L2CAP socket.
+unsafe fn set_mtu(socket_fd: c_int, mtu: u16) {
+    let mut options = std::mem::zeroed::<l2cap_options>();
+    let options_ptr = std::ptr::addr_of_mut!(options).cast();
+    let mut options_size = std::mem::size_of::<l2cap_options>() as socklen_t;
+    if getsockopt(
+        socket_fd,
+        SOL_L2CAP as _,
+        L2CAP_OPTIONS as _,
+        options_ptr,
+        &mut options_size,
+    ) < 0
+    {
+        warn!("Unable to read L2CAP options: {}", Errno::last().desc());
+        return;
+    }
+
+    options.imtu = mtu;
+    options.omtu = mtu;
+    if setsockopt(
+        socket_fd,
+        SOL_L2CAP as _,
+        L2CAP_OPTIONS as _,
+        options_ptr,
+        options_size,
+    ) < 0
+    {
+        warn!("Unable to set L2CAP MTU {mtu}: {}", Errno::last().desc());
+    }
+}
+
+/// Applies the send timeout and the non-blocking flag of the configuration to the connected data socket.
+unsafe fn configure_data_socket(data_socket: c_int, config: &L2capConfig) {
+    if let Some(send_timeout) = config.send_timeout {
+        let timeout = timeval {
+            tv_sec: send_timeout.as_secs() as _,
+            tv_usec: send_timeout.subsec_micros() as _,
+        };
+        if setsockopt(
+            data_socket,
+            SOL_SOCKET,
+            SO_SNDTIMEO,
+            std::ptr::addr_of!(timeout).cast(),
+            std::mem::size_of::<timeval>() as socklen_t,
+        ) < 0
+        {
+            warn!("Unable to set send timeout: {}", Errno::last().desc());
+        }
+    }
+
+    if config.non_blocking {
+        let flags = fcntl(data_socket, F_GETFL);
+        if flags < 0 || fcntl(data_socket, F_SETFL, flags | O_NONBLOCK) < 0 {
+            warn!(
+                "Unable to make data socket non-blocking: {}",
+                Errno::last().desc()
+            );
+        }
+    }
+}
+
+/// The request of `HCIGETCONNINFO` with room for the information of one connection.
+#[repr(C)]
+struct ConnectionInfoRequest {
+    bdaddr: bdaddr_t,
+    link_type: u8,
+    info: hci_conn_info,
+}
+
+/// Removes sniff mode from the link policy of the ACL connection to the Wii remote.
+unsafe fn disable_sniff_mode(bdaddr: bdaddr_t) {
+    let mut remote = bdaddr;
+    let bt_device_id = hci_get_route(&mut remote);
+    let bt_socket = hci_open_dev(bt_device_id);
+    if bt_device_id < 0 || bt_socket < 0 {
+        warn!(
+            "Failed to open bluetooth device to disable sniff mode: {}",
+            Errno::last().desc()
+        );
+        return;
+    }
+
+    let mut request = ConnectionInfoRequest {
+        bdaddr,
+        link_type: ACL_LINK as _,
+        info: std::mem::zeroed(),
+    };
+    let mut policy = 0u16;
+    if ioctl(
+        bt_socket,
+        HCIGETCONNINFO as _,
+        std::ptr::addr_of_mut!(request),
+    ) < 0
+    {
+        warn!(
+            "Unable to find connection to disable sniff mode: {}",
+            Errno::last().desc()
+        );
+    } else if hci_read_link_policy(
+        bt_socket,
+        request.info.handle,
+        &mut policy,
+        HCI_COMMAND_TIMEOUT_MILLIS,
+    ) < 0
+        || hci_write_link_policy(
+            bt_socket,
+            request.info.handle,
+            policy & !(HCI_LP_SNIFF as u16),
+            HCI_COMMAND_TIMEOUT_MILLIS,
+        ) < 0
+    {
+        warn!("Unable to disable sniff mode: {}", Errno::last().desc());
+    } else {
+        debug!(
+            "Disabled sniff mode of Wii remote {}",
+            bdaddr_identifier(&bdaddr)
+        );
+    }
+    _ = close(bt_socket);
+}
+
+/// Returns the identifier of the Wii remote with the address, the bytes of `bdaddr_t` are stored in reverse order.
+fn bdaddr_identifier(bdaddr: &bdaddr_t) -> String {
+    let mut address = bdaddr.b;
+    address.reverse();
+    format_bluetooth_address(address)
+}
+
+unsafe fn handle_wiimote(bdaddr: bdaddr_t, model: WiimoteModel) -> Option<LinuxNativeWiimote> {
     let mut addr = std::mem::zeroed::<sockaddr_l2>();
     addr.l2_family = AF_BLUETOOTH as _;
     addr.l2_bdaddr = bdaddr;
 
+    let config = l2cap_config();
     addr.l2_psm = CONTROL_PIPE_ID;
-    let control_socket = connect_socket(addr)?;
+    let control_socket = connect_socket(addr, &config)?;
 
     addr.l2_psm = DATA_PIPE_ID;
-    let data_socket = connect_socket(addr);
+    let data_socket = connect_socket(addr, &config);
     if data_socket.is_none() {
         _ = close(control_socket);
         return None;
     }
 
-    let mut address_string = [0u8; 19];
-    ba2str(&bdaddr, address_string.as_mut_ptr().cast());
-
-    let address = String::from_utf8_lossy(&address_string);
-    Some(LinuxNativeWiimote::new(
+    let address = bdaddr_identifier(&bdaddr);
+    info!("Opened control and data channels of Wii remote {address}");
+    let data_socket = data_socket.unwrap();
+    configure_data_socket(data_socket, &config);
+    if config.disable_sniff_mode {
+        disable_sniff_mode(bdaddr);
+    }
+    match LinuxNativeWiimote::new(
         &address,
+        model,
         control_socket,
-        data_socket.unwrap(),
-    ))
+        data_socket,
+        control_pipe_writes(&address),
+    ) {
+        Ok(wiimote) => Some(wiimote),
+        Err(err) => {
+            error!("Failed to read from Wii remote {address}: {err:?}");
+            _ = close(control_socket);
+            _ = close(data_socket);
+            None
+        }
+    }
 }
 
-pub fn wiimotes_scan(wiimotes: &mut Vec<LinuxNativeWiimote>) {
+pub fn wiimotes_scan(wiimotes: &mut Vec<LinuxNativeWiimote>, progress: &dyn Fn(&str, InitPhase)) {
     unsafe {
         let mut infos = Vec::with_capacity(MAX_INQUIRIES as _);
         for _ in 0..MAX_INQUIRIES {
@@ -81,13 +264,14 @@ pub fn wiimotes_scan(wiimotes: &mut Vec<LinuxNativeWiimote>) {
         let bt_device_id = hci_get_route(std::ptr::null_mut());
         let bt_socket = hci_open_dev(bt_device_id);
         if bt_device_id < 0 || bt_socket < 0 {
-            eprintln!(
+            error!(
                 "Failed to open default bluetooth device: {}",
                 Errno::last().desc()
             );
             return;
         }
 
+        debug!("Scanning for bluetooth devices for {SCAN_SECONDS} seconds");
         let device_count = hci_inquiry(
             bt_device_id,
             SCAN_SECONDS,
@@ -98,7 +282,7 @@ pub fn wiimotes_scan(wiimotes: &mut Vec<LinuxNativeWiimote>) {
         );
         if device_count < 0 {
             _ = close(bt_socket);
-            eprintln!(
+            error!(
                 "hci_inquiry failed while scanning for bluetooth devices: {}",
                 Errno::last().desc()
             );
@@ -106,23 +290,15 @@ pub fn wiimotes_scan(wiimotes: &mut Vec<LinuxNativeWiimote>) {
         }
 
         for info in infos.iter().take(device_count as _) {
-            let mut name = [0u8; (MAX_NAME_LENGTH + 1) as _];
-
-            if hci_read_remote_name(
-                bt_socket,
-                &info.bdaddr,
-                MAX_NAME_LENGTH,
-                name.as_mut_ptr().cast(),
-                0,
-            ) < 0
-            {
+            let [minor, major, service] = info.dev_class;
+            let class_of_device = u32::from_le_bytes([minor, major, service, 0]);
+            if !is_wiimote_device_class(class_of_device) {
                 continue;
             }
 
-            let name_length = name.iter().position(|&c| c == 0).unwrap();
-            let name = String::from_utf8_lossy(&name[..name_length]);
-            if is_wiimote_device_name(&name) {
-                if let Some(wiimote) = handle_wiimote(info.bdaddr) {
+            if let Some(model) = remote_wiimote_model(bt_socket, &info.bdaddr) {
+                progress(&bdaddr_identifier(&info.bdaddr), InitPhase::OpeningHid);
+                if let Some(wiimote) = handle_wiimote(info.bdaddr, model) {
                     wiimotes.push(wiimote);
                 }
             }
@@ -132,102 +308,441 @@ pub fn wiimotes_scan(wiimotes: &mut Vec<LinuxNativeWiimote>) {
     }
 }
 
+/// Connects to the Wii remote with the Bluetooth address `identifier` again without scanning.
+pub fn wiimote_reopen(identifier: &str, model: WiimoteModel) -> Option<LinuxNativeWiimote> {
+    let mut address = parse_bluetooth_address(identifier)?;
+    address.reverse();
+    unsafe { handle_wiimote(bdaddr_t { b: address }, model) }
+}
+
+/// Reads the name of the remote device to check whether it is a Wii remote.
+///
+/// Reading the name can take seconds for unrelated devices, so the result is cached per address
+/// for [`REMOTE_NAME_EXPIRY`]. Failed lookups are not cached and retried on the next scan.
+unsafe fn remote_wiimote_model(bt_socket: c_int, bdaddr: &bdaddr_t) -> Option<WiimoteModel> {
+    /// The model of the device and the time of the lookup by address,
+    /// `None` for devices that are not Wii remotes.
+    type RemoteNames = HashMap<[u8; 6], (Option<WiimoteModel>, Instant)>;
+    static REMOTE_NAMES: Lazy<Mutex<RemoteNames>> = Lazy::new(Mutex::default);
+
+    let now = Instant::now();
+    {
+        let mut remote_names = REMOTE_NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+        remote_names
+            .retain(|_, (_, looked_up)| now.duration_since(*looked_up) < REMOTE_NAME_EXPIRY);
+        if let Some((model, _)) = remote_names.get(&bdaddr.b) {
+            return *model;
+        }
+    }
+
+    let mut name = [0u8; (MAX_NAME_LENGTH + 1) as _];
+    if hci_read_remote_name(
+        bt_socket,
+        bdaddr,
+        MAX_NAME_LENGTH,
+        name.as_mut_ptr().cast(),
+        0,
+    ) < 0
+    {
+        return None;
+    }
+
+    let name_length = name.iter().position(|&c| c == 0).unwrap();
+    let name = String::from_utf8_lossy(&name[..name_length]);
+    debug!("Found bluetooth device {name:?}");
+    let model = is_wiimote_device_name(&name).then(|| model_from_device_name(&name));
+    REMOTE_NAMES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(bdaddr.b, (model, now));
+    model
+}
+
 pub const fn wiimotes_scan_cleanup() {}
 
 pub struct LinuxNativeWiimote {
     address: String,
+    model: WiimoteModel,
     control_socket: c_int,
     data_socket: c_int,
+    /// Whether output reports are written to the control socket, shared with the writers.
+    control_pipe: Arc<AtomicBool>,
+    /// Input reports received from the data socket on the I/O thread.
+    reads: ReadRegistration,
+    /// Registration of the readiness of `reads` with the tokio reactor, created on the first async read.
+    #[cfg(feature = "tokio")]
+    async_ready: Option<AsyncFd<ReadyFd>>,
+}
+
+#[cfg(feature = "tokio")]
+struct ReadyFd(c_int);
+
+#[cfg(feature = "tokio")]
+impl AsRawFd for ReadyFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
 }
 
 impl LinuxNativeWiimote {
-    fn new(address: &str, control_socket: c_int, data_socket: c_int) -> Self {
-        Self {
+    fn new(
+        address: &str,
+        model: WiimoteModel,
+        control_socket: c_int,
+        data_socket: c_int,
+        control_pipe_writes: bool,
+    ) -> WiimoteResult<Self> {
+        let reads = multiplexer::register(data_socket)?;
+        Ok(Self {
             address: address.to_string(),
+            model,
             control_socket,
             data_socket,
-        }
+            control_pipe: Arc::new(AtomicBool::new(control_pipe_writes)),
+            reads,
+            #[cfg(feature = "tokio")]
+            async_ready: None,
+        })
     }
+}
 
-    fn read_timeout_impl(
-        &mut self,
-        buffer: &mut [u8],
-        timeout_millis: Option<i32>,
-    ) -> Option<usize> {
-        const TIMED_OUT: i32 = 0;
-        let mut read_poll = unsafe { std::mem::zeroed::<pollfd>() };
-        read_poll.fd = self.data_socket;
-        read_poll.events = POLLIN;
+/// Removes the HID prefix byte from the received input report in place.
+fn strip_input_prefix(report: &mut [u8], bytes_read: usize) -> WiimoteResult<usize> {
+    if bytes_read == 0 {
+        return Err(WiimoteError::Disconnected);
+    }
 
-        let mut fds = [read_poll];
+    debug_assert!(report[0] == INPUT_PREFIX);
+    report.copy_within(1..bytes_read, 0);
 
-        let result = unsafe { poll(fds.as_mut_ptr(), 1, timeout_millis.unwrap_or(-1)) };
-        if result == TIMED_OUT {
-            return Some(0);
-        }
-        if result < 0 {
-            return None;
-        }
+    Ok(bytes_read - 1)
+}
+
+#[cfg(feature = "tokio")]
+fn io_error(error: &std::io::Error) -> WiimoteError {
+    WiimoteError::Os {
+        code: error.raw_os_error().unwrap_or_default(),
+        operation: OsOperation::Read,
+    }
+}
 
-        let mut read_buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+const fn os_error(errno: Errno, operation: OsOperation) -> WiimoteError {
+    WiimoteError::Os {
+        code: errno as i32,
+        operation,
+    }
+}
 
-        let max_data_size = usize::min(read_buffer.len() - 1, buffer.len());
-        let bytes_read = read(self.data_socket, &mut read_buffer[..max_data_size]).ok()?;
-        if bytes_read == 0 {
-            return None;
+const INPUT_PREFIX: u8 = 0xA1;
+const OUTPUT_PREFIX: u8 = 0xA2;
+
+/// The HIDP SET_REPORT transaction header for output reports on the control channel.
+const SET_REPORT_OUTPUT_PREFIX: u8 = 0x52;
+/// How long a write to the control channel waits for the HANDSHAKE reply of the SET_REPORT transaction.
+const HANDSHAKE_TIMEOUT_MILLIS: i32 = 20;
+
+/// Writes an output report to the data socket, or to the control socket as a SET_REPORT
+/// transaction once `control_pipe` is set.
+///
+/// Some original Wii remotes reject output reports on the data channel, the first rejected
+/// write sets `control_pipe` so all further writes of the device use the control channel.
+/// Other errors are returned without falling back, see [`rejects_output_reports`].
+fn write_output_report(
+    data_socket: c_int,
+    control_socket: c_int,
+    control_pipe: &AtomicBool,
+    buffer: &[u8],
+) -> WiimoteResult<usize> {
+    if !control_pipe.load(Ordering::Relaxed) {
+        match send_report(data_socket, OUTPUT_PREFIX, buffer) {
+            Err(errno) if rejects_output_reports(errno) => {
+                warn!(
+                    "Data channel rejected output report ({}), writing to the control channel",
+                    errno.desc()
+                );
+                control_pipe.store(true, Ordering::Relaxed);
+            }
+            result => return report_write_result(result),
         }
+    }
+
+    let result = send_report(control_socket, SET_REPORT_OUTPUT_PREFIX, buffer);
+    if result.is_ok() {
+        receive_handshake(control_socket);
+    }
+    report_write_result(result)
+}
 
-        debug_assert!(read_buffer[0] == INPUT_PREFIX);
-        buffer[..bytes_read - 1].copy_from_slice(&read_buffer[1..bytes_read]);
+/// Writes the report with the prefix byte, returns the number of bytes written without the prefix.
+fn send_report(socket: c_int, prefix: u8, buffer: &[u8]) -> Result<usize, Errno> {
+    let mut write_buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+    write_buffer[0] = prefix;
 
-        Some(bytes_read - 1)
+    let data_bytes = usize::min(write_buffer.len() - 1, buffer.len());
+    write_buffer[1..=data_bytes].copy_from_slice(&buffer[..data_bytes]);
+
+    let bytes_written = unsafe { write(socket, write_buffer.as_ptr().cast(), data_bytes + 1) };
+    if bytes_written < 0 {
+        Err(Errno::last())
+    } else {
+        Ok((bytes_written as usize).saturating_sub(1))
     }
 }
 
-const INPUT_PREFIX: u8 = 0xA1;
-const OUTPUT_PREFIX: u8 = 0xA2;
+/// Returns true for the errors of a data channel that does not accept output reports.
+///
+/// Disconnects, timeouts and resource errors such as `ENOBUFS`, `ENOMEM`, `EINVAL` or `EMSGSIZE`
+/// are not rejections, the control channel would fail with them as well.
+const fn rejects_output_reports(errno: Errno) -> bool {
+    matches!(errno, Errno::EOPNOTSUPP | Errno::EPROTO)
+}
+
+fn report_write_result(result: Result<usize, Errno>) -> WiimoteResult<usize> {
+    match result {
+        Ok(0) => Err(WiimoteError::Disconnected),
+        Ok(bytes_written) => Ok(bytes_written),
+        // The send timeout elapsed or the non-blocking socket is full, see `L2capConfig`.
+        Err(Errno::EAGAIN) => Err(WiimoteError::WriteTimedOut),
+        Err(errno) => Err(os_error(errno, OsOperation::Write)),
+    }
+}
+
+/// Waits for the HANDSHAKE reply to the SET_REPORT transaction just written and reads it,
+/// so the replies do not fill the receive buffer. Replies to earlier transactions that
+/// timed out are read as well.
+fn receive_handshake(control_socket: c_int) {
+    let mut fd = pollfd {
+        fd: control_socket,
+        events: POLLIN,
+        revents: 0,
+    };
+    // The write completed once the reply arrived, a missing reply only leaves it unread.
+    unsafe { poll(&mut fd, 1, HANDSHAKE_TIMEOUT_MILLIS) };
+
+    let mut buffer = [0u8; 8];
+    while unsafe {
+        recv(
+            control_socket,
+            buffer.as_mut_ptr().cast(),
+            buffer.len(),
+            MSG_DONTWAIT,
+        )
+    } > 0
+    {}
+}
 
 impl NativeWiimote for LinuxNativeWiimote {
-    fn read(&mut self, buffer: &mut [u8]) -> Option<usize> {
-        self.read_timeout_impl(buffer, None)
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        self.reads.receive(buffer, None)
+    }
+
+    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize> {
+        let timeout = Duration::from_millis(timeout_millis as u64);
+        self.reads.receive(buffer, Some(timeout))
     }
 
-    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> Option<usize> {
-        self.read_timeout_impl(
+    #[cfg(feature = "tokio")]
+    fn poll_read(&mut self, cx: &mut Context<'_>, buffer: &mut [u8]) -> Poll<WiimoteResult<usize>> {
+        let async_ready = match self.async_ready.take() {
+            Some(async_ready) => async_ready,
+            None => AsyncFd::with_interest(ReadyFd(self.reads.ready_fd()), Interest::READABLE)
+                .map_err(|err| io_error(&err))?,
+        };
+        let async_ready = self.async_ready.insert(async_ready);
+
+        loop {
+            let mut guard =
+                ready!(async_ready.poll_read_ready(cx)).map_err(|err| io_error(&err))?;
+            match self.reads.try_receive(buffer) {
+                // The readiness was signaled for a report that was already read.
+                Ok(0) => guard.clear_ready(),
+                result => return Poll::Ready(result),
+            }
+        }
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        write_output_report(
+            self.data_socket,
+            self.control_socket,
+            &self.control_pipe,
             buffer,
-            Some(i32::try_from(timeout_millis).expect("Invalid read timeout")),
         )
     }
 
-    fn write(&mut self, buffer: &[u8]) -> Option<usize> {
-        let mut write_buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
-        write_buffer[0] = OUTPUT_PREFIX;
+    fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+        let data_socket = unsafe { dup(self.data_socket) };
+        if data_socket < 0 {
+            warn!(
+                "Failed to duplicate data socket of {}: {}",
+                self.address,
+                Errno::last().desc()
+            );
+            return None;
+        }
+        let control_socket = unsafe { dup(self.control_socket) };
+        if control_socket < 0 {
+            warn!(
+                "Failed to duplicate control socket of {}: {}",
+                self.address,
+                Errno::last().desc()
+            );
+            _ = close(data_socket);
+            return None;
+        }
+        Some(Box::new(LinuxWiimoteWriter {
+            data_socket,
+            control_socket,
+            control_pipe: Arc::clone(&self.control_pipe),
+        }))
+    }
 
-        let data_bytes = usize::min(write_buffer.len() - 1, buffer.len());
-        write_buffer[1..=data_bytes].copy_from_slice(&buffer[..data_bytes]);
+    fn readiness_fd(&self) -> Option<RawFd> {
+        Some(self.reads.ready_fd())
+    }
 
-        let bytes_written = unsafe {
-            write(
-                self.data_socket,
-                write_buffer.as_ptr().cast(),
-                data_bytes + 1,
-            )
-        };
-        if bytes_written <= 0 {
-            None
-        } else {
-            Some((bytes_written - 1) as _)
+    fn last_received(&self) -> Option<Instant> {
+        self.reads.last_received()
+    }
+
+    fn identifier(&self) -> &str {
+        &self.address
+    }
+
+    fn model(&self) -> WiimoteModel {
+        self.model
+    }
+}
+
+/// Writes output reports to duplicates of the sockets, so writes do not wait for a blocking read.
+struct LinuxWiimoteWriter {
+    data_socket: c_int,
+    control_socket: c_int,
+    control_pipe: Arc<AtomicBool>,
+}
+
+impl NativeWiimoteWriter for LinuxWiimoteWriter {
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        write_output_report(
+            self.data_socket,
+            self.control_socket,
+            &self.control_pipe,
+            buffer,
+        )
+    }
+
+    /// Shuts the data socket down, the I/O thread then receives the end of the stream
+    /// and dispatches `WiimoteError::Disconnected` to waiting reads.
+    fn cancel_pending_io(&mut self) {
+        unsafe {
+            _ = shutdown(self.data_socket, SHUT_RDWR);
         }
     }
+}
 
-    fn identifier(&self) -> String {
-        self.address.clone()
+impl Drop for LinuxWiimoteWriter {
+    fn drop(&mut self) {
+        _ = close(self.control_socket);
+        _ = close(self.data_socket);
     }
 }
 
 impl Drop for LinuxNativeWiimote {
     fn drop(&mut self) {
+        // The readiness needs to be deregistered from the tokio reactor before it is closed.
+        #[cfg(feature = "tokio")]
+        drop(self.async_ready.take());
         _ = close(self.control_socket);
         _ = close(self.data_socket);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nix::libc::{send, socketpair, AF_UNIX};
+
+    use super::*;
+
+    /// Returns a connected pair of sockets that keep the boundaries of the sent reports like L2CAP.
+    fn socket_pair() -> (c_int, c_int) {
+        let mut sockets = [0; 2];
+        let result = unsafe { socketpair(AF_UNIX, SOCK_SEQPACKET, 0, sockets.as_mut_ptr()) };
+        assert_eq!(result, 0, "socketpair failed: {}", Errno::last().desc());
+        (sockets[0], sockets[1])
+    }
+
+    #[test]
+    fn test_rejects_output_reports() {
+        assert!(rejects_output_reports(Errno::EOPNOTSUPP));
+        for errno in [
+            Errno::EINVAL,
+            Errno::ENOBUFS,
+            Errno::ENOMEM,
+            Errno::EMSGSIZE,
+            Errno::EAGAIN,
+            Errno::ENOTCONN,
+        ] {
+            assert!(!rejects_output_reports(errno), "{errno:?}");
+        }
+    }
+
+    #[test]
+    fn test_control_pipe_write_receives_handshake() {
+        let (data_socket, data_peer) = socket_pair();
+        let (control_socket, control_peer) = socket_pair();
+        let remote = std::thread::spawn(move || {
+            let mut request = [0u8; 8];
+            let size = unsafe { recv(control_peer, request.as_mut_ptr().cast(), request.len(), 0) };
+            // HANDSHAKE with result code SUCCESSFUL
+            let handshake = [0x00u8];
+            unsafe { send(control_peer, handshake.as_ptr().cast(), 1, 0) };
+            (request, size, control_peer)
+        });
+
+        let control_pipe = AtomicBool::new(true);
+        let written =
+            write_output_report(data_socket, control_socket, &control_pipe, &[0x11, 0x10]);
+        assert!(matches!(written, Ok(2)));
+        let (request, size, control_peer) = remote.join().unwrap();
+        assert_eq!(
+            &request[..size as usize],
+            &[SET_REPORT_OUTPUT_PREFIX, 0x11, 0x10]
+        );
+
+        // The reply to this write was read before the write returned.
+        let mut buffer = [0u8; 8];
+        let pending = unsafe {
+            recv(
+                control_socket,
+                buffer.as_mut_ptr().cast(),
+                buffer.len(),
+                MSG_DONTWAIT,
+            )
+        };
+        assert!(pending < 0);
+        for socket in [data_socket, data_peer, control_socket, control_peer] {
+            _ = close(socket);
+        }
+    }
+
+    #[test]
+    fn test_report_write_result() {
+        assert!(matches!(report_write_result(Ok(23)), Ok(23)));
+        assert!(matches!(
+            report_write_result(Ok(0)),
+            Err(WiimoteError::Disconnected)
+        ));
+        // A full send buffer of a non-blocking socket does not disconnect the Wii remote.
+        assert!(matches!(
+            report_write_result(Err(Errno::EAGAIN)),
+            Err(WiimoteError::WriteTimedOut)
+        ));
+        assert!(matches!(
+            report_write_result(Err(Errno::ENOTCONN)),
+            Err(WiimoteError::Os {
+                code,
+                operation: OsOperation::Write,
+            }) if code == Errno::ENOTCONN as i32
+        ));
+    }
+}
@@ -0,0 +1,435 @@
+//! A single I/O thread reading the input reports of all connected Wii remotes.
+//!
+//! The data sockets of all registered Wii remotes are watched with one epoll instance,
+//! received reports are dispatched to the report queue of the device, which drops the oldest report
+//! once `REPORT_QUEUE_CAPACITY` reports were not read. Every device has an `eventfd`
+//! that is readable while reports are queued, so it can be waited on by other event loops.
+
+use std::collections::HashMap;
+use std::ffi::c_int;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{RecvTimeoutError, TryRecvError};
+use nix::errno::Errno;
+use nix::libc::{
+    close, dup, epoll_create1, epoll_ctl, epoll_event, epoll_wait, eventfd, read, recv, write,
+    EFD_CLOEXEC, EFD_NONBLOCK, EPOLLIN, EPOLL_CLOEXEC, EPOLL_CTL_ADD, EPOLL_CTL_DEL, MSG_DONTWAIT,
+};
+
+use super::{os_error, strip_input_prefix};
+use crate::native::common::{report_queue, ReportReceiver, ReportSender, REPORT_QUEUE_CAPACITY};
+use crate::result::{OsOperation, WiimoteError, WiimoteResult};
+use crate::WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE;
+
+/// The epoll token of the event signaled when a command was added.
+const WAKE_TOKEN: u64 = u64::MAX;
+const MAX_EVENTS: usize = 16;
+
+/// A received report without the HID prefix byte, sent without allocating.
+type ReportResult = WiimoteResult<([u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE], usize)>;
+
+/// An `eventfd` closed once neither the device nor the I/O thread use it.
+struct EventFd(c_int);
+
+impl EventFd {
+    fn new() -> WiimoteResult<Self> {
+        let fd = unsafe { eventfd(0, EFD_CLOEXEC | EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(os_error(Errno::last(), OsOperation::Read));
+        }
+        Ok(Self(fd))
+    }
+
+    fn signal(&self) {
+        let value = 1u64;
+        unsafe {
+            _ = write(self.0, std::ptr::addr_of!(value).cast(), 8);
+        }
+    }
+
+    /// Resets the counter, so the file descriptor is no longer readable.
+    fn drain(&self) {
+        let mut value = 0u64;
+        unsafe {
+            _ = read(self.0, std::ptr::addr_of_mut!(value).cast(), 8);
+        }
+    }
+}
+
+impl Drop for EventFd {
+    fn drop(&mut self) {
+        unsafe {
+            _ = close(self.0);
+        }
+    }
+}
+
+/// The receiving end of the reads of a Wii remote, unregisters the device when dropped.
+pub(super) struct ReadRegistration {
+    id: u64,
+    reports: ReportReceiver<ReportResult>,
+    ready: Arc<EventFd>,
+}
+
+impl ReadRegistration {
+    /// Copies the next report into `buffer`, waiting at most `timeout` if set.
+    /// Returns 0 bytes if no report was received in time.
+    pub(super) fn receive(
+        &self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> WiimoteResult<usize> {
+        let result = match timeout {
+            Some(timeout) if timeout.is_zero() => return self.try_receive(buffer),
+            None => self
+                .reports
+                .recv()
+                .map_err(|_| WiimoteError::Disconnected)?,
+            Some(timeout) => match self.reports.recv_timeout(timeout) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => return self.try_receive(buffer),
+                Err(RecvTimeoutError::Disconnected) => return Err(WiimoteError::Disconnected),
+            },
+        };
+        copy_report(result, buffer)
+    }
+
+    /// Copies the next report into `buffer` without waiting, returns 0 bytes if none is available.
+    /// Clears the readiness of [`Self::ready_fd`] if no report is queued.
+    pub(super) fn try_receive(&self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        let result = match self.reports.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Disconnected) => return Err(WiimoteError::Disconnected),
+            Err(TryRecvError::Empty) => {
+                self.ready.drain();
+                // A report dispatched before draining would no longer be signaled.
+                let result = match self.reports.try_recv() {
+                    Ok(result) => result,
+                    Err(TryRecvError::Empty) => return Ok(0),
+                    Err(TryRecvError::Disconnected) => {
+                        self.ready.signal();
+                        return Err(WiimoteError::Disconnected);
+                    }
+                };
+                if !self.reports.is_empty() {
+                    self.ready.signal();
+                }
+                result
+            }
+        };
+        copy_report(result, buffer)
+    }
+
+    /// Returns when the report received last was read from the socket by the I/O thread.
+    pub(super) fn last_received(&self) -> Option<Instant> {
+        self.reports.last_received()
+    }
+
+    /// Returns the file descriptor that is readable while reports are queued or the device disconnected.
+    pub(super) fn ready_fd(&self) -> c_int {
+        self.ready.0
+    }
+}
+
+fn copy_report(result: ReportResult, buffer: &mut [u8]) -> WiimoteResult<usize> {
+    let (report, size) = result?;
+    let bytes_to_copy = usize::min(size, buffer.len());
+    buffer[..bytes_to_copy].copy_from_slice(&report[..bytes_to_copy]);
+    Ok(bytes_to_copy)
+}
+
+impl Drop for ReadRegistration {
+    fn drop(&mut self) {
+        if let Some(multiplexer) = MULTIPLEXER.get() {
+            multiplexer.send(Command::Unregister(self.id));
+        }
+    }
+}
+
+/// Starts reading from the data socket on the I/O thread.
+///
+/// The socket is duplicated, so it stays valid on the I/O thread until it is unregistered,
+/// even if the device closed its socket before.
+pub(super) fn register(data_socket: c_int) -> WiimoteResult<ReadRegistration> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let multiplexer = Multiplexer::get()?;
+    let ready = Arc::new(EventFd::new()?);
+    let socket = unsafe { dup(data_socket) };
+    if socket < 0 {
+        return Err(os_error(Errno::last(), OsOperation::Read));
+    }
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (sender, reports) = report_queue(REPORT_QUEUE_CAPACITY);
+    multiplexer.send(Command::Register(Reader {
+        id,
+        socket,
+        sender: Some(sender),
+        ready: Arc::clone(&ready),
+    }));
+
+    Ok(ReadRegistration { id, reports, ready })
+}
+
+static MULTIPLEXER: OnceLock<Multiplexer> = OnceLock::new();
+
+enum Command {
+    Register(Reader),
+    Unregister(u64),
+}
+
+struct Multiplexer {
+    epoll: c_int,
+    commands: Mutex<Vec<Command>>,
+    wake: EventFd,
+}
+
+impl Multiplexer {
+    /// Returns the multiplexer, starting the I/O thread on first use.
+    fn get() -> WiimoteResult<&'static Self> {
+        if let Some(multiplexer) = MULTIPLEXER.get() {
+            return Ok(multiplexer);
+        }
+        let epoll = unsafe { epoll_create1(EPOLL_CLOEXEC) };
+        if epoll < 0 {
+            return Err(os_error(Errno::last(), OsOperation::Read));
+        }
+        let wake = match EventFd::new() {
+            Ok(wake) => wake,
+            Err(err) => {
+                unsafe { _ = close(epoll) };
+                return Err(err);
+            }
+        };
+        if let Err(err) = add_to_epoll(epoll, wake.0, WAKE_TOKEN) {
+            unsafe { _ = close(epoll) };
+            return Err(err);
+        }
+
+        let mut multiplexer = Some(Self {
+            epoll,
+            commands: Mutex::new(Vec::new()),
+            wake,
+        });
+        let instance = MULTIPLEXER.get_or_init(|| multiplexer.take().unwrap());
+        if let Some(unused) = multiplexer {
+            // Another thread initialized the multiplexer first.
+            unsafe { _ = close(unused.epoll) };
+        } else {
+            std::thread::Builder::new()
+                .name("wiimote-reader".to_string())
+                .spawn(move || instance.run())
+                .expect("Failed to spawn Wii remote reader thread");
+        }
+        Ok(instance)
+    }
+
+    fn send(&self, command: Command) {
+        lock(&self.commands).push(command);
+        self.wake.signal();
+    }
+
+    fn run(&self) {
+        let mut readers = HashMap::new();
+        let mut events = [epoll_event { events: 0, u64: 0 }; MAX_EVENTS];
+        loop {
+            let event_count =
+                unsafe { epoll_wait(self.epoll, events.as_mut_ptr(), MAX_EVENTS as c_int, -1) };
+            if event_count < 0 {
+                let errno = Errno::last();
+                if errno != Errno::EINTR {
+                    log::error!("Waiting for Wii remote reads failed: {}", errno.desc());
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                continue;
+            }
+
+            for event in &events[..event_count as usize] {
+                let token = event.u64;
+                if token == WAKE_TOKEN {
+                    self.wake.drain();
+                    self.handle_commands(&mut readers);
+                } else if let Some(reader) = readers.get(&token) {
+                    if !reader.receive() {
+                        self.remove(&mut readers, token);
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_commands(&self, readers: &mut HashMap<u64, Reader>) {
+        let commands = std::mem::take(&mut *lock(&self.commands));
+        for command in commands {
+            match command {
+                Command::Register(reader) => {
+                    match add_to_epoll(self.epoll, reader.socket, reader.id) {
+                        Ok(()) => {
+                            readers.insert(reader.id, reader);
+                        }
+                        Err(err) => {
+                            reader.dispatch(Err(err));
+                        }
+                    }
+                }
+                Command::Unregister(id) => self.remove(readers, id),
+            }
+        }
+    }
+
+    fn remove(&self, readers: &mut HashMap<u64, Reader>, id: u64) {
+        if let Some(reader) = readers.remove(&id) {
+            unsafe {
+                _ = epoll_ctl(
+                    self.epoll,
+                    EPOLL_CTL_DEL,
+                    reader.socket,
+                    std::ptr::null_mut(),
+                );
+            }
+        }
+    }
+}
+
+fn add_to_epoll(epoll: c_int, fd: c_int, token: u64) -> WiimoteResult<()> {
+    let mut event = epoll_event {
+        events: EPOLLIN as u32,
+        u64: token,
+    };
+    if unsafe { epoll_ctl(epoll, EPOLL_CTL_ADD, fd, &mut event) } < 0 {
+        return Err(os_error(Errno::last(), OsOperation::Read));
+    }
+    Ok(())
+}
+
+/// The duplicated data socket of a device on the I/O thread.
+struct Reader {
+    id: u64,
+    socket: c_int,
+    /// Dropped before signaling the disconnect when the reader is dropped.
+    sender: Option<ReportSender<ReportResult>>,
+    ready: Arc<EventFd>,
+}
+
+impl Reader {
+    /// Receives and dispatches an input report, returns false if the device failed or was closed.
+    fn receive(&self) -> bool {
+        let mut report = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        let bytes_read = unsafe {
+            recv(
+                self.socket,
+                report.as_mut_ptr().cast(),
+                report.len(),
+                MSG_DONTWAIT,
+            )
+        };
+        if bytes_read < 0 {
+            let errno = Errno::last();
+            if matches!(errno, Errno::EAGAIN | Errno::EINTR) {
+                return true;
+            }
+            self.dispatch(Err(os_error(errno, OsOperation::Read)));
+            return false;
+        }
+
+        match strip_input_prefix(&mut report, bytes_read as usize) {
+            Ok(size) => self.dispatch(Ok((report, size))),
+            Err(err) => {
+                self.dispatch(Err(err));
+                false
+            }
+        }
+    }
+
+    /// Sends the result to the device, returns false if the device was closed.
+    fn dispatch(&self, result: ReportResult) -> bool {
+        let sent = self
+            .sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(result).is_ok());
+        self.ready.signal();
+        sent
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        // Reads of the device fail once the queue is disconnected,
+        // the readiness stays signaled so waiting event loops notice the disconnect.
+        drop(self.sender.take());
+        self.ready.signal();
+        unsafe {
+            _ = close(self.socket);
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(err) => err.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::libc::{send, socketpair, AF_UNIX, SOCK_SEQPACKET};
+
+    use super::*;
+
+    /// Returns a connected pair of sockets that keep the boundaries of the sent reports like L2CAP.
+    fn socket_pair() -> (c_int, c_int) {
+        let mut sockets = [0; 2];
+        let result = unsafe { socketpair(AF_UNIX, SOCK_SEQPACKET, 0, sockets.as_mut_ptr()) };
+        assert_eq!(result, 0, "socketpair failed: {}", Errno::last().desc());
+        (sockets[0], sockets[1])
+    }
+
+    fn send_report(socket: c_int, report: &[u8]) {
+        let sent = unsafe { send(socket, report.as_ptr().cast(), report.len(), 0) };
+        assert_eq!(sent, report.len() as isize);
+    }
+
+    #[test]
+    fn test_register_drops_oldest_reports() {
+        let (data_socket, remote) = socket_pair();
+        let registration = register(data_socket).unwrap();
+        unsafe { _ = close(data_socket) };
+
+        let report_count = REPORT_QUEUE_CAPACITY + 8;
+        for index in 0..report_count {
+            send_report(remote, &[0xA1, 0x30, 0x00, index as u8]);
+        }
+        unsafe { _ = close(remote) };
+        // The reader is dropped on the I/O thread after dispatching all reports and the disconnect.
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !registration.reports.is_disconnected() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "reports were not dispatched"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        let mut received = Vec::new();
+        let error = loop {
+            match registration.try_receive(&mut buffer) {
+                Ok(size) => {
+                    assert_eq!(buffer[..size], [0x30, 0x00, buffer[2]]);
+                    received.push(buffer[2] as usize);
+                }
+                Err(err) => break err,
+            }
+        };
+        // The disconnect takes the place of one more report in the full queue.
+        assert_eq!(
+            received,
+            (report_count - REPORT_QUEUE_CAPACITY + 1..report_count).collect::<Vec<_>>()
+        );
+        assert!(matches!(error, WiimoteError::Disconnected));
+    }
+}
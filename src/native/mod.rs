@@ -1,25 +1,185 @@
+#[cfg(unix)]
+use std::os::fd::RawFd;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use crate::backend::Backend;
+use crate::device::WiimoteModel;
+#[cfg(all(target_os = "windows", feature = "windows-backend"))]
+use crate::hid::HidInfo;
+use crate::result::WiimoteResult;
+
 mod common;
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", feature = "linux-backend"))]
 mod linux;
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[cfg(not(any(
+    all(target_os = "linux", feature = "linux-backend"),
+    all(target_os = "windows", feature = "windows-backend")
+)))]
 mod null;
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", feature = "windows-backend"))]
 mod windows;
 
-#[cfg(target_os = "linux")]
-pub use linux::{wiimotes_scan, wiimotes_scan_cleanup, LinuxNativeWiimote as NativeWiimoteDevice};
+#[cfg(feature = "manager-thread")]
+pub(crate) use common::normalize_identifier;
+pub(crate) use common::{parse_bluetooth_address, queued_report_count, wait_for_queued_report};
+#[cfg(all(target_os = "linux", feature = "linux-backend"))]
+pub use linux::{
+    wiimote_reopen, wiimotes_scan, wiimotes_scan_cleanup, LinuxNativeWiimote as NativeWiimoteDevice,
+};
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
-pub use null::{wiimotes_scan, wiimotes_scan_cleanup, NullNativeWiimote as NativeWiimoteDevice};
+#[cfg(not(any(
+    all(target_os = "linux", feature = "linux-backend"),
+    all(target_os = "windows", feature = "windows-backend")
+)))]
+pub use null::NullNativeWiimote as NativeWiimoteDevice;
+#[cfg(all(
+    feature = "manager-thread",
+    not(any(
+        all(target_os = "linux", feature = "linux-backend"),
+        all(target_os = "windows", feature = "windows-backend")
+    ))
+))]
+pub use null::{wiimote_reopen, wiimotes_scan, wiimotes_scan_cleanup};
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", feature = "windows-backend"))]
 pub use windows::{
-    wiimotes_scan, wiimotes_scan_cleanup, WindowsNativeWiimote as NativeWiimoteDevice,
+    wiimote_reopen, wiimotes_scan, wiimotes_scan_cleanup,
+    WindowsNativeWiimote as NativeWiimoteDevice,
 };
 
-pub trait NativeWiimote {
-    fn read(&mut self, buffer: &mut [u8]) -> Option<usize>;
-    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> Option<usize>;
-    fn write(&mut self, buffer: &[u8]) -> Option<usize>;
-    fn identifier(&self) -> String;
+/// The backend of `NativeWiimoteDevice`, `None` on platforms without a backend.
+#[cfg(all(target_os = "linux", feature = "linux-backend"))]
+pub(crate) const NATIVE_BACKEND: Option<Backend> = Some(Backend::Bluez);
+#[cfg(not(any(
+    all(target_os = "linux", feature = "linux-backend"),
+    all(target_os = "windows", feature = "windows-backend")
+)))]
+pub(crate) const NATIVE_BACKEND: Option<Backend> = None;
+#[cfg(all(target_os = "windows", feature = "windows-backend"))]
+pub(crate) const NATIVE_BACKEND: Option<Backend> = Some(Backend::WindowsHid);
+
+/// Scans for Wii remotes with `backend`, which finds none unless it is the platform backend.
+#[cfg(feature = "manager-thread")]
+pub(crate) fn wiimotes_scan_with(
+    backend: Backend,
+    wiimotes: &mut Vec<NativeWiimoteDevice>,
+    progress: &dyn Fn(&str, crate::result::InitPhase),
+) {
+    if NATIVE_BACKEND == Some(backend) {
+        wiimotes_scan(wiimotes, progress);
+    }
+}
+
+/// The transport used to exchange reports with a Wii remote.
+///
+/// The platform backends implement it for Bluetooth connections, other implementations
+/// can be wrapped with [`WiimoteDevice::with_transport`](crate::prelude::WiimoteDevice::with_transport).
+/// Buffers contain reports without the HID prefix byte, starting with the report ID.
+/// Transports are `Send`, so the `WiimoteDevice` using them can be shared between threads.
+pub trait NativeWiimote: Send {
+    /// Reads an input report, returns `WiimoteError::Disconnected` or `WiimoteError::Os` on failure.
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize>;
+    /// Reads an input report, returns 0 bytes if no report was received within `timeout_millis`.
+    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize>;
+    /// Reads an input report once available, registers the waker of `cx` otherwise.
+    ///
+    /// The default implementation polls [`Self::read_timeout`] without waiting and wakes the task
+    /// again if no report is available, so the task keeps being polled. Transports with a source
+    /// of readiness, e.g. a notification of the operating system, should override it and wake the
+    /// waker from that source. Transports whose reads never wait can return the result of a read instead.
+    #[cfg(feature = "tokio")]
+    fn poll_read(&mut self, cx: &mut Context<'_>, buffer: &mut [u8]) -> Poll<WiimoteResult<usize>> {
+        match self.read_timeout(buffer, 0) {
+            Ok(0) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            result => Poll::Ready(result),
+        }
+    }
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize>;
+    /// Returns a writer for the same Wii remote that does not wait for reads of this transport,
+    /// `None` if reads and writes can not run concurrently and are serialized instead.
+    ///
+    /// Transports observing the written output reports must observe the writes of the returned writer too,
+    /// e.g. by wrapping the writer of the inner transport, or keep the default.
+    fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+        None
+    }
+    /// Returns a file descriptor that becomes readable once an input report can be read without waiting,
+    /// `None` if the transport can not be waited on by an external event loop.
+    ///
+    /// The file descriptor may be reported readable spuriously, reading with a timeout of 0 clears it.
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<RawFd> {
+        None
+    }
+    /// Returns the HID device the transport was opened with, `None` if it is not a HID device.
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    fn hid_info(&self) -> Option<HidInfo> {
+        None
+    }
+    /// Returns when the report returned by the last read was received, e.g. by the I/O thread of the backend,
+    /// `None` if the transport does not record it and the time of the read is used instead.
+    fn last_received(&self) -> Option<Instant> {
+        None
+    }
+    fn identifier(&self) -> &str;
+    fn model(&self) -> WiimoteModel;
+}
+
+/// The write half of a transport, see [`NativeWiimote::try_clone_writer`].
+pub trait NativeWiimoteWriter: Send {
+    /// Writes an output report, returns `WiimoteError::Disconnected` or `WiimoteError::Os` on failure.
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize>;
+    /// Cancels the pending I/O of the transport, so reads blocked on another thread
+    /// fail with `WiimoteError::Disconnected`. The transport can not be used afterwards.
+    fn cancel_pending_io(&mut self) {}
+}
+
+impl<T: NativeWiimote + ?Sized> NativeWiimote for Box<T> {
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        (**self).read(buffer)
+    }
+
+    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize> {
+        (**self).read_timeout(buffer, timeout_millis)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll_read(&mut self, cx: &mut Context<'_>, buffer: &mut [u8]) -> Poll<WiimoteResult<usize>> {
+        (**self).poll_read(cx, buffer)
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        (**self).write(buffer)
+    }
+
+    fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+        (**self).try_clone_writer()
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<RawFd> {
+        (**self).readiness_fd()
+    }
+
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    fn hid_info(&self) -> Option<HidInfo> {
+        (**self).hid_info()
+    }
+
+    fn last_received(&self) -> Option<Instant> {
+        (**self).last_received()
+    }
+
+    fn identifier(&self) -> &str {
+        (**self).identifier()
+    }
+
+    fn model(&self) -> WiimoteModel {
+        (**self).model()
+    }
 }
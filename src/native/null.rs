@@ -1,33 +1,55 @@
 use super::NativeWiimote;
-
-pub fn wiimotes_scan(_wiimotes: &mut Vec<NullNativeWiimote>) {
-    static mut WARNING_PRINTED: bool = false;
-    unsafe {
-        if !WARNING_PRINTED {
-            eprintln!("wiimote-rs does not support this platform. You will not be able to connect Wii remotes.");
-            WARNING_PRINTED = true;
-        }
-    }
+use crate::device::WiimoteModel;
+use crate::result::WiimoteResult;
+
+#[cfg(feature = "manager-thread")]
+pub fn wiimotes_scan(
+    _wiimotes: &mut Vec<NullNativeWiimote>,
+    _progress: &dyn Fn(&str, crate::result::InitPhase),
+) {
+    static WARNING_LOGGED: std::sync::Once = std::sync::Once::new();
+    WARNING_LOGGED.call_once(|| {
+        log::warn!("wiimote-rs does not support this platform. You will not be able to connect Wii remotes.");
+    });
 }
 
+#[cfg(feature = "manager-thread")]
 pub const fn wiimotes_scan_cleanup() {}
 
+#[cfg(feature = "manager-thread")]
+pub const fn wiimote_reopen(_identifier: &str, _model: WiimoteModel) -> Option<NullNativeWiimote> {
+    None
+}
+
 pub struct NullNativeWiimote;
 
 impl NativeWiimote for NullNativeWiimote {
-    fn read(&mut self, _buffer: &mut [u8]) -> Option<usize> {
+    fn read(&mut self, _buffer: &mut [u8]) -> WiimoteResult<usize> {
+        unreachable!()
+    }
+
+    fn read_timeout(&mut self, _buffer: &mut [u8], _timeout_millis: usize) -> WiimoteResult<usize> {
+        unreachable!()
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll_read(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+        _buffer: &mut [u8],
+    ) -> std::task::Poll<WiimoteResult<usize>> {
         unreachable!()
     }
 
-    fn read_timeout(&mut self, _buffer: &mut [u8], _timeout_millis: usize) -> Option<usize> {
+    fn write(&mut self, _buffer: &[u8]) -> WiimoteResult<usize> {
         unreachable!()
     }
 
-    fn write(&mut self, _buffer: &[u8]) -> Option<usize> {
+    fn identifier(&self) -> &str {
         unreachable!()
     }
 
-    fn identifier(&self) -> String {
+    fn model(&self) -> WiimoteModel {
         unreachable!()
     }
 }
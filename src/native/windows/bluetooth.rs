@@ -1,6 +1,8 @@
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::mem;
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 use once_cell::sync::Lazy;
 use windows::Win32::Devices::Bluetooth::{
@@ -12,12 +14,20 @@ use windows::Win32::Devices::Bluetooth::{
 };
 use windows::Win32::Foundation::{CloseHandle, ERROR_SUCCESS, HANDLE, TRUE};
 
-use crate::native::common::is_wiimote_device_name;
+use crate::native::common::{format_bluetooth_address, is_wiimote_device_name};
+use crate::result::InitPhase;
 
 use super::from_wstring;
 
 const HUMAN_INTERFACE_DEVICE_SERVICE_CLASS_ID: u128 = 0x1124_0000_1000_8000_0080_5F9B_34FB;
 
+/// The minimum time between Bluetooth inquiries, scans in between only enumerate known devices.
+const MIN_INQUIRY_INTERVAL: Duration = Duration::from_secs(5);
+/// The time between inquiries is doubled after every inquiry that found no new Wii remote up to this.
+const MAX_INQUIRY_INTERVAL: Duration = Duration::from_secs(80);
+
+static INQUIRY_SCHEDULE: Mutex<InquirySchedule> = Mutex::new(InquirySchedule::new());
+
 static mut CONNECTED_WIIMOTES: Lazy<Mutex<HashMap<String, BLUETOOTH_DEVICE_INFO>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
@@ -75,26 +85,33 @@ where
     })
 }
 
+/// Enables the HID service of the Wii remote, returns whether it was newly registered.
 unsafe fn register_as_hid_device(
     radio: HANDLE,
     device_info: &BLUETOOTH_DEVICE_INFO,
-) -> Result<(), String> {
-    let device_id = format!("{:x}", device_info.Address.Anonymous.ullLong);
+    progress: &dyn Fn(&str, InitPhase),
+) -> Result<bool, String> {
+    // The address is stored in the lower 6 bytes, in the format of the Wii remote identifiers.
+    let address = device_info.Address.Anonymous.ullLong.to_be_bytes();
+    let mut device_address = [0u8; 6];
+    device_address.copy_from_slice(&address[2..]);
+    let device_id = format_bluetooth_address(device_address);
     let mut connected = match CONNECTED_WIIMOTES.lock() {
         Ok(connected) => connected,
         Err(connected) => connected.into_inner(),
     };
     if connected.contains_key(&device_id) {
-        return Ok(());
+        return Ok(false);
     }
 
     if !device_info.fConnected.as_bool() && device_info.fRemembered.as_bool() {
         BluetoothRemoveDevice(&device_info.Address);
     }
     if device_info.fConnected.as_bool() || device_info.fRemembered.as_bool() {
-        return Ok(());
+        return Ok(false);
     }
 
+    progress(&device_id, InitPhase::Pairing);
     let hid_serivce_class_guid = HUMAN_INTERFACE_DEVICE_SERVICE_CLASS_ID.into();
 
     let result = BluetoothSetServiceState(
@@ -110,29 +127,87 @@ unsafe fn register_as_hid_device(
     }
 
     connected.insert(device_id, *device_info);
-    Ok(())
+    Ok(true)
 }
 
-pub(super) fn register_wiimotes_as_hid_devices() -> Result<(), String> {
+/// When to issue Bluetooth inquiries, which block for several seconds.
+///
+/// Inquiries back off exponentially while they find no new Wii remote, so a manager
+/// whose remotes are all connected rarely blocks its scans. Finding a new one resets the interval.
+struct InquirySchedule {
+    last_inquiry: Option<Instant>,
+    interval: Duration,
+}
+
+impl InquirySchedule {
+    const fn new() -> Self {
+        Self {
+            last_inquiry: None,
+            interval: MIN_INQUIRY_INTERVAL,
+        }
+    }
+
+    /// Returns whether the scan at `now` should issue an inquiry to discover new devices.
+    fn should_issue(&mut self, now: Instant) -> bool {
+        if self
+            .last_inquiry
+            .is_some_and(|last_inquiry| now.duration_since(last_inquiry) < self.interval)
+        {
+            return false;
+        }
+        self.last_inquiry = Some(now);
+        true
+    }
+
+    /// Adjusts the interval after an inquiry, depending on whether it found a new Wii remote.
+    fn finished(&mut self, found_new_device: bool) {
+        self.interval = if found_new_device {
+            MIN_INQUIRY_INTERVAL
+        } else {
+            Duration::min(self.interval * 2, MAX_INQUIRY_INTERVAL)
+        };
+    }
+}
+
+fn inquiry_schedule() -> MutexGuard<'static, InquirySchedule> {
+    match INQUIRY_SCHEDULE.lock() {
+        Ok(schedule) => schedule,
+        Err(schedule) => schedule.into_inner(),
+    }
+}
+
+pub(super) fn register_wiimotes_as_hid_devices(
+    progress: &dyn Fn(&str, InitPhase),
+) -> Result<(), String> {
     let mut search = BLUETOOTH_DEVICE_SEARCH_PARAMS::default();
     search.dwSize = mem::size_of_val(&search) as u32;
     search.fReturnAuthenticated = TRUE;
     search.fReturnRemembered = TRUE;
     search.fReturnUnknown = TRUE;
     search.fReturnConnected = TRUE;
-    search.fIssueInquiry = TRUE;
+    let issue_inquiry = inquiry_schedule().should_issue(Instant::now());
+    search.fIssueInquiry = issue_inquiry.into();
     search.cTimeoutMultiplier = 2;
 
-    unsafe {
+    let found_new_device = Cell::new(false);
+    let result = unsafe {
         enumerate_bluetooth_devices(&mut search, |radio, _radio_info, device_info| {
             let name = from_wstring(&device_info.szName);
             if is_wiimote_device_name(&name) {
-                if let Err(error) = register_as_hid_device(radio, device_info) {
-                    eprintln!("Failed to register wiimote as interface device: {error}");
+                log::debug!("Registering Wii remote {name:?} as interface device");
+                match register_as_hid_device(radio, device_info, progress) {
+                    Ok(registered) => found_new_device.set(found_new_device.get() || registered),
+                    Err(error) => {
+                        log::warn!("Failed to register Wii remote as interface device: {error}");
+                    }
                 }
             }
         })
+    };
+    if issue_inquiry {
+        inquiry_schedule().finished(found_new_device.get());
     }
+    result
 }
 
 pub(super) fn forget_wiimote(identifier: &str) {
@@ -168,3 +243,32 @@ pub(super) unsafe fn disconnect_wiimotes() {
     };
     connected_wiimotes.clear();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inquiry_backoff() {
+        let mut schedule = InquirySchedule::new();
+        let start = Instant::now();
+        assert!(schedule.should_issue(start));
+        assert!(!schedule.should_issue(start + MIN_INQUIRY_INTERVAL / 2));
+
+        // Every inquiry without a new Wii remote doubles the interval.
+        schedule.finished(false);
+        assert!(!schedule.should_issue(start + MIN_INQUIRY_INTERVAL));
+        let second = start + MIN_INQUIRY_INTERVAL * 2;
+        assert!(schedule.should_issue(second));
+        for _ in 0..8 {
+            schedule.finished(false);
+        }
+        assert!(!schedule.should_issue(second + MAX_INQUIRY_INTERVAL / 2));
+        let third = second + MAX_INQUIRY_INTERVAL;
+        assert!(schedule.should_issue(third));
+
+        // A new Wii remote resets the interval.
+        schedule.finished(true);
+        assert!(schedule.should_issue(third + MIN_INQUIRY_INTERVAL));
+    }
+}
@@ -1,5 +1,7 @@
-use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::ffi::c_void;
+use std::sync::Mutex;
 use std::{iter, mem};
 
 use once_cell::sync::Lazy;
@@ -17,13 +19,16 @@ use windows::Win32::Storage::FileSystem::{
     CreateFileW, FILE_FLAG_OVERLAPPED, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
 };
 
-use crate::native::common::is_wiimote;
+use crate::native::common::{is_wiimote, normalize_identifier};
 
 use super::from_wstring;
 
+#[derive(Clone)]
 pub(super) struct DeviceInfo {
     vendor_id: u16,
     product_id: u16,
+    /// The serial number of the HID device, which is the Bluetooth address of a Wii remote,
+    /// normalized with [`normalize_identifier`].
     serial_number: String,
     capabilities: HIDP_CAPS,
 }
@@ -67,7 +72,7 @@ impl DeviceInfo {
             Some(Self {
                 vendor_id: attributes.VendorID,
                 product_id: attributes.ProductID,
-                serial_number: from_wstring(&name_buffer),
+                serial_number: normalize_identifier(&from_wstring(&name_buffer)),
                 capabilities,
             })
         } else {
@@ -99,7 +104,10 @@ pub(super) unsafe fn enumerate_wiimote_hid_devices<F>(mut callback: F) -> Result
 where
     F: FnMut(&DeviceInfo, &str),
 {
-    static mut UNRELATED_DEVICES: Lazy<HashSet<String>> = Lazy::new(HashSet::new);
+    /// The probed device paths of the previous enumeration, with the device info of Wii remotes
+    /// and `None` for unrelated devices. Only device paths that newly arrived are probed.
+    static KNOWN_DEVICES: Lazy<Mutex<HashMap<String, Option<DeviceInfo>>>> =
+        Lazy::new(|| Mutex::new(HashMap::new()));
 
     let hid_id = HidD_GetHidGuid();
 
@@ -125,6 +133,11 @@ where
         return Err(String::from("Failed to get HID device list"));
     }
 
+    let mut known_devices = match KNOWN_DEVICES.lock() {
+        Ok(known_devices) => known_devices,
+        Err(known_devices) => known_devices.into_inner(),
+    };
+    let mut present_devices = HashSet::new();
     let mut start_index = 0;
     while let Some(device_path_length) = device_list[start_index..].iter().position(|&c| c == 0) {
         if device_list[start_index] == 0 {
@@ -135,17 +148,24 @@ where
         let device_path = &device_list[start_index..end_index];
         let device_path_string = from_wstring(device_path);
         start_index = end_index;
-        if UNRELATED_DEVICES.contains(&device_path_string) {
-            continue;
-        }
-
-        if let Some(device_info) = DeviceInfo::from_device_path(&device_path_string) {
-            if is_wiimote(device_info.vendor_id(), device_info.product_id()) {
-                callback(&device_info, &device_path_string);
-            } else {
-                UNRELATED_DEVICES.insert(device_path_string);
+        present_devices.insert(device_path_string.clone());
+
+        let device_info = match known_devices.entry(device_path_string.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                // Devices that can not be opened yet are probed again in the next enumeration.
+                let Some(device_info) = DeviceInfo::from_device_path(&device_path_string) else {
+                    continue;
+                };
+                let is_wiimote = is_wiimote(device_info.vendor_id(), device_info.product_id());
+                entry.insert(is_wiimote.then_some(device_info))
             }
+        };
+        if let Some(device_info) = device_info {
+            callback(device_info, &device_path_string);
         }
     }
+    // Removed devices are probed again if they arrive with the same path.
+    known_devices.retain(|device_path, _| present_devices.contains(device_path));
     Ok(())
 }
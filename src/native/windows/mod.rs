@@ -1,24 +1,32 @@
 mod bluetooth;
 mod hid;
+mod multiplexer;
 
 use std::collections::HashSet;
 use std::sync::Mutex;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use once_cell::sync::Lazy;
-use windows::Win32::Devices::HumanInterfaceDevice::HIDP_CAPS;
 use windows::Win32::Foundation::{
-    CloseHandle, GetLastError, ERROR_IO_PENDING, GENERIC_READ, GENERIC_WRITE, HANDLE, WAIT_FAILED,
-    WAIT_OBJECT_0, WAIT_TIMEOUT,
+    CloseHandle, DuplicateHandle, GetLastError, DUPLICATE_SAME_ACCESS, ERROR_IO_PENDING,
+    GENERIC_READ, GENERIC_WRITE, HANDLE, WAIT_FAILED, WAIT_OBJECT_0, WAIT_TIMEOUT,
 };
 use windows::Win32::Globalization::{WideCharToMultiByte, CP_UTF8};
-use windows::Win32::Storage::FileSystem::{ReadFile, WriteFile};
-use windows::Win32::System::Threading::{CreateEventW, ResetEvent, WaitForSingleObject, INFINITE};
-use windows::Win32::System::IO::{GetOverlappedResult, OVERLAPPED};
+use windows::Win32::Storage::FileSystem::WriteFile;
+use windows::Win32::System::Threading::{CreateEventW, GetCurrentProcess, WaitForSingleObject};
+use windows::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
 
 use self::bluetooth::{disconnect_wiimotes, forget_wiimote, register_wiimotes_as_hid_devices};
-use self::hid::{enumerate_wiimote_hid_devices, open_wiimote_device};
+use self::hid::{enumerate_wiimote_hid_devices, open_wiimote_device, DeviceInfo};
+use self::multiplexer::ReadRegistration;
 
-use super::NativeWiimote;
+use super::common::model_from_product_id;
+use super::{NativeWiimote, NativeWiimoteWriter};
+use crate::device::WiimoteModel;
+use crate::hid::HidInfo;
+use crate::result::{InitPhase, OsOperation, WiimoteError, WiimoteResult};
 
 static mut WIIMOTES_HANDLED: Lazy<Mutex<HashSet<String>>> =
     Lazy::new(|| Mutex::new(HashSet::new()));
@@ -39,9 +47,9 @@ unsafe fn from_wstring(wstr: &[u16]) -> String {
     String::from_utf8_unchecked(result)
 }
 
-pub fn wiimotes_scan(wiimotes: &mut Vec<WindowsNativeWiimote>) {
+pub fn wiimotes_scan(wiimotes: &mut Vec<WindowsNativeWiimote>, progress: &dyn Fn(&str, InitPhase)) {
     unsafe {
-        _ = register_wiimotes_as_hid_devices();
+        _ = register_wiimotes_as_hid_devices(progress);
 
         _ = enumerate_wiimote_hid_devices(|device_info, device_path| {
             let mut wiimotes_handled = match WIIMOTES_HANDLED.lock() {
@@ -50,23 +58,74 @@ pub fn wiimotes_scan(wiimotes: &mut Vec<WindowsNativeWiimote>) {
             };
 
             if !wiimotes_handled.contains(device_info.serial_number()) {
-                open_wiimote_device(device_path, (GENERIC_READ | GENERIC_WRITE).0).map_or_else(
-                    |_| {
-                        eprintln!("Failed to connect to wiimote");
-                    },
-                    |wiimote_handle| {
-                        let serial_number = device_info.serial_number();
-                        wiimotes_handled.insert(serial_number.to_string());
-                        wiimotes.push(WindowsNativeWiimote::new(
-                            wiimote_handle,
-                            serial_number.to_string(),
-                            device_info.capabilities(),
-                        ));
-                    },
-                );
+                progress(device_info.serial_number(), InitPhase::OpeningHid);
+                if let Some(wiimote) = open_wiimote(device_info, device_path) {
+                    wiimotes_handled.insert(device_info.serial_number().to_string());
+                    wiimotes.push(wiimote);
+                }
+            }
+        });
+    }
+}
+
+/// Opens the HID device of the Wii remote with the serial number `identifier` again without pairing.
+pub fn wiimote_reopen(identifier: &str, _model: WiimoteModel) -> Option<WindowsNativeWiimote> {
+    let mut reopened = None;
+    unsafe {
+        _ = enumerate_wiimote_hid_devices(|device_info, device_path| {
+            if reopened.is_some() || device_info.serial_number() != identifier {
+                return;
+            }
+            let mut wiimotes_handled = match WIIMOTES_HANDLED.lock() {
+                Ok(wiimotes_handled) => wiimotes_handled,
+                Err(wiimotes_handled) => wiimotes_handled.into_inner(),
+            };
+            // The Wii remote may have been opened again by a scan in the meantime.
+            if !wiimotes_handled.contains(identifier) {
+                reopened = open_wiimote(device_info, device_path);
+                if reopened.is_some() {
+                    wiimotes_handled.insert(identifier.to_string());
+                }
             }
         });
     }
+    reopened
+}
+
+unsafe fn open_wiimote(
+    device_info: &DeviceInfo,
+    device_path: &str,
+) -> Option<WindowsNativeWiimote> {
+    let serial_number = device_info.serial_number();
+    let Ok(wiimote_handle) = open_wiimote_device(device_path, (GENERIC_READ | GENERIC_WRITE).0)
+    else {
+        log::warn!("Failed to open Wii remote {serial_number}");
+        return None;
+    };
+    let capabilities = device_info.capabilities();
+    let hid_info = HidInfo {
+        device_path: device_path.to_string(),
+        vendor_id: device_info.vendor_id(),
+        product_id: device_info.product_id(),
+        input_report_length: capabilities.InputReportByteLength,
+        output_report_length: capabilities.OutputReportByteLength,
+        feature_report_length: capabilities.FeatureReportByteLength,
+    };
+    match WindowsNativeWiimote::new(
+        OwnedHandle::new(wiimote_handle),
+        serial_number.to_string(),
+        model_from_product_id(device_info.product_id()),
+        hid_info,
+    ) {
+        Ok(wiimote) => {
+            log::info!("Opened Wii remote {serial_number}");
+            Some(wiimote)
+        }
+        Err(err) => {
+            log::warn!("Failed to read from Wii remote {serial_number}: {err:?}");
+            None
+        }
+    }
 }
 
 pub fn wiimotes_scan_cleanup() {
@@ -76,158 +135,275 @@ pub fn wiimotes_scan_cleanup() {
 }
 
 pub struct WindowsNativeWiimote {
-    handle: HANDLE,
+    handle: OwnedHandle,
     identifier: String,
-    read_pending: bool,
-    write_pending: bool,
-    overlapped_read: OVERLAPPED,
-    overlapped_write: OVERLAPPED,
-    read_buffer: Vec<u8>,
-    write_buffer: Vec<u8>,
+    model: WiimoteModel,
+    hid_info: HidInfo,
+    /// The reads of all Wii remotes are serviced by a single I/O thread.
+    reads: ReadRegistration,
+    writer: WindowsWiimoteWriter,
 }
 
 impl WindowsNativeWiimote {
-    fn new(handle: HANDLE, identifier: String, capabilities: &HIDP_CAPS) -> Self {
-        let read_buffer_size = capabilities.InputReportByteLength as usize;
-        let write_buffer_size = capabilities.OutputReportByteLength as usize;
-        let mut wiimote = Self {
+    unsafe fn new(
+        handle: OwnedHandle,
+        identifier: String,
+        model: WiimoteModel,
+        hid_info: HidInfo,
+    ) -> WiimoteResult<Self> {
+        let read_buffer_size = hid_info.input_report_length as usize;
+        let write_buffer_size = hid_info.output_report_length as usize;
+        let reads = multiplexer::register(&handle, read_buffer_size)?;
+        let writer = WindowsWiimoteWriter::new(
+            handle.duplicate(OsOperation::Write)?,
+            write_buffer_size,
+            reads.id(),
+        )?;
+        Ok(Self {
             handle,
             identifier,
-            read_pending: false,
-            write_pending: false,
-            overlapped_read: OVERLAPPED::default(),
-            overlapped_write: OVERLAPPED::default(),
-            read_buffer: vec![0; read_buffer_size],
-            write_buffer: vec![0; write_buffer_size],
-        };
-        wiimote.overlapped_read.hEvent = unsafe { CreateEventW(None, true, false, None).unwrap() };
-        wiimote.overlapped_write.hEvent = unsafe { CreateEventW(None, true, false, None).unwrap() };
-        wiimote
-    }
-
-    unsafe fn read_timeout_impl(
-        &mut self,
-        buffer: &mut [u8],
-        timeout_millis: Option<usize>,
-    ) -> Option<usize> {
-        let buffer_size = usize::min(buffer.len(), self.read_buffer.len());
-        let mut did_read = false;
-        if !self.read_pending {
-            _ = ResetEvent(self.overlapped_read.hEvent);
-            self.read_buffer.fill(0);
-            did_read = ReadFile(
-                self.handle,
-                Some(&mut self.read_buffer),
-                None,
-                Some(&mut self.overlapped_read),
-            )
-            .is_ok();
-            if !did_read && GetLastError() != ERROR_IO_PENDING {
-                return None;
-            }
+            model,
+            hid_info,
+            reads,
+            writer,
+        })
+    }
+}
 
-            self.read_pending = true;
-        }
+/// A handle owned by a device, closed when dropped.
+///
+/// Handles can be used from any thread, so the owner is `Send` and `Sync` like the `HANDLE` it wraps.
+/// The handle is valid until dropped, so every copy returned by [`Self::get`] must be used
+/// while the owner is alive.
+pub(super) struct OwnedHandle(HANDLE);
 
-        if !did_read && timeout_millis.is_some() {
-            let wait_result =
-                WaitForSingleObject(self.overlapped_read.hEvent, timeout_millis.unwrap() as u32);
-            if wait_result == WAIT_TIMEOUT {
-                return Some(0);
-            }
-            if wait_result != WAIT_OBJECT_0 {
-                // Wait failed
-                return None;
-            }
-        }
+impl OwnedHandle {
+    /// Takes ownership of a valid handle, which is no longer closed by the caller.
+    pub(super) const unsafe fn new(handle: HANDLE) -> Self {
+        Self(handle)
+    }
 
-        let mut bytes_read = 0;
-        let result =
-            GetOverlappedResult(self.handle, &self.overlapped_read, &mut bytes_read, true).is_ok();
-        self.read_pending = false;
-        if result {
-            let bytes_to_copy = usize::min(bytes_read as usize, buffer_size);
-            buffer[..bytes_to_copy].copy_from_slice(&self.read_buffer[..bytes_to_copy]);
-            Some(bytes_to_copy)
-        } else {
-            None
-        }
+    pub(super) const fn get(&self) -> HANDLE {
+        self.0
+    }
+
+    /// Duplicates the handle, e.g. to use it on another thread independently of this owner.
+    pub(super) unsafe fn duplicate(&self, operation: OsOperation) -> WiimoteResult<Self> {
+        let mut handle = HANDLE::default();
+        let process = GetCurrentProcess();
+        DuplicateHandle(
+            process,
+            self.0,
+            process,
+            &mut handle,
+            0,
+            false,
+            DUPLICATE_SAME_ACCESS,
+        )
+        .map_err(|_| last_os_error(operation))?;
+        Ok(Self(handle))
     }
 
-    unsafe fn write_impl(&mut self, buffer: &[u8]) -> Option<usize> {
-        if self.write_pending {
-            WaitForSingleObject(self.overlapped_write.hEvent, INFINITE);
+    /// Creates a manual-reset event for overlapped I/O.
+    pub(super) unsafe fn event(operation: OsOperation) -> WiimoteResult<Self> {
+        CreateEventW(None, true, false, None)
+            .map(Self)
+            .map_err(|_| last_os_error(operation))
+    }
+}
+
+impl Drop for OwnedHandle {
+    fn drop(&mut self) {
+        unsafe {
+            _ = CloseHandle(self.0);
         }
-        self.write_pending = true;
+    }
+}
+
+/// How long a write waits for its completion before it is cancelled,
+/// output reports are sent within milliseconds unless the Wii remote stopped responding.
+const WRITE_TIMEOUT_MILLIS: u32 = 1000;
+
+/// Writes output reports with an overlapped write through its own handle,
+/// so writes do not wait for a blocking read.
+///
+/// The `OVERLAPPED` structure only lives during a write, which completes or is cancelled
+/// before returning, so the writer holds no pointers used by pending I/O and can be sent to other threads.
+struct WindowsWiimoteWriter {
+    handle: OwnedHandle,
+    event: OwnedHandle,
+    write_buffer: Vec<u8>,
+    /// The registration of the reads of the device with the I/O thread, cancelled with the writes.
+    reads_id: u64,
+}
+
+impl WindowsWiimoteWriter {
+    unsafe fn new(
+        handle: OwnedHandle,
+        write_buffer_size: usize,
+        reads_id: u64,
+    ) -> WiimoteResult<Self> {
+        Ok(Self {
+            handle,
+            event: OwnedHandle::event(OsOperation::Write)?,
+            write_buffer: vec![0; write_buffer_size],
+            reads_id,
+        })
+    }
 
+    /// Writes an output report with an overlapped write and waits for its completion.
+    unsafe fn write_overlapped(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
         let data_size = usize::min(buffer.len(), self.write_buffer.len());
         self.write_buffer[..data_size].copy_from_slice(&buffer[..data_size]);
         self.write_buffer[data_size..].fill(0);
 
+        let mut overlapped_write = OVERLAPPED {
+            hEvent: self.event.get(),
+            ..OVERLAPPED::default()
+        };
         if WriteFile(
-            self.handle,
+            self.handle.get(),
             Some(&self.write_buffer),
             None,
-            Some(&mut self.overlapped_write),
+            Some(&mut overlapped_write),
         )
         .is_err()
         {
             if GetLastError() != ERROR_IO_PENDING {
-                return None;
+                return Err(last_os_error(OsOperation::Write));
             }
 
-            let wait_result = WaitForSingleObject(self.overlapped_write.hEvent, INFINITE);
+            let wait_result = WaitForSingleObject(self.event.get(), WRITE_TIMEOUT_MILLIS);
             if wait_result != WAIT_OBJECT_0 {
-                self.write_pending = false;
-                if wait_result == WAIT_FAILED {
-                    println!("error: {}", GetLastError().0);
-                }
-                return None;
+                let error = if wait_result == WAIT_FAILED {
+                    last_os_error(OsOperation::Write)
+                } else if wait_result == WAIT_TIMEOUT {
+                    WiimoteError::WriteTimedOut
+                } else {
+                    WiimoteError::Disconnected
+                };
+                // The write must not use the buffer and the OVERLAPPED structure after returning.
+                _ = CancelIoEx(self.handle.get(), Some(&overlapped_write));
+                let mut bytes_written = 0;
+                _ = GetOverlappedResult(
+                    self.handle.get(),
+                    &overlapped_write,
+                    &mut bytes_written,
+                    true,
+                );
+                return Err(error);
             }
         }
 
-        self.write_pending = false;
         let mut bytes_written = 0;
         if GetOverlappedResult(
-            self.handle,
-            &self.overlapped_write,
+            self.handle.get(),
+            &overlapped_write,
             &mut bytes_written,
             true,
         )
         .is_err()
         {
-            None
+            Err(last_os_error(OsOperation::Write))
         } else {
-            Some(bytes_written as usize)
+            Ok(bytes_written as usize)
+        }
+    }
+}
+
+impl NativeWiimoteWriter for WindowsWiimoteWriter {
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        unsafe { self.write_overlapped(buffer) }
+    }
+
+    /// Cancels the I/O of the device handle and stops the reads on the I/O thread,
+    /// which cancels the pending read and disconnects the reports channel.
+    fn cancel_pending_io(&mut self) {
+        unsafe {
+            _ = CancelIoEx(self.handle.get(), None);
         }
+        multiplexer::unregister(self.reads_id);
+    }
+}
+
+unsafe fn last_os_error(operation: OsOperation) -> WiimoteError {
+    #[allow(clippy::cast_possible_wrap)] // Same as `std::io::Error::last_os_error`
+    WiimoteError::Os {
+        code: GetLastError().0 as i32,
+        operation,
     }
 }
 
 impl NativeWiimote for WindowsNativeWiimote {
-    fn read(&mut self, buffer: &mut [u8]) -> Option<usize> {
-        unsafe { self.read_timeout_impl(buffer, None) }
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        self.reads.receive(buffer, None)
     }
 
-    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> Option<usize> {
-        unsafe { self.read_timeout_impl(buffer, Some(timeout_millis)) }
+    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize> {
+        let timeout = Duration::from_millis(timeout_millis as u64);
+        self.reads.receive(buffer, Some(timeout))
     }
 
-    fn write(&mut self, buffer: &[u8]) -> Option<usize> {
-        unsafe { self.write_impl(buffer) }
+    #[cfg(feature = "tokio")]
+    fn poll_read(&mut self, cx: &mut Context<'_>, buffer: &mut [u8]) -> Poll<WiimoteResult<usize>> {
+        match self.reads.try_receive(buffer) {
+            Ok(0) => {}
+            result => return Poll::Ready(result),
+        }
+        self.reads.set_waker(cx.waker());
+        // A report dispatched before the waker was set would not wake the task.
+        match self.reads.try_receive(buffer) {
+            Ok(0) => Poll::Pending,
+            result => Poll::Ready(result),
+        }
     }
 
-    fn identifier(&self) -> String {
-        self.identifier.clone()
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        self.writer.write(buffer)
+    }
+
+    fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+        unsafe {
+            let writer = self
+                .handle
+                .duplicate(OsOperation::Write)
+                .and_then(|handle| {
+                    WindowsWiimoteWriter::new(
+                        handle,
+                        self.writer.write_buffer.len(),
+                        self.reads.id(),
+                    )
+                });
+            match writer {
+                Ok(writer) => Some(Box::new(writer)),
+                Err(err) => {
+                    log::warn!("Failed to duplicate handle of {}: {err:?}", self.identifier);
+                    None
+                }
+            }
+        }
+    }
+
+    fn last_received(&self) -> Option<Instant> {
+        self.reads.last_received()
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn model(&self) -> WiimoteModel {
+        self.model
+    }
+
+    fn hid_info(&self) -> Option<HidInfo> {
+        Some(self.hid_info.clone())
     }
 }
 
 impl Drop for WindowsNativeWiimote {
     fn drop(&mut self) {
         unsafe {
-            _ = CloseHandle(self.overlapped_read.hEvent);
-            _ = CloseHandle(self.overlapped_write.hEvent);
-            _ = CloseHandle(self.handle);
-
             forget_wiimote(&self.identifier);
             let mut wiimotes_handled = match WIIMOTES_HANDLED.lock() {
                 Ok(wiimotes_handled) => wiimotes_handled,
@@ -0,0 +1,373 @@
+//! A single I/O thread reading the input reports of all connected Wii remotes.
+//!
+//! Every registered Wii remote has an overlapped read pending on the thread, which waits for the events
+//! of all reads with `WaitForMultipleObjects` and dispatches the completed reports to the report queue of the device.
+//! The queue drops the oldest report once `REPORT_QUEUE_CAPACITY` reports were not read.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+#[cfg(feature = "tokio")]
+use std::task::Waker;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "tokio")]
+use crossbeam_channel::TryRecvError;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use windows::Win32::Foundation::{
+    CloseHandle, GetLastError, ERROR_IO_PENDING, HANDLE, WAIT_OBJECT_0,
+};
+use windows::Win32::Storage::FileSystem::ReadFile;
+use windows::Win32::System::Threading::{
+    CreateEventW, ResetEvent, SetEvent, WaitForMultipleObjects, WaitForSingleObject, INFINITE,
+};
+use windows::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
+
+use super::{last_os_error, OwnedHandle};
+use crate::native::common::{report_queue, ReportReceiver, ReportSender, REPORT_QUEUE_CAPACITY};
+use crate::result::{OsOperation, WiimoteError, WiimoteResult};
+
+/// `WaitForMultipleObjects` waits for at most 64 handles, one of them is the wake event.
+const MAXIMUM_READERS: usize = 63;
+
+/// A received report in a buffer sized by the input report length of the device and the number of bytes read.
+/// The buffers are returned to the reader once copied, so reports are received without allocating.
+type ReportResult = WiimoteResult<(Vec<u8>, usize)>;
+
+/// The receiving end of the reads of a Wii remote, unregisters the device when dropped.
+pub(super) struct ReadRegistration {
+    id: u64,
+    reports: ReportReceiver<ReportResult>,
+    recycled: Sender<Vec<u8>>,
+    #[cfg(feature = "tokio")]
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl ReadRegistration {
+    /// Copies the next report into `buffer`, waiting at most `timeout` if set.
+    /// Returns 0 bytes if no report was received in time.
+    pub(super) fn receive(
+        &self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> WiimoteResult<usize> {
+        let result = match timeout {
+            None => self
+                .reports
+                .recv()
+                .map_err(|_| WiimoteError::Disconnected)?,
+            Some(timeout) => match self.reports.recv_timeout(timeout) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => return Ok(0),
+                Err(RecvTimeoutError::Disconnected) => return Err(WiimoteError::Disconnected),
+            },
+        };
+        self.copy_report(result, buffer)
+    }
+
+    /// Copies the next report into `buffer` without waiting, returns 0 bytes if none is available.
+    #[cfg(feature = "tokio")]
+    pub(super) fn try_receive(&self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        match self.reports.try_recv() {
+            Ok(result) => self.copy_report(result, buffer),
+            Err(TryRecvError::Empty) => Ok(0),
+            Err(TryRecvError::Disconnected) => Err(WiimoteError::Disconnected),
+        }
+    }
+
+    /// Returns when the report received last was read from the device by the I/O thread.
+    pub(super) fn last_received(&self) -> Option<Instant> {
+        self.reports.last_received()
+    }
+
+    /// Returns the identifier to [`unregister`] the device with from another thread.
+    pub(super) const fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Wakes the task once the next report is dispatched.
+    #[cfg(feature = "tokio")]
+    pub(super) fn set_waker(&self, waker: &Waker) {
+        *lock(&self.waker) = Some(waker.clone());
+    }
+
+    fn copy_report(&self, result: ReportResult, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        let (report, size) = result?;
+        let bytes_to_copy = usize::min(size, buffer.len());
+        buffer[..bytes_to_copy].copy_from_slice(&report[..bytes_to_copy]);
+        _ = self.recycled.try_send(report);
+        Ok(bytes_to_copy)
+    }
+}
+
+impl Drop for ReadRegistration {
+    fn drop(&mut self) {
+        unregister(self.id);
+    }
+}
+
+/// Stops reading from the device registered as `id`, the pending read is cancelled
+/// and reads waiting for a report fail with `WiimoteError::Disconnected`.
+pub(super) fn unregister(id: u64) {
+    if let Some(multiplexer) = MULTIPLEXER.get() {
+        multiplexer.send(Command::Unregister(id));
+    }
+}
+
+/// Starts reading from the device on the I/O thread.
+///
+/// The handle is duplicated, so the pending read can be cancelled on the I/O thread
+/// independently of the device closing its handle.
+pub(super) unsafe fn register(
+    handle: &OwnedHandle,
+    read_buffer_size: usize,
+) -> WiimoteResult<ReadRegistration> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let multiplexer = Multiplexer::get()?;
+
+    let read_handle = handle.duplicate(OsOperation::Read)?;
+    let event = OwnedHandle::event(OsOperation::Read)?;
+    let overlapped = ReadOverlapped(Box::new(OVERLAPPED {
+        hEvent: event.get(),
+        ..OVERLAPPED::default()
+    }));
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (sender, reports) = report_queue(REPORT_QUEUE_CAPACITY);
+    // At most the queued reports and the copied report are in use at once.
+    let (recycled, recycled_receiver) = crossbeam_channel::bounded(REPORT_QUEUE_CAPACITY + 1);
+    #[cfg(feature = "tokio")]
+    let waker = Arc::new(Mutex::new(None));
+    multiplexer.send(Command::Register(Reader {
+        id,
+        handle: read_handle,
+        event,
+        overlapped,
+        buffer: vec![0; read_buffer_size],
+        pending: false,
+        sender: Some(sender),
+        recycled: recycled_receiver,
+        #[cfg(feature = "tokio")]
+        waker: Arc::clone(&waker),
+    }));
+
+    Ok(ReadRegistration {
+        id,
+        reports,
+        recycled,
+        #[cfg(feature = "tokio")]
+        waker,
+    })
+}
+
+static MULTIPLEXER: OnceLock<Multiplexer> = OnceLock::new();
+
+enum Command {
+    Register(Reader),
+    Unregister(u64),
+}
+
+struct Multiplexer {
+    commands: Mutex<Vec<Command>>,
+    /// Auto-reset event signaled when a command was added.
+    wake_event: HANDLE,
+}
+
+impl Multiplexer {
+    /// Returns the multiplexer, starting the I/O thread on first use.
+    unsafe fn get() -> WiimoteResult<&'static Self> {
+        if let Some(multiplexer) = MULTIPLEXER.get() {
+            return Ok(multiplexer);
+        }
+        let wake_event =
+            CreateEventW(None, false, false, None).map_err(|_| last_os_error(OsOperation::Read))?;
+        let mut created = false;
+        let multiplexer = MULTIPLEXER.get_or_init(|| {
+            created = true;
+            Self {
+                commands: Mutex::new(Vec::new()),
+                wake_event,
+            }
+        });
+        if created {
+            std::thread::Builder::new()
+                .name("wiimote-reader".to_string())
+                .spawn(move || multiplexer.run())
+                .expect("Failed to spawn Wii remote reader thread");
+        } else {
+            // Another thread initialized the multiplexer first.
+            _ = CloseHandle(wake_event);
+        }
+        Ok(multiplexer)
+    }
+
+    fn send(&self, command: Command) {
+        lock(&self.commands).push(command);
+        unsafe {
+            _ = SetEvent(self.wake_event);
+        }
+    }
+
+    fn run(&self) {
+        let mut readers: Vec<Reader> = Vec::new();
+        let mut events = Vec::new();
+        loop {
+            let commands = std::mem::take(&mut *lock(&self.commands));
+            for command in commands {
+                match command {
+                    Command::Register(reader) if readers.len() < MAXIMUM_READERS => {
+                        readers.push(reader);
+                    }
+                    Command::Register(reader) => {
+                        log::error!("Too many Wii remotes connected, dropping reader");
+                        drop(reader);
+                    }
+                    Command::Unregister(id) => readers.retain(|reader| reader.id != id),
+                }
+            }
+            readers.retain_mut(|reader| unsafe { reader.start_read() });
+
+            events.clear();
+            events.push(self.wake_event);
+            events.extend(readers.iter().map(|reader| reader.event.get()));
+            let wait_result = unsafe { WaitForMultipleObjects(&events, false, INFINITE) };
+            if wait_result.0.wrapping_sub(WAIT_OBJECT_0.0) as usize >= events.len() {
+                log::error!("Waiting for Wii remote reads failed: {:?}", unsafe {
+                    last_os_error(OsOperation::Read)
+                });
+                std::thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            // Check every read instead of only the signaled one, so a busy Wii remote
+            // earlier in the list does not starve the others.
+            readers.retain_mut(|reader| unsafe {
+                WaitForSingleObject(reader.event.get(), 0) != WAIT_OBJECT_0
+                    || reader.complete_read()
+            });
+        }
+    }
+}
+
+/// The `OVERLAPPED` structure of the read of a device,
+/// boxed so the address used by the pending read stays valid when the reader is moved.
+struct ReadOverlapped(Box<OVERLAPPED>);
+
+// The structure is created before the reader is sent to the I/O thread and only used there afterwards.
+// It contains the event handle of the reader and offsets, its pointer field is never set.
+unsafe impl Send for ReadOverlapped {}
+
+/// The state of the overlapped read of a device on the I/O thread.
+struct Reader {
+    id: u64,
+    handle: OwnedHandle,
+    /// The event of `overlapped`, signaled when the read completed.
+    event: OwnedHandle,
+    overlapped: ReadOverlapped,
+    buffer: Vec<u8>,
+    pending: bool,
+    /// Dropped before waking a waiting async read when the reader is dropped.
+    sender: Option<ReportSender<ReportResult>>,
+    /// Buffers of dispatched reports that were copied by the device.
+    recycled: Receiver<Vec<u8>>,
+    #[cfg(feature = "tokio")]
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Reader {
+    /// Starts the overlapped read if none is pending, returns false if the device failed.
+    unsafe fn start_read(&mut self) -> bool {
+        if self.pending {
+            return true;
+        }
+        _ = ResetEvent(self.event.get());
+        self.buffer.fill(0);
+        // The event is also signaled if the read completes immediately.
+        if ReadFile(
+            self.handle.get(),
+            Some(&mut self.buffer),
+            None,
+            Some(&mut *self.overlapped.0),
+        )
+        .is_err()
+            && GetLastError() != ERROR_IO_PENDING
+        {
+            self.dispatch(Err(last_os_error(OsOperation::Read)));
+            return false;
+        }
+        self.pending = true;
+        true
+    }
+
+    /// Dispatches the completed read, returns false if the device failed or was closed.
+    unsafe fn complete_read(&mut self) -> bool {
+        let mut bytes_read = 0;
+        let result = GetOverlappedResult(
+            self.handle.get(),
+            &*self.overlapped.0,
+            &mut bytes_read,
+            false,
+        );
+        self.pending = false;
+        if result.is_err() {
+            self.dispatch(Err(last_os_error(OsOperation::Read)));
+            return false;
+        }
+        // The completed buffer is dispatched as is and replaced for the next read.
+        let next_buffer = self
+            .recycled
+            .try_recv()
+            .unwrap_or_else(|_| vec![0; self.buffer.len()]);
+        let report = std::mem::replace(&mut self.buffer, next_buffer);
+        let bytes_read = usize::min(bytes_read as usize, report.len());
+        self.dispatch(Ok((report, bytes_read)))
+    }
+
+    /// Sends the result to the device, returns false if the device was closed.
+    fn dispatch(&self, result: ReportResult) -> bool {
+        // The buffer of a dropped report is not recycled, the next read allocates a new one instead.
+        let sent = self
+            .sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(result).is_ok());
+        #[cfg(feature = "tokio")]
+        if let Some(waker) = lock(&self.waker).take() {
+            waker.wake();
+        }
+        sent
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        unsafe {
+            if self.pending {
+                _ = CancelIoEx(self.handle.get(), Some(&*self.overlapped.0));
+                // Waits until the read no longer uses the buffer,
+                // the handles are closed afterwards when the fields are dropped.
+                let mut bytes_read = 0;
+                _ = GetOverlappedResult(
+                    self.handle.get(),
+                    &*self.overlapped.0,
+                    &mut bytes_read,
+                    true,
+                );
+            }
+        }
+        // Wakes a waiting async read, which fails as the queue is disconnected.
+        drop(self.sender.take());
+        #[cfg(feature = "tokio")]
+        if let Some(waker) = lock(&self.waker).take() {
+            waker.wake();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(err) => err.into_inner(),
+    }
+}
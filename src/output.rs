@@ -1,17 +1,17 @@
 use crate::prelude::*;
 use bitflags::bitflags;
 
-const RUMBLE_ID: u8 = 0x10;
-const PLAYER_LED_ID: u8 = 0x11;
-const DATA_REPORTING_MODE_ID: u8 = 0x12;
-const IR_CAMERA_ENABLE_ID: u8 = 0x13;
-const SPEAKER_ENABLE_ID: u8 = 0x14;
-const STATUS_REQUEST_ID: u8 = 0x15;
-const WRITE_MEMORY_ID: u8 = 0x16;
-const READ_MEMORY_ID: u8 = 0x17;
-const SPEAKER_DATA_ID: u8 = 0x18;
-const SPEAKER_MUTE_ID: u8 = 0x19;
-const IR_CAMERA_ENABLE_2_ID: u8 = 0x1A;
+const RUMBLE_ID: u8 = ReportId::Rumble.to_u8();
+const PLAYER_LED_ID: u8 = ReportId::PlayerLed.to_u8();
+const DATA_REPORTING_MODE_ID: u8 = ReportId::DataReportingMode.to_u8();
+const IR_CAMERA_ENABLE_ID: u8 = ReportId::IrCameraEnable.to_u8();
+const SPEAKER_ENABLE_ID: u8 = ReportId::SpeakerEnable.to_u8();
+const STATUS_REQUEST_ID: u8 = ReportId::StatusRequest.to_u8();
+const WRITE_MEMORY_ID: u8 = ReportId::WriteMemory.to_u8();
+const READ_MEMORY_ID: u8 = ReportId::ReadMemory.to_u8();
+const SPEAKER_DATA_ID: u8 = ReportId::SpeakerData.to_u8();
+const SPEAKER_MUTE_ID: u8 = ReportId::SpeakerMute.to_u8();
+const IR_CAMERA_ENABLE_2_ID: u8 = ReportId::IrCameraEnable2.to_u8();
 
 bitflags! {
     #[derive(Debug, Clone, Copy)]
@@ -23,13 +23,13 @@ bitflags! {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct DataReporingMode {
     pub continuous: bool,
     pub mode: u8,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Addressing {
     /// If true, read from control registers, otherwise from EEPROM.
     control_registers: bool,
@@ -111,6 +111,124 @@ pub enum OutputReport {
 }
 
 impl OutputReport {
+    /// The maximum number of bytes written by a [`Self::WriteMemory`] report.
+    pub const WRITE_MEMORY_MAX_SIZE: usize = 16;
+    /// The maximum number of bytes sent by a [`Self::SpeakerData`] report.
+    pub const SPEAKER_DATA_MAX_SIZE: usize = 20;
+
+    /// Creates a report writing `data` to the memory at `addressing`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` is longer than 16 bytes
+    /// or its length differs from the size of `addressing`.
+    pub fn write_memory(addressing: Addressing, data: &[u8]) -> WiimoteResult<Self> {
+        check_size(WRITE_MEMORY_ID, data.len(), Self::WRITE_MEMORY_MAX_SIZE)?;
+        if usize::from(addressing.size) != data.len() {
+            return Err(WiimoteDeviceError::InvalidData.into());
+        }
+        let mut buffer = [0u8; 16];
+        buffer[..data.len()].copy_from_slice(data);
+        Ok(Self::WriteMemory(addressing, buffer))
+    }
+
+    /// Splits `data` into reports writing up to 16 bytes each, starting at the address of `addressing`
+    /// and advancing by 16 bytes per report. Only the memory and the address of `addressing` are used.
+    ///
+    /// ```
+    /// use wiimote_rs::output::{Addressing, OutputReport};
+    ///
+    /// let reports: Vec<_> = OutputReport::write_memory_chunks(Addressing::eeprom(0x0FCA, 0), &[0; 20]).collect();
+    /// assert_eq!(reports.len(), 2);
+    /// ```
+    pub fn write_memory_chunks(
+        addressing: Addressing,
+        data: &[u8],
+    ) -> impl Iterator<Item = Self> + '_ {
+        (addressing.address..)
+            .step_by(Self::WRITE_MEMORY_MAX_SIZE)
+            .zip(data.chunks(Self::WRITE_MEMORY_MAX_SIZE))
+            .map(move |(address, chunk)| {
+                let mut buffer = [0u8; 16];
+                buffer[..chunk.len()].copy_from_slice(chunk);
+                let chunk_addressing = Addressing {
+                    address,
+                    #[allow(clippy::cast_possible_truncation)] // At most 16 bytes
+                    size: chunk.len() as u16,
+                    ..addressing
+                };
+                Self::WriteMemory(chunk_addressing, buffer)
+            })
+    }
+
+    /// Creates a report sending `data` to the speaker.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` is longer than 20 bytes.
+    pub fn speaker_data(data: &[u8]) -> WiimoteResult<Self> {
+        check_size(SPEAKER_DATA_ID, data.len(), Self::SPEAKER_DATA_MAX_SIZE)?;
+        let mut buffer = [0u8; 20];
+        buffer[..data.len()].copy_from_slice(data);
+        #[allow(clippy::cast_possible_truncation)] // At most 20 bytes
+        Ok(Self::SpeakerData(data.len() as u8, buffer))
+    }
+
+    /// Returns the ID of the output report, the first byte sent to the Wii remote.
+    #[must_use]
+    pub const fn report_id(&self) -> u8 {
+        match self {
+            Self::Rumble(_) => RUMBLE_ID,
+            Self::PlayerLed(_) => PLAYER_LED_ID,
+            Self::DataReportingMode(_) => DATA_REPORTING_MODE_ID,
+            Self::IrCameraEnable(_) => IR_CAMERA_ENABLE_ID,
+            Self::SpeakerEnable(_) => SPEAKER_ENABLE_ID,
+            Self::StatusRequest => STATUS_REQUEST_ID,
+            Self::WriteMemory(..) => WRITE_MEMORY_ID,
+            Self::ReadMemory(_) => READ_MEMORY_ID,
+            Self::SpeakerData(..) => SPEAKER_DATA_ID,
+            Self::SpeakerMute(_) => SPEAKER_MUTE_ID,
+            Self::IrCameraEnable2(_) => IR_CAMERA_ENABLE_2_ID,
+        }
+    }
+
+    /// Returns the ID of the input report the Wii remote answers this report with:
+    /// the status (0x20), the memory data (0x21) or the acknowledgement (0x22) of a memory write.
+    /// Other reports are only acknowledged if they fail.
+    ///
+    /// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#0x22:_Acknowledge_output_report.2C_return_function_result>
+    #[must_use]
+    pub const fn expected_ack(&self) -> Option<ReportId> {
+        match self {
+            Self::StatusRequest => Some(ReportId::Status),
+            Self::ReadMemory(_) => Some(ReportId::ReadMemoryData),
+            Self::WriteMemory(..) => Some(ReportId::Acknowledge),
+            _ => None,
+        }
+    }
+
+    /// Checks that the report fits the wire format, [`Self::fill_buffer`] truncates data that does not fit.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a [`Self::WriteMemory`] report writes more than 16 bytes
+    /// or a [`Self::SpeakerData`] report contains more than 20 bytes.
+    pub fn validate(&self) -> WiimoteResult<()> {
+        match self {
+            Self::WriteMemory(addressing, _) => check_size(
+                WRITE_MEMORY_ID,
+                usize::from(addressing.size),
+                Self::WRITE_MEMORY_MAX_SIZE,
+            ),
+            Self::SpeakerData(length, _) => check_size(
+                SPEAKER_DATA_ID,
+                usize::from(*length),
+                Self::SPEAKER_DATA_MAX_SIZE,
+            ),
+            _ => Ok(()),
+        }
+    }
+
     /// Converts the output report to a byte array.
     /// The rumble flag is used in all output reports to enable or disable the rumble motor.
     ///
@@ -127,40 +245,32 @@ impl OutputReport {
     ///
     /// Returns the actual length of the data.
     pub fn fill_buffer(&self, mut rumble: bool, buffer: &mut [u8]) -> usize {
+        buffer[0] = self.report_id();
         buffer[1] = 0;
         let length = match self {
             Self::Rumble(rumble_enabled) => {
                 rumble = *rumble_enabled;
-                buffer[0] = RUMBLE_ID;
                 2
             }
             Self::PlayerLed(flags) => {
-                buffer[0] = PLAYER_LED_ID;
                 buffer[1] = flags.bits();
                 2
             }
             Self::DataReportingMode(mode) => {
-                buffer[0] = DATA_REPORTING_MODE_ID;
                 buffer[1] = if mode.continuous { 0x04 } else { 0x00 };
                 buffer[2] = mode.mode;
                 3
             }
             Self::IrCameraEnable(enable) => {
-                buffer[0] = IR_CAMERA_ENABLE_ID;
                 buffer[1] = if *enable { 0x04 } else { 0x00 };
                 2
             }
             Self::SpeakerEnable(enable) => {
-                buffer[0] = SPEAKER_ENABLE_ID;
                 buffer[1] = if *enable { 0x04 } else { 0x00 };
                 2
             }
-            Self::StatusRequest => {
-                buffer[0] = STATUS_REQUEST_ID;
-                2
-            }
+            Self::StatusRequest => 2,
             Self::WriteMemory(addressing, data) => {
-                buffer[0] = WRITE_MEMORY_ID;
                 buffer[1..=4].copy_from_slice(&addressing.address.to_be_bytes());
                 // Address is 3 bytes long, byte 1 is used for control register and rumble.
                 buffer[1] = if addressing.control_registers {
@@ -173,7 +283,6 @@ impl OutputReport {
                 22
             }
             Self::ReadMemory(addressing) => {
-                buffer[0] = READ_MEMORY_ID;
                 buffer[1..=4].copy_from_slice(&addressing.address.to_be_bytes());
                 // Address is 3 bytes long, byte 1 is used for control register and rumble.
                 buffer[1] = if addressing.control_registers {
@@ -185,18 +294,15 @@ impl OutputReport {
                 7
             }
             Self::SpeakerData(length, data) => {
-                buffer[0] = SPEAKER_DATA_ID;
                 buffer[1] = (*length) << 3;
                 buffer[2..=21].copy_from_slice(data);
                 22
             }
             Self::SpeakerMute(mute) => {
-                buffer[0] = SPEAKER_MUTE_ID;
                 buffer[1] = if *mute { 0x04 } else { 0x00 };
                 2
             }
             Self::IrCameraEnable2(enable) => {
-                buffer[0] = IR_CAMERA_ENABLE_2_ID;
                 buffer[1] = if *enable { 0x04 } else { 0x00 };
                 2
             }
@@ -212,10 +318,40 @@ impl OutputReport {
     }
 }
 
+fn check_size(report_id: u8, size: usize, maximum: usize) -> WiimoteResult<()> {
+    if size > maximum {
+        return Err(WiimoteDeviceError::OutputReportTooLong {
+            report_id,
+            size,
+            maximum,
+        }
+        .into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_write_memory_chunks() {
+        let data: Vec<u8> = (0..20).collect();
+        let reports: Vec<_> =
+            OutputReport::write_memory_chunks(Addressing::control_registers(0xA2_0001, 0), &data)
+                .collect();
+        assert_eq!(reports.len(), 2);
+
+        let (buffer, size) = reports[0].to_array(false);
+        assert_eq!(&buffer[..6], [0x16, 0x04, 0xA2, 0x00, 0x01, 16]);
+        assert_eq!(&buffer[6..size], &data[..16]);
+        let (buffer, size) = reports[1].to_array(false);
+        assert_eq!(&buffer[..6], [0x16, 0x04, 0xA2, 0x00, 0x11, 4]);
+        assert_eq!(&buffer[6..10], &data[16..]);
+        assert!(buffer[10..size].iter().all(|&byte| byte == 0));
+        assert!(reports.iter().all(|report| report.validate().is_ok()));
+    }
+
     #[test]
     fn test_rumble_report() {
         let report = OutputReport::Rumble(true);
@@ -294,4 +430,34 @@ mod tests {
         assert_eq!(buffer[1], (20 << 3) | 1); // length and rumble
         assert_eq!(&buffer[2..=21], *b"12345678901234567890");
     }
+
+    #[test]
+    fn test_report_validation() {
+        let report = OutputReport::write_memory(Addressing::eeprom(0x0020, 3), &[1, 2, 3]).unwrap();
+        assert_eq!(report.report_id(), WRITE_MEMORY_ID);
+        assert_eq!(report.expected_ack(), Some(ReportId::Acknowledge));
+        assert!(report.validate().is_ok());
+        assert!(OutputReport::write_memory(Addressing::eeprom(0x0020, 17), &[0; 17]).is_err());
+        assert!(OutputReport::write_memory(Addressing::eeprom(0x0020, 4), &[0; 3]).is_err());
+        assert!(
+            OutputReport::WriteMemory(Addressing::eeprom(0x0020, 17), [0; 16])
+                .validate()
+                .is_err()
+        );
+
+        let report = OutputReport::speaker_data(&[0xAA; 20]).unwrap();
+        assert!(matches!(report, OutputReport::SpeakerData(20, _)));
+        assert_eq!(report.expected_ack(), None);
+        assert!(matches!(
+            OutputReport::speaker_data(&[0; 21]),
+            Err(WiimoteError::WiimoteDeviceError(
+                WiimoteDeviceError::OutputReportTooLong {
+                    report_id: SPEAKER_DATA_ID,
+                    size: 21,
+                    maximum: 20,
+                }
+            ))
+        ));
+        assert!(OutputReport::SpeakerData(21, [0; 20]).validate().is_err());
+    }
 }
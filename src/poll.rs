@@ -0,0 +1,121 @@
+//! Waiting for the reports of several Wii remotes at once.
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::native::{queued_report_count, wait_for_queued_report};
+use crate::prelude::*;
+
+/// How long to sleep between checking custom transports, which can not be waited on together.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A report read ahead by [`poll_all`], returned by the next read of the device.
+pub(crate) type PeekedReport = WiimoteResult<([u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE], usize)>;
+
+/// Waits until at least one of the Wii remotes received a report or `timeout_millis` elapsed
+/// and returns the indices of the devices with a fresh report, empty if the timeout elapsed.
+///
+/// The reports stay queued and are returned by the next read of the device,
+/// so a game can call this once per frame and only read the returned devices.
+/// Devices whose read failed are returned as well, their next read returns the error.
+///
+/// On Linux the readiness file descriptors of the devices are waited on together,
+/// on other platforms the I/O thread of the backend wakes the caller once a report arrived.
+/// Custom transports are checked every millisecond.
+pub fn poll_all(devices: &[Arc<Mutex<WiimoteDevice>>], timeout_millis: usize) -> Vec<usize> {
+    let deadline = Instant::now() + Duration::from_millis(timeout_millis as u64);
+    loop {
+        // Counted before checking the devices, so a report queued in between ends the wait.
+        let queued = queued_report_count();
+        let ready: Vec<usize> = devices
+            .iter()
+            .enumerate()
+            .filter(|(_, device)| lock(device).peek_report())
+            .map(|(index, _)| index)
+            .collect();
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !ready.is_empty() || remaining.is_zero() {
+            return ready;
+        }
+        wait(devices, remaining, queued);
+    }
+}
+
+fn lock(device: &Mutex<WiimoteDevice>) -> MutexGuard<'_, WiimoteDevice> {
+    device.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Waits until one of the readiness file descriptors is readable,
+/// falls back to waiting for the I/O thread if any device has none.
+#[cfg(all(target_os = "linux", feature = "linux-backend"))]
+fn wait(devices: &[Arc<Mutex<WiimoteDevice>>], remaining: Duration, queued: u64) {
+    use nix::libc::{poll, pollfd, POLLIN};
+
+    let fds: Option<Vec<pollfd>> = devices
+        .iter()
+        .map(|device| {
+            lock(device).readiness_fd().map(|fd| pollfd {
+                fd,
+                events: POLLIN,
+                revents: 0,
+            })
+        })
+        .collect();
+    let Some(mut fds) = fds else {
+        wait_for_backend(devices, remaining, queued);
+        return;
+    };
+    // Rounded up, so the deadline has passed once poll timed out.
+    let timeout = i32::try_from(remaining.as_micros().div_ceil(1000)).unwrap_or(i32::MAX);
+    // The file descriptors may be closed by a concurrent reconnect, poll then reports them
+    // as invalid and the devices are checked again.
+    unsafe { poll(fds.as_mut_ptr(), fds.len() as _, timeout) };
+}
+
+#[cfg(not(all(target_os = "linux", feature = "linux-backend")))]
+fn wait(devices: &[Arc<Mutex<WiimoteDevice>>], remaining: Duration, queued: u64) {
+    wait_for_backend(devices, remaining, queued);
+}
+
+/// Waits until the I/O thread of the backend queued a report for any device,
+/// sleeps for [`POLL_INTERVAL`] instead if a device uses a custom transport.
+fn wait_for_backend(devices: &[Arc<Mutex<WiimoteDevice>>], remaining: Duration, queued: u64) {
+    if devices
+        .iter()
+        .all(|device| lock(device).backend() != Backend::Custom)
+    {
+        wait_for_queued_report(queued, remaining);
+    } else {
+        std::thread::sleep(remaining.min(POLL_INTERVAL));
+    }
+}
+
+impl WiimoteDevice {
+    /// Reads a report ahead without waiting if none was read ahead yet,
+    /// returns true if a report or a read error is queued for the next read.
+    pub(crate) fn peek_report(&self) -> bool {
+        let mut peeked = self.lock_peeked_report();
+        if peeked.is_some() {
+            return true;
+        }
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        match self.read_unpeeked(&mut buffer, Some(0)) {
+            Ok(0) => false,
+            result => {
+                *peeked = Some(result.map(|size| (buffer, size)));
+                true
+            }
+        }
+    }
+
+    /// Takes the report read ahead by [`poll_all`].
+    pub(crate) fn take_peeked_report(&self) -> Option<PeekedReport> {
+        self.lock_peeked_report().take()
+    }
+
+    fn lock_peeked_report(&self) -> MutexGuard<'_, Option<PeekedReport>> {
+        self.peeked_report
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,14 @@
+use std::sync::Arc;
+
+use crate::result::InitPhase;
+
+/// Receives the identifier of the Wii remote and the step of connecting to it.
+/// Called on the scan thread of the manager, so it should return quickly.
+pub type ProgressObserver = Arc<dyn Fn(&str, InitPhase) + Send + Sync>;
+
+/// Reports the progress to the observer if set.
+pub(crate) fn report(observer: Option<&ProgressObserver>, identifier: &str, progress: InitPhase) {
+    if let Some(observer) = observer {
+        observer(identifier, progress);
+    }
+}
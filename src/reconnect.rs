@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+/// How a `WiimoteDevice` reconnects after reading or writing failed,
+/// see [`WiimoteDevice::set_reconnect_policy`](crate::prelude::WiimoteDevice::set_reconnect_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// The number of attempts to open the Wii remote again before the read fails.
+    pub max_attempts: u32,
+    /// The time waited before each attempt.
+    pub retry_interval: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            retry_interval: Duration::from_secs(1),
+        }
+    }
+}
@@ -0,0 +1,553 @@
+//! Recording and replaying the reports exchanged with a Wii remote.
+//!
+//! A [`Recorder`] writes every input and output report with a timestamp to a log,
+//! a [`ReplayWiimote`] plays the input reports of the log back with their original timing.
+//! This allows reproducing issues such as extension detection races without the hardware.
+//!
+//! The log starts with the magic `WMRL`, a version byte, the model byte and the identifier
+//! prefixed with its length. Each entry consists of a kind byte (input, output or disconnect),
+//! the timestamp in microseconds since the start of the recording as little-endian `u64`,
+//! the length of the report and the report itself.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::prelude::*;
+
+const MAGIC: &[u8; 4] = b"WMRL";
+const VERSION: u8 = 1;
+
+const KIND_INPUT: u8 = 0;
+const KIND_OUTPUT: u8 = 1;
+const KIND_DISCONNECT: u8 = 2;
+
+const fn model_to_byte(model: WiimoteModel) -> u8 {
+    match model {
+        WiimoteModel::Original => 0,
+        WiimoteModel::Plus => 1,
+        WiimoteModel::BalanceBoard => 2,
+    }
+}
+
+const fn model_from_byte(byte: u8) -> Option<WiimoteModel> {
+    match byte {
+        0 => Some(WiimoteModel::Original),
+        1 => Some(WiimoteModel::Plus),
+        2 => Some(WiimoteModel::BalanceBoard),
+        _ => None,
+    }
+}
+
+fn write_header(
+    writer: &mut impl Write,
+    identifier: &str,
+    model: WiimoteModel,
+) -> std::io::Result<()> {
+    let identifier = &identifier.as_bytes()[..identifier.len().min(u8::MAX.into())];
+    writer.write_all(MAGIC)?;
+    #[allow(clippy::cast_possible_truncation)] // Truncated to 255 bytes above
+    writer.write_all(&[VERSION, model_to_byte(model), identifier.len() as u8])?;
+    writer.write_all(identifier)?;
+    writer.flush()
+}
+
+/// Writes an entry to the log, stops the recording if writing it failed.
+fn record<W: Write>(sink: &Mutex<Option<W>>, start: Instant, kind: u8, report: &[u8]) {
+    let mut sink = sink.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(writer) = sink.as_mut() else {
+        return;
+    };
+    let timestamp = u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX);
+    let report = &report[..report.len().min(WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE)];
+
+    #[allow(clippy::cast_possible_truncation)] // Reports are at most 22 bytes
+    let result = writer
+        .write_all(&[kind])
+        .and_then(|()| writer.write_all(&timestamp.to_le_bytes()))
+        .and_then(|()| writer.write_all(&[report.len() as u8]))
+        .and_then(|()| writer.write_all(report))
+        .and_then(|()| writer.flush());
+    if let Err(err) = result {
+        log::warn!("Stopped recording reports of Wii remote: {err}");
+        *sink = None;
+    }
+}
+
+/// A transport that writes the reports exchanged with the wrapped transport to a log.
+///
+/// Failing to write the log stops the recording without affecting the communication.
+pub struct Recorder<T: NativeWiimote, W: Write> {
+    transport: T,
+    /// Shared with the writers returned by [`NativeWiimote::try_clone_writer`].
+    sink: Arc<Mutex<Option<W>>>,
+    start: Instant,
+}
+
+/// The writer of a [`Recorder`], records the output reports written by the wrapped writer.
+struct RecordingWriter<W: Write> {
+    writer: Box<dyn NativeWiimoteWriter>,
+    sink: Arc<Mutex<Option<W>>>,
+    start: Instant,
+}
+
+impl<W: Write + Send> NativeWiimoteWriter for RecordingWriter<W> {
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        let result = self.writer.write(buffer);
+        match &result {
+            Ok(_) => record(&self.sink, self.start, KIND_OUTPUT, buffer),
+            Err(_) => record(&self.sink, self.start, KIND_DISCONNECT, &[]),
+        }
+        result
+    }
+
+    fn cancel_pending_io(&mut self) {
+        self.writer.cancel_pending_io();
+    }
+}
+
+impl<T: NativeWiimote, W: Write> Recorder<T, W> {
+    /// Wraps `transport` and writes the log header to `writer`.
+    /// Use [`WiimoteDevice::with_transport`] to communicate through the recorder.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the header could not be written.
+    pub fn new(transport: T, mut writer: W) -> std::io::Result<Self> {
+        write_header(&mut writer, transport.identifier(), transport.model())?;
+        Ok(Self::with_header_written(transport, writer))
+    }
+
+    fn with_header_written(transport: T, writer: W) -> Self {
+        Self {
+            transport,
+            sink: Arc::new(Mutex::new(Some(writer))),
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the wrapped transport and the writer, `None` if writing the log failed.
+    /// Writers cloned from the recorder stop recording.
+    pub fn into_inner(self) -> (T, Option<W>) {
+        let writer = self
+            .sink
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        (self.transport, writer)
+    }
+
+    fn record(&self, kind: u8, report: &[u8]) {
+        record(&self.sink, self.start, kind, report);
+    }
+
+    fn record_read(&self, result: &WiimoteResult<usize>, buffer: &[u8]) {
+        match result {
+            Ok(0) => {}
+            Ok(size) => self.record(KIND_INPUT, &buffer[..*size]),
+            Err(_) => self.record(KIND_DISCONNECT, &[]),
+        }
+    }
+}
+
+impl<T: NativeWiimote, W: Write + Send + 'static> NativeWiimote for Recorder<T, W> {
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        let result = self.transport.read(buffer);
+        self.record_read(&result, buffer);
+        result
+    }
+
+    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize> {
+        let result = self.transport.read_timeout(buffer, timeout_millis);
+        self.record_read(&result, buffer);
+        result
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll_read(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        buffer: &mut [u8],
+    ) -> std::task::Poll<WiimoteResult<usize>> {
+        let result = self.transport.poll_read(cx, buffer);
+        if let std::task::Poll::Ready(result) = &result {
+            self.record_read(result, buffer);
+        }
+        result
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        let result = self.transport.write(buffer);
+        match &result {
+            Ok(_) => self.record(KIND_OUTPUT, buffer),
+            Err(_) => self.record(KIND_DISCONNECT, &[]),
+        }
+        result
+    }
+
+    fn try_clone_writer(&self) -> Option<Box<dyn NativeWiimoteWriter>> {
+        let writer = self.transport.try_clone_writer()?;
+        Some(Box::new(RecordingWriter {
+            writer,
+            sink: Arc::clone(&self.sink),
+            start: self.start,
+        }))
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::fd::RawFd> {
+        self.transport.readiness_fd()
+    }
+
+    #[cfg(all(target_os = "windows", feature = "windows-backend"))]
+    fn hid_info(&self) -> Option<crate::hid::HidInfo> {
+        self.transport.hid_info()
+    }
+
+    fn last_received(&self) -> Option<std::time::Instant> {
+        self.transport.last_received()
+    }
+
+    fn identifier(&self) -> &str {
+        self.transport.identifier()
+    }
+
+    fn model(&self) -> WiimoteModel {
+        self.transport.model()
+    }
+}
+
+impl WiimoteDevice {
+    /// Records the reports exchanged with the Wii remote from now on to `writer`,
+    /// see [`Recorder`]. The recording ends when the Wii remote disconnects.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected
+    /// or the log header could not be written.
+    pub fn start_recording(&self, mut writer: impl Write + Send + 'static) -> std::io::Result<()> {
+        if !self.is_connected() {
+            return Err(ErrorKind::NotConnected.into());
+        }
+        write_header(&mut writer, self.identifier(), self.model())?;
+        self.wrap_transport(|transport| Box::new(Recorder::with_header_written(transport, writer)))
+            .map_err(|_| ErrorKind::NotConnected.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    kind: u8,
+    timestamp: Duration,
+    report: Vec<u8>,
+}
+
+/// A transport that plays back the input reports of a log written by a [`Recorder`].
+///
+/// Input reports are returned once the time since the creation of the replay reaches their timestamp,
+/// written output reports are discarded. Reads fail with `WiimoteError::Disconnected`
+/// at the end of the log or where the recorded Wii remote disconnected.
+pub struct ReplayWiimote {
+    identifier: String,
+    model: WiimoteModel,
+    entries: VecDeque<Entry>,
+    start: Instant,
+    /// The due time of the pending timer thread and the waker it wakes, see [`NativeWiimote::poll_read`].
+    #[cfg(feature = "tokio")]
+    timer: Option<(Instant, Arc<Mutex<std::task::Waker>>)>,
+}
+
+impl ReplayWiimote {
+    /// Reads the log from the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read or is not a valid log.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads the whole log from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the log could not be read or is invalid.
+    pub fn from_reader(mut reader: impl Read) -> std::io::Result<Self> {
+        let invalid = |message: &str| std::io::Error::new(ErrorKind::InvalidData, message);
+
+        let mut header = [0u8; 7];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid("not a Wii remote report log"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid("unsupported report log version"));
+        }
+        let model = model_from_byte(header[5]).ok_or_else(|| invalid("unknown model"))?;
+        let mut identifier = vec![0u8; header[6].into()];
+        reader.read_exact(&mut identifier)?;
+
+        let mut entries = VecDeque::new();
+        let mut entry_header = [0u8; 10];
+        loop {
+            match reader.read_exact(&mut entry_header) {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err),
+            }
+            let kind = entry_header[0];
+            if !matches!(kind, KIND_INPUT | KIND_OUTPUT | KIND_DISCONNECT) {
+                return Err(invalid("unknown entry kind"));
+            }
+            let mut timestamp = [0u8; 8];
+            timestamp.copy_from_slice(&entry_header[1..9]);
+            let mut report = vec![0u8; entry_header[9].into()];
+            reader.read_exact(&mut report)?;
+
+            // Output reports are only recorded for reference.
+            if kind != KIND_OUTPUT {
+                entries.push_back(Entry {
+                    kind,
+                    timestamp: Duration::from_micros(u64::from_le_bytes(timestamp)),
+                    report,
+                });
+            }
+        }
+
+        Ok(Self {
+            identifier: String::from_utf8_lossy(&identifier).into_owned(),
+            model,
+            entries,
+            start: Instant::now(),
+            #[cfg(feature = "tokio")]
+            timer: None,
+        })
+    }
+
+    /// Returns the number of input reports and disconnects left to replay.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Waits until the next entry is due, at most until `deadline`.
+    /// Returns the entry or `None` if it is not due before the deadline.
+    fn next_entry(&mut self, deadline: Option<Instant>) -> Option<Entry> {
+        let due = self.start + self.entries.front()?.timestamp;
+        if let Some(deadline) = deadline.filter(|deadline| *deadline < due) {
+            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+            return None;
+        }
+        std::thread::sleep(due.saturating_duration_since(Instant::now()));
+        self.entries.pop_front()
+    }
+
+    fn read_until(&mut self, buffer: &mut [u8], deadline: Option<Instant>) -> WiimoteResult<usize> {
+        if self.entries.is_empty() {
+            return Err(WiimoteError::Disconnected);
+        }
+        match self.next_entry(deadline) {
+            None => Ok(0),
+            Some(entry) if entry.kind == KIND_DISCONNECT => {
+                self.entries.clear();
+                Err(WiimoteError::Disconnected)
+            }
+            Some(entry) => {
+                let size = entry.report.len().min(buffer.len());
+                buffer[..size].copy_from_slice(&entry.report[..size]);
+                Ok(size)
+            }
+        }
+    }
+}
+
+impl NativeWiimote for ReplayWiimote {
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        self.read_until(buffer, None)
+    }
+
+    fn read_timeout(&mut self, buffer: &mut [u8], timeout_millis: usize) -> WiimoteResult<usize> {
+        let timeout = Duration::from_millis(timeout_millis as u64);
+        self.read_until(buffer, Some(Instant::now() + timeout))
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll_read(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        buffer: &mut [u8],
+    ) -> std::task::Poll<WiimoteResult<usize>> {
+        let now = Instant::now();
+        let due = self
+            .entries
+            .front()
+            .map(|entry| self.start + entry.timestamp);
+        match due {
+            Some(due) if due > now => {
+                // The replay has no readiness source, a timer thread wakes the task once the entry is due.
+                // Polls before the entry is due only replace the waker of the pending timer.
+                match &self.timer {
+                    Some((timer_due, waker)) if *timer_due == due => {
+                        let mut waker = waker.lock().unwrap_or_else(PoisonError::into_inner);
+                        if !waker.will_wake(cx.waker()) {
+                            waker.clone_from(cx.waker());
+                        }
+                    }
+                    _ => {
+                        let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                        self.timer = Some((due, Arc::clone(&waker)));
+                        std::thread::spawn(move || {
+                            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+                            waker
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .wake_by_ref();
+                        });
+                    }
+                }
+                std::task::Poll::Pending
+            }
+            _ => std::task::Poll::Ready(self.read_until(buffer, Some(now))),
+        }
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        if self.entries.is_empty() {
+            return Err(WiimoteError::Disconnected);
+        }
+        Ok(buffer.len())
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn model(&self) -> WiimoteModel {
+        self.model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A writer whose content can be inspected after it was moved into a recorder.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn log(entries: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut log = MAGIC.to_vec();
+        log.extend_from_slice(&[VERSION, 1, 3]);
+        log.extend_from_slice(b"abc");
+        for (kind, report) in entries {
+            log.push(*kind);
+            log.extend_from_slice(&0u64.to_le_bytes());
+            log.push(report.len() as u8);
+            log.extend_from_slice(report);
+        }
+        log
+    }
+
+    #[test]
+    fn test_replay() {
+        let log = log(&[
+            (KIND_OUTPUT, &[0x15, 0x00]),
+            (KIND_INPUT, &[0x30, 0x00, 0x08]),
+            (KIND_DISCONNECT, &[]),
+            (KIND_INPUT, &[0x30, 0x00, 0x00]),
+        ]);
+        let mut replay = ReplayWiimote::from_reader(log.as_slice()).unwrap();
+        assert_eq!(replay.identifier(), "abc");
+        assert_eq!(replay.model(), WiimoteModel::Plus);
+        assert_eq!(replay.remaining(), 3);
+
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        assert_eq!(replay.write(&[0x11, 0x10]).unwrap(), 2);
+        assert_eq!(replay.read_timeout(&mut buffer, 10).unwrap(), 3);
+        assert_eq!(buffer[..3], [0x30, 0x00, 0x08]);
+        assert!(matches!(
+            replay.read(&mut buffer),
+            Err(WiimoteError::Disconnected)
+        ));
+        assert_eq!(replay.remaining(), 0);
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let source = log(&[(KIND_INPUT, &[0x30, 0x00, 0x08])]);
+        let replay = ReplayWiimote::from_reader(source.as_slice()).unwrap();
+        let output = SharedBuffer::default();
+        let mut recorder = Recorder::new(replay, output.clone()).unwrap();
+
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+        recorder.write(&[0x12, 0x00, 0x30]).unwrap();
+        assert_eq!(recorder.read(&mut buffer).unwrap(), 3);
+        assert!(recorder.read(&mut buffer).is_err());
+
+        let recorded = output.0.lock().unwrap().clone();
+        let mut replay = ReplayWiimote::from_reader(recorded.as_slice()).unwrap();
+        assert_eq!(replay.identifier(), "abc");
+        assert_eq!(replay.remaining(), 2);
+        assert_eq!(replay.read(&mut buffer).unwrap(), 3);
+        assert!(matches!(
+            replay.read(&mut buffer),
+            Err(WiimoteError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_log() {
+        assert!(ReplayWiimote::from_reader(&b"WMRX\x01\x00\x00"[..]).is_err());
+        assert!(ReplayWiimote::from_reader(&b"WMRL"[..]).is_err());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_poll_replay_single_timer() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::task::{Context, Poll, Wake, Waker};
+
+        struct CountingWaker(AtomicUsize);
+
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let mut log = log(&[(KIND_INPUT, &[0x30, 0x00, 0x08])]);
+        // The entry is due 50 ms after the start of the replay.
+        log[11..19].copy_from_slice(&50_000u64.to_le_bytes());
+        let mut replay = ReplayWiimote::from_reader(log.as_slice()).unwrap();
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&counter));
+        let mut cx = Context::from_waker(&waker);
+        let mut buffer = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+
+        assert!(replay.poll_read(&mut cx, &mut buffer).is_pending());
+        let timer = Arc::clone(&replay.timer.as_ref().unwrap().1);
+        assert!(replay.poll_read(&mut cx, &mut buffer).is_pending());
+        assert!(Arc::ptr_eq(&timer, &replay.timer.as_ref().unwrap().1));
+
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            replay.poll_read(&mut cx, &mut buffer),
+            Poll::Ready(Ok(3))
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+//! The IDs of the input and output reports.
+//!
+//! WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#HID_Interface>
+
+use crate::prelude::*;
+
+/// The ID of a report, the first byte of every input and output report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReportId {
+    /// Output report 0x10, turns the rumble motor on or off.
+    Rumble,
+    /// Output report 0x11, sets the player LEDs.
+    PlayerLed,
+    /// Output report 0x12, sets the data reporting mode.
+    DataReportingMode,
+    /// Output report 0x13, enables the IR camera clock.
+    IrCameraEnable,
+    /// Output report 0x14, enables the speaker.
+    SpeakerEnable,
+    /// Output report 0x15, requests a status report.
+    StatusRequest,
+    /// Output report 0x16, writes to the EEPROM or the control registers.
+    WriteMemory,
+    /// Output report 0x17, reads from the EEPROM or the control registers.
+    ReadMemory,
+    /// Output report 0x18, sends sound data to the speaker.
+    SpeakerData,
+    /// Output report 0x19, mutes the speaker.
+    SpeakerMute,
+    /// Output report 0x1A, enables the IR camera logic.
+    IrCameraEnable2,
+    /// Input report 0x20, the status of the Wii remote.
+    Status,
+    /// Input report 0x21, the data read from the memory.
+    ReadMemoryData,
+    /// Input report 0x22, acknowledges an output report.
+    Acknowledge,
+    /// Input reports 0x30-0x3F, contains the ID of the data reporting mode.
+    DataReport(u8),
+}
+
+impl ReportId {
+    /// Returns the report with the ID, `None` if it is not a known input or output report.
+    #[must_use]
+    pub const fn from_u8(id: u8) -> Option<Self> {
+        let report_id = match id {
+            0x10 => Self::Rumble,
+            0x11 => Self::PlayerLed,
+            0x12 => Self::DataReportingMode,
+            0x13 => Self::IrCameraEnable,
+            0x14 => Self::SpeakerEnable,
+            0x15 => Self::StatusRequest,
+            0x16 => Self::WriteMemory,
+            0x17 => Self::ReadMemory,
+            0x18 => Self::SpeakerData,
+            0x19 => Self::SpeakerMute,
+            0x1A => Self::IrCameraEnable2,
+            0x20 => Self::Status,
+            0x21 => Self::ReadMemoryData,
+            0x22 => Self::Acknowledge,
+            0x30..=0x3F => Self::DataReport(id),
+            _ => return None,
+        };
+        Some(report_id)
+    }
+
+    /// Returns the ID sent as the first byte of the report.
+    #[must_use]
+    pub const fn to_u8(self) -> u8 {
+        match self {
+            Self::Rumble => 0x10,
+            Self::PlayerLed => 0x11,
+            Self::DataReportingMode => 0x12,
+            Self::IrCameraEnable => 0x13,
+            Self::SpeakerEnable => 0x14,
+            Self::StatusRequest => 0x15,
+            Self::WriteMemory => 0x16,
+            Self::ReadMemory => 0x17,
+            Self::SpeakerData => 0x18,
+            Self::SpeakerMute => 0x19,
+            Self::IrCameraEnable2 => 0x1A,
+            Self::Status => 0x20,
+            Self::ReadMemoryData => 0x21,
+            Self::Acknowledge => 0x22,
+            Self::DataReport(id) => id,
+        }
+    }
+
+    /// Returns true for the reports sent by the Wii remote.
+    #[must_use]
+    pub const fn is_input(self) -> bool {
+        self.to_u8() >= 0x20
+    }
+}
+
+impl From<ReportId> for u8 {
+    fn from(report_id: ReportId) -> Self {
+        report_id.to_u8()
+    }
+}
+
+impl TryFrom<u8> for ReportId {
+    type Error = WiimoteError;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        Self::from_u8(id).ok_or_else(|| WiimoteDeviceError::InvalidData.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for id in 0..=u8::MAX {
+            if let Some(report_id) = ReportId::from_u8(id) {
+                assert_eq!(report_id.to_u8(), id);
+                assert_eq!(report_id.is_input(), id >= 0x20);
+            }
+        }
+        assert_eq!(ReportId::from_u8(0x35), Some(ReportId::DataReport(0x35)));
+        assert_eq!(ReportId::from_u8(0x23), None);
+        assert!(ReportId::try_from(0x40).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::input::InputReport;
+use crate::prelude::*;
+
+/// A `Stream` of the input reports of a `WiimoteDevice`, see [`WiimoteDevice::report_stream`].
+///
+/// Errors reading or parsing a report are yielded as items,
+/// the stream ends after yielding [`WiimoteError::Disconnected`] or an OS error.
+pub struct ReportStream<'a> {
+    wiimote: &'a WiimoteDevice,
+    finished: bool,
+}
+
+impl<'a> ReportStream<'a> {
+    pub(crate) const fn new(wiimote: &'a WiimoteDevice) -> Self {
+        Self {
+            wiimote,
+            finished: false,
+        }
+    }
+}
+
+impl futures_core::Stream for ReportStream<'_> {
+    type Item = WiimoteResult<InputReport>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        let result = std::task::ready!(self.wiimote.poll_read(cx));
+        if result.is_err() && !self.wiimote.is_connected() {
+            self.finished = true;
+        }
+        Poll::Ready(Some(result))
+    }
+}
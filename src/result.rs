@@ -1,16 +1,132 @@
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum WiimoteError {
     WiimoteDeviceError(WiimoteDeviceError),
     Disconnected,
+    /// An operation of the operating system failed and the Wii remote was disconnected.
+    /// The code is the `errno` on Linux and the `GetLastError` code on Windows,
+    /// see [`std::io::Error::from_raw_os_error`].
+    Os {
+        code: i32,
+        operation: OsOperation,
+    },
+    /// Writing an output report did not complete in time, e.g. because the send buffer is full
+    /// or the Wii remote is out of range. The report was not sent, the Wii remote stays connected
+    /// and a lost connection is detected by the reads.
+    WriteTimedOut,
+    /// Initializing the Wii remote after connecting failed in `phase`.
+    InitError {
+        phase: InitPhase,
+        error: Box<WiimoteError>,
+    },
+    /// The WAV data passed to the speaker is invalid.
+    #[cfg(feature = "wav")]
+    Wav(hound::Error),
+}
+
+/// A step of connecting to a Wii remote, the step that failed with [`WiimoteError::InitError`]
+/// and reported to the observer set with
+/// [`WiimoteManager::set_progress_observer`](crate::prelude::WiimoteManager::set_progress_observer).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InitPhase {
+    /// The Wii remote was discovered and is registered as input device (Windows only).
+    Pairing,
+    /// The connection to the Wii remote is opened.
+    OpeningHid,
+    /// The accelerometer calibration is read and the extension initialized.
+    ReadingCalibration,
+    DetectingMotionPlus,
+    DetectingExtension,
+    /// Applying the `MotionPlusPolicy`, which may activate the Motion Plus.
+    ActivatingMotionPlus,
+    /// The Wii remote is initialized and ready to use, only reported to the progress observer.
+    Ready,
+}
+
+impl WiimoteError {
+    pub(crate) fn during(self, phase: InitPhase) -> Self {
+        Self::InitError {
+            phase,
+            error: Box::new(self),
+        }
+    }
+}
+
+/// The operation that failed with [`WiimoteError::Os`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OsOperation {
+    /// Waiting for or reading an input report.
+    Read,
+    /// Writing an output report.
+    Write,
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum WiimoteDeviceError {
     InvalidVendorID(u16),
     InvalidProductID(u16),
     MissingData,
     InvalidChecksum,
     InvalidData,
+    /// Reading or writing the memory at `address` failed.
+    Memory {
+        address: u32,
+        error: MemoryError,
+    },
+    /// The output report with `report_id` contains `size` bytes of data, more than the `maximum` it can send.
+    OutputReportTooLong {
+        report_id: u8,
+        size: usize,
+        maximum: usize,
+    },
+    /// The data read back from the register block at `address` differs from the written data.
+    VerificationFailed {
+        address: u32,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+}
+
+/// The error flag of a memory read or the error code of a write acknowledgement.
+///
+/// Both use the same codes, code 0 (success) is represented as `None` by
+/// [`MemoryData::error`](crate::input::MemoryData::error) and [`AcknowledgeData::error`](crate::input::AcknowledgeData::error).
+///
+/// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Memory_and_Registers>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MemoryError {
+    /// Error 7: the register is write-only, or no extension is connected for an access to its registers.
+    WriteOnly,
+    /// Error 8: the address does not exist.
+    NonexistentAddress,
+    /// An error code without known meaning.
+    Unknown(u8),
+}
+
+impl MemoryError {
+    /// Returns the error for the error code, `None` if the code is 0 (no error).
+    #[must_use]
+    pub const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => None,
+            7 => Some(Self::WriteOnly),
+            8 => Some(Self::NonexistentAddress),
+            code => Some(Self::Unknown(code)),
+        }
+    }
+
+    /// Returns the error code of the report, the inverse of [`Self::from_code`].
+    #[must_use]
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::WriteOnly => 7,
+            Self::NonexistentAddress => 8,
+            Self::Unknown(code) => code,
+        }
+    }
 }
 
 impl From<WiimoteDeviceError> for WiimoteError {
@@ -19,4 +135,11 @@ impl From<WiimoteDeviceError> for WiimoteError {
     }
 }
 
+#[cfg(feature = "wav")]
+impl From<hound::Error> for WiimoteError {
+    fn from(e: hound::Error) -> Self {
+        Self::Wav(e)
+    }
+}
+
 pub type WiimoteResult<T> = Result<T, WiimoteError>;
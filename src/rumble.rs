@@ -0,0 +1,266 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+
+use crate::device::SharedDevice;
+use crate::output::OutputReport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RumbleStep {
+    enabled: bool,
+    duration: Duration,
+}
+
+/// A sequence of rumble on and off durations, optionally repeated.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use wiimote_rs::prelude::*;
+///
+/// // Three short pulses
+/// let pattern = RumblePattern::new()
+///     .on(Duration::from_millis(100))
+///     .off(Duration::from_millis(50))
+///     .repeat(3);
+/// assert_eq!(pattern.total_duration(), Duration::from_millis(450));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RumblePattern {
+    steps: Vec<RumbleStep>,
+    repeat_count: u32,
+}
+
+impl Default for RumblePattern {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RumblePattern {
+    /// Creates an empty pattern, which turns the rumble off.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            repeat_count: 1,
+        }
+    }
+
+    /// Creates a pattern that turns the rumble on for `duration`.
+    #[must_use]
+    pub fn pulse(duration: Duration) -> Self {
+        Self::new().on(duration)
+    }
+
+    /// Adds a step with the rumble turned on.
+    #[must_use]
+    pub fn on(mut self, duration: Duration) -> Self {
+        self.steps.push(RumbleStep {
+            enabled: true,
+            duration,
+        });
+        self
+    }
+
+    /// Adds a step with the rumble turned off.
+    #[must_use]
+    pub fn off(mut self, duration: Duration) -> Self {
+        self.steps.push(RumbleStep {
+            enabled: false,
+            duration,
+        });
+        self
+    }
+
+    /// Sets how many times the steps are played, at least once.
+    #[must_use]
+    pub fn repeat(mut self, count: u32) -> Self {
+        self.repeat_count = u32::max(count, 1);
+        self
+    }
+
+    /// Returns the time it takes to play the pattern including all repetitions.
+    #[must_use]
+    pub fn total_duration(&self) -> Duration {
+        let steps_duration: Duration = self.steps.iter().map(|step| step.duration).sum();
+        steps_duration * self.repeat_count
+    }
+}
+
+/// The period of the duty cycle used to modulate the rumble intensity,
+/// which limits the rumble reports to two per period regardless of the intensity.
+const DUTY_CYCLE_PERIOD: Duration = Duration::from_millis(50);
+
+enum RumbleCommand {
+    Play(RumblePattern),
+    Intensity(f32),
+}
+
+/// Why the worker stopped waiting.
+enum Interrupt {
+    Command(RumbleCommand),
+    Disconnected,
+}
+
+/// Plays rumble patterns and the rumble intensity on a background thread.
+/// A new pattern replaces the pattern currently playing,
+/// the rumble returns to the set intensity after a pattern finished.
+pub(crate) struct RumbleScheduler {
+    sender: Sender<RumbleCommand>,
+}
+
+impl RumbleScheduler {
+    pub(crate) fn new(device: Arc<SharedDevice>) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        std::thread::spawn(move || {
+            let mut worker = RumbleWorker {
+                device,
+                receiver,
+                intensity: 0.0,
+            };
+            worker.run();
+        });
+        Self { sender }
+    }
+
+    pub(crate) fn play(&self, pattern: RumblePattern) {
+        _ = self.sender.send(RumbleCommand::Play(pattern));
+    }
+
+    pub(crate) fn set_intensity(&self, intensity: f32) {
+        _ = self
+            .sender
+            .send(RumbleCommand::Intensity(intensity.clamp(0.0, 1.0)));
+    }
+}
+
+struct RumbleWorker {
+    device: Arc<SharedDevice>,
+    receiver: Receiver<RumbleCommand>,
+    intensity: f32,
+}
+
+impl RumbleWorker {
+    /// Runs until the scheduler is dropped together with the `WiimoteDevice`.
+    fn run(&mut self) {
+        let Ok(mut command) = self.receiver.recv() else {
+            return;
+        };
+        loop {
+            let interrupt = match command {
+                RumbleCommand::Play(pattern) => match self.play_pattern(&pattern) {
+                    Ok(()) => self.hold_intensity(),
+                    Err(interrupt) => interrupt,
+                },
+                RumbleCommand::Intensity(intensity) => {
+                    self.intensity = intensity;
+                    self.hold_intensity()
+                }
+            };
+            match interrupt {
+                Interrupt::Command(next_command) => command = next_command,
+                Interrupt::Disconnected => {
+                    _ = self.device.write(&OutputReport::Rumble(false));
+                    return;
+                }
+            }
+        }
+    }
+
+    fn play_pattern(&self, pattern: &RumblePattern) -> Result<(), Interrupt> {
+        for _ in 0..pattern.repeat_count {
+            for step in &pattern.steps {
+                self.set_rumble(step.enabled);
+                self.wait(step.duration)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the rumble at the set intensity until the next command.
+    fn hold_intensity(&self) -> Interrupt {
+        if self.intensity <= 0.0 || self.intensity >= 1.0 {
+            self.set_rumble(self.intensity >= 1.0);
+            return match self.receiver.recv() {
+                Ok(command) => Interrupt::Command(command),
+                Err(_) => Interrupt::Disconnected,
+            };
+        }
+
+        let (on_duration, off_duration) = duty_cycle(self.intensity);
+        loop {
+            self.set_rumble(true);
+            if let Err(interrupt) = self.wait(on_duration) {
+                return interrupt;
+            }
+            self.set_rumble(false);
+            if let Err(interrupt) = self.wait(off_duration) {
+                return interrupt;
+            }
+        }
+    }
+
+    fn set_rumble(&self, enabled: bool) {
+        // The timing continues while disconnected, the Wii remote may reconnect.
+        _ = self.device.write(&OutputReport::Rumble(enabled));
+    }
+
+    fn wait(&self, duration: Duration) -> Result<(), Interrupt> {
+        match self.receiver.recv_timeout(duration) {
+            Ok(command) => Err(Interrupt::Command(command)),
+            Err(RecvTimeoutError::Timeout) => Ok(()),
+            Err(RecvTimeoutError::Disconnected) => Err(Interrupt::Disconnected),
+        }
+    }
+}
+
+/// Returns the on and off durations for an intensity between 0 and 1 (exclusive).
+fn duty_cycle(intensity: f32) -> (Duration, Duration) {
+    let on_duration = DUTY_CYCLE_PERIOD.mul_f64(f64::from(intensity));
+    (on_duration, DUTY_CYCLE_PERIOD.saturating_sub(on_duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_steps() {
+        let pattern = RumblePattern::new()
+            .on(Duration::from_millis(200))
+            .off(Duration::from_millis(100))
+            .on(Duration::from_millis(50))
+            .repeat(0);
+
+        assert_eq!(pattern.repeat_count, 1);
+        assert_eq!(pattern.steps.len(), 3);
+        assert!(pattern.steps[0].enabled);
+        assert!(!pattern.steps[1].enabled);
+        assert_eq!(pattern.total_duration(), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_duty_cycle() {
+        assert_eq!(
+            duty_cycle(0.5),
+            (Duration::from_millis(25), Duration::from_millis(25))
+        );
+        // The period stays the same for every intensity
+        let (on, off) = duty_cycle(0.1);
+        assert!((on.as_secs_f64() - 0.005).abs() < 1e-6);
+        assert_eq!(on + off, DUTY_CYCLE_PERIOD);
+    }
+
+    #[test]
+    fn test_empty_pattern() {
+        assert_eq!(RumblePattern::default().total_duration(), Duration::ZERO);
+        assert_eq!(
+            RumblePattern::pulse(Duration::from_secs(1)).total_duration(),
+            Duration::from_secs(1)
+        );
+    }
+}
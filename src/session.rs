@@ -0,0 +1,521 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use bitflags::bitflags;
+
+use crate::extensions::PassthroughDecoder;
+use crate::input::{ButtonData, InputReport, WiimoteData};
+use crate::ir::IrData;
+use crate::output::{DataReporingMode, OutputReport};
+use crate::prelude::*;
+
+/// The data a [`WiimoteSession`] requests from the Wii remote, the buttons are always reported.
+/// Start from [`SessionConfig::default`] and change it with the setters of the same name as the fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+#[non_exhaustive]
+pub struct SessionConfig {
+    pub accelerometer: bool,
+    /// Enables the IR camera with the given sensitivity.
+    pub ir_camera: Option<IrSensitivity>,
+    /// The data of the extension, including an extension passed through by the Motion Plus.
+    pub extension: bool,
+    /// Activates the Motion Plus if connected or built in.
+    pub motion_plus: bool,
+    /// Reports data continuously instead of only when it changes.
+    pub continuous: bool,
+    /// Skips data reports equal to the previous one, see [`DuplicateFilter`].
+    pub suppress_duplicates: Option<DuplicateSuppression>,
+    /// Requests all 19 extension bytes with mode 0x34 instead of the 8 bytes of mode 0x32
+    /// if only the extension is requested. The balance board reports its status in the additional bytes.
+    pub full_extension_data: bool,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            accelerometer: true,
+            ir_camera: None,
+            extension: true,
+            motion_plus: false,
+            continuous: true,
+            suppress_duplicates: None,
+            full_extension_data: true,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// The configuration of a [`BalanceBoardSession`], which only reads the extension data,
+    /// including the temperature and battery level of the balance board if `with_status` is set.
+    #[must_use]
+    pub const fn balance_board(with_status: bool) -> Self {
+        Self {
+            accelerometer: false,
+            ir_camera: None,
+            extension: true,
+            motion_plus: false,
+            continuous: true,
+            suppress_duplicates: None,
+            full_extension_data: with_status,
+        }
+    }
+
+    /// Requests the accelerometer data, enabled by default.
+    #[must_use]
+    pub const fn accelerometer(mut self, accelerometer: bool) -> Self {
+        self.accelerometer = accelerometer;
+        self
+    }
+
+    /// Enables the IR camera with the given sensitivity, disabled by default.
+    #[must_use]
+    pub const fn ir_camera(mut self, ir_camera: Option<IrSensitivity>) -> Self {
+        self.ir_camera = ir_camera;
+        self
+    }
+
+    /// Requests the extension data, enabled by default.
+    #[must_use]
+    pub const fn extension(mut self, extension: bool) -> Self {
+        self.extension = extension;
+        self
+    }
+
+    /// Activates the Motion Plus if connected or built in, disabled by default.
+    #[must_use]
+    pub const fn motion_plus(mut self, motion_plus: bool) -> Self {
+        self.motion_plus = motion_plus;
+        self
+    }
+
+    /// Reports data continuously instead of only when it changes, enabled by default.
+    #[must_use]
+    pub const fn continuous(mut self, continuous: bool) -> Self {
+        self.continuous = continuous;
+        self
+    }
+
+    /// Skips data reports equal to the previous one, disabled by default.
+    #[must_use]
+    pub const fn suppress_duplicates(
+        mut self,
+        suppress_duplicates: Option<DuplicateSuppression>,
+    ) -> Self {
+        self.suppress_duplicates = suppress_duplicates;
+        self
+    }
+
+    /// Requests all 19 extension bytes if only the extension is requested, enabled by default.
+    #[must_use]
+    pub const fn full_extension_data(mut self, full_extension_data: bool) -> Self {
+        self.full_extension_data = full_extension_data;
+        self
+    }
+
+    /// Returns the data reporting mode containing the requested data and the IR mode matching it.
+    ///
+    /// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Data_Reporting>
+    #[must_use]
+    pub const fn reporting_mode(&self) -> (u8, Option<IrMode>) {
+        let extension = self.extension || self.motion_plus;
+        match (self.accelerometer, self.ir_camera.is_some(), extension) {
+            (false, false, false) => (0x30, None),
+            (true, false, false) => (0x31, None),
+            // The balance board needs more than the 8 extension bytes of report 0x32 for its status.
+            (false, false, true) if self.full_extension_data => (0x34, None),
+            (false, false, true) => (0x32, None),
+            (true, true, false) => (0x33, Some(IrMode::Extended)),
+            (true, false, true) => (0x35, None),
+            (false, true, _) => (0x36, Some(IrMode::Basic)),
+            (true, true, true) => (0x37, Some(IrMode::Basic)),
+        }
+    }
+}
+
+bitflags! {
+    /// The parts of a data report compared by the [`DuplicateFilter`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ReportFields: u8 {
+        const BUTTONS = 1 << 0;
+        const ACCELEROMETER = 1 << 1;
+        const IR = 1 << 2;
+        const EXTENSION = 1 << 3;
+    }
+}
+
+/// The configuration of the [`DuplicateFilter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateSuppression {
+    /// The parts of the data reports compared, changes of other parts are suppressed as well.
+    pub fields: ReportFields,
+    /// A duplicate is still passed on if no report was passed on for this long,
+    /// so consumers can tell a Wii remote at rest from a lost connection.
+    pub keep_alive: Duration,
+}
+
+impl Default for DuplicateSuppression {
+    fn default() -> Self {
+        Self {
+            fields: ReportFields::all(),
+            keep_alive: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Suppresses consecutive identical data reports, which the Wii remote sends constantly
+/// in continuous reporting mode.
+#[derive(Debug, Clone)]
+pub struct DuplicateFilter {
+    config: DuplicateSuppression,
+    last: Option<(WiimoteData, Instant)>,
+}
+
+impl DuplicateFilter {
+    #[must_use]
+    pub const fn new(config: DuplicateSuppression) -> Self {
+        Self { config, last: None }
+    }
+
+    /// Returns true if the data report should be passed on, because the compared fields changed,
+    /// the reporting mode changed or the keep-alive interval elapsed.
+    pub fn accept(&mut self, data: &WiimoteData) -> bool {
+        let now = Instant::now();
+        if let Some((last, passed_at)) = &self.last {
+            let duplicate = last.report_id() == data.report_id()
+                && self.fields_equal(last, data)
+                && now.duration_since(*passed_at) < self.config.keep_alive;
+            if duplicate {
+                return false;
+            }
+        }
+        self.last = Some((*data, now));
+        true
+    }
+
+    /// Forgets the last report, the next report is passed on.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn fields_equal(&self, last: &WiimoteData, data: &WiimoteData) -> bool {
+        let fields = self.config.fields;
+        // The unused bits of the button bytes contain accelerometer data.
+        (!fields.contains(ReportFields::BUTTONS)
+            || data.report_id() == 0x3D
+            || last.buttons() & ButtonData::all() == data.buttons() & ButtonData::all())
+            && (!fields.contains(ReportFields::ACCELEROMETER)
+                || last.accelerometer() == data.accelerometer())
+            && (!fields.contains(ReportFields::IR) || last.ir_data() == data.ir_data())
+            && (!fields.contains(ReportFields::EXTENSION)
+                || last.extension_data() == data.extension_data())
+    }
+}
+
+/// The decoded data of a data report, see [`WiimoteSession::read_frame`].
+/// Data that was not requested or is not available is `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionFrame {
+    pub buttons: ButtonData,
+    /// The calibrated acceleration in g.
+    pub acceleration: Option<(f64, f64, f64)>,
+    pub ir: Option<IrData>,
+    /// The extension data, also when passed through by the Motion Plus.
+    pub extension: Option<ExtensionInput>,
+    /// The calibrated angular velocity in degrees per second.
+    pub angular_velocity: Option<AngularVelocity>,
+}
+
+/// How long a read of a shared session holds the lock of the `WiimoteDevice`.
+const SHARED_READ_MILLIS: usize = 10;
+
+/// The Wii remote of a session, either borrowed or locked for each read.
+enum SessionDevice<'a> {
+    Borrowed(&'a WiimoteDevice),
+    Shared(Arc<Mutex<WiimoteDevice>>),
+}
+
+impl SessionDevice<'_> {
+    fn with<R>(&self, f: impl FnOnce(&WiimoteDevice) -> R) -> R {
+        match self {
+            Self::Borrowed(wiimote) => f(wiimote),
+            Self::Shared(wiimote) => f(&wiimote.lock().unwrap_or_else(PoisonError::into_inner)),
+        }
+    }
+
+    /// Reads the next input report, a shared device returns `None` if no report arrived
+    /// within [`SHARED_READ_MILLIS`] so other threads can lock it in between.
+    fn read(&self) -> WiimoteResult<Option<InputReport>> {
+        match self {
+            Self::Borrowed(wiimote) => wiimote.read().map(Some),
+            Self::Shared(_) => {
+                match self.with(|wiimote| wiimote.read_timeout(SHARED_READ_MILLIS)) {
+                    Ok(input_report) => Ok(Some(input_report)),
+                    Err(WiimoteError::WiimoteDeviceError(WiimoteDeviceError::MissingData)) => {
+                        Ok(None)
+                    }
+                    Err(error) => Err(error),
+                }
+            }
+        }
+    }
+}
+
+/// Manages the data reporting of a Wii remote for the data declared in a [`SessionConfig`].
+///
+/// The session picks the data reporting mode, enables the IR camera and Motion Plus,
+/// sets the data reporting mode again after status reports and decodes the data reports.
+/// A session started with [`Self::start_shared`] locks the `WiimoteDevice` for each read,
+/// so the Wii remote can be used from other threads in between.
+///
+/// ```no_run
+/// use wiimote_rs::prelude::*;
+///
+/// fn print_frames(wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+///     let config = SessionConfig::default().motion_plus(true);
+///     let session = WiimoteSession::start(wiimote, config)?;
+///     loop {
+///         let frame = session.read_frame()?;
+///         println!("{:?} {:?}", frame.acceleration, frame.angular_velocity);
+///     }
+/// }
+/// ```
+pub struct WiimoteSession<'a> {
+    device: SessionDevice<'a>,
+    config: SessionConfig,
+    ir_mode: Option<IrMode>,
+    duplicate_filter: Option<RefCell<DuplicateFilter>>,
+}
+
+impl<'a> WiimoteSession<'a> {
+    /// Enables the requested data and sets the data reporting mode.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the IR camera or the Motion Plus could not be enabled
+    /// or the data reporting mode could not be written.
+    pub fn start(wiimote: &'a WiimoteDevice, config: SessionConfig) -> WiimoteResult<Self> {
+        Self::start_with(SessionDevice::Borrowed(wiimote), config)
+    }
+
+    /// Enables the requested data and sets the data reporting mode of a Wii remote
+    /// shared with other threads, such as the devices found by the manager.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the IR camera or the Motion Plus could not be enabled
+    /// or the data reporting mode could not be written.
+    pub fn start_shared(
+        wiimote: Arc<Mutex<WiimoteDevice>>,
+        config: SessionConfig,
+    ) -> WiimoteResult<WiimoteSession<'static>> {
+        WiimoteSession::start_with(SessionDevice::Shared(wiimote), config)
+    }
+
+    fn start_with(device: SessionDevice<'a>, config: SessionConfig) -> WiimoteResult<Self> {
+        let (mode, ir_mode) = config.reporting_mode();
+        device.with(|wiimote| {
+            if config.motion_plus && wiimote.motion_plus().is_some() {
+                let policy = if config.extension {
+                    MotionPlusPolicy::Passthrough
+                } else {
+                    MotionPlusPolicy::Active
+                };
+                wiimote.enable_motion_plus(policy)?;
+            }
+            if let (Some(ir_mode), Some(sensitivity)) = (ir_mode, config.ir_camera) {
+                IrCamera::enable(wiimote, ir_mode, sensitivity)?;
+            }
+            wiimote.write(&OutputReport::DataReportingMode(DataReporingMode {
+                continuous: config.continuous,
+                mode,
+            }))
+        })?;
+
+        Ok(Self {
+            device,
+            config,
+            ir_mode,
+            duplicate_filter: config
+                .suppress_duplicates
+                .map(|config| RefCell::new(DuplicateFilter::new(config))),
+        })
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// Reads until the next data report arrives and decodes it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected or read failed.
+    pub fn read_frame(&self) -> WiimoteResult<SessionFrame> {
+        let data = self.read_data()?;
+        Ok(self.device.with(|wiimote| self.decode(wiimote, &data)))
+    }
+
+    /// Reads the next input report waiting for a maximum of `timeout_millis`,
+    /// returns `None` if it is not a data report or a suppressed duplicate.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote is disconnected, read failed
+    /// or no report was received within the timeout.
+    pub fn read_frame_timeout(&self, timeout_millis: usize) -> WiimoteResult<Option<SessionFrame>> {
+        let data = self.read_data_timeout(timeout_millis)?;
+        Ok(data.map(|data| self.device.with(|wiimote| self.decode(wiimote, &data))))
+    }
+
+    /// Reads until the next data report arrives that is not a suppressed duplicate,
+    /// for sessions decoding the data themselves like [`BalanceBoardSession`].
+    pub(crate) fn read_data(&self) -> WiimoteResult<WiimoteData> {
+        loop {
+            let Some(input_report) = self.device.read()? else {
+                continue;
+            };
+            if let Some(data) = self.handle_report(input_report)? {
+                return Ok(data);
+            }
+        }
+    }
+
+    /// Reads the next input report waiting for a maximum of `timeout_millis`,
+    /// returns `None` if it is not a data report or a suppressed duplicate.
+    pub(crate) fn read_data_timeout(
+        &self,
+        timeout_millis: usize,
+    ) -> WiimoteResult<Option<WiimoteData>> {
+        let input_report = self
+            .device
+            .with(|wiimote| wiimote.read_timeout(timeout_millis))?;
+        self.handle_report(input_report)
+    }
+
+    fn handle_report(&self, input_report: InputReport) -> WiimoteResult<Option<WiimoteData>> {
+        self.device.with(|wiimote| {
+            wiimote.process_pending_changes()?;
+            match input_report {
+                InputReport::DataReport(_, data) => {
+                    let accepted = self
+                        .duplicate_filter
+                        .as_ref()
+                        .map_or(true, |filter| filter.borrow_mut().accept(&data));
+                    Ok(accepted.then_some(data))
+                }
+                InputReport::StatusInformation(_) => {
+                    // The Wii remote stops sending data reports after a status report.
+                    wiimote.restore_reporting_mode()?;
+                    Ok(None)
+                }
+                _ => Ok(None),
+            }
+        })
+    }
+
+    fn decode(&self, wiimote: &WiimoteDevice, data: &WiimoteData) -> SessionFrame {
+        let acceleration = data
+            .accelerometer()
+            .filter(|_| self.config.accelerometer)
+            .map(|accelerometer| {
+                wiimote
+                    .accelerometer_calibration()
+                    .get_acceleration(&accelerometer)
+            });
+        let ir = self
+            .ir_mode
+            .zip(data.ir_data())
+            .and_then(|(mode, ir_data)| IrData::decode(mode, ir_data));
+
+        let mut frame = SessionFrame {
+            buttons: data.buttons(),
+            acceleration,
+            ir,
+            extension: None,
+            angular_velocity: None,
+        };
+        self.decode_extension(wiimote, data, &mut frame);
+        frame
+    }
+
+    fn decode_extension(
+        &self,
+        wiimote: &WiimoteDevice,
+        data: &WiimoteData,
+        frame: &mut SessionFrame,
+    ) {
+        let motion_plus = wiimote
+            .motion_plus()
+            .filter(|motion_plus| !matches!(motion_plus.mode(), MotionPlusMode::Inactive));
+        let Some(motion_plus) = motion_plus else {
+            if self.config.extension {
+                frame.extension = wiimote
+                    .extension()
+                    .and_then(|extension| extension.decode(data));
+            }
+            return;
+        };
+
+        let Some(bytes) = data.extension_data().and_then(|bytes| bytes.get(..6)) else {
+            return;
+        };
+        let mut six_bytes = [0u8; 6];
+        six_bytes.copy_from_slice(bytes);
+        match PassthroughDecoder::new(motion_plus.mode()).decode(six_bytes) {
+            Some(PassthroughData::MotionPlus(motion_plus_data)) => {
+                frame.angular_velocity = Some(
+                    motion_plus
+                        .calibration()
+                        .angular_velocity(&motion_plus_data),
+                );
+            }
+            Some(PassthroughData::Nunchuck(nunchuck)) if self.config.extension => {
+                frame.extension = Some(ExtensionInput::Nunchuck(nunchuck));
+            }
+            Some(PassthroughData::ClassicController(classic_controller))
+                if self.config.extension =>
+            {
+                frame.extension = Some(ExtensionInput::ClassicController(classic_controller));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(bytes: &[u8]) -> WiimoteData {
+        match InputReport::try_from(bytes).unwrap() {
+            InputReport::DataReport(_, data) => data,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_filter() {
+        let mut filter = DuplicateFilter::new(DuplicateSuppression {
+            fields: ReportFields::BUTTONS,
+            keep_alive: Duration::from_secs(60),
+        });
+        assert!(filter.accept(&data(&[0x31, 0x00, 0x00, 0x80, 0x80, 0x80])));
+        // Only the acceleration changed, which is not compared
+        assert!(!filter.accept(&data(&[0x31, 0x00, 0x00, 0x81, 0x80, 0x80])));
+        assert!(filter.accept(&data(&[0x31, 0x00, 0x08, 0x81, 0x80, 0x80])));
+        assert!(filter.accept(&data(&[0x30, 0x00, 0x08])));
+
+        filter.reset();
+        assert!(filter.accept(&data(&[0x30, 0x00, 0x08])));
+
+        let mut filter = DuplicateFilter::new(DuplicateSuppression {
+            keep_alive: Duration::ZERO,
+            ..DuplicateSuppression::default()
+        });
+        assert!(filter.accept(&data(&[0x30, 0x00, 0x00])));
+        assert!(filter.accept(&data(&[0x30, 0x00, 0x00])));
+    }
+}
@@ -2,9 +2,110 @@ use crate::prelude::*;
 
 use crate::input::{AcknowledgeData, InputReport, MemoryData};
 use crate::output::{Addressing, OutputReport};
+use crate::trace::enter_span;
 
-const RETRY_COUNT: usize = 5;
-const READ_TIMEOUT: usize = 250;
+pub(crate) const RETRY_COUNT: usize = 5;
+pub(crate) const READ_TIMEOUT: usize = 250;
+const WRITE_MEMORY_ID: u8 = ReportId::WriteMemory.to_u8();
+
+/// Reads the next report while waiting for a memory access.
+/// Status reports still update the device state, as they are not seen by the caller of the read functions.
+pub(crate) fn read_setup_report(wiimote: &WiimoteDevice) -> WiimoteResult<InputReport> {
+    let input_report = wiimote.read_report(Some(READ_TIMEOUT))?;
+    if matches!(input_report, InputReport::StatusInformation(_)) {
+        wiimote.handle_report(&input_report);
+    }
+    Ok(input_report)
+}
+
+/// A memory access sent by [`exchange`].
+pub enum MemoryRequest {
+    Read(Addressing),
+    Write(Addressing, [u8; 16]),
+}
+
+/// The response to a [`MemoryRequest`].
+pub enum MemoryResponse {
+    Read(MemoryData),
+    Write(AcknowledgeData),
+}
+
+/// Sends all requests without waiting for the responses in between and returns the responses in request order.
+///
+/// <https://www.wiibrew.org/wiki/Wiimote#Memory_and_Registers>
+/// The Wii remote processes a single memory read at a time, so at most one read may be pipelined with the writes.
+/// The read data is matched to the request by its address offset, responses to earlier reads are skipped.
+/// The acknowledgement report 0x22 contains the report number but not the address of the write,
+/// so the acknowledgements of report 0x16 are matched to the writes in the order they were sent.
+/// Discards other reports, only use during setup to prevent race-conditions.
+pub fn exchange(
+    wiimote: &WiimoteDevice,
+    requests: &[MemoryRequest],
+) -> WiimoteResult<Vec<MemoryResponse>> {
+    enter_span!(DEBUG, "exchange_memory", identifier = %wiimote.identifier(), requests = requests.len());
+    let write_count = requests
+        .iter()
+        .filter(|request| matches!(request, MemoryRequest::Write(..)))
+        .count();
+    let read = requests.iter().find_map(|request| match request {
+        MemoryRequest::Read(addressing) => Some(*addressing),
+        MemoryRequest::Write(..) => None,
+    });
+    debug_assert!(
+        requests.len() - write_count <= 1,
+        "The Wii remote only handles a single read at a time"
+    );
+
+    for request in requests {
+        let output_report = match request {
+            MemoryRequest::Read(addressing) => OutputReport::ReadMemory(*addressing),
+            MemoryRequest::Write(addressing, data) => OutputReport::WriteMemory(*addressing, *data),
+        };
+        wiimote.write(&output_report)?;
+    }
+
+    let mut memory_data = None;
+    let mut acknowledgements = Vec::with_capacity(write_count);
+    let mut remaining_reports = RETRY_COUNT * requests.len();
+    while acknowledgements.len() < write_count || (read.is_some() && memory_data.is_none()) {
+        if remaining_reports == 0 {
+            return Err(WiimoteDeviceError::InvalidData.into());
+        }
+        remaining_reports -= 1;
+        match read_setup_report(wiimote)? {
+            InputReport::ReadMemory(data)
+                if memory_data.is_none()
+                    && read.is_some_and(|read| is_response_to(&data, read)) =>
+            {
+                memory_data = Some(data);
+            }
+            InputReport::Acknowledge(ack)
+                if ack.report_number() == WRITE_MEMORY_ID
+                    && acknowledgements.len() < write_count =>
+            {
+                acknowledgements.push(ack);
+            }
+            _ => {}
+        }
+    }
+
+    let mut acknowledgements = acknowledgements.into_iter();
+    Ok(requests
+        .iter()
+        .filter_map(|request| match request {
+            MemoryRequest::Read(_) => memory_data.map(MemoryResponse::Read),
+            MemoryRequest::Write(..) => acknowledgements.next().map(MemoryResponse::Write),
+        })
+        .collect())
+}
+
+/// Returns whether the read data answers the read of `addressing`,
+/// the report only contains the lower 16 bits of the address.
+const fn is_response_to(memory_data: &MemoryData, addressing: Addressing) -> bool {
+    #[allow(clippy::cast_possible_truncation)] // The offset is the lower 16 bits of the address.
+    let offset = addressing.address as u16;
+    memory_data.address_offset() == offset
+}
 
 /// Reads up to 16 bytes from the Wii remote.
 /// Discards reports other than the expected data, only use during setup to prevent race-conditions.
@@ -12,11 +113,12 @@ pub fn read_16_bytes_sync(
     wiimote: &WiimoteDevice,
     addressing: Addressing,
 ) -> WiimoteResult<MemoryData> {
+    enter_span!(DEBUG, "read_memory", identifier = %wiimote.identifier(), address = addressing.address);
     let memory_read_request = OutputReport::ReadMemory(addressing);
     wiimote.write(&memory_read_request).unwrap();
 
     for _i in 0..RETRY_COUNT {
-        let input_report = wiimote.read_timeout(READ_TIMEOUT)?;
+        let input_report = read_setup_report(wiimote)?;
         if let InputReport::ReadMemory(memory_data) = input_report {
             return Ok(memory_data);
         }
@@ -29,11 +131,22 @@ pub fn read_16_bytes_sync(
 pub fn read_16_bytes_sync_checked(
     wiimote: &WiimoteDevice,
     addressing: Addressing,
+) -> WiimoteResult<[u8; 16]> {
+    let memory_data = read_16_bytes_sync(wiimote, addressing)?;
+    check_memory_data(&memory_data, addressing)
+}
+
+/// Checks that the report contains the data read from `addressing` without error.
+pub fn check_memory_data(
+    memory_data: &MemoryData,
+    addressing: Addressing,
 ) -> WiimoteResult<[u8; 16]> {
     let address = addressing.address;
     let size = addressing.size;
 
-    let memory_data = read_16_bytes_sync(wiimote, addressing)?;
+    if let Some(error) = memory_data.error() {
+        return Err(WiimoteDeviceError::Memory { address, error }.into());
+    }
     if memory_data.address_offset() != address as u16 || (memory_data.size() as u16) < size {
         Err(WiimoteDeviceError::InvalidData.into())
     } else {
@@ -48,14 +161,78 @@ pub fn write_16_bytes_sync(
     addressing: Addressing,
     data: &[u8; 16],
 ) -> WiimoteResult<AcknowledgeData> {
-    let memory_write_request = OutputReport::WriteMemory(addressing, *data);
-    wiimote.write(&memory_write_request).unwrap();
+    write_report_sync(wiimote, &OutputReport::WriteMemory(addressing, *data))
+}
+
+/// Writes a memory write report and waits for its acknowledgement.
+fn write_report_sync(
+    wiimote: &WiimoteDevice,
+    memory_write_request: &OutputReport,
+) -> WiimoteResult<AcknowledgeData> {
+    enter_span!(DEBUG, "write_memory", identifier = %wiimote.identifier(), report = ?memory_write_request);
+    wiimote.write(memory_write_request).unwrap();
 
     for _i in 0..RETRY_COUNT {
-        let input_report = wiimote.read_timeout(READ_TIMEOUT)?;
+        let input_report = read_setup_report(wiimote)?;
         if let InputReport::Acknowledge(acknowledge_data) = input_report {
             return Ok(acknowledge_data);
         }
     }
     Err(WiimoteDeviceError::InvalidData.into())
 }
+
+/// Writes `data` to the control registers of the Wii remote and checks the acknowledgements.
+/// Discards reports other than the acknowledge result, only use during setup to prevent race-conditions.
+pub fn write_register_checked(
+    wiimote: &WiimoteDevice,
+    address: u32,
+    data: &[u8],
+) -> WiimoteResult<()> {
+    write_memory_checked(wiimote, Addressing::control_registers(address, 0), data)
+}
+
+/// Writes `data` in chunks of 16 bytes starting at the address of `addressing` and checks the acknowledgements,
+/// see [`OutputReport::write_memory_chunks`].
+/// Discards reports other than the acknowledge result, only use during setup to prevent race-conditions.
+pub fn write_memory_checked(
+    wiimote: &WiimoteDevice,
+    addressing: Addressing,
+    data: &[u8],
+) -> WiimoteResult<()> {
+    for report in OutputReport::write_memory_chunks(addressing, data) {
+        let ack = write_report_sync(wiimote, &report)?;
+        if let OutputReport::WriteMemory(chunk_addressing, _) = report {
+            check_acknowledge(&ack, chunk_addressing.address)?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts the error code of the acknowledgement of a write to `address` to an error.
+pub fn check_acknowledge(ack: &AcknowledgeData, address: u32) -> WiimoteResult<()> {
+    match ack.error() {
+        Some(error) => Err(WiimoteDeviceError::Memory { address, error }.into()),
+        None => Ok(()),
+    }
+}
+
+/// Writes up to 16 bytes to the control registers of the Wii remote, reads them back and compares the data.
+/// Only use for registers that can be read back unchanged, writes triggering an action may read differently.
+/// Discards reports other than the expected data, only use during setup to prevent race-conditions.
+pub fn write_verified(wiimote: &WiimoteDevice, address: u32, data: &[u8]) -> WiimoteResult<()> {
+    write_register_checked(wiimote, address, data)?;
+
+    #[allow(clippy::cast_possible_truncation)] // At most 16 bytes
+    let addressing = Addressing::control_registers(address, data.len() as u16);
+    let actual = read_16_bytes_sync_checked(wiimote, addressing)?;
+    let actual = &actual[..data.len()];
+    if actual != data {
+        return Err(WiimoteDeviceError::VerificationFailed {
+            address,
+            expected: data.to_vec(),
+            actual: actual.to_vec(),
+        }
+        .into());
+    }
+    Ok(())
+}
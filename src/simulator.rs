@@ -0,0 +1,610 @@
+//! A simulated Wii remote that responds to output reports like the hardware.
+//!
+//! Unlike the [`MockWiimote`](crate::mock::MockWiimote), the simulator models the behavior
+//! of the Wii remote instead of answering from scripted memory:
+//!
+//! - The EEPROM is 0x1700 bytes, reads past its end fail with `MemoryError::NonexistentAddress`.
+//! - The extension registers at 0xA40000 only exist while an extension or an active Motion Plus
+//!   is connected, the Motion Plus registers at 0xA60000 only while the Motion Plus is inactive.
+//!   Accessing missing registers fails with `MemoryError::WriteOnly`.
+//! - Writing 0x04, 0x05 or 0x07 to 0xA600FE activates the Motion Plus, which then moves to the
+//!   extension registers. Writing 0x55 to 0xA400F0 deactivates it again.
+//! - Attaching or detaching extensions and (de)activating the Motion Plus sends a status report,
+//!   after which data reports stop until the data reporting mode is set again.
+//! - Data reports are generated for the modes 0x30 to 0x37 and 0x3D from the simulated buttons,
+//!   accelerometer, gyroscope and extension bytes, continuously or only when the state changed.
+//!   The Motion Plus pass-through modes only report Motion Plus frames.
+//!
+//! Reads never block: 0 bytes (no report) are returned if no report is due.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::input::ButtonData;
+use crate::mock::{motion_plus_calibration, ACCELEROMETER_CALIBRATION};
+use crate::output::DataReporingMode;
+use crate::prelude::*;
+
+const EEPROM_SIZE: usize = 0x1700;
+const EXTENSION_REGISTERS: u32 = 0xA4_0000;
+const MOTION_PLUS_REGISTERS: u32 = 0xA6_0000;
+
+const MOTION_PLUS_INITIALIZE_ADDRESS: u8 = 0xF0;
+const MOTION_PLUS_ACTIVATE_ADDRESS: u8 = 0xFE;
+const EXTENSION_IDENTIFIER_ADDRESS: usize = 0xFA;
+
+/// The identifier of the extension as read from 0xA400FA.
+const fn extension_identifier(extension: WiimoteExtension) -> [u8; 6] {
+    match extension {
+        WiimoteExtension::Nunchuck => [0x00, 0x00, 0xA4, 0x20, 0x00, 0x00],
+        WiimoteExtension::ClassicController => [0x00, 0x00, 0xA4, 0x20, 0x01, 0x01],
+        WiimoteExtension::ClassicControllerPro => [0x01, 0x00, 0xA4, 0x20, 0x01, 0x01],
+        WiimoteExtension::BalanceBoard => [0x00, 0x00, 0xA4, 0x20, 0x04, 0x02],
+        WiimoteExtension::Unknown(identifier) => identifier,
+    }
+}
+
+/// A transport simulating a Wii remote, see the [module documentation](self).
+///
+/// Clones share their state, so a clone can control the simulation after the original
+/// was passed to [`WiimoteDevice::with_transport`].
+#[derive(Clone)]
+pub struct SimulatedWiimote {
+    state: Arc<Mutex<SimulatorState>>,
+}
+
+struct SimulatorState {
+    identifier: &'static str,
+    model: WiimoteModel,
+    connected: bool,
+    eeprom: Vec<u8>,
+    /// Registers other than the extension and Motion Plus registers, e.g. of the IR camera.
+    registers: BTreeMap<u32, u8>,
+    extension: Option<WiimoteExtension>,
+    extension_registers: Option<[u8; 0x100]>,
+    /// The identifier of the Motion Plus when inactive, `None` without Motion Plus.
+    motion_plus: Option<[u8; 6]>,
+    motion_plus_registers: Option<[u8; 0x100]>,
+    motion_plus_mode: MotionPlusMode,
+    reporting_mode: Option<DataReporingMode>,
+    /// Data reports are suspended after an unsolicited status report until the reporting mode is set.
+    reporting_suspended: bool,
+    changed: bool,
+    buttons: ButtonData,
+    acceleration: [u16; 3],
+    angular_velocity: [u16; 3],
+    extension_data: [u8; 21],
+    battery_level: u8,
+    status_flags: u8,
+    input_reports: VecDeque<Vec<u8>>,
+    written_reports: Vec<Vec<u8>>,
+}
+
+impl Default for SimulatedWiimote {
+    fn default() -> Self {
+        Self::new(WiimoteModel::Original)
+    }
+}
+
+impl SimulatedWiimote {
+    /// Creates a Wii remote without extension at rest, the `Plus` model has a built-in Motion Plus
+    /// and the `BalanceBoard` model the balance board extension.
+    #[must_use]
+    pub fn new(model: WiimoteModel) -> Self {
+        let mut eeprom = vec![0u8; EEPROM_SIZE];
+        eeprom[0x16..0x20].copy_from_slice(&ACCELEROMETER_CALIBRATION);
+        eeprom[0x20..0x2A].copy_from_slice(&ACCELEROMETER_CALIBRATION);
+
+        let mut state = SimulatorState {
+            identifier: "00:00:00:00:00:00",
+            model,
+            connected: true,
+            eeprom,
+            registers: BTreeMap::new(),
+            extension: None,
+            extension_registers: None,
+            motion_plus: None,
+            motion_plus_registers: None,
+            motion_plus_mode: MotionPlusMode::Inactive,
+            reporting_mode: None,
+            reporting_suspended: false,
+            changed: false,
+            buttons: ButtonData::empty(),
+            // Zero offset 0x200 and 1 g of 0x268 of the default calibration on the Z axis.
+            acceleration: [0x200, 0x200, 0x268],
+            angular_velocity: [0x1F00; 3],
+            extension_data: [0; 21],
+            battery_level: 0xC8,
+            status_flags: 0,
+            input_reports: VecDeque::new(),
+            written_reports: Vec::new(),
+        };
+        match model {
+            WiimoteModel::Original => {}
+            WiimoteModel::Plus => state.motion_plus = Some([0x01, 0x00, 0xA6, 0x20, 0x00, 0x05]),
+            // The balance board is a Wii remote with a permanently connected extension.
+            WiimoteModel::BalanceBoard => state.extension = Some(WiimoteExtension::BalanceBoard),
+        }
+        state.update_registers();
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, SimulatorState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Sets the identifier of the simulated Wii remote and its clones. Each identifier set is kept until the program
+    /// exits, so the transport can hand out references to it. Meant for tests setting a few identifiers.
+    pub fn set_identifier(&self, identifier: &str) {
+        self.state().identifier = Box::leak(identifier.into());
+    }
+
+    /// Plugs an external Motion Plus into the Wii remote, signaled by a status report like an extension.
+    pub fn attach_motion_plus(&self) {
+        let mut state = self.state();
+        state.motion_plus = Some([0x00, 0x00, 0xA6, 0x20, 0x00, 0x05]);
+        state.plugged_changed();
+    }
+
+    /// Unplugs the external Motion Plus together with an extension plugged into its pass-through port.
+    pub fn detach_motion_plus(&self) {
+        let mut state = self.state();
+        state.motion_plus = None;
+        state.motion_plus_mode = MotionPlusMode::Inactive;
+        state.extension = None;
+        state.extension_data = [0; 21];
+        state.plugged_changed();
+    }
+
+    /// Plugs the extension into the Wii remote or the pass-through port of the Motion Plus.
+    pub fn attach_extension(&self, extension: WiimoteExtension) {
+        let mut state = self.state();
+        state.extension = Some(extension);
+        // Centered sticks and released buttons
+        let data: &[u8] = match extension {
+            WiimoteExtension::Nunchuck => &[0x80, 0x80, 0x80, 0x80, 0xB3, 0xFF],
+            WiimoteExtension::ClassicController | WiimoteExtension::ClassicControllerPro => {
+                &[0x60, 0x20, 0x10, 0x00, 0xFF, 0xFF]
+            }
+            _ => &[],
+        };
+        state.extension_data = [0; 21];
+        state.extension_data[..data.len()].copy_from_slice(data);
+        state.extension_plugged();
+    }
+
+    /// Unplugs the extension.
+    pub fn detach_extension(&self) {
+        let mut state = self.state();
+        state.extension = None;
+        state.extension_data = [0; 21];
+        state.extension_plugged();
+    }
+
+    /// Returns the active mode of the Motion Plus.
+    #[must_use]
+    pub fn motion_plus_mode(&self) -> MotionPlusMode {
+        self.state().motion_plus_mode
+    }
+
+    /// Returns the data reporting mode set by the last output report 0x12.
+    #[must_use]
+    pub fn reporting_mode(&self) -> Option<DataReporingMode> {
+        self.state().reporting_mode
+    }
+
+    pub fn set_buttons(&self, buttons: ButtonData) {
+        let mut state = self.state();
+        state.buttons = buttons;
+        state.changed = true;
+    }
+
+    /// Sets the raw 10-bit accelerometer values (X, Y, Z).
+    pub fn set_acceleration(&self, acceleration: [u16; 3]) {
+        let mut state = self.state();
+        state.acceleration = acceleration.map(|value| value & 0x3FF);
+        state.changed = true;
+    }
+
+    /// Sets the raw 14-bit gyroscope values (yaw, roll, pitch) of the Motion Plus in slow mode.
+    pub fn set_angular_velocity(&self, angular_velocity: [u16; 3]) {
+        let mut state = self.state();
+        state.angular_velocity = angular_velocity.map(|value| value & 0x3FFF);
+        state.changed = true;
+    }
+
+    /// Sets the unencrypted extension bytes of data reports, e.g. the 6 bytes of a Nunchuck.
+    pub fn set_extension_data(&self, data: &[u8]) {
+        let mut state = self.state();
+        let size = data.len().min(state.extension_data.len());
+        state.extension_data[..size].copy_from_slice(&data[..size]);
+        state.changed = true;
+    }
+
+    /// Sets the raw battery level reported in status reports.
+    pub fn set_battery_level(&self, level: u8) {
+        self.state().battery_level = level;
+    }
+
+    /// Returns the output reports written to the simulator, starting with the report ID.
+    #[must_use]
+    pub fn written_reports(&self) -> Vec<Vec<u8>> {
+        self.state().written_reports.clone()
+    }
+
+    /// Simulates losing the connection, following reads and writes fail with `WiimoteError::Disconnected`.
+    pub fn disconnect(&self) {
+        self.state().connected = false;
+    }
+}
+
+impl SimulatorState {
+    fn motion_plus_active(&self) -> bool {
+        !matches!(self.motion_plus_mode, MotionPlusMode::Inactive)
+    }
+
+    /// Rebuilds the extension and Motion Plus registers after a change of the connected devices.
+    fn update_registers(&mut self) {
+        let mut motion_plus_registers = self.motion_plus.map(|identifier| {
+            let mut registers = [0u8; 0x100];
+            registers[0x20..0x40].copy_from_slice(&motion_plus_calibration());
+            registers[EXTENSION_IDENTIFIER_ADDRESS..].copy_from_slice(&identifier);
+            registers
+        });
+
+        self.extension_registers = if self.motion_plus_active() {
+            // The active Motion Plus identifies as extension with the mode in byte 4.
+            motion_plus_registers.take().map(|mut registers| {
+                registers[EXTENSION_IDENTIFIER_ADDRESS + 2] = 0xA4;
+                registers[EXTENSION_IDENTIFIER_ADDRESS + 4] = match self.motion_plus_mode {
+                    MotionPlusMode::NunchuckPassthrough => 0x05,
+                    MotionPlusMode::ClassicControllerPassthrough => 0x07,
+                    _ => 0x04,
+                };
+                registers
+            })
+        } else {
+            self.extension.map(|extension| {
+                let mut registers = [0u8; 0x100];
+                registers[EXTENSION_IDENTIFIER_ADDRESS..]
+                    .copy_from_slice(&extension_identifier(extension));
+                registers
+            })
+        };
+        self.motion_plus_registers = motion_plus_registers;
+    }
+
+    /// Extensions plugged into an active Motion Plus are only signaled in the Motion Plus data.
+    fn extension_plugged(&mut self) {
+        if self.motion_plus_active() {
+            self.update_registers();
+        } else {
+            self.plugged_changed();
+        }
+    }
+
+    /// Sends the unsolicited status report of an extension change, which suspends data reports.
+    fn plugged_changed(&mut self) {
+        self.update_registers();
+        let status_report = self.status_report();
+        self.input_reports.push_back(status_report);
+        self.reporting_suspended = true;
+    }
+
+    fn status_report(&self) -> Vec<u8> {
+        // The flag is set while anything is plugged in, also an inactive Motion Plus.
+        let extension_flag = if self.extension_registers.is_some() || self.motion_plus.is_some() {
+            0x02
+        } else {
+            0x00
+        };
+        let [low, high] = self.buttons.bits().to_le_bytes();
+        vec![
+            0x20,
+            low,
+            high,
+            self.status_flags | extension_flag,
+            0,
+            0,
+            self.battery_level,
+        ]
+    }
+
+    fn register_block(&mut self, address: u32) -> Option<Option<&mut [u8; 0x100]>> {
+        match address & 0xFF_0000 {
+            EXTENSION_REGISTERS => Some(self.extension_registers.as_mut()),
+            MOTION_PLUS_REGISTERS => Some(self.motion_plus_registers.as_mut()),
+            _ => None,
+        }
+    }
+
+    /// Reads a register byte, `None` if the register does not exist.
+    fn read_register(&mut self, address: u32) -> Option<u8> {
+        match self.register_block(address) {
+            Some(block) => block.map(|block| block[(address & 0xFF) as usize]),
+            None => Some(self.registers.get(&address).copied().unwrap_or(0)),
+        }
+    }
+
+    fn read_memory(&mut self, control_registers: bool, address: u32, size: u16) {
+        let mut offset = 0;
+        while offset < u32::from(size) {
+            let chunk_address = address + offset;
+            let chunk_size = (u32::from(size) - offset).min(16);
+            let mut data = [0u8; 16];
+            let mut error = None;
+            for (index, byte) in (0..chunk_size).zip(&mut data) {
+                let address = chunk_address + index;
+                let value = if control_registers {
+                    self.read_register(address).ok_or(MemoryError::WriteOnly)
+                } else {
+                    self.eeprom
+                        .get(address as usize)
+                        .copied()
+                        .ok_or(MemoryError::NonexistentAddress)
+                };
+                match value {
+                    Ok(value) => *byte = value,
+                    Err(value_error) => {
+                        error = Some(value_error);
+                        break;
+                    }
+                }
+            }
+
+            let mut report = vec![
+                0x21,
+                0,
+                0,
+                ((chunk_size as u8 - 1) << 4) | error.map_or(0, MemoryError::code),
+            ];
+            report.extend_from_slice(&(chunk_address as u16).to_be_bytes());
+            report.extend_from_slice(&if error.is_some() { [0; 16] } else { data });
+            self.input_reports.push_back(report);
+
+            if error.is_some() {
+                break;
+            }
+            offset += chunk_size;
+        }
+    }
+
+    fn write_memory(
+        &mut self,
+        control_registers: bool,
+        address: u32,
+        data: &[u8],
+    ) -> Option<MemoryError> {
+        if !control_registers {
+            let end = address as usize + data.len();
+            let Some(memory) = self.eeprom.get_mut(address as usize..end) else {
+                return Some(MemoryError::NonexistentAddress);
+            };
+            memory.copy_from_slice(data);
+            return None;
+        }
+
+        match self.register_block(address) {
+            Some(None) => return Some(MemoryError::WriteOnly),
+            Some(Some(block)) => {
+                for (offset, byte) in (0..).zip(data) {
+                    block[(address as usize + offset) & 0xFF] = *byte;
+                }
+            }
+            None => {
+                for (offset, byte) in (0..).zip(data) {
+                    self.registers.insert(address + offset, *byte);
+                }
+            }
+        }
+
+        let [_, block, _, register] = address.to_be_bytes();
+        let first_byte = data.first().copied();
+        match (block, register, first_byte) {
+            (0xA6, MOTION_PLUS_ACTIVATE_ADDRESS, Some(mode @ (0x04 | 0x05 | 0x07))) => {
+                self.motion_plus_mode = match mode {
+                    0x05 => MotionPlusMode::NunchuckPassthrough,
+                    0x07 => MotionPlusMode::ClassicControllerPassthrough,
+                    _ => MotionPlusMode::Active,
+                };
+                self.plugged_changed();
+            }
+            (0xA4, MOTION_PLUS_INITIALIZE_ADDRESS, Some(0x55)) if self.motion_plus_active() => {
+                self.motion_plus_mode = MotionPlusMode::Inactive;
+                self.plugged_changed();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_output_report(&mut self, buffer: &[u8]) {
+        self.written_reports.push(buffer.to_vec());
+        let Some((&report_id, data)) = buffer.split_first() else {
+            return;
+        };
+        let Some(&flags) = data.first() else {
+            return;
+        };
+
+        match report_id {
+            // Player LEDs
+            0x11 => self.status_flags = self.status_flags & 0x0F | flags & 0xF0,
+            0x12 if data.len() >= 2 => {
+                self.reporting_mode = Some(DataReporingMode {
+                    continuous: flags & 0x04 != 0,
+                    mode: data[1],
+                });
+                self.reporting_suspended = false;
+                self.changed = true;
+            }
+            // IR camera
+            0x13 => self.set_status_flag(0x08, flags & 0x04 != 0),
+            // Speaker
+            0x14 => self.set_status_flag(0x04, flags & 0x04 != 0),
+            0x15 => {
+                let status_report = self.status_report();
+                self.input_reports.push_back(status_report);
+            }
+            0x16 if data.len() >= 21 => {
+                let address = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+                let size = usize::from(data[4]).min(16);
+                let error = self.write_memory(flags & 0x04 != 0, address, &data[5..5 + size]);
+                self.acknowledge(report_id, error);
+            }
+            0x17 if data.len() >= 6 => {
+                let address = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+                let size = u16::from_be_bytes([data[4], data[5]]);
+                self.read_memory(flags & 0x04 != 0, address, size);
+            }
+            _ => {
+                if flags & 0x02 != 0 {
+                    self.acknowledge(report_id, None);
+                }
+            }
+        }
+    }
+
+    fn set_status_flag(&mut self, flag: u8, enabled: bool) {
+        if enabled {
+            self.status_flags |= flag;
+        } else {
+            self.status_flags &= !flag;
+        }
+    }
+
+    fn acknowledge(&mut self, report_id: u8, error: Option<MemoryError>) {
+        let [low, high] = self.buttons.bits().to_le_bytes();
+        let report = vec![
+            0x22,
+            low,
+            high,
+            report_id,
+            error.map_or(0, MemoryError::code),
+        ];
+        self.input_reports.push_back(report);
+    }
+
+    /// Returns the next data report if one is due in the current reporting mode.
+    fn data_report(&mut self) -> Option<Vec<u8>> {
+        let mode = self.reporting_mode?;
+        if self.reporting_suspended || !(mode.continuous || self.changed) {
+            return None;
+        }
+        self.changed = false;
+
+        let (accelerometer, ir_bytes, extension_bytes) = match mode.mode {
+            0x31 => (true, 0, 0),
+            0x32 => (false, 0, 8),
+            0x33 => (true, 12, 0),
+            0x34 => (false, 0, 19),
+            0x35 => (true, 0, 16),
+            0x36 => (false, 10, 9),
+            0x37 => (true, 10, 6),
+            0x3D => (false, 0, 21),
+            _ => (false, 0, 0),
+        };
+        let report_id = if (0x30..=0x37).contains(&mode.mode) || mode.mode == 0x3D {
+            mode.mode
+        } else {
+            0x30
+        };
+
+        let mut report = vec![report_id];
+        if report_id != 0x3D {
+            let [mut low, mut high] = self.buttons.bits().to_le_bytes();
+            if accelerometer {
+                // The low bits of the acceleration are stored in the unused bits of the buttons.
+                let [x, y, z] = self.acceleration;
+                low |= ((x & 0b11) as u8) << 5;
+                high |= (((y >> 1) & 1) as u8) << 5 | (((z >> 1) & 1) as u8) << 6;
+                report.extend_from_slice(&[low, high]);
+                report.extend(self.acceleration.map(|value| (value >> 2) as u8));
+            } else {
+                report.extend_from_slice(&[low, high]);
+            }
+        }
+        // No IR sources in view
+        report.resize(report.len() + ir_bytes, 0xFF);
+
+        let extension_data = if self.motion_plus_active() {
+            let mut data = self.extension_data;
+            data[..6].copy_from_slice(&self.motion_plus_frame());
+            data
+        } else {
+            self.extension_data
+        };
+        report.extend_from_slice(&extension_data[..extension_bytes]);
+        Some(report)
+    }
+
+    /// Encodes the gyroscope values as Motion Plus data with all axes in slow mode.
+    fn motion_plus_frame(&self) -> [u8; 6] {
+        let [yaw, roll, pitch] = self.angular_velocity;
+        let extension_connected = u8::from(self.extension.is_some());
+        [
+            yaw as u8,
+            roll as u8,
+            pitch as u8,
+            ((yaw >> 8) as u8) << 2 | 0b11,
+            ((roll >> 8) as u8) << 2 | 0b10 | extension_connected,
+            ((pitch >> 8) as u8) << 2 | 0b10,
+        ]
+    }
+}
+
+impl NativeWiimote for SimulatedWiimote {
+    fn read(&mut self, buffer: &mut [u8]) -> WiimoteResult<usize> {
+        let mut state = self.state();
+        if !state.connected {
+            return Err(WiimoteError::Disconnected);
+        }
+        let Some(report) = state
+            .input_reports
+            .pop_front()
+            .or_else(|| state.data_report())
+        else {
+            return Ok(0);
+        };
+        let size = report.len().min(buffer.len());
+        buffer[..size].copy_from_slice(&report[..size]);
+        Ok(size)
+    }
+
+    fn read_timeout(&mut self, buffer: &mut [u8], _timeout_millis: usize) -> WiimoteResult<usize> {
+        self.read(buffer)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll_read(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+        buffer: &mut [u8],
+    ) -> std::task::Poll<WiimoteResult<usize>> {
+        // Reads never wait, a report is either queued or not available.
+        std::task::Poll::Ready(self.read(buffer))
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> WiimoteResult<usize> {
+        let mut state = self.state();
+        if !state.connected {
+            return Err(WiimoteError::Disconnected);
+        }
+        state.handle_output_report(buffer);
+        Ok(buffer.len())
+    }
+
+    fn identifier(&self) -> &str {
+        self.state().identifier
+    }
+
+    fn model(&self) -> WiimoteModel {
+        self.state().model
+    }
+}
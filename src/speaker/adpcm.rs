@@ -0,0 +1,91 @@
+/// Encodes 16-bit samples to the 4-bit Yamaha ADPCM format played by the speaker.
+/// The state is kept between calls, so a stream must be encoded with a single encoder.
+#[derive(Debug, Clone)]
+pub struct AdpcmEncoder {
+    predicted: i32,
+    step: i32,
+}
+
+const DIFF_TABLE: [i32; 8] = [1, 3, 5, 7, 9, 11, 13, 15];
+const STEP_SCALE: [i32; 8] = [230, 230, 230, 230, 307, 409, 512, 614];
+const MIN_STEP: i32 = 127;
+const MAX_STEP: i32 = 24576;
+
+impl Default for AdpcmEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdpcmEncoder {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            predicted: 0,
+            step: MIN_STEP,
+        }
+    }
+
+    /// Encodes two samples per byte, the first sample in the high nibble.
+    /// A missing last sample of an odd number of samples is encoded as silence.
+    #[must_use]
+    pub fn encode(&mut self, samples: &[i16]) -> Vec<u8> {
+        samples
+            .chunks(2)
+            .map(|pair| {
+                let high = self.encode_sample(pair[0]);
+                let low = self.encode_sample(pair.get(1).copied().unwrap_or(0));
+                (high << 4) | low
+            })
+            .collect()
+    }
+
+    fn encode_sample(&mut self, sample: i16) -> u8 {
+        let delta = i32::from(sample) - self.predicted;
+        let magnitude = i32::min(7, delta.abs() * 4 / self.step);
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)] // 0 to 7
+        let mut nibble = magnitude as u8;
+
+        #[allow(clippy::cast_sign_loss)]
+        let difference = self.step * DIFF_TABLE[magnitude as usize] / 8;
+        if delta < 0 {
+            nibble |= 0b1000;
+            self.predicted -= difference;
+        } else {
+            self.predicted += difference;
+        }
+        self.predicted = self
+            .predicted
+            .clamp(i32::from(i16::MIN), i32::from(i16::MAX));
+        #[allow(clippy::cast_sign_loss)]
+        let scale = STEP_SCALE[magnitude as usize];
+        self.step = ((self.step * scale) >> 8).clamp(MIN_STEP, MAX_STEP);
+        nibble
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_silence() {
+        let mut encoder = AdpcmEncoder::new();
+
+        // The prediction oscillates around zero with the smallest step
+        assert_eq!(encoder.encode(&[0; 5]), [0x08, 0x08, 0x08]);
+    }
+
+    #[test]
+    fn test_follows_signal() {
+        let mut encoder = AdpcmEncoder::new();
+
+        let encoded = encoder.encode(&[i16::MAX; 64]);
+
+        // The steps grow until the prediction reaches the signal
+        assert_eq!(encoded[0], 0x77);
+        assert!(encoder.predicted > 30000);
+        let encoded = encoder.encode(&[i16::MIN; 2]);
+        assert_eq!(encoded[0] & 0x80, 0x80);
+    }
+}
@@ -0,0 +1,131 @@
+pub(crate) mod adpcm;
+pub(crate) mod stream;
+#[cfg(feature = "wav")]
+mod wav;
+
+use crate::output::OutputReport;
+use crate::prelude::*;
+use crate::simple_io;
+
+pub use adpcm::*;
+pub use stream::*;
+
+/// The sample format of the data sent to the speaker.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SpeakerFormat {
+    /// 4-bit Yamaha ADPCM, two samples per byte.
+    #[default]
+    Adpcm,
+    /// 8-bit signed PCM, one sample per byte.
+    Pcm8,
+}
+
+/// The configuration of the speaker written during initialization.
+///
+/// WiiBrew Documentation: <https://www.wiibrew.org/wiki/Wiimote#Speaker_Configuration>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeakerConfig {
+    pub format: SpeakerFormat,
+    /// The sample rate in Hz, at most 4000 Hz for ADPCM and 2000 Hz for 8-bit PCM
+    /// can be streamed reliably over Bluetooth.
+    pub sample_rate: u32,
+    /// The volume from 0 to 0x7F for ADPCM and 0 to 0xFF for 8-bit PCM.
+    pub volume: u8,
+}
+
+impl Default for SpeakerConfig {
+    fn default() -> Self {
+        Self {
+            format: SpeakerFormat::Adpcm,
+            sample_rate: 3000,
+            volume: 0x40,
+        }
+    }
+}
+
+impl SpeakerConfig {
+    /// Returns the 7 bytes written to register 0xA20001.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; 7] {
+        // https://www.wiibrew.org/wiki/Wiimote#Speaker_Configuration
+        let (format, clock) = match self.format {
+            SpeakerFormat::Adpcm => (0x00, 6_000_000),
+            SpeakerFormat::Pcm8 => (0x40, 12_000_000),
+        };
+        let rate = u16::try_from(clock / u32::max(self.sample_rate, 1)).unwrap_or(u16::MAX);
+        let [rate_low, rate_high] = rate.to_le_bytes();
+        [0x00, format, rate_low, rate_high, self.volume, 0x00, 0x00]
+    }
+}
+
+/// The built-in speaker of the Wii remote.
+#[derive(Debug, Clone, Copy)]
+pub struct Speaker {
+    config: SpeakerConfig,
+}
+
+impl Speaker {
+    /// Enables and configures the speaker, so it is ready to receive sound data.
+    /// The `WiimoteDevice` initializes the speaker again if a reconnect or a status report shows it was reset.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error or if a register write is not acknowledged.
+    pub fn initialize(wiimote: &WiimoteDevice, config: SpeakerConfig) -> WiimoteResult<Self> {
+        // https://www.wiibrew.org/wiki/Wiimote#Initialization_Sequence
+        wiimote.write(&OutputReport::SpeakerEnable(true))?;
+        wiimote.write(&OutputReport::SpeakerMute(true))?;
+        simple_io::write_register_checked(wiimote, 0xA2_0009, &[0x01])?;
+        simple_io::write_register_checked(wiimote, 0xA2_0001, &[0x08])?;
+        simple_io::write_register_checked(wiimote, 0xA2_0001, &config.to_bytes())?;
+        simple_io::write_register_checked(wiimote, 0xA2_0008, &[0x01])?;
+        wiimote.write(&OutputReport::SpeakerMute(false))?;
+        wiimote.set_speaker_config(Some(config));
+        Ok(Self { config })
+    }
+
+    /// Mutes and disables the speaker.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error on I/O error.
+    pub fn disable(wiimote: &WiimoteDevice) -> WiimoteResult<()> {
+        wiimote.set_speaker_config(None);
+        wiimote.write(&OutputReport::SpeakerMute(true))?;
+        wiimote.write(&OutputReport::SpeakerEnable(false))
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &SpeakerConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adpcm_config() {
+        let config = SpeakerConfig::default();
+
+        assert_eq!(
+            config.to_bytes(),
+            [0x00, 0x00, 0xD0, 0x07, 0x40, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_pcm_config() {
+        let config = SpeakerConfig {
+            format: SpeakerFormat::Pcm8,
+            sample_rate: 2000,
+            volume: 0xFF,
+        };
+
+        assert_eq!(
+            config.to_bytes(),
+            [0x00, 0x40, 0x70, 0x17, 0xFF, 0x00, 0x00]
+        );
+    }
+}
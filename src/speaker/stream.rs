@@ -0,0 +1,201 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::TryRecvError;
+
+use crate::output::OutputReport;
+use crate::prelude::*;
+
+use super::AdpcmEncoder;
+
+/// The maximum number of sound bytes in a single speaker data report.
+const REPORT_DATA_SIZE: usize = 20;
+/// Reports that are late by more than this are skipped instead of sent in a burst.
+const MAX_LATE_REPORTS: u32 = 4;
+
+/// A sound being streamed to the speaker on a background thread.
+#[derive(Debug)]
+pub struct SpeakerPlayback {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<WiimoteResult<()>>,
+}
+
+impl SpeakerPlayback {
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits until all samples were sent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote was disconnected during playback.
+    pub fn wait(self) -> WiimoteResult<()> {
+        self.handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+
+    /// Stops the playback and waits for the background thread to finish.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Wii remote was disconnected during playback.
+    pub fn stop(self) -> WiimoteResult<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait()
+    }
+}
+
+impl Speaker {
+    /// Streams 16-bit samples at the configured sample rate to the speaker.
+    ///
+    /// The samples are converted to the configured format and sent in speaker data reports paced
+    /// at the sample rate on a background thread. If the source cannot provide the samples of a
+    /// report in time, the report is skipped and sent once the samples are available.
+    ///
+    /// The device is locked for every report, so reads on other threads should use
+    /// [`WiimoteDevice::read_timeout`] with a short timeout to not delay the sound.
+    pub fn play<I>(&self, wiimote: Arc<Mutex<WiimoteDevice>>, source: I) -> SpeakerPlayback
+    where
+        I: IntoIterator<Item = i16>,
+        I::IntoIter: Send + 'static,
+    {
+        let config = *self.config();
+        let samples_per_report = match config.format {
+            SpeakerFormat::Adpcm => REPORT_DATA_SIZE * 2,
+            SpeakerFormat::Pcm8 => REPORT_DATA_SIZE,
+        };
+
+        // Buffer half a second of samples, the source runs on its own thread so a slow
+        // source causes an under-run instead of delaying the reports.
+        // A capacity of 0 would hand over each sample synchronously, so at least one is buffered.
+        let capacity = usize::max(config.sample_rate as usize / 2, 1);
+        let (sender, receiver) = crossbeam_channel::bounded(capacity);
+        let source = source.into_iter();
+        std::thread::spawn(move || {
+            for sample in source {
+                if sender.send(sample).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let handle = std::thread::spawn(move || -> WiimoteResult<()> {
+            #[allow(clippy::cast_precision_loss)]
+            let interval = Duration::from_secs_f64(
+                samples_per_report as f64 / f64::from(u32::max(config.sample_rate, 1)),
+            );
+            let mut encoder = AdpcmEncoder::new();
+            let mut samples = vec![0i16; samples_per_report];
+            let mut next_report = Instant::now();
+            // Samples received for the next report, kept across an under-run.
+            let mut count = 0;
+
+            while !thread_stop.load(Ordering::Relaxed) {
+                let mut finished = false;
+                while count < samples_per_report {
+                    match receiver.try_recv() {
+                        Ok(sample) => {
+                            samples[count] = sample;
+                            count += 1;
+                        }
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => {
+                            finished = true;
+                            break;
+                        }
+                    }
+                }
+                if finished && count == 0 {
+                    break;
+                }
+                if count < samples_per_report && !finished {
+                    // Under-run, the report is sent once the source provided the remaining samples.
+                    wait_for_next_report(&mut next_report, interval);
+                    continue;
+                }
+                // End of the source, the last report is filled with silence.
+                samples[count..].fill(0);
+                count = 0;
+
+                let mut data = [0u8; REPORT_DATA_SIZE];
+                match config.format {
+                    SpeakerFormat::Adpcm => data.copy_from_slice(&encoder.encode(&samples)),
+                    SpeakerFormat::Pcm8 => {
+                        for (byte, sample) in data.iter_mut().zip(&samples) {
+                            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                            let pcm = (sample >> 8) as i8 as u8;
+                            *byte = pcm;
+                        }
+                    }
+                }
+
+                {
+                    let wiimote = match wiimote.lock() {
+                        Ok(wiimote) => wiimote,
+                        Err(wiimote) => wiimote.into_inner(),
+                    };
+                    // The rumble bit is set unless disabled with `WiimoteDevice::set_speaker_rumble`.
+                    #[allow(clippy::cast_possible_truncation)]
+                    wiimote.write(&OutputReport::SpeakerData(REPORT_DATA_SIZE as u8, data))?;
+                }
+
+                wait_for_next_report(&mut next_report, interval);
+            }
+            Ok(())
+        });
+
+        SpeakerPlayback { stop, handle }
+    }
+}
+
+/// Sleeps until the next report is due, restarts the schedule if the reports are too late to catch up.
+fn wait_for_next_report(next_report: &mut Instant, interval: Duration) {
+    *next_report += interval;
+    let now = Instant::now();
+    if *next_report > now {
+        std::thread::sleep(*next_report - now);
+    } else if now - *next_report > interval * MAX_LATE_REPORTS {
+        *next_report = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::SimulatedWiimote;
+
+    #[test]
+    fn test_play_pads_only_the_last_report() {
+        let simulator = SimulatedWiimote::default();
+        let wiimote = WiimoteDevice::with_transport(simulator.clone()).unwrap();
+        let config = SpeakerConfig {
+            format: SpeakerFormat::Pcm8,
+            sample_rate: 2000,
+            volume: 0x40,
+        };
+        let speaker = Speaker::initialize(&wiimote, config).unwrap();
+
+        let wiimote = Arc::new(Mutex::new(wiimote));
+        speaker
+            .play(wiimote, vec![0x7F00; REPORT_DATA_SIZE + 5])
+            .wait()
+            .unwrap();
+
+        let speaker_reports = simulator
+            .written_reports()
+            .into_iter()
+            .filter(|report| report[0] == 0x18)
+            .collect::<Vec<_>>();
+        assert_eq!(speaker_reports.len(), 2);
+        assert!(speaker_reports[0][2..].iter().all(|&byte| byte == 0x7F));
+        assert!(speaker_reports[1][2..7].iter().all(|&byte| byte == 0x7F));
+        assert!(speaker_reports[1][7..].iter().all(|&byte| byte == 0));
+    }
+}
@@ -0,0 +1,121 @@
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+use hound::{SampleFormat, WavReader};
+
+use crate::prelude::*;
+
+impl Speaker {
+    /// Plays a WAV file on the speaker, see [`Self::play`].
+    ///
+    /// The samples are mixed down to mono and resampled to the configured sample rate.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the WAV data is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use wiimote_rs::prelude::*;
+    ///
+    /// # fn play(wiimote: Arc<Mutex<WiimoteDevice>>) -> WiimoteResult<()> {
+    /// let speaker = Speaker::initialize(&wiimote.lock().unwrap(), SpeakerConfig::default())?;
+    /// let file = std::fs::File::open("sound.wav").unwrap();
+    /// speaker.play_wav(wiimote, std::io::BufReader::new(file))?.wait()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn play_wav<R: Read>(
+        &self,
+        wiimote: Arc<Mutex<WiimoteDevice>>,
+        reader: R,
+    ) -> WiimoteResult<SpeakerPlayback> {
+        let mut reader = WavReader::new(reader)?;
+        let spec = reader.spec();
+
+        let samples = match spec.sample_format {
+            SampleFormat::Int => reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| Self::int_to_i16(sample, spec.bits_per_sample)))
+                .collect::<Result<Vec<_>, _>>()?,
+            #[allow(clippy::cast_possible_truncation)]
+            SampleFormat::Float => reader
+                .samples::<f32>()
+                .map(|sample| sample.map(|sample| (sample.clamp(-1.0, 1.0) * 32767.0) as i16))
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        let channels = usize::from(u16::max(spec.channels, 1));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let mono: Vec<i16> = samples
+            .chunks(channels)
+            .map(|frame| {
+                let sum: i32 = frame.iter().copied().map(i32::from).sum();
+                (sum / frame.len() as i32) as i16
+            })
+            .collect();
+
+        let resampled = Self::resample(&mono, spec.sample_rate, self.config().sample_rate);
+        Ok(self.play(wiimote, resampled))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    const fn int_to_i16(sample: i32, bits_per_sample: u16) -> i16 {
+        if bits_per_sample > 16 {
+            (sample >> (bits_per_sample - 16)) as i16
+        } else {
+            (sample << (16 - bits_per_sample)) as i16
+        }
+    }
+
+    /// Resamples with linear interpolation.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn resample(samples: &[i16], from_rate: u32, to_rate: u32) -> Vec<i16> {
+        if from_rate == to_rate || samples.is_empty() || from_rate == 0 || to_rate == 0 {
+            return samples.to_vec();
+        }
+
+        let step = f64::from(from_rate) / f64::from(to_rate);
+        let length = (samples.len() as f64 / step) as usize;
+        (0..length)
+            .map(|index| {
+                let position = index as f64 * step;
+                let first = position as usize;
+                let second = usize::min(first + 1, samples.len() - 1);
+                let fraction = position - first as f64;
+                let value = f64::from(samples[first]) * (1.0 - fraction)
+                    + f64::from(samples[second]) * fraction;
+                value.round() as i16
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        let samples = [0, 100, 200, 300, 400, 500];
+
+        assert_eq!(Speaker::resample(&samples, 2000, 1000), [0, 200, 400]);
+        assert_eq!(
+            Speaker::resample(&samples[..3], 1000, 2000),
+            [0, 50, 100, 150, 200, 200]
+        );
+    }
+
+    #[test]
+    fn test_int_to_i16() {
+        assert_eq!(Speaker::int_to_i16(-128, 8), i16::MIN);
+        assert_eq!(Speaker::int_to_i16(0x7F_FFFF, 24), i16::MAX);
+    }
+}
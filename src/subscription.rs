@@ -0,0 +1,174 @@
+//! Typed subscriptions to the input reports read from a Wii remote.
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvError, RecvTimeoutError, TryRecvError, TrySendError};
+
+use crate::input::{AcknowledgeData, InputReport, MemoryData, StatusData, WiimoteData};
+use crate::prelude::*;
+
+/// The maximum number of reports queued for a subscription, about 2.5 seconds of data reports.
+const SUBSCRIPTION_CAPACITY: usize = 256;
+
+/// A kind of input report that can be subscribed to with [`WiimoteDevice::subscribe`].
+pub trait ReportKind: Sized + Send + 'static {
+    /// Returns the data of the report if it is of this kind.
+    fn from_report(report: &InputReport) -> Option<Self>;
+}
+
+impl ReportKind for InputReport {
+    fn from_report(report: &InputReport) -> Option<Self> {
+        Some(*report)
+    }
+}
+
+impl ReportKind for StatusData {
+    fn from_report(report: &InputReport) -> Option<Self> {
+        match report {
+            InputReport::StatusInformation(status) => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl ReportKind for MemoryData {
+    fn from_report(report: &InputReport) -> Option<Self> {
+        match report {
+            InputReport::ReadMemory(memory) => Some(*memory),
+            _ => None,
+        }
+    }
+}
+
+impl ReportKind for AcknowledgeData {
+    fn from_report(report: &InputReport) -> Option<Self> {
+        match report {
+            InputReport::Acknowledge(acknowledge) => Some(*acknowledge),
+            _ => None,
+        }
+    }
+}
+
+impl ReportKind for WiimoteData {
+    fn from_report(report: &InputReport) -> Option<Self> {
+        match report {
+            InputReport::DataReport(_, data) => Some(*data),
+            _ => None,
+        }
+    }
+}
+
+/// The reports of kind `T` read from a Wii remote after subscribing, see [`WiimoteDevice::subscribe`].
+///
+/// Reports are only received while another thread reads from the Wii remote, e.g. a
+/// [`ReportWorker`](crate::prelude::ReportWorker) or [`WiimoteDevice::run_event_loop`].
+/// At most 256 reports are queued, newer reports are dropped until the subscription received some of them.
+/// Dropping the subscription unsubscribes.
+#[derive(Debug)]
+pub struct ReportSubscription<T> {
+    receiver: Receiver<T>,
+}
+
+impl<T> ReportSubscription<T> {
+    /// Waits for the next report.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `WiimoteDevice` was dropped.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Waits for the next report for a maximum of `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no report arrived within the timeout or the `WiimoteDevice` was dropped.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Returns the next report if one was received.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no report is queued or the `WiimoteDevice` was dropped.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+}
+
+/// Sends the report to a subscription if it is of its kind, returns false if it was dropped.
+type ReportSender = Box<dyn Fn(&InputReport) -> bool + Send + Sync>;
+
+/// The subscriptions of a `WiimoteDevice`.
+#[derive(Default)]
+pub(crate) struct ReportSubscribers {
+    senders: Mutex<Vec<ReportSender>>,
+}
+
+impl ReportSubscribers {
+    fn add<T: ReportKind>(&self) -> ReportSubscription<T> {
+        let (sender, receiver) = crossbeam_channel::bounded(SUBSCRIPTION_CAPACITY);
+        self.lock().push(Box::new(move |report| {
+            T::from_report(report).map_or(true, |data| {
+                !matches!(sender.try_send(data), Err(TrySendError::Disconnected(_)))
+            })
+        }));
+        ReportSubscription { receiver }
+    }
+
+    pub(crate) fn publish(&self, report: &InputReport) {
+        // Subscriptions that have been dropped are removed with their next report.
+        self.lock().retain(|send| send(report));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<ReportSender>> {
+        self.senders.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl WiimoteDevice {
+    /// Subscribes to the reports of kind `T` read from the Wii remote, e.g. `subscribe::<StatusData>()`
+    /// to wait for a status report while another thread is reading.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use wiimote_rs::input::StatusData;
+    /// use wiimote_rs::output::OutputReport;
+    /// use wiimote_rs::prelude::*;
+    ///
+    /// fn request_status(wiimote: &WiimoteDevice) -> Option<StatusData> {
+    ///     let status = wiimote.subscribe::<StatusData>();
+    ///     wiimote.write(&OutputReport::StatusRequest).ok()?;
+    ///     status.recv_timeout(Duration::from_secs(1)).ok()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn subscribe<T: ReportKind>(&self) -> ReportSubscription<T> {
+        self.report_subscribers.add()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_bounded() {
+        let subscribers = ReportSubscribers::default();
+        let subscription = subscribers.add::<WiimoteData>();
+        let report = InputReport::try_from([0x30, 0x00, 0x00].as_slice()).unwrap();
+        for _ in 0..=SUBSCRIPTION_CAPACITY {
+            subscribers.publish(&report);
+        }
+        assert_eq!(subscription.receiver.len(), SUBSCRIPTION_CAPACITY);
+        assert_eq!(subscribers.lock().len(), 1);
+
+        drop(subscription);
+        subscribers.publish(&report);
+        assert!(subscribers.lock().is_empty());
+    }
+}
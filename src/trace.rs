@@ -0,0 +1,15 @@
+//! Spans around the initialization and I/O of Wii remotes, recorded with the `tracing` feature.
+//!
+//! Log records of the crate are attached to the current span when they are forwarded
+//! to `tracing`, e.g. with `tracing-log`, so a captured trace shows which step failed.
+
+/// Enters a span with the level, name and fields until the end of the enclosing scope,
+/// expands to nothing without the `tracing` feature.
+macro_rules! enter_span {
+    ($level:ident, $name:literal $(, $($field:tt)+)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::span!(tracing::Level::$level, $name $(, $($field)+)?).entered();
+    };
+}
+
+pub(crate) use enter_span;
@@ -0,0 +1,368 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+
+use crate::input::InputReport;
+use crate::prelude::*;
+
+/// The read timeout of the worker, the lock of the `WiimoteDevice` is released at least this often.
+const WORKER_POLL_MILLIS: usize = 50;
+
+/// What the [`ReportWorker`] does when its queue is full because the consumer is slow.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Discard the oldest queued event to make room for the new one.
+    #[default]
+    DropOldest,
+    /// Discard the oldest queued data report, so the consumer catches up with the latest motion.
+    /// Other reports and events are only discarded if the queue contains no data report.
+    CoalesceMotion,
+    /// Stop reading until the consumer made room. Reports arriving in the meantime are queued by the
+    /// backend, which drops the oldest ones once its queue is full.
+    Block,
+}
+
+/// The configuration of the queue of a [`ReportWorker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryConfig {
+    /// The maximum number of queued events, at least 1.
+    pub capacity: usize,
+    pub policy: BackpressurePolicy,
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            policy: BackpressurePolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    events: VecDeque<WiimoteEvent>,
+    /// The worker ended, no further events are queued.
+    closed: bool,
+    /// The `ReportWorker` was dropped, the worker ends.
+    abandoned: bool,
+    dropped: u64,
+}
+
+/// The bounded queue between the worker thread and the consumer.
+#[derive(Debug)]
+struct DeliveryQueue {
+    config: DeliveryConfig,
+    state: Mutex<QueueState>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl DeliveryQueue {
+    fn new(config: DeliveryConfig) -> Self {
+        Self {
+            config: DeliveryConfig {
+                capacity: config.capacity.max(1),
+                ..config
+            },
+            state: Mutex::default(),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Queues the event according to the backpressure policy, returns false if the consumer is gone.
+    fn push(&self, event: WiimoteEvent) -> bool {
+        let mut state = self.lock();
+        while !state.abandoned && state.events.len() >= self.config.capacity {
+            match self.config.policy {
+                BackpressurePolicy::DropOldest => {
+                    state.events.pop_front();
+                    state.dropped += 1;
+                }
+                BackpressurePolicy::CoalesceMotion => {
+                    let oldest_data_report = state.events.iter().position(|event| {
+                        matches!(event, WiimoteEvent::Report(InputReport::DataReport(..)))
+                    });
+                    state.events.remove(oldest_data_report.unwrap_or(0));
+                    state.dropped += 1;
+                }
+                BackpressurePolicy::Block => {
+                    state = match self.not_full.wait(state) {
+                        Ok(state) => state,
+                        Err(err) => err.into_inner(),
+                    };
+                }
+            }
+        }
+        if state.abandoned {
+            return false;
+        }
+        state.events.push_back(event);
+        drop(state);
+        self.not_empty.notify_one();
+        true
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+    }
+
+    fn abandon(&self) {
+        self.lock().abandoned = true;
+        self.not_full.notify_all();
+    }
+
+    fn pop(&self, deadline: Option<Instant>) -> Result<WiimoteEvent, RecvTimeoutError> {
+        let mut state = self.lock();
+        loop {
+            if let Some(event) = state.events.pop_front() {
+                drop(state);
+                self.not_full.notify_one();
+                return Ok(event);
+            }
+            if state.closed {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    if timeout.is_zero() {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                    match self.not_empty.wait_timeout(state, timeout) {
+                        Ok((state, _)) => state,
+                        Err(err) => err.into_inner().0,
+                    }
+                }
+                None => match self.not_empty.wait(state) {
+                    Ok(state) => state,
+                    Err(err) => err.into_inner(),
+                },
+            };
+        }
+    }
+}
+
+/// Reads the input reports of a Wii remote on a background thread and delivers them
+/// together with the published events through a bounded queue.
+///
+/// Each input report is delivered as [`WiimoteEvent::Report`], followed by the events published
+/// while processing it. The data reporting mode is restored after each status report like in
+/// [`WiimoteDevice::run_event_loop`]. The worker ends after delivering [`WiimoteEvent::Disconnected`]
+/// or when it is dropped.
+pub struct ReportWorker {
+    queue: Arc<DeliveryQueue>,
+}
+
+impl ReportWorker {
+    /// Starts reading from the Wii remote, the `WiimoteDevice` is locked for each read.
+    #[must_use]
+    pub fn spawn(wiimote: Arc<Mutex<WiimoteDevice>>, config: DeliveryConfig) -> Self {
+        let queue = Arc::new(DeliveryQueue::new(config));
+        let thread_queue = Arc::clone(&queue);
+        std::thread::spawn(move || {
+            Self::run(&wiimote, &thread_queue);
+            thread_queue.close();
+        });
+        Self { queue }
+    }
+
+    fn run(wiimote: &Mutex<WiimoteDevice>, queue: &DeliveryQueue) {
+        let events = lock_device(wiimote).events_receiver_with_reports();
+        loop {
+            let error = {
+                let wiimote = lock_device(wiimote);
+                match wiimote.read_timeout(WORKER_POLL_MILLIS) {
+                    Ok(input_report) => wiimote.process_report_changes(&input_report).err(),
+                    Err(WiimoteError::WiimoteDeviceError(WiimoteDeviceError::MissingData)) => None,
+                    // Skip reports with an unknown report id
+                    Err(WiimoteError::WiimoteDeviceError(WiimoteDeviceError::InvalidData)) => None,
+                    Err(error) => Some(error),
+                }
+            };
+
+            let mut disconnect_delivered = false;
+            for event in events.try_iter() {
+                disconnect_delivered |= matches!(event, WiimoteEvent::Disconnected);
+                if !queue.push(event) {
+                    return;
+                }
+            }
+            if let Some(error) = error {
+                log::debug!("Stopping the report worker: {error:?}");
+                if !disconnect_delivered {
+                    queue.push(WiimoteEvent::Disconnected);
+                }
+                return;
+            }
+            if queue.lock().abandoned {
+                return;
+            }
+        }
+    }
+
+    /// Waits for the next event.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error once the worker ended and all events were received.
+    pub fn recv(&self) -> Result<WiimoteEvent, RecvError> {
+        self.queue.pop(None).map_err(|_| RecvError)
+    }
+
+    /// Waits for the next event for a maximum of `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no event arrived within the timeout
+    /// or the worker ended and all events were received.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<WiimoteEvent, RecvTimeoutError> {
+        self.queue.pop(Some(Instant::now() + timeout))
+    }
+
+    /// Returns the next event if one is queued.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no event is queued or the worker ended and all events were received.
+    pub fn try_recv(&self) -> Result<WiimoteEvent, TryRecvError> {
+        self.queue
+            .pop(Some(Instant::now()))
+            .map_err(|err| match err {
+                RecvTimeoutError::Timeout => TryRecvError::Empty,
+                RecvTimeoutError::Disconnected => TryRecvError::Disconnected,
+            })
+    }
+
+    /// Returns the number of events discarded because the queue was full.
+    #[must_use]
+    pub fn dropped_count(&self) -> u64 {
+        self.queue.lock().dropped
+    }
+}
+
+impl Drop for ReportWorker {
+    fn drop(&mut self) {
+        self.queue.abandon();
+    }
+}
+
+fn lock_device(wiimote: &Mutex<WiimoteDevice>) -> MutexGuard<'_, WiimoteDevice> {
+    match wiimote.lock() {
+        Ok(wiimote) => wiimote,
+        Err(err) => err.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{DataReporingMode, OutputReport};
+
+    fn data_report(report_id: u8) -> WiimoteEvent {
+        let report = InputReport::try_from(&[report_id, 0, 0][..]).unwrap();
+        WiimoteEvent::Report(report)
+    }
+
+    fn report_ids(queue: &DeliveryQueue) -> Vec<Option<u8>> {
+        queue
+            .lock()
+            .events
+            .iter()
+            .map(|event| match event {
+                WiimoteEvent::Report(InputReport::DataReport(report_id, _)) => Some(*report_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let queue = DeliveryQueue::new(DeliveryConfig {
+            capacity: 2,
+            policy: BackpressurePolicy::DropOldest,
+        });
+        for report_id in 0x30..0x33 {
+            assert!(queue.push(data_report(report_id)));
+        }
+
+        assert_eq!(report_ids(&queue), [Some(0x31), Some(0x32)]);
+        assert_eq!(queue.lock().dropped, 1);
+    }
+
+    #[test]
+    fn test_coalesce_motion() {
+        let queue = DeliveryQueue::new(DeliveryConfig {
+            capacity: 3,
+            policy: BackpressurePolicy::CoalesceMotion,
+        });
+        queue.push(WiimoteEvent::Idle);
+        queue.push(data_report(0x31));
+        queue.push(WiimoteEvent::Active);
+        queue.push(data_report(0x32));
+
+        assert_eq!(report_ids(&queue), [None, None, Some(0x32)]);
+        assert!(matches!(queue.pop(None), Ok(WiimoteEvent::Idle)));
+    }
+
+    #[test]
+    fn test_block_until_received() {
+        let queue = Arc::new(DeliveryQueue::new(DeliveryConfig {
+            capacity: 1,
+            policy: BackpressurePolicy::Block,
+        }));
+        queue.push(data_report(0x30));
+
+        let sender = Arc::clone(&queue);
+        let handle = std::thread::spawn(move || sender.push(data_report(0x31)));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(report_ids(&queue), [Some(0x30)]);
+
+        assert!(queue.pop(None).is_ok());
+        assert!(handle.join().unwrap());
+        assert_eq!(report_ids(&queue), [Some(0x31)]);
+
+        queue.abandon();
+        assert!(!queue.push(data_report(0x32)));
+    }
+
+    #[test]
+    fn test_worker_delivers_reports() {
+        let simulator = crate::simulator::SimulatedWiimote::default();
+        let wiimote = WiimoteDevice::with_transport(simulator.clone()).unwrap();
+        let wiimote = Arc::new(Mutex::new(wiimote));
+        let worker = ReportWorker::spawn(Arc::clone(&wiimote), DeliveryConfig::default());
+
+        lock_device(&wiimote)
+            .write(&OutputReport::DataReportingMode(DataReporingMode {
+                continuous: true,
+                mode: 0x31,
+            }))
+            .unwrap();
+        let report = loop {
+            if let WiimoteEvent::Report(report) =
+                worker.recv_timeout(Duration::from_secs(1)).unwrap()
+            {
+                break report;
+            }
+        };
+        assert!(matches!(report, InputReport::DataReport(0x31, _)));
+
+        simulator.disconnect();
+        let disconnected = std::iter::from_fn(|| worker.recv_timeout(Duration::from_secs(1)).ok())
+            .any(|event| matches!(event, WiimoteEvent::Disconnected));
+        assert!(disconnected);
+        assert!(worker.recv().is_err());
+    }
+}
@@ -0,0 +1,599 @@
+//! Regression tests of the initialization and reporting pipeline against the simulated Wii remote.
+
+use std::sync::{Arc, Mutex};
+
+use wiimote_rs::extensions::ExtensionCalibration;
+use wiimote_rs::input::{ButtonData, InputReport, StatusData, WiimoteData};
+use wiimote_rs::output::{Addressing, DataReporingMode, OutputReport};
+use wiimote_rs::prelude::*;
+use wiimote_rs::simulator::SimulatedWiimote;
+
+fn connect(simulator: &SimulatedWiimote) -> WiimoteDevice {
+    WiimoteDevice::with_transport(simulator.clone()).unwrap()
+}
+
+fn set_reporting_mode(wiimote: &WiimoteDevice, mode: u8, continuous: bool) {
+    let mode = DataReporingMode { continuous, mode };
+    wiimote
+        .write(&OutputReport::DataReportingMode(mode))
+        .unwrap();
+}
+
+fn read_data_report(wiimote: &WiimoteDevice) -> WiimoteData {
+    match wiimote.read_timeout(0).unwrap() {
+        InputReport::DataReport(_, data) => data,
+        report => panic!("Expected a data report, received {report:?}"),
+    }
+}
+
+fn extension_type(wiimote: &WiimoteDevice) -> Option<WiimoteExtension> {
+    wiimote.extension().map(|extension| extension.extension())
+}
+
+#[test]
+fn test_initialize_original() {
+    let simulator = SimulatedWiimote::new(WiimoteModel::Original);
+    let wiimote = connect(&simulator);
+
+    assert_eq!(wiimote.model(), WiimoteModel::Original);
+    assert_eq!(
+        wiimote.accelerometer_calibration().block(),
+        CalibrationBlock::Primary
+    );
+    assert!(wiimote.motion_plus().is_none());
+    assert!(wiimote.extension().is_none());
+}
+
+#[test]
+fn test_initialize_pipelines_extension_initialization() {
+    let simulator = SimulatedWiimote::default();
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let wiimote = connect(&simulator);
+
+    // The calibration read and both extension initialization writes are sent before any response is awaited.
+    let report_ids: Vec<u8> = simulator
+        .written_reports()
+        .iter()
+        .take(3)
+        .map(|report| report[0])
+        .collect();
+    assert_eq!(report_ids, [0x17, 0x16, 0x16]);
+    assert!(matches!(
+        extension_type(&wiimote),
+        Some(WiimoteExtension::Nunchuck)
+    ));
+}
+
+#[test]
+fn test_initialize_plus() {
+    let simulator = SimulatedWiimote::new(WiimoteModel::Plus);
+    let wiimote = connect(&simulator);
+
+    let motion_plus = wiimote.motion_plus().unwrap();
+    assert!(matches!(
+        motion_plus.motion_plus_type(),
+        MotionPlusType::Builtin
+    ));
+    assert!(matches!(motion_plus.mode(), MotionPlusMode::Inactive));
+    // The built-in Motion Plus is known from the model without reading its identifier.
+    assert!(!simulator
+        .written_reports()
+        .iter()
+        .any(|report| report[0] == 0x17 && report[2..5] == [0xA6, 0x00, 0xFA]));
+}
+
+#[test]
+fn test_initialize_balance_board() {
+    let simulator = SimulatedWiimote::new(WiimoteModel::BalanceBoard);
+    let wiimote = connect(&simulator);
+
+    assert_eq!(wiimote.model(), WiimoteModel::BalanceBoard);
+    assert_eq!(
+        extension_type(&wiimote),
+        Some(WiimoteExtension::BalanceBoard)
+    );
+    let capabilities = wiimote.capabilities();
+    assert!(!capabilities.speaker && !capabilities.ir_camera);
+    assert!(WiimoteModel::Plus.capabilities().speaker);
+}
+
+#[test]
+fn test_balance_board_session() {
+    let simulator = SimulatedWiimote::new(WiimoteModel::BalanceBoard);
+    let wiimote = connect(&simulator);
+    let mut sensors = [0u8; 11];
+    sensors[..2].copy_from_slice(&1234u16.to_be_bytes());
+    sensors[8] = 25; // Temperature
+    sensors[10] = 0x83; // Battery
+    simulator.set_extension_data(&sensors);
+
+    let session = BalanceBoardSession::start(&wiimote, false).unwrap();
+    assert_eq!(session.reporting_mode(), 0x32);
+    assert!(simulator
+        .reporting_mode()
+        .is_some_and(|mode| mode.mode == 0x32));
+    let frame = session.read_frame().unwrap();
+    assert_eq!(frame.data.sensors.top_right, 1234);
+    assert_eq!(frame.data.status, None);
+
+    let session = BalanceBoardSession::start(&wiimote, true).unwrap();
+    assert_eq!(session.reporting_mode(), 0x34);
+    let frame = session.read_frame().unwrap();
+    assert_eq!(frame.data.sensors.top_right, 1234);
+    assert_eq!(
+        frame.data.status,
+        Some(BalanceBoardStatus {
+            temperature: 25,
+            battery: 0x83
+        })
+    );
+
+    let wiimote = connect(&SimulatedWiimote::default());
+    assert!(BalanceBoardSession::start(&wiimote, true).is_err());
+}
+
+#[test]
+fn test_initialize_with_extension() {
+    let simulator = SimulatedWiimote::default();
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let wiimote = connect(&simulator);
+
+    assert!(matches!(
+        extension_type(&wiimote),
+        Some(WiimoteExtension::Nunchuck)
+    ));
+}
+
+#[test]
+fn test_buttons_and_acceleration() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    set_reporting_mode(&wiimote, 0x31, true);
+
+    simulator.set_buttons(ButtonData::A | ButtonData::LEFT);
+    let data = read_data_report(&wiimote);
+
+    assert_eq!(data.report_id(), 0x31);
+    assert!(data.buttons().contains(ButtonData::A | ButtonData::LEFT));
+    let acceleration = AccelerometerData::from_normal_reporting(&data.data);
+    let (x, y, z) = wiimote
+        .accelerometer_calibration()
+        .get_acceleration(&acceleration);
+    assert!(x.abs() < 0.01 && y.abs() < 0.01);
+    assert!((z - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn test_non_continuous_reporting() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    set_reporting_mode(&wiimote, 0x30, false);
+
+    read_data_report(&wiimote);
+    assert!(wiimote.read_timeout(0).is_err());
+
+    simulator.set_buttons(ButtonData::B);
+    assert!(read_data_report(&wiimote).buttons().contains(ButtonData::B));
+}
+
+#[test]
+fn test_motion_plus_hotplug() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    let events = wiimote.events_receiver();
+    wiimote
+        .enable_motion_plus(MotionPlusPolicy::Active)
+        .unwrap();
+    assert!(wiimote.motion_plus().is_none());
+
+    simulator.attach_motion_plus();
+    let report = wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert!(matches!(report, InputReport::StatusInformation(_)));
+    assert!(events.try_iter().any(|event| matches!(
+        event,
+        WiimoteEvent::MotionPlusAttached(MotionPlusType::External)
+    )));
+    let motion_plus = wiimote.motion_plus().unwrap();
+    assert_eq!(motion_plus.motion_plus_type(), MotionPlusType::External);
+    // The policy activates the Motion Plus once it was detected.
+    assert!(matches!(
+        simulator.motion_plus_mode(),
+        MotionPlusMode::Active
+    ));
+
+    simulator.detach_motion_plus();
+    wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert!(events
+        .try_iter()
+        .any(|event| matches!(event, WiimoteEvent::MotionPlusDetached)));
+    assert!(wiimote.motion_plus().is_none());
+}
+
+#[test]
+fn test_extension_hotplug() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    let events = wiimote.events_receiver();
+    set_reporting_mode(&wiimote, 0x35, true);
+    read_data_report(&wiimote);
+
+    simulator.attach_extension(WiimoteExtension::ClassicController);
+    let report = wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert!(matches!(report, InputReport::StatusInformation(_)));
+    assert!(matches!(
+        events.try_recv(),
+        Ok(WiimoteEvent::ExtensionChanged(Some(_)))
+    ));
+    assert!(matches!(
+        extension_type(&wiimote),
+        Some(WiimoteExtension::ClassicController)
+    ));
+
+    // Data reports are suspended until the reporting mode is set again.
+    assert!(wiimote.read_timeout(0).is_err());
+    set_reporting_mode(&wiimote, 0x35, true);
+    let data = read_data_report(&wiimote);
+    let input = wiimote.extension().unwrap().decode(&data);
+    assert!(matches!(input, Some(ExtensionInput::ClassicController(_))));
+
+    simulator.detach_extension();
+    wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert!(matches!(
+        events.try_recv(),
+        Ok(WiimoteEvent::ExtensionChanged(None))
+    ));
+    assert!(wiimote.extension().is_none());
+}
+
+#[test]
+fn test_extension_hotplug_during_memory_read() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+
+    // The status report signaling the extension is read while waiting for the memory data.
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let stream = wiimote
+        .read_memory_stream(Addressing::eeprom(0x0016, 16))
+        .unwrap();
+    assert_eq!(stream.count(), 1);
+    assert!(wiimote.extension().is_none());
+
+    wiimote.process_pending_changes().unwrap();
+    assert!(matches!(
+        extension_type(&wiimote),
+        Some(WiimoteExtension::Nunchuck)
+    ));
+}
+
+#[test]
+fn test_extension_calibration_refreshed_on_swap() {
+    let simulator = SimulatedWiimote::default();
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let wiimote = connect(&simulator);
+    assert!(matches!(
+        wiimote.extension_calibration(),
+        Some(ExtensionCalibration::Nunchuck(_))
+    ));
+
+    simulator.detach_extension();
+    wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert!(wiimote.extension_calibration().is_none());
+
+    simulator.attach_extension(WiimoteExtension::ClassicControllerPro);
+    wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert_eq!(
+        wiimote
+            .extension_calibration()
+            .map(|calibration| match calibration {
+                ExtensionCalibration::ClassicController(calibration) => calibration,
+                _ => panic!("Unexpected calibration"),
+            }),
+        Some(ClassicControllerCalibration::default())
+    );
+}
+
+#[test]
+fn test_nunchuck_data() {
+    let simulator = SimulatedWiimote::default();
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let wiimote = connect(&simulator);
+    set_reporting_mode(&wiimote, 0x32, true);
+
+    // Stick fully right, C pressed
+    simulator.set_extension_data(&[0xE0, 0x80, 0x80, 0x80, 0xB3, 0b1111_1101]);
+    let data = read_data_report(&wiimote);
+
+    let extension = wiimote.extension().unwrap();
+    let Some(ExtensionInput::Nunchuck(nunchuck)) = extension.decode(&data) else {
+        panic!("Expected Nunchuck data");
+    };
+    assert!(nunchuck.buttons.contains(NunchuckButtons::C));
+    assert!(!nunchuck.buttons.contains(NunchuckButtons::Z));
+    let ExtensionCalibration::Nunchuck(calibration) = extension.calibration() else {
+        panic!("Expected Nunchuck calibration");
+    };
+    let (x, y) = calibration.get_stick(&nunchuck);
+    assert!((x - 1.0).abs() < 0.01 && y.abs() < 0.01);
+}
+
+#[test]
+fn test_motion_plus_activation() {
+    let simulator = SimulatedWiimote::default();
+    simulator.attach_motion_plus();
+    let wiimote = connect(&simulator);
+    assert!(wiimote.motion_plus().is_some());
+
+    wiimote
+        .enable_motion_plus(MotionPlusPolicy::Active)
+        .unwrap();
+    assert!(matches!(
+        simulator.motion_plus_mode(),
+        MotionPlusMode::Active
+    ));
+
+    set_reporting_mode(&wiimote, 0x35, true);
+    let data = loop {
+        match wiimote.read_timeout(0).unwrap() {
+            InputReport::DataReport(_, data) => break data,
+            // The status report of the activation suspends data reports.
+            InputReport::StatusInformation(_) => set_reporting_mode(&wiimote, 0x35, true),
+            _ => {}
+        }
+    };
+    assert!(matches!(
+        simulator.motion_plus_mode(),
+        MotionPlusMode::Active
+    ));
+
+    let bytes: [u8; 6] = data.extension_data().unwrap()[..6].try_into().unwrap();
+    let motion_plus_data = MotionPlusData::try_from(bytes).unwrap();
+    let (yaw, roll, pitch) = wiimote
+        .motion_plus()
+        .unwrap()
+        .calibration()
+        .get_angular_velocity(&motion_plus_data);
+    assert!(yaw.abs() < 0.01 && roll.abs() < 0.01 && pitch.abs() < 0.01);
+}
+
+#[test]
+fn test_motion_plus_deactivation() {
+    let simulator = SimulatedWiimote::new(WiimoteModel::Plus);
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let wiimote = connect(&simulator);
+    let motion_plus = wiimote.motion_plus().unwrap();
+
+    motion_plus.initialize(&wiimote).unwrap();
+    motion_plus
+        .change_mode(&wiimote, MotionPlusMode::Active)
+        .unwrap();
+    assert!(matches!(
+        simulator.motion_plus_mode(),
+        MotionPlusMode::Active
+    ));
+
+    motion_plus.deactivate(&wiimote).unwrap();
+    assert!(matches!(
+        simulator.motion_plus_mode(),
+        MotionPlusMode::Inactive
+    ));
+    assert!(matches!(
+        extension_type(&wiimote),
+        Some(WiimoteExtension::Nunchuck)
+    ));
+}
+
+#[test]
+fn test_refresh_extension() {
+    let simulator = SimulatedWiimote::new(WiimoteModel::Plus);
+    let mut wiimote = connect(&simulator);
+    wiimote
+        .enable_motion_plus(MotionPlusPolicy::Active)
+        .unwrap();
+
+    // The extension is plugged in without reading the status report signaling it.
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let state = wiimote.refresh_extension().unwrap();
+    assert!(matches!(state.motion_plus, Some(MotionPlusType::Builtin)));
+    assert!(matches!(
+        state.extension.map(|extension| extension.extension()),
+        Some(WiimoteExtension::Nunchuck)
+    ));
+    assert!(matches!(
+        extension_type(&wiimote),
+        Some(WiimoteExtension::Nunchuck)
+    ));
+    assert!(matches!(
+        simulator.motion_plus_mode(),
+        MotionPlusMode::Active
+    ));
+}
+
+#[test]
+fn test_refresh_extension_updates_model() {
+    // A balance board whose transport reports the model of the original Wii remote.
+    let simulator = SimulatedWiimote::new(WiimoteModel::Original);
+    let mut wiimote = connect(&simulator);
+    assert_eq!(wiimote.model(), WiimoteModel::Original);
+
+    simulator.attach_extension(WiimoteExtension::BalanceBoard);
+    wiimote.refresh_extension().unwrap();
+    assert_eq!(wiimote.model(), WiimoteModel::BalanceBoard);
+}
+
+#[test]
+fn test_status_and_battery() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    simulator.set_battery_level(0x64);
+
+    wiimote.write(&OutputReport::StatusRequest).unwrap();
+    let report = wiimote.read_timeout(0).unwrap();
+
+    assert!(matches!(report, InputReport::StatusInformation(_)));
+    assert_eq!(wiimote.battery().unwrap().percentage(), 50);
+}
+
+#[test]
+fn test_subscribe() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    set_reporting_mode(&wiimote, 0x30, false);
+    read_data_report(&wiimote);
+    let status = wiimote.subscribe::<StatusData>();
+    let data = wiimote.subscribe::<WiimoteData>();
+    let dropped = wiimote.subscribe::<InputReport>();
+    drop(dropped);
+
+    wiimote.write(&OutputReport::StatusRequest).unwrap();
+    wiimote.read_timeout(0).unwrap();
+    assert!(status.try_recv().is_ok());
+    assert!(data.try_recv().is_err());
+
+    simulator.set_buttons(ButtonData::A);
+    read_data_report(&wiimote);
+    assert!(data.try_recv().unwrap().buttons().contains(ButtonData::A));
+    assert!(status.try_recv().is_err());
+
+    drop(wiimote);
+    assert!(status.recv().is_err());
+}
+
+#[test]
+fn test_events_with_reports() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    let events = wiimote.events_receiver();
+    let events_with_reports = wiimote.events_receiver_with_reports();
+
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert!(matches!(
+        events_with_reports.try_recv(),
+        Ok(WiimoteEvent::Report(InputReport::StatusInformation(_)))
+    ));
+    assert!(matches!(
+        events_with_reports.try_recv(),
+        Ok(WiimoteEvent::ExtensionChanged(Some(_)))
+    ));
+    assert!(matches!(
+        events.try_recv(),
+        Ok(WiimoteEvent::ExtensionChanged(Some(_)))
+    ));
+    assert!(events.try_recv().is_err());
+}
+
+#[test]
+fn test_disconnect() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+    let events = wiimote.events_receiver();
+
+    simulator.disconnect();
+
+    assert!(matches!(wiimote.read(), Err(WiimoteError::Disconnected)));
+    assert!(matches!(events.try_recv(), Ok(WiimoteEvent::Disconnected)));
+    assert!(!wiimote.is_connected());
+}
+
+#[test]
+fn test_ir_camera_reenabled_after_reset() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = connect(&simulator);
+
+    IrCamera::enable(&wiimote, IrMode::Basic, IrSensitivity::Level3).unwrap();
+    assert!(wiimote.is_ir_camera_enabled());
+    assert!(!wiimote.is_speaker_enabled());
+
+    // The camera is turned off without disabling it through `IrCamera`, e.g. by the Wii remote itself.
+    wiimote.write(&OutputReport::IrCameraEnable(false)).unwrap();
+    wiimote.write(&OutputReport::StatusRequest).unwrap();
+    let report = wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+
+    assert!(matches!(report, InputReport::StatusInformation(_)));
+    assert!(wiimote.is_ir_camera_enabled());
+
+    IrCamera::disable(&wiimote).unwrap();
+    wiimote.write(&OutputReport::StatusRequest).unwrap();
+    wiimote.read_timeout(0).unwrap();
+    wiimote.process_pending_changes().unwrap();
+    assert!(!wiimote.is_ir_camera_enabled());
+}
+
+#[test]
+fn test_session() {
+    let simulator = SimulatedWiimote::default();
+    simulator.attach_motion_plus();
+    simulator.attach_extension(WiimoteExtension::Nunchuck);
+    let wiimote = connect(&simulator);
+
+    let config = SessionConfig::default().motion_plus(true);
+    let session = WiimoteSession::start(&wiimote, config).unwrap();
+    assert!(simulator
+        .reporting_mode()
+        .is_some_and(|mode| mode.mode == 0x35 && mode.continuous));
+    assert!(matches!(
+        simulator.motion_plus_mode(),
+        MotionPlusMode::NunchuckPassthrough
+    ));
+
+    // The status report of the activation is handled by the session.
+    simulator.set_buttons(ButtonData::A);
+    let frame = session.read_frame().unwrap();
+    assert!(frame.buttons.contains(ButtonData::A));
+    let (x, y, z) = frame.acceleration.unwrap();
+    assert!(x.abs() < 0.01 && y.abs() < 0.01 && (z - 1.0).abs() < 0.01);
+    let AngularVelocity {
+        yaw, roll, pitch, ..
+    } = frame.angular_velocity.unwrap();
+    assert!(yaw.abs() < 0.01 && roll.abs() < 0.01 && pitch.abs() < 0.01);
+    assert!(frame.ir.is_none());
+}
+
+#[test]
+fn test_shared_session() {
+    let simulator = SimulatedWiimote::default();
+    let wiimote = Arc::new(Mutex::new(connect(&simulator)));
+    let config = SessionConfig::default().accelerometer(false);
+    let session = WiimoteSession::start_shared(Arc::clone(&wiimote), config).unwrap();
+    assert!(simulator
+        .reporting_mode()
+        .is_some_and(|mode| mode.mode == 0x34));
+
+    // The device is not locked between reads.
+    simulator.set_buttons(ButtonData::B);
+    let frame = session.read_frame().unwrap();
+    assert!(frame.buttons.contains(ButtonData::B));
+    assert!(frame.acceleration.is_none());
+    assert!(wiimote.try_lock().is_ok());
+}
+
+#[test]
+fn test_poll_all() {
+    let simulators = [SimulatedWiimote::default(), SimulatedWiimote::default()];
+    let wiimotes: Vec<_> = simulators
+        .iter()
+        .map(|simulator| {
+            let wiimote = connect(simulator);
+            set_reporting_mode(&wiimote, 0x30, false);
+            while wiimote.read_timeout(0).is_ok() {}
+            Arc::new(Mutex::new(wiimote))
+        })
+        .collect();
+    assert!(wiimote_rs::poll_all(&wiimotes, 0).is_empty());
+
+    simulators[1].set_buttons(ButtonData::A);
+    assert_eq!(wiimote_rs::poll_all(&wiimotes, 10), vec![1]);
+    // The report stays queued until the device is read.
+    assert_eq!(wiimote_rs::poll_all(&wiimotes, 0), vec![1]);
+    let data = read_data_report(&wiimotes[1].lock().unwrap());
+    assert!(data.buttons().contains(ButtonData::A));
+    assert!(wiimote_rs::poll_all(&wiimotes, 0).is_empty());
+}
@@ -0,0 +1,54 @@
+/* C interface of wiimote-rs, build the library with `cargo build -p wiimote-rs-ffi`. */
+#ifndef WIIMOTE_RS_H
+#define WIIMOTE_RS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define WIIMOTE_OK 0
+/* The Wii remote is disconnected. */
+#define WIIMOTE_ERROR_DISCONNECTED (-1)
+/* A pointer is null or a size is invalid. */
+#define WIIMOTE_ERROR_INVALID_ARGUMENT (-2)
+/* Any other error, such as an error reported by the operating system. */
+#define WIIMOTE_ERROR_OTHER (-3)
+
+/* An opaque handle to a connected Wii remote. */
+typedef struct WiimoteHandle WiimoteHandle;
+
+/* Waits up to timeout_millis for newly connected Wii remotes and stores up to capacity handles.
+ * Returns the number of stored handles, each must be released with wiimote_free. */
+size_t wiimotes_scan(WiimoteHandle **handles, size_t capacity, uint32_t timeout_millis);
+
+/* Stops scanning and disconnects all Wii remotes. */
+void wiimotes_scan_cleanup(void);
+
+/* Releases a handle returned by wiimotes_scan. */
+void wiimote_free(WiimoteHandle *handle);
+
+/* Reads an input report starting with the report ID, waits forever if timeout_millis is negative.
+ * Returns the number of bytes read, 0 on timeout, or a negative error code. */
+int32_t wiimote_read(const WiimoteHandle *handle, uint8_t *buffer, size_t buffer_size, int32_t timeout_millis);
+
+/* Writes a raw output report starting with the report ID, the rumble bit is applied automatically. */
+int32_t wiimote_write(const WiimoteHandle *handle, const uint8_t *data, size_t size);
+
+/* Sets the player LEDs, bit 0 to 3 turn on LED 1 to 4. */
+int32_t wiimote_set_leds(const WiimoteHandle *handle, uint8_t leds);
+
+/* Turns the rumble on or off. */
+int32_t wiimote_set_rumble(const WiimoteHandle *handle, bool enabled);
+
+/* Copies the NUL-terminated identifier into buffer, returns the length of the full identifier. */
+int32_t wiimote_identifier(const WiimoteHandle *handle, char *buffer, size_t buffer_size);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,212 @@
+//! C ABI of wiimote-rs to use the library from other languages.
+//!
+//! Wii remotes are passed as opaque `WiimoteHandle` pointers returned by [`wiimotes_scan`],
+//! each handle must be released with [`wiimote_free`]. Functions returning `i32` return
+//! [`WIIMOTE_OK`] or a negative error code. The declarations are in `include/wiimote_rs.h`.
+
+use std::ffi::c_char;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use wiimote_rs::output::{OutputReport, PlayerLedFlags};
+use wiimote_rs::prelude::*;
+
+pub const WIIMOTE_OK: i32 = 0;
+/// The Wii remote is disconnected.
+pub const WIIMOTE_ERROR_DISCONNECTED: i32 = -1;
+/// A pointer is null or a size is invalid.
+pub const WIIMOTE_ERROR_INVALID_ARGUMENT: i32 = -2;
+/// Any other error, such as an error reported by the operating system.
+pub const WIIMOTE_ERROR_OTHER: i32 = -3;
+
+/// An opaque handle to a connected Wii remote.
+pub struct WiimoteHandle {
+    device: Arc<Mutex<WiimoteDevice>>,
+}
+
+impl WiimoteHandle {
+    fn lock(&self) -> MutexGuard<'_, WiimoteDevice> {
+        match self.device.lock() {
+            Ok(device) => device,
+            Err(err) => err.into_inner(),
+        }
+    }
+}
+
+fn error_code(error: &WiimoteError) -> i32 {
+    match error {
+        WiimoteError::Disconnected => WIIMOTE_ERROR_DISCONNECTED,
+        _ => WIIMOTE_ERROR_OTHER,
+    }
+}
+
+fn result_code(result: &WiimoteResult<()>) -> i32 {
+    result.as_ref().map_or_else(error_code, |()| WIIMOTE_OK)
+}
+
+/// Waits up to `timeout_millis` for newly connected Wii remotes and stores up to `capacity`
+/// handles in `handles`. Returns the number of stored handles.
+///
+/// Scanning runs in the background from the first call on, Wii remotes connect by pressing the `1`+`2` buttons.
+///
+/// # Safety
+///
+/// `handles` must be valid for writes of `capacity` pointers.
+#[no_mangle]
+pub unsafe extern "C" fn wiimotes_scan(
+    handles: *mut *mut WiimoteHandle,
+    capacity: usize,
+    timeout_millis: u32,
+) -> usize {
+    if handles.is_null() || capacity == 0 {
+        return 0;
+    }
+
+    let new_devices = {
+        let manager = WiimoteManager::get_instance();
+        let manager = match manager.lock() {
+            Ok(manager) => manager,
+            Err(err) => err.into_inner(),
+        };
+        manager.new_devices_receiver()
+    };
+
+    let mut count = 0;
+    let timeout = Duration::from_millis(u64::from(timeout_millis));
+    if let Ok(device) = new_devices.recv_timeout(timeout) {
+        let devices = std::iter::once(device).chain(new_devices.try_iter());
+        for device in devices.take(capacity) {
+            let handle = Box::new(WiimoteHandle { device });
+            handles.add(count).write(Box::into_raw(handle));
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Stops scanning and disconnects all Wii remotes, handles must still be released with [`wiimote_free`].
+#[no_mangle]
+pub extern "C" fn wiimotes_scan_cleanup() {
+    WiimoteManager::cleanup();
+}
+
+/// Releases a handle returned by [`wiimotes_scan`].
+///
+/// # Safety
+///
+/// `handle` must be null or a handle returned by [`wiimotes_scan`] that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn wiimote_free(handle: *mut WiimoteHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Reads an input report into `buffer`, waiting up to `timeout_millis` or forever if negative.
+/// Returns the number of bytes read, 0 on timeout, or a negative error code.
+/// The first byte is the report ID, at most 32 bytes are written.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle and `buffer` must be valid for writes of `buffer_size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn wiimote_read(
+    handle: *const WiimoteHandle,
+    buffer: *mut u8,
+    buffer_size: usize,
+    timeout_millis: i32,
+) -> i32 {
+    let Some(handle) = handle.as_ref() else {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    };
+    if buffer.is_null() {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    }
+
+    let mut report = [0u8; WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE];
+    let timeout_millis = usize::try_from(timeout_millis).ok();
+    match handle.lock().read_raw(&mut report, timeout_millis) {
+        Ok(bytes_read) => {
+            let size = usize::min(bytes_read, buffer_size);
+            std::ptr::copy_nonoverlapping(report.as_ptr(), buffer, size);
+            i32::try_from(size).unwrap_or(i32::MAX)
+        }
+        Err(err) => error_code(&err),
+    }
+}
+
+/// Writes a raw output report starting with the report ID. The rumble bit is applied automatically.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle and `data` must be valid for reads of `size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn wiimote_write(
+    handle: *const WiimoteHandle,
+    data: *const u8,
+    size: usize,
+) -> i32 {
+    let Some(handle) = handle.as_ref() else {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    };
+    if data.is_null() || !(2..=WIIMOTE_DEFAULT_REPORT_BUFFER_SIZE).contains(&size) {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    }
+
+    let data = std::slice::from_raw_parts(data, size);
+    result_code(&handle.lock().write_raw(data))
+}
+
+/// Sets the player LEDs, bit 0 to 3 of `leds` turn on LED 1 to 4.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn wiimote_set_leds(handle: *const WiimoteHandle, leds: u8) -> i32 {
+    let Some(handle) = handle.as_ref() else {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    };
+    let flags = PlayerLedFlags::from_bits_truncate(leds << 4);
+    result_code(&handle.lock().write(&OutputReport::PlayerLed(flags)))
+}
+
+/// Turns the rumble on or off.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn wiimote_set_rumble(handle: *const WiimoteHandle, enabled: bool) -> i32 {
+    let Some(handle) = handle.as_ref() else {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    };
+    result_code(&handle.lock().write(&OutputReport::Rumble(enabled)))
+}
+
+/// Copies the NUL-terminated unique identifier of the Wii remote into `buffer`, truncated to `buffer_size`.
+/// Returns the length of the full identifier without the NUL terminator, or a negative error code.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle and `buffer` must be valid for writes of `buffer_size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn wiimote_identifier(
+    handle: *const WiimoteHandle,
+    buffer: *mut c_char,
+    buffer_size: usize,
+) -> i32 {
+    let Some(handle) = handle.as_ref() else {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    };
+    if buffer.is_null() || buffer_size == 0 {
+        return WIIMOTE_ERROR_INVALID_ARGUMENT;
+    }
+
+    let device = handle.lock();
+    let identifier = device.identifier().as_bytes();
+    let size = usize::min(identifier.len(), buffer_size - 1);
+    std::ptr::copy_nonoverlapping(identifier.as_ptr().cast::<c_char>(), buffer, size);
+    buffer.add(size).write(0);
+    i32::try_from(identifier.len()).unwrap_or(i32::MAX)
+}
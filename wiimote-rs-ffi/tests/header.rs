@@ -0,0 +1,66 @@
+//! Checks that the hand-written C header matches the exported functions and constants.
+
+const HEADER: &str = include_str!("../include/wiimote_rs.h");
+const SOURCE: &str = include_str!("../src/lib.rs");
+
+/// Returns the identifier at the start of `text`.
+fn identifier(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+fn exported_functions() -> Vec<&'static str> {
+    SOURCE
+        .split("extern \"C\" fn ")
+        .skip(1)
+        .map(identifier)
+        .collect()
+}
+
+fn declared_functions() -> Vec<&'static str> {
+    HEADER
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim_start().starts_with("/*"))
+        .filter_map(|line| {
+            let (declaration, _) = line.split_once('(')?;
+            declaration.rsplit([' ', '*']).next()
+        })
+        .filter(|name| name.starts_with("wiimote"))
+        .collect()
+}
+
+#[test]
+fn test_header_declares_exported_functions() {
+    let mut exported = exported_functions();
+    let mut declared = declared_functions();
+    exported.sort_unstable();
+    declared.sort_unstable();
+
+    assert!(!exported.is_empty());
+    assert_eq!(exported, declared);
+}
+
+#[test]
+fn test_header_defines_error_codes() {
+    let constants = SOURCE
+        .lines()
+        .filter_map(|line| line.strip_prefix("pub const "))
+        .map(|line| {
+            let name = identifier(line);
+            let value = line.rsplit("= ").next().unwrap().trim_end_matches(';');
+            (name, value.parse::<i32>().unwrap())
+        })
+        .collect::<Vec<_>>();
+
+    assert!(!constants.is_empty());
+    for (name, value) in constants {
+        let define = HEADER
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("#define {name} ")))
+            .unwrap_or_else(|| panic!("{name} is not defined in the header"));
+        let define = define.trim_start_matches('(').trim_end_matches(')');
+        assert_eq!(define.parse::<i32>(), Ok(value), "{name}");
+    }
+}